@@ -0,0 +1 @@
+ALTER TABLE sso_key DROP COLUMN "scope";
@@ -0,0 +1 @@
+ALTER TABLE sso_key ADD COLUMN "scope" VARCHAR[] NOT NULL DEFAULT '{}';
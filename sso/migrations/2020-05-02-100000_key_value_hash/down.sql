@@ -0,0 +1,9 @@
+-- Hashed key values cannot be recovered, these keys will not authenticate.
+DROP INDEX idx_sso_key_value_prefix;
+DROP INDEX idx_sso_key_value_hash;
+UPDATE sso_key SET "value" = "value_hash" WHERE "value" IS NULL;
+ALTER TABLE sso_key DROP COLUMN "secret";
+ALTER TABLE sso_key DROP COLUMN "value_hash";
+ALTER TABLE sso_key DROP COLUMN "value_prefix";
+ALTER TABLE sso_key ALTER COLUMN "value" SET NOT NULL;
+ALTER TABLE sso_key ADD CONSTRAINT uq_sso_key_value UNIQUE("value");
@@ -0,0 +1,10 @@
+-- Key values are hashed and encrypted by the driver after migrations
+-- are run, plaintext values are then cleared.
+ALTER TABLE sso_key DROP CONSTRAINT uq_sso_key_value;
+ALTER TABLE sso_key ALTER COLUMN "value" DROP NOT NULL;
+ALTER TABLE sso_key ADD COLUMN "value_prefix" VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE sso_key ADD COLUMN "value_hash" VARCHAR;
+ALTER TABLE sso_key ADD COLUMN "secret" VARCHAR;
+UPDATE sso_key SET "value_prefix" = substring("value" from 1 for 8);
+CREATE UNIQUE INDEX idx_sso_key_value_hash ON sso_key ("value_hash");
+CREATE INDEX idx_sso_key_value_prefix ON sso_key ("value_prefix");
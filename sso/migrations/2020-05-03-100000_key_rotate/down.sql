@@ -0,0 +1,13 @@
+DROP INDEX idx_sso_key_type_totp;
+DROP INDEX idx_sso_key_type_token;
+CREATE UNIQUE INDEX idx_sso_key_type_token ON sso_key ("service_id", "user_id")
+    WHERE "is_enabled" IS TRUE AND "type" = 'Token';
+CREATE UNIQUE INDEX idx_sso_key_type_totp ON sso_key ("service_id", "user_id")
+    WHERE "is_enabled" IS TRUE AND "type" = 'Totp';
+
+DROP INDEX idx_sso_key_revoke_at;
+ALTER TABLE sso_key DROP CONSTRAINT fk_sso_key_successor;
+ALTER TABLE sso_key DROP CONSTRAINT fk_sso_key_predecessor;
+ALTER TABLE sso_key DROP COLUMN "revoke_at";
+ALTER TABLE sso_key DROP COLUMN "successor_id";
+ALTER TABLE sso_key DROP COLUMN "predecessor_id";
@@ -0,0 +1,25 @@
+-- Rotated keys are linked to their successor, predecessors are revoked
+-- after their revoke_at time has passed.
+ALTER TABLE sso_key ADD COLUMN "predecessor_id" UUID;
+ALTER TABLE sso_key ADD COLUMN "successor_id" UUID;
+ALTER TABLE sso_key ADD COLUMN "revoke_at" TIMESTAMPTZ;
+ALTER TABLE sso_key ADD CONSTRAINT fk_sso_key_predecessor
+    FOREIGN KEY ("predecessor_id")
+    REFERENCES sso_key("id")
+    ON DELETE SET NULL
+    DEFERRABLE INITIALLY DEFERRED;
+ALTER TABLE sso_key ADD CONSTRAINT fk_sso_key_successor
+    FOREIGN KEY ("successor_id")
+    REFERENCES sso_key("id")
+    ON DELETE SET NULL
+    DEFERRABLE INITIALLY DEFERRED;
+CREATE INDEX idx_sso_key_revoke_at ON sso_key ("revoke_at")
+    WHERE "is_revoked" IS FALSE AND "revoke_at" IS NOT NULL;
+
+-- Rotated token and TOTP keys are excluded from user constraints.
+DROP INDEX idx_sso_key_type_token;
+DROP INDEX idx_sso_key_type_totp;
+CREATE UNIQUE INDEX idx_sso_key_type_token ON sso_key ("service_id", "user_id")
+    WHERE "is_enabled" IS TRUE AND "type" = 'Token' AND "successor_id" IS NULL;
+CREATE UNIQUE INDEX idx_sso_key_type_totp ON sso_key ("service_id", "user_id")
+    WHERE "is_enabled" IS TRUE AND "type" = 'Totp' AND "successor_id" IS NULL;
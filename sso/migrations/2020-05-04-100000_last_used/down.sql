@@ -0,0 +1,6 @@
+DROP INDEX idx_sso_user_last_login_at;
+DROP INDEX idx_sso_key_last_used_at;
+ALTER TABLE sso_user DROP COLUMN "last_login_remote";
+ALTER TABLE sso_user DROP COLUMN "last_login_at";
+ALTER TABLE sso_key DROP COLUMN "last_used_remote";
+ALTER TABLE sso_key DROP COLUMN "last_used_at";
@@ -0,0 +1,36 @@
+-- Key last used and user last login information is updated when
+-- audit logs are created, existing values are calculated from audit logs.
+ALTER TABLE sso_key ADD COLUMN "last_used_at" TIMESTAMPTZ;
+ALTER TABLE sso_key ADD COLUMN "last_used_remote" VARCHAR;
+ALTER TABLE sso_user ADD COLUMN "last_login_at" TIMESTAMPTZ;
+ALTER TABLE sso_user ADD COLUMN "last_login_remote" VARCHAR;
+
+UPDATE sso_key SET "last_used_at" = a."created_at", "last_used_remote" = a."remote"
+FROM (
+    SELECT DISTINCT ON ("id") "id", "created_at", "remote"
+    FROM (
+        SELECT "key_id" AS "id", "created_at", "remote" FROM sso_audit
+            WHERE "key_id" IS NOT NULL
+        UNION ALL
+        SELECT "user_key_id" AS "id", "created_at", "remote" FROM sso_audit
+            WHERE "user_key_id" IS NOT NULL
+    ) AS u
+    ORDER BY "id", "created_at" DESC
+) AS a
+WHERE sso_key."id" = a."id";
+
+UPDATE sso_user SET "last_login_at" = a."created_at", "last_login_remote" = a."remote"
+FROM (
+    SELECT DISTINCT ON ("user_id") "user_id", "created_at", "remote"
+    FROM sso_audit
+    WHERE "user_id" IS NOT NULL AND "status_code" = 0 AND "type" IN (
+        'sso:AuthLocalLogin',
+        'sso:AuthGithubOauth2Callback',
+        'sso:AuthMicrosoftOauth2Callback'
+    )
+    ORDER BY "user_id", "created_at" DESC
+) AS a
+WHERE sso_user."id" = a."user_id";
+
+CREATE INDEX idx_sso_key_last_used_at ON sso_key ("last_used_at");
+CREATE INDEX idx_sso_user_last_login_at ON sso_user ("last_login_at");
@@ -0,0 +1,2 @@
+ALTER TABLE sso_key DROP COLUMN "allow_remote";
+ALTER TABLE sso_service DROP COLUMN "allow_remote";
@@ -0,0 +1,2 @@
+ALTER TABLE sso_service ADD COLUMN "allow_remote" VARCHAR[] NOT NULL DEFAULT '{}';
+ALTER TABLE sso_key ADD COLUMN "allow_remote" VARCHAR[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,2 @@
+DROP TABLE sso_user_role;
+DROP TABLE sso_role;
@@ -0,0 +1,29 @@
+CREATE TABLE sso_role (
+    "created_at" TIMESTAMPTZ NOT NULL,
+    "updated_at" TIMESTAMPTZ NOT NULL,
+    "id"         UUID        NOT NULL,
+    "service_id" UUID        NOT NULL,
+    "name"       VARCHAR     NOT NULL,
+    PRIMARY KEY ("id"),
+    CONSTRAINT uq_sso_role_service_name UNIQUE("service_id", "name"),
+    CONSTRAINT fk_sso_role_service
+        FOREIGN KEY ("service_id")
+        REFERENCES sso_service("id")
+        ON DELETE CASCADE
+);
+
+CREATE TABLE sso_user_role (
+    "created_at" TIMESTAMPTZ NOT NULL,
+    "user_id"    UUID        NOT NULL,
+    "role_id"    UUID        NOT NULL,
+    PRIMARY KEY ("user_id", "role_id"),
+    CONSTRAINT fk_sso_user_role_user
+        FOREIGN KEY ("user_id")
+        REFERENCES sso_user("id")
+        ON DELETE CASCADE,
+    CONSTRAINT fk_sso_user_role_role
+        FOREIGN KEY ("role_id")
+        REFERENCES sso_role("id")
+        ON DELETE CASCADE
+);
+CREATE INDEX idx_sso_user_role_role_id ON sso_user_role ("role_id");
@@ -0,0 +1,3 @@
+DROP TABLE sso_group_role;
+DROP TABLE sso_user_group;
+DROP TABLE sso_group;
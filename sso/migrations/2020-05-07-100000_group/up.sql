@@ -0,0 +1,47 @@
+CREATE TABLE sso_group (
+    "created_at" TIMESTAMPTZ NOT NULL,
+    "updated_at" TIMESTAMPTZ NOT NULL,
+    "id"         UUID        NOT NULL,
+    "is_enabled" BOOLEAN     NOT NULL,
+    "name"       VARCHAR     NOT NULL,
+    "parent_id"  UUID,
+    PRIMARY KEY ("id"),
+    CONSTRAINT uq_sso_group_name UNIQUE("name"),
+    CONSTRAINT fk_sso_group_parent
+        FOREIGN KEY ("parent_id")
+        REFERENCES sso_group("id")
+        ON DELETE SET NULL
+);
+CREATE INDEX idx_sso_group_parent_id ON sso_group ("parent_id");
+
+CREATE TABLE sso_user_group (
+    "created_at" TIMESTAMPTZ NOT NULL,
+    "user_id"    UUID        NOT NULL,
+    "group_id"   UUID        NOT NULL,
+    PRIMARY KEY ("user_id", "group_id"),
+    CONSTRAINT fk_sso_user_group_user
+        FOREIGN KEY ("user_id")
+        REFERENCES sso_user("id")
+        ON DELETE CASCADE,
+    CONSTRAINT fk_sso_user_group_group
+        FOREIGN KEY ("group_id")
+        REFERENCES sso_group("id")
+        ON DELETE CASCADE
+);
+CREATE INDEX idx_sso_user_group_group_id ON sso_user_group ("group_id");
+
+CREATE TABLE sso_group_role (
+    "created_at" TIMESTAMPTZ NOT NULL,
+    "group_id"   UUID        NOT NULL,
+    "role_id"    UUID        NOT NULL,
+    PRIMARY KEY ("group_id", "role_id"),
+    CONSTRAINT fk_sso_group_role_group
+        FOREIGN KEY ("group_id")
+        REFERENCES sso_group("id")
+        ON DELETE CASCADE,
+    CONSTRAINT fk_sso_group_role_role
+        FOREIGN KEY ("role_id")
+        REFERENCES sso_role("id")
+        ON DELETE CASCADE
+);
+CREATE INDEX idx_sso_group_role_role_id ON sso_group_role ("role_id");
@@ -0,0 +1,3 @@
+DROP TABLE sso_user_service;
+ALTER TABLE sso_service DROP COLUMN "user_claims";
+ALTER TABLE sso_user DROP COLUMN "attributes";
@@ -0,0 +1,20 @@
+ALTER TABLE sso_user ADD COLUMN "attributes" JSONB NOT NULL DEFAULT '{}';
+ALTER TABLE sso_service ADD COLUMN "user_claims" VARCHAR[] NOT NULL DEFAULT '{}';
+
+CREATE TABLE sso_user_service (
+    "created_at" TIMESTAMPTZ NOT NULL,
+    "updated_at" TIMESTAMPTZ NOT NULL,
+    "user_id"    UUID        NOT NULL,
+    "service_id" UUID        NOT NULL,
+    "attributes" JSONB       NOT NULL,
+    PRIMARY KEY ("user_id", "service_id"),
+    CONSTRAINT fk_sso_user_service_user
+        FOREIGN KEY ("user_id")
+        REFERENCES sso_user("id")
+        ON DELETE CASCADE,
+    CONSTRAINT fk_sso_user_service_service
+        FOREIGN KEY ("service_id")
+        REFERENCES sso_service("id")
+        ON DELETE CASCADE
+);
+CREATE INDEX idx_sso_user_service_service_id ON sso_user_service ("service_id");
@@ -0,0 +1,3 @@
+DROP INDEX idx_sso_user_service_service_id_status;
+ALTER TABLE sso_user_service ALTER COLUMN "attributes" DROP DEFAULT;
+ALTER TABLE sso_user_service DROP COLUMN "status";
@@ -0,0 +1,10 @@
+ALTER TABLE sso_user_service ADD COLUMN "status" VARCHAR;
+ALTER TABLE sso_user_service ALTER COLUMN "attributes" SET DEFAULT '{}';
+CREATE INDEX idx_sso_user_service_service_id_status ON sso_user_service ("service_id", "status");
+
+INSERT INTO sso_user_service ("created_at", "updated_at", "user_id", "service_id", "status")
+SELECT MIN("created_at"), MIN("created_at"), "user_id", "service_id", 'Active'
+FROM sso_key
+WHERE "user_id" IS NOT NULL AND "service_id" IS NOT NULL
+GROUP BY "user_id", "service_id"
+ON CONFLICT ("user_id", "service_id") DO UPDATE SET "status" = 'Active';
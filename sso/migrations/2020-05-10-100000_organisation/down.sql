@@ -0,0 +1,13 @@
+DROP INDEX idx_sso_audit_organisation_id;
+ALTER TABLE sso_audit DROP COLUMN "organisation_id";
+
+ALTER TABLE sso_key DROP COLUMN "organisation_id";
+
+DROP INDEX uq_sso_user_organisation_email;
+ALTER TABLE sso_user ADD CONSTRAINT uq_sso_user_email UNIQUE("email");
+ALTER TABLE sso_user DROP COLUMN "organisation_id";
+
+DROP INDEX idx_sso_service_organisation_id;
+ALTER TABLE sso_service DROP COLUMN "organisation_id";
+
+DROP TABLE sso_organisation;
@@ -0,0 +1,40 @@
+CREATE TABLE sso_organisation (
+    "created_at" TIMESTAMPTZ NOT NULL,
+    "updated_at" TIMESTAMPTZ NOT NULL,
+    "id"         UUID        NOT NULL,
+    "is_enabled" BOOLEAN     NOT NULL,
+    "name"       VARCHAR     NOT NULL,
+    PRIMARY KEY ("id")
+);
+
+ALTER TABLE sso_service ADD COLUMN "organisation_id" UUID,
+    ADD CONSTRAINT fk_sso_service_organisation
+        FOREIGN KEY ("organisation_id")
+        REFERENCES sso_organisation("id")
+        ON DELETE RESTRICT;
+CREATE INDEX idx_sso_service_organisation_id ON sso_service ("organisation_id");
+
+ALTER TABLE sso_user ADD COLUMN "organisation_id" UUID,
+    ADD CONSTRAINT fk_sso_user_organisation
+        FOREIGN KEY ("organisation_id")
+        REFERENCES sso_organisation("id")
+        ON DELETE RESTRICT;
+ALTER TABLE sso_user DROP CONSTRAINT uq_sso_user_email;
+CREATE UNIQUE INDEX uq_sso_user_organisation_email ON sso_user (
+    COALESCE("organisation_id", '00000000-0000-0000-0000-000000000000'), "email"
+);
+
+ALTER TABLE sso_key ADD COLUMN "organisation_id" UUID,
+    ADD CONSTRAINT fk_sso_key_organisation
+        FOREIGN KEY ("organisation_id")
+        REFERENCES sso_organisation("id")
+        ON DELETE RESTRICT,
+    ADD CONSTRAINT ck_sso_key_organisation_root
+        CHECK ("organisation_id" IS NULL OR ("service_id" IS NULL AND "user_id" IS NULL));
+
+ALTER TABLE sso_audit ADD COLUMN "organisation_id" UUID,
+    ADD CONSTRAINT fk_sso_audit_organisation
+        FOREIGN KEY ("organisation_id")
+        REFERENCES sso_organisation("id")
+        ON DELETE RESTRICT;
+CREATE INDEX idx_sso_audit_organisation_id ON sso_audit ("organisation_id", "created_at" DESC);
@@ -0,0 +1,2 @@
+DROP INDEX idx_sso_audit_actor_key_id;
+ALTER TABLE sso_audit DROP COLUMN "actor_key_id";
@@ -0,0 +1,7 @@
+ALTER TABLE sso_audit ADD COLUMN "actor_key_id" UUID,
+    ADD CONSTRAINT fk_sso_audit_actor_key
+        FOREIGN KEY ("actor_key_id")
+        REFERENCES sso_key("id")
+        ON DELETE RESTRICT;
+CREATE INDEX idx_sso_audit_actor_key_id ON sso_audit ("actor_key_id", "created_at" DESC)
+    WHERE "actor_key_id" IS NOT NULL;
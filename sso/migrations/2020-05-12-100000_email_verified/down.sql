@@ -0,0 +1,2 @@
+ALTER TABLE sso_service DROP COLUMN "user_require_email_verified";
+ALTER TABLE sso_user DROP COLUMN "email_verified_at";
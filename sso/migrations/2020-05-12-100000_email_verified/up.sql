@@ -0,0 +1,2 @@
+ALTER TABLE sso_user ADD COLUMN "email_verified_at" TIMESTAMPTZ;
+ALTER TABLE sso_service ADD COLUMN "user_require_email_verified" BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1 @@
+ALTER TABLE sso_user_service DROP COLUMN "invite_expires_at";
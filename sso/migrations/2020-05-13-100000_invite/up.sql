@@ -0,0 +1 @@
+ALTER TABLE sso_user_service ADD COLUMN "invite_expires_at" TIMESTAMPTZ;
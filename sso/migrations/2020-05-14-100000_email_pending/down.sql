@@ -0,0 +1 @@
+ALTER TABLE sso_user DROP COLUMN "email_pending";
@@ -0,0 +1 @@
+ALTER TABLE sso_user ADD COLUMN "email_pending" VARCHAR;
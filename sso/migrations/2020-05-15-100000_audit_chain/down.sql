@@ -0,0 +1,6 @@
+DROP TABLE sso_audit_chain_point;
+DROP INDEX uq_sso_audit_chain_seq;
+ALTER TABLE sso_audit DROP COLUMN "chain_seq",
+    DROP COLUMN "chain_previous",
+    DROP COLUMN "chain_hash",
+    DROP COLUMN "chain_superseded";
@@ -0,0 +1,17 @@
+ALTER TABLE sso_audit ADD COLUMN "chain_seq" BIGINT,
+    ADD COLUMN "chain_previous" VARCHAR,
+    ADD COLUMN "chain_hash" VARCHAR,
+    ADD COLUMN "chain_superseded" VARCHAR[] NOT NULL DEFAULT '{}';
+CREATE UNIQUE INDEX uq_sso_audit_chain_seq ON sso_audit ("chain_seq");
+
+CREATE TABLE sso_audit_chain_point (
+    "created_at" TIMESTAMPTZ NOT NULL,
+    "id"         UUID        NOT NULL,
+    "type"       VARCHAR     NOT NULL,
+    "chain_seq"  BIGINT      NOT NULL,
+    "chain_hash" VARCHAR     NOT NULL,
+    "count"      BIGINT      NOT NULL,
+    "signature"  VARCHAR     NOT NULL,
+    PRIMARY KEY ("id")
+);
+CREATE INDEX idx_sso_audit_chain_point_chain_seq ON sso_audit_chain_point ("chain_seq" DESC);
@@ -0,0 +1,2 @@
+DROP TABLE sso_webhook_delivery;
+DROP TABLE sso_webhook;
@@ -0,0 +1,40 @@
+CREATE TABLE sso_webhook (
+    "created_at" TIMESTAMPTZ NOT NULL,
+    "updated_at" TIMESTAMPTZ NOT NULL,
+    "id"         UUID        NOT NULL,
+    "service_id" UUID        NOT NULL,
+    "is_enabled" BOOLEAN     NOT NULL,
+    "name"       VARCHAR     NOT NULL,
+    "url"        VARCHAR     NOT NULL,
+    "secret"     VARCHAR     NOT NULL,
+    "audit_type" VARCHAR[]   NOT NULL,
+    PRIMARY KEY ("id"),
+    CONSTRAINT fk_sso_webhook_service
+        FOREIGN KEY ("service_id")
+        REFERENCES sso_service("id")
+        ON DELETE CASCADE
+);
+CREATE INDEX idx_sso_webhook_service_id ON sso_webhook ("service_id");
+
+CREATE TABLE sso_webhook_delivery (
+    "created_at"      TIMESTAMPTZ NOT NULL,
+    "updated_at"      TIMESTAMPTZ NOT NULL,
+    "id"              UUID        NOT NULL,
+    "webhook_id"      UUID        NOT NULL,
+    "audit_id"        UUID        NOT NULL,
+    "audit_type"      VARCHAR     NOT NULL,
+    "status"          VARCHAR     NOT NULL,
+    "attempts"        INTEGER     NOT NULL,
+    "next_attempt_at" TIMESTAMPTZ NOT NULL,
+    "delivered_at"    TIMESTAMPTZ,
+    "status_code"     SMALLINT,
+    "error"           VARCHAR,
+    PRIMARY KEY ("id"),
+    CONSTRAINT fk_sso_webhook_delivery_webhook
+        FOREIGN KEY ("webhook_id")
+        REFERENCES sso_webhook("id")
+        ON DELETE CASCADE
+);
+CREATE INDEX idx_sso_webhook_delivery_webhook_id ON sso_webhook_delivery ("webhook_id");
+CREATE INDEX idx_sso_webhook_delivery_pending ON sso_webhook_delivery ("next_attempt_at")
+    WHERE "status" = 'Pending';
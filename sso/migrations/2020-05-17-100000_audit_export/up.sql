@@ -0,0 +1,7 @@
+CREATE TABLE sso_audit_export (
+    "created_at" TIMESTAMPTZ NOT NULL,
+    "updated_at" TIMESTAMPTZ NOT NULL,
+    "name"       VARCHAR     NOT NULL,
+    "chain_seq"  BIGINT      NOT NULL,
+    PRIMARY KEY ("name")
+);
@@ -0,0 +1,9 @@
+DROP INDEX idx_sso_audit_data;
+DROP INDEX idx_sso_audit_user_key_id;
+DROP INDEX idx_sso_audit_key_id;
+DROP INDEX idx_sso_audit_forwarded;
+DROP INDEX idx_sso_audit_remote_inet;
+DROP INDEX idx_sso_audit_remote;
+DROP INDEX idx_sso_audit_status_code;
+DROP FUNCTION sso_audit_inet_array;
+DROP FUNCTION sso_audit_remote_inet;
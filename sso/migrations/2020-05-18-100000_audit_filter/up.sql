@@ -0,0 +1,30 @@
+-- Returns IP address of remote column value, which is an IP address
+-- or socket address string, or null if value cannot be parsed.
+CREATE FUNCTION sso_audit_remote_inet(remote VARCHAR) RETURNS INET AS $$
+BEGIN
+    RETURN CASE
+        WHEN remote LIKE '[%]:%' THEN substring(remote FROM '^\[([^\]]+)\]')
+        WHEN remote ~ '^[0-9.]+:[0-9]+$' THEN split_part(remote, ':', 1)
+        ELSE remote
+    END::INET;
+EXCEPTION WHEN invalid_text_representation THEN
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql IMMUTABLE STRICT;
+
+-- Returns CIDR string array as IP address array.
+CREATE FUNCTION sso_audit_inet_array(cidr VARCHAR[]) RETURNS INET[] AS $$
+    SELECT cidr::INET[]
+$$ LANGUAGE SQL IMMUTABLE STRICT;
+
+CREATE INDEX idx_sso_audit_status_code ON sso_audit ("status_code", "created_at" DESC)
+    WHERE "status_code" IS NOT NULL;
+CREATE INDEX idx_sso_audit_remote ON sso_audit ("remote" text_pattern_ops);
+CREATE INDEX idx_sso_audit_remote_inet ON sso_audit USING gist (sso_audit_remote_inet("remote") inet_ops);
+CREATE INDEX idx_sso_audit_forwarded ON sso_audit ("forwarded" text_pattern_ops)
+    WHERE "forwarded" IS NOT NULL;
+CREATE INDEX idx_sso_audit_key_id ON sso_audit ("key_id", "created_at" DESC)
+    WHERE "key_id" IS NOT NULL;
+CREATE INDEX idx_sso_audit_user_key_id ON sso_audit ("user_key_id", "created_at" DESC)
+    WHERE "user_key_id" IS NOT NULL;
+CREATE INDEX idx_sso_audit_data ON sso_audit USING gin ("data" jsonb_path_ops);
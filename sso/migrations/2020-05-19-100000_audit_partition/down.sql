@@ -0,0 +1,62 @@
+DROP TABLE sso_audit_retention;
+DROP FUNCTION sso_audit_partition_create;
+
+ALTER TABLE sso_audit RENAME TO sso_audit_partitioned;
+ALTER INDEX sso_audit_pkey RENAME TO sso_audit_partitioned_pkey;
+DROP INDEX idx_sso_audit_created_at;
+DROP INDEX idx_sso_audit_organisation_id;
+DROP INDEX idx_sso_audit_actor_key_id;
+DROP INDEX idx_sso_audit_chain_seq;
+DROP INDEX idx_sso_audit_status_code;
+DROP INDEX idx_sso_audit_remote;
+DROP INDEX idx_sso_audit_remote_inet;
+DROP INDEX idx_sso_audit_forwarded;
+DROP INDEX idx_sso_audit_key_id;
+DROP INDEX idx_sso_audit_user_key_id;
+DROP INDEX idx_sso_audit_data;
+
+CREATE TABLE sso_audit (LIKE sso_audit_partitioned INCLUDING DEFAULTS);
+ALTER TABLE sso_audit ADD PRIMARY KEY ("created_at", "id"),
+    ADD CONSTRAINT fk_sso_audit_key
+        FOREIGN KEY ("key_id")
+        REFERENCES sso_key("id")
+        ON DELETE RESTRICT,
+    ADD CONSTRAINT fk_sso_audit_service
+        FOREIGN KEY ("service_id")
+        REFERENCES sso_service("id")
+        ON DELETE RESTRICT,
+    ADD CONSTRAINT fk_sso_audit_user
+        FOREIGN KEY ("user_id")
+        REFERENCES sso_user("id")
+        ON DELETE RESTRICT,
+    ADD CONSTRAINT fk_sso_audit_user_key
+        FOREIGN KEY ("user_key_id")
+        REFERENCES sso_key("id")
+        ON DELETE RESTRICT,
+    ADD CONSTRAINT fk_sso_audit_organisation
+        FOREIGN KEY ("organisation_id")
+        REFERENCES sso_organisation("id")
+        ON DELETE RESTRICT,
+    ADD CONSTRAINT fk_sso_audit_actor_key
+        FOREIGN KEY ("actor_key_id")
+        REFERENCES sso_key("id")
+        ON DELETE RESTRICT;
+INSERT INTO sso_audit SELECT * FROM sso_audit_partitioned;
+DROP TABLE sso_audit_partitioned;
+
+CREATE INDEX idx_sso_audit_created_at ON sso_audit ("created_at" DESC, "type");
+CREATE INDEX idx_sso_audit_organisation_id ON sso_audit ("organisation_id", "created_at" DESC);
+CREATE INDEX idx_sso_audit_actor_key_id ON sso_audit ("actor_key_id", "created_at" DESC)
+    WHERE "actor_key_id" IS NOT NULL;
+CREATE UNIQUE INDEX uq_sso_audit_chain_seq ON sso_audit ("chain_seq");
+CREATE INDEX idx_sso_audit_status_code ON sso_audit ("status_code", "created_at" DESC)
+    WHERE "status_code" IS NOT NULL;
+CREATE INDEX idx_sso_audit_remote ON sso_audit ("remote" text_pattern_ops);
+CREATE INDEX idx_sso_audit_remote_inet ON sso_audit USING gist (sso_audit_remote_inet("remote") inet_ops);
+CREATE INDEX idx_sso_audit_forwarded ON sso_audit ("forwarded" text_pattern_ops)
+    WHERE "forwarded" IS NOT NULL;
+CREATE INDEX idx_sso_audit_key_id ON sso_audit ("key_id", "created_at" DESC)
+    WHERE "key_id" IS NOT NULL;
+CREATE INDEX idx_sso_audit_user_key_id ON sso_audit ("user_key_id", "created_at" DESC)
+    WHERE "user_key_id" IS NOT NULL;
+CREATE INDEX idx_sso_audit_data ON sso_audit USING gin ("data" jsonb_path_ops);
@@ -0,0 +1,165 @@
+-- Audit logs are partitioned by month of creation, so retention can drop
+-- partitions instead of deleting rows. Sequence numbers are assigned under
+-- an advisory lock, unique index is replaced as it cannot span partitions.
+ALTER TABLE sso_audit RENAME TO sso_audit_unpartitioned;
+ALTER INDEX sso_audit_pkey RENAME TO sso_audit_unpartitioned_pkey;
+DROP INDEX idx_sso_audit_created_at;
+DROP INDEX idx_sso_audit_organisation_id;
+DROP INDEX idx_sso_audit_actor_key_id;
+DROP INDEX uq_sso_audit_chain_seq;
+DROP INDEX idx_sso_audit_status_code;
+DROP INDEX idx_sso_audit_remote;
+DROP INDEX idx_sso_audit_remote_inet;
+DROP INDEX idx_sso_audit_forwarded;
+DROP INDEX idx_sso_audit_key_id;
+DROP INDEX idx_sso_audit_user_key_id;
+DROP INDEX idx_sso_audit_data;
+
+CREATE TABLE sso_audit (
+    "created_at"       TIMESTAMPTZ NOT NULL,
+    "updated_at"       TIMESTAMPTZ NOT NULL,
+    "id"               UUID        NOT NULL,
+    "user_agent"       VARCHAR     NOT NULL,
+    "remote"           VARCHAR     NOT NULL,
+    "forwarded"        VARCHAR,
+    "status_code"      SMALLINT,
+    "type"             VARCHAR     NOT NULL,
+    "subject"          VARCHAR,
+    "data"             JSONB       NOT NULL,
+    "key_id"           UUID,
+    "service_id"       UUID,
+    "user_id"          UUID,
+    "user_key_id"      UUID,
+    "organisation_id"  UUID,
+    "actor_key_id"     UUID,
+    "chain_seq"        BIGINT,
+    "chain_previous"   VARCHAR,
+    "chain_hash"       VARCHAR,
+    "chain_superseded" VARCHAR[]   NOT NULL DEFAULT '{}',
+    PRIMARY KEY ("created_at", "id"),
+    CONSTRAINT fk_sso_audit_key
+        FOREIGN KEY ("key_id")
+        REFERENCES sso_key("id")
+        ON DELETE RESTRICT,
+    CONSTRAINT fk_sso_audit_service
+        FOREIGN KEY ("service_id")
+        REFERENCES sso_service("id")
+        ON DELETE RESTRICT,
+    CONSTRAINT fk_sso_audit_user
+        FOREIGN KEY ("user_id")
+        REFERENCES sso_user("id")
+        ON DELETE RESTRICT,
+    CONSTRAINT fk_sso_audit_user_key
+        FOREIGN KEY ("user_key_id")
+        REFERENCES sso_key("id")
+        ON DELETE RESTRICT,
+    CONSTRAINT fk_sso_audit_organisation
+        FOREIGN KEY ("organisation_id")
+        REFERENCES sso_organisation("id")
+        ON DELETE RESTRICT,
+    CONSTRAINT fk_sso_audit_actor_key
+        FOREIGN KEY ("actor_key_id")
+        REFERENCES sso_key("id")
+        ON DELETE RESTRICT
+) PARTITION BY RANGE ("created_at");
+
+-- Audit logs created outside of existing partitions are inserted into
+-- default partition, and moved when partition for month is created.
+CREATE TABLE sso_audit_default PARTITION OF sso_audit DEFAULT;
+
+CREATE INDEX idx_sso_audit_created_at ON sso_audit ("created_at" DESC, "type");
+CREATE INDEX idx_sso_audit_organisation_id ON sso_audit ("organisation_id", "created_at" DESC);
+CREATE INDEX idx_sso_audit_actor_key_id ON sso_audit ("actor_key_id", "created_at" DESC)
+    WHERE "actor_key_id" IS NOT NULL;
+CREATE INDEX idx_sso_audit_chain_seq ON sso_audit ("chain_seq");
+CREATE INDEX idx_sso_audit_status_code ON sso_audit ("status_code", "created_at" DESC)
+    WHERE "status_code" IS NOT NULL;
+CREATE INDEX idx_sso_audit_remote ON sso_audit ("remote" text_pattern_ops);
+CREATE INDEX idx_sso_audit_remote_inet ON sso_audit USING gist (sso_audit_remote_inet("remote") inet_ops);
+CREATE INDEX idx_sso_audit_forwarded ON sso_audit ("forwarded" text_pattern_ops)
+    WHERE "forwarded" IS NOT NULL;
+CREATE INDEX idx_sso_audit_key_id ON sso_audit ("key_id", "created_at" DESC)
+    WHERE "key_id" IS NOT NULL;
+CREATE INDEX idx_sso_audit_user_key_id ON sso_audit ("user_key_id", "created_at" DESC)
+    WHERE "user_key_id" IS NOT NULL;
+CREATE INDEX idx_sso_audit_data ON sso_audit USING gin ("data" jsonb_path_ops);
+
+-- Create partition for month of time if it does not exist, audit logs in
+-- range are moved from default partition. Returns true if created.
+CREATE FUNCTION sso_audit_partition_create(partition_at TIMESTAMPTZ) RETURNS BOOLEAN AS $$
+DECLARE
+    range_lower TIMESTAMPTZ := date_trunc('month', partition_at AT TIME ZONE 'UTC') AT TIME ZONE 'UTC';
+    range_upper TIMESTAMPTZ := range_lower + INTERVAL '1 month';
+    partition_name VARCHAR := 'sso_audit_' || to_char(range_lower AT TIME ZONE 'UTC', 'YYYYMM');
+BEGIN
+    IF to_regclass(partition_name) IS NOT NULL THEN
+        RETURN FALSE;
+    END IF;
+    EXECUTE format('CREATE TABLE %I (LIKE sso_audit INCLUDING DEFAULTS)', partition_name);
+    EXECUTE format(
+        'WITH moved AS (DELETE FROM sso_audit_default WHERE "created_at" >= $1 AND "created_at" < $2 RETURNING *) '
+        'INSERT INTO %I SELECT * FROM moved',
+        partition_name
+    ) USING range_lower, range_upper;
+    EXECUTE format(
+        'ALTER TABLE sso_audit ATTACH PARTITION %I FOR VALUES FROM (%L) TO (%L)',
+        partition_name,
+        range_lower,
+        range_upper
+    );
+    RETURN TRUE;
+END;
+$$ LANGUAGE plpgsql;
+
+SELECT sso_audit_partition_create(m."month")
+FROM (
+    SELECT DISTINCT date_trunc('month', "created_at" AT TIME ZONE 'UTC') AT TIME ZONE 'UTC' AS "month"
+    FROM sso_audit_unpartitioned
+    UNION
+    SELECT date_trunc('month', now() AT TIME ZONE 'UTC') AT TIME ZONE 'UTC'
+) AS m;
+
+INSERT INTO sso_audit SELECT
+    "created_at",
+    "updated_at",
+    "id",
+    "user_agent",
+    "remote",
+    "forwarded",
+    "status_code",
+    "type",
+    "subject",
+    "data",
+    "key_id",
+    "service_id",
+    "user_id",
+    "user_key_id",
+    "organisation_id",
+    "actor_key_id",
+    "chain_seq",
+    "chain_previous",
+    "chain_hash",
+    "chain_superseded"
+FROM sso_audit_unpartitioned;
+DROP TABLE sso_audit_unpartitioned;
+
+-- Retention periods of audit logs by service and type, the most specific
+-- retention applies. Null service or type matches all audit logs.
+CREATE TABLE sso_audit_retention (
+    "created_at" TIMESTAMPTZ NOT NULL,
+    "updated_at" TIMESTAMPTZ NOT NULL,
+    "id"         UUID        NOT NULL,
+    "service_id" UUID,
+    "type"       VARCHAR,
+    "days"       INTEGER     NOT NULL,
+    PRIMARY KEY ("id"),
+    CONSTRAINT ck_sso_audit_retention_days CHECK ("days" > 0),
+    CONSTRAINT fk_sso_audit_retention_service
+        FOREIGN KEY ("service_id")
+        REFERENCES sso_service("id")
+        ON DELETE CASCADE
+);
+CREATE UNIQUE INDEX uq_sso_audit_retention ON sso_audit_retention (
+    COALESCE("service_id", '00000000-0000-0000-0000-000000000000'),
+    COALESCE("type", '')
+);
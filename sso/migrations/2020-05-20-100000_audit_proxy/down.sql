@@ -0,0 +1 @@
+ALTER TABLE sso_audit DROP COLUMN "proxy";
@@ -0,0 +1,2 @@
+-- Peer address and proxy headers of requests forwarded by a proxy.
+ALTER TABLE sso_audit ADD COLUMN "proxy" JSONB;
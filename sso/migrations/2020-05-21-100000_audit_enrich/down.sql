@@ -0,0 +1,11 @@
+DROP INDEX idx_sso_audit_user_agent_browser;
+DROP INDEX idx_sso_audit_remote_asn;
+DROP INDEX idx_sso_audit_remote_country;
+ALTER TABLE sso_audit
+    DROP COLUMN "user_agent_device",
+    DROP COLUMN "user_agent_os",
+    DROP COLUMN "user_agent_browser",
+    DROP COLUMN "remote_as_org",
+    DROP COLUMN "remote_asn",
+    DROP COLUMN "remote_city",
+    DROP COLUMN "remote_country";
@@ -0,0 +1,17 @@
+-- Remote location and user agent fields, set when audit logs are created
+-- if enrichment is enabled.
+ALTER TABLE sso_audit
+    ADD COLUMN "remote_country"     VARCHAR,
+    ADD COLUMN "remote_city"        VARCHAR,
+    ADD COLUMN "remote_asn"         BIGINT,
+    ADD COLUMN "remote_as_org"      VARCHAR,
+    ADD COLUMN "user_agent_browser" VARCHAR,
+    ADD COLUMN "user_agent_os"      VARCHAR,
+    ADD COLUMN "user_agent_device"  VARCHAR;
+
+CREATE INDEX idx_sso_audit_remote_country ON sso_audit ("remote_country", "created_at" DESC)
+    WHERE "remote_country" IS NOT NULL;
+CREATE INDEX idx_sso_audit_remote_asn ON sso_audit ("remote_asn", "created_at" DESC)
+    WHERE "remote_asn" IS NOT NULL;
+CREATE INDEX idx_sso_audit_user_agent_browser ON sso_audit ("user_agent_browser", "created_at" DESC)
+    WHERE "user_agent_browser" IS NOT NULL;
@@ -0,0 +1,6 @@
+DELETE FROM sso_group;
+ALTER TABLE sso_group
+    DROP CONSTRAINT fk_sso_group_service,
+    DROP CONSTRAINT uq_sso_group_service_name,
+    DROP COLUMN "service_id",
+    ADD CONSTRAINT uq_sso_group_name UNIQUE("name");
@@ -0,0 +1,11 @@
+-- Groups are owned by a service, existing groups have no service to be
+-- assigned to and are removed.
+DELETE FROM sso_group;
+ALTER TABLE sso_group
+    DROP CONSTRAINT uq_sso_group_name,
+    ADD COLUMN "service_id" UUID NOT NULL,
+    ADD CONSTRAINT uq_sso_group_service_name UNIQUE("service_id", "name"),
+    ADD CONSTRAINT fk_sso_group_service
+        FOREIGN KEY ("service_id")
+        REFERENCES sso_service("id")
+        ON DELETE CASCADE;
@@ -18,23 +18,47 @@
 //!
 //! Postgres connections, optional.
 //!
+//! ### SSO_KEY_SECRET
+//!
+//! Secret used to hash and encrypt key values, encrypt webhook secrets and sign audit log chain
+//! points, required.
+//!
 #[macro_use]
 extern crate clap;
 #[macro_use]
 extern crate log;
 
 use clap::{App, Arg, SubCommand};
-use sso::{log_init, KeyCreate, Postgres, ServiceCreate};
+use serde_json::Value;
+use sso::{
+    env, log_init, AuditBuilder, AuditExportFormat, AuditExportSink, AuditExportTarget, AuditMeta,
+    AuditRetentionDelete, AuditRetentionSet, AuditRetentionTask, AuditType, Cidr, KeyCreate,
+    KeyScope, OrganisationCreate, Postgres, ServiceCreate, UserRead, DEFAULT_AUDIT_EXPORT_NAME,
+    DEFAULT_AUDIT_RETENTION_DAYS,
+};
+use std::{path::PathBuf, str::FromStr};
+use uuid::Uuid;
 
 const CRATE_NAME: &str = crate_name!();
 const CRATE_VERSION: &str = crate_version!();
 const CRATE_DESCRIPTION: &str = crate_description!();
 const CRATE_AUTHORS: &str = "Sam Ward <git@mojzu.net>";
 
+const CMD_AUDIT: &str = "audit";
+const CMD_AUDIT_CHECKPOINT: &str = "checkpoint";
+const CMD_AUDIT_EXPORT: &str = "export";
+const CMD_AUDIT_RETENTION_DELETE: &str = "retention-delete";
+const CMD_AUDIT_RETENTION_LIST: &str = "retention-list";
+const CMD_AUDIT_RETENTION_SET: &str = "retention-set";
+const CMD_AUDIT_VERIFY: &str = "verify";
+const CMD_CREATE_ORGANISATION: &str = "create-organisation";
 const CMD_CREATE_ROOT_KEY: &str = "create-root-key";
 const CMD_CREATE_SERVICE_WITH_KEY: &str = "create-service-with-key";
 const CMD_TASK_RETENTION: &str = "task-retention";
+const CMD_USER_EXPORT: &str = "user-export";
+const CMD_USER_ERASE: &str = "user-erase";
 
+const ARG_ID: &str = "ID";
 const ARG_NAME: &str = "NAME";
 const ARG_URL: &str = "URL";
 const ARG_ALLOW_REGISTER: &str = "ALLOW_REGISTER";
@@ -43,6 +67,18 @@ const ARG_LOCAL_URL: &str = "LOCAL_URL";
 const ARG_GITHUB_OAUTH2_URL: &str = "GITHUB_OAUTH2_URL";
 const ARG_MICROSOFT_OAUTH2_URL: &str = "MICROSOFT_OAUTH2_URL";
 const ARG_WEEKS: &str = "WEEKS";
+const ARG_SCOPE: &str = "SCOPE";
+const ARG_ALLOW_REMOTE: &str = "ALLOW_REMOTE";
+const ARG_USER_CLAIMS: &str = "USER_CLAIMS";
+const ARG_ORGANISATION: &str = "ORGANISATION";
+const ARG_TARGET: &str = "TARGET";
+const ARG_FORMAT: &str = "FORMAT";
+const ARG_SINCE: &str = "SINCE";
+const ARG_MAX_BYTES: &str = "MAX_BYTES";
+const ARG_ARCHIVE: &str = "ARCHIVE";
+const ARG_DAYS: &str = "DAYS";
+const ARG_SERVICE: &str = "SERVICE";
+const ARG_TYPE: &str = "TYPE";
 
 fn main() {
     // Logging, error handling.
@@ -54,16 +90,125 @@ fn main() {
         .about(CRATE_DESCRIPTION)
         .author(CRATE_AUTHORS)
         .subcommands(vec![
+            SubCommand::with_name(CMD_AUDIT)
+                .version(CRATE_VERSION)
+                .about("Audit log chain, export and retention commands")
+                .author(CRATE_AUTHORS)
+                .subcommands(vec![
+                    SubCommand::with_name(CMD_AUDIT_CHECKPOINT)
+                        .version(CRATE_VERSION)
+                        .about("Create signed checkpoint of audit log chain")
+                        .author(CRATE_AUTHORS),
+                    SubCommand::with_name(CMD_AUDIT_EXPORT)
+                        .version(CRATE_VERSION)
+                        .about("Export audit logs from last exported position")
+                        .author(CRATE_AUTHORS)
+                        .args(&[
+                            Arg::with_name(ARG_TARGET)
+                                .help("Target URL (e.g. tls://host:6514, file:///path)")
+                                .required(true)
+                                .index(1),
+                            Arg::with_name(ARG_FORMAT)
+                                .long("format")
+                                .help("Format, one of syslog, cef or jsonl, defaults to jsonl")
+                                .takes_value(true)
+                                .required(false),
+                            Arg::with_name(ARG_NAME)
+                                .long("name")
+                                .help("Export position name, defaults to default")
+                                .takes_value(true)
+                                .required(false),
+                            Arg::with_name(ARG_SINCE)
+                                .long("since")
+                                .help("Export from RFC 3339 date and time, ignores position")
+                                .takes_value(true)
+                                .required(false),
+                            Arg::with_name(ARG_MAX_BYTES)
+                                .long("max-bytes")
+                                .help("Rotate file target when size exceeds bytes")
+                                .takes_value(true)
+                                .required(false),
+                        ]),
+                    SubCommand::with_name(CMD_AUDIT_RETENTION_DELETE)
+                        .version(CRATE_VERSION)
+                        .about("Delete audit log retention of service and type")
+                        .author(CRATE_AUTHORS)
+                        .args(&[
+                            Arg::with_name(ARG_SERVICE)
+                                .long("service")
+                                .help("Service UUID, matches all services if not defined")
+                                .takes_value(true)
+                                .required(false),
+                            Arg::with_name(ARG_TYPE)
+                                .long("type")
+                                .help("Audit type, matches all types if not defined")
+                                .takes_value(true)
+                                .required(false),
+                        ]),
+                    SubCommand::with_name(CMD_AUDIT_RETENTION_LIST)
+                        .version(CRATE_VERSION)
+                        .about("List audit log retentions")
+                        .author(CRATE_AUTHORS),
+                    SubCommand::with_name(CMD_AUDIT_RETENTION_SET)
+                        .version(CRATE_VERSION)
+                        .about("Set audit log retention of service and type in days")
+                        .author(CRATE_AUTHORS)
+                        .args(&[
+                            Arg::with_name(ARG_DAYS)
+                                .help("Retention days")
+                                .required(true)
+                                .index(1),
+                            Arg::with_name(ARG_SERVICE)
+                                .long("service")
+                                .help("Service UUID, matches all services if not defined")
+                                .takes_value(true)
+                                .required(false),
+                            Arg::with_name(ARG_TYPE)
+                                .long("type")
+                                .help("Audit type, matches all types if not defined")
+                                .takes_value(true)
+                                .required(false),
+                        ]),
+                    SubCommand::with_name(CMD_AUDIT_VERIFY)
+                        .version(CRATE_VERSION)
+                        .about("Verify audit log chain, reports where chain is broken")
+                        .author(CRATE_AUTHORS),
+                ]),
+            SubCommand::with_name(CMD_CREATE_ORGANISATION)
+                .version(CRATE_VERSION)
+                .about("Create an organisation")
+                .author(CRATE_AUTHORS)
+                .arg(
+                    Arg::with_name(ARG_NAME)
+                        .help("Organisation name")
+                        .required(true)
+                        .index(1),
+                ),
             SubCommand::with_name(CMD_CREATE_ROOT_KEY)
                 .version(CRATE_VERSION)
                 .about("Create a root key")
                 .author(CRATE_AUTHORS)
-                .arg(
+                .args(&[
                     Arg::with_name(ARG_NAME)
                         .help("Key name")
                         .required(true)
                         .index(1),
-                ),
+                    Arg::with_name(ARG_SCOPE)
+                        .long("scope")
+                        .help("Key scope, comma separated (e.g. user:read,user:write)")
+                        .takes_value(true)
+                        .required(false),
+                    Arg::with_name(ARG_ALLOW_REMOTE)
+                        .long("allow-remote")
+                        .help("Key allowed remote CIDR ranges, comma separated (e.g. 10.0.0.0/8)")
+                        .takes_value(true)
+                        .required(false),
+                    Arg::with_name(ARG_ORGANISATION)
+                        .long("organisation")
+                        .help("Organisation UUID, scopes key to organisation")
+                        .takes_value(true)
+                        .required(false),
+                ]),
             SubCommand::with_name(CMD_CREATE_SERVICE_WITH_KEY)
                 .version(CRATE_VERSION)
                 .about("Create service with service key")
@@ -102,29 +247,182 @@ fn main() {
                         .help("Microsoft OAuth2 provider callback URL")
                         .takes_value(true)
                         .required(false),
+                    Arg::with_name(ARG_SCOPE)
+                        .long("scope")
+                        .help("Service key scope, comma separated (e.g. user:read,user:write)")
+                        .takes_value(true)
+                        .required(false),
+                    Arg::with_name(ARG_ALLOW_REMOTE)
+                        .long("allow-remote")
+                        .help(
+                            "Service allowed remote CIDR ranges, comma separated (e.g. 10.0.0.0/8)",
+                        )
+                        .takes_value(true)
+                        .required(false),
+                    Arg::with_name(ARG_USER_CLAIMS)
+                        .long("user-claims")
+                        .help("User attribute keys included as token claims, comma separated")
+                        .takes_value(true)
+                        .required(false),
+                    Arg::with_name(ARG_ORGANISATION)
+                        .long("organisation")
+                        .help("Organisation UUID, service is owned by organisation")
+                        .takes_value(true)
+                        .required(false),
                 ]),
             SubCommand::with_name(CMD_TASK_RETENTION)
                 .version(CRATE_VERSION)
                 .about("Run retention task")
                 .author(CRATE_AUTHORS)
-                .arg(
+                .args(&[
                     Arg::with_name(ARG_WEEKS)
                         .long("weeks")
-                        .help("Weeks")
+                        .help("Default audit log retention in weeks, defaults to 12")
                         .takes_value(true)
                         .required(false),
+                    Arg::with_name(ARG_ARCHIVE)
+                        .long("archive")
+                        .help("Archive audit logs to directory before removal")
+                        .takes_value(true)
+                        .required(false),
+                ]),
+            SubCommand::with_name(CMD_USER_EXPORT)
+                .version(CRATE_VERSION)
+                .about("Export user data as JSON")
+                .author(CRATE_AUTHORS)
+                .arg(
+                    Arg::with_name(ARG_ID)
+                        .help("User UUID")
+                        .required(true)
+                        .index(1),
+                ),
+            SubCommand::with_name(CMD_USER_ERASE)
+                .version(CRATE_VERSION)
+                .about("Erase user, pseudonymises audit logs and deletes keys and user")
+                .author(CRATE_AUTHORS)
+                .arg(
+                    Arg::with_name(ARG_ID)
+                        .help("User UUID")
+                        .required(true)
+                        .index(1),
                 ),
         ])
         .get_matches();
 
     // Build driver from environment variables.
-    let driver = Postgres::from_env("SSO_POSTGRES_URL", "SSO_POSTGRES_CONNECTIONS");
+    let driver = Postgres::from_env(
+        "SSO_POSTGRES_URL",
+        "SSO_POSTGRES_CONNECTIONS",
+        "SSO_KEY_SECRET",
+    );
     let result = Ok(driver).and_then(|driver| {
         // Call library functions with command line arguments.
         match matches.subcommand() {
+            (CMD_AUDIT, Some(submatches)) => match submatches.subcommand() {
+                (CMD_AUDIT_CHECKPOINT, Some(_)) => {
+                    driver.audit_chain_checkpoint().map(|point| match point {
+                        Some(point) => {
+                            println!("{}", point);
+                            0
+                        }
+                        None => 1,
+                    })
+                }
+                (CMD_AUDIT_EXPORT, Some(submatches)) => {
+                    let target = submatches.value_of(ARG_TARGET).unwrap();
+                    let target = AuditExportTarget::from_str(target)?;
+                    let format = submatches.value_of(ARG_FORMAT).unwrap_or("jsonl");
+                    let format =
+                        AuditExportFormat::from_str(format).expect("Failed to parse format.");
+                    let name = submatches
+                        .value_of(ARG_NAME)
+                        .unwrap_or(DEFAULT_AUDIT_EXPORT_NAME);
+                    let since = submatches.value_of(ARG_SINCE).map(|x| {
+                        chrono::DateTime::parse_from_rfc3339(x)
+                            .expect("Failed to parse since.")
+                            .with_timezone(&chrono::Utc)
+                    });
+                    let max_bytes = submatches
+                        .value_of(ARG_MAX_BYTES)
+                        .map(|x| x.parse::<u64>().expect("Failed to parse max bytes."));
+                    let mut sink = AuditExportSink::new(target, format)
+                        .hostname(env::string_opt("HOSTNAME"))
+                        .file_max_bytes(max_bytes);
+                    driver
+                        .audit_export(name, since.as_ref(), &mut sink)
+                        .map(|count| {
+                            println!("{}", count);
+                            0
+                        })
+                }
+                (CMD_AUDIT_RETENTION_DELETE, Some(submatches)) => {
+                    let delete = AuditRetentionDelete {
+                        service_id: service_parse(submatches.value_of(ARG_SERVICE)),
+                        type_: submatches.value_of(ARG_TYPE).map(|x| x.to_owned()),
+                    };
+                    driver.audit_retention_delete(&delete).map(|count| {
+                        println!("{}", count);
+                        0
+                    })
+                }
+                (CMD_AUDIT_RETENTION_LIST, Some(_)) => {
+                    driver.audit_retention_list().map(|retention| {
+                        for retention in retention {
+                            println!("{}", retention);
+                        }
+                        0
+                    })
+                }
+                (CMD_AUDIT_RETENTION_SET, Some(submatches)) => {
+                    let days = submatches.value_of(ARG_DAYS).unwrap();
+                    let days = days
+                        .parse::<i32>()
+                        .ok()
+                        .filter(|x| *x > 0)
+                        .expect("Failed to parse days.");
+                    let set = AuditRetentionSet {
+                        service_id: service_parse(submatches.value_of(ARG_SERVICE)),
+                        type_: submatches.value_of(ARG_TYPE).map(|x| x.to_owned()),
+                        days,
+                    };
+                    driver.audit_retention_set(&set).map(|retention| {
+                        println!("{}", retention);
+                        0
+                    })
+                }
+                (CMD_AUDIT_VERIFY, Some(_)) => driver.audit_chain_verify().map(|verify| {
+                    println!("{}", verify);
+                    if verify.is_ok() {
+                        0
+                    } else {
+                        1
+                    }
+                }),
+                _ => {
+                    println!("{}", submatches.usage());
+                    Ok(1)
+                }
+            },
+            (CMD_CREATE_ORGANISATION, Some(submatches)) => {
+                let name = submatches.value_of(ARG_NAME).unwrap();
+                let create = OrganisationCreate {
+                    is_enabled: true,
+                    name: name.to_owned(),
+                };
+                driver.organisation_create(&create).map(|organisation| {
+                    println!("{}", organisation);
+                    0
+                })
+            }
             (CMD_CREATE_ROOT_KEY, Some(submatches)) => {
                 let name = submatches.value_of(ARG_NAME).unwrap();
-                let create = KeyCreate::root(true, name);
+                let scope = scope_parse(submatches.value_of(ARG_SCOPE));
+                let allow_remote = allow_remote_parse(submatches.value_of(ARG_ALLOW_REMOTE));
+                let organisation_id = organisation_parse(submatches.value_of(ARG_ORGANISATION));
+                let create = KeyCreate::root(true, name)
+                    .scope(scope)
+                    .allow_remote(allow_remote)
+                    .organisation_id(organisation_id);
                 driver.key_create(&create).map(|key| {
                     println!("{}", key);
                     0
@@ -153,9 +451,14 @@ fn main() {
                     provider_github_oauth2_url: provider_github_oauth2_url.map(|x| x.to_owned()),
                     provider_microsoft_oauth2_url: provider_microsoft_oauth2_url
                         .map(|x| x.to_owned()),
+                    allow_remote: allow_remote_parse(submatches.value_of(ARG_ALLOW_REMOTE)),
+                    user_claims: user_claims_parse(submatches.value_of(ARG_USER_CLAIMS)),
+                    organisation_id: organisation_parse(submatches.value_of(ARG_ORGANISATION)),
+                    user_require_email_verified: false,
                 };
                 let service = driver.service_create(&service_create)?;
-                let key_create = KeyCreate::service(true, name, service.id);
+                let scope = scope_parse(submatches.value_of(ARG_SCOPE));
+                let key_create = KeyCreate::service(true, name, service.id).scope(scope);
                 let key = driver.key_create(&key_create)?;
                 Ok((service, key)).map(|(service, key)| {
                     println!("{}", service);
@@ -164,15 +467,44 @@ fn main() {
                 })
             }
             (CMD_TASK_RETENTION, Some(submatches)) => {
-                let weeks = submatches.value_of(ARG_WEEKS).unwrap_or("12");
-                let weeks: i64 = weeks.parse().unwrap();
-                let audit_retention = chrono::Duration::weeks(weeks);
-                let created_at = chrono::Utc::now() - audit_retention;
-                driver.audit_delete(&created_at).map(|deleted| {
-                    println!("{}", deleted);
+                let days = submatches
+                    .value_of(ARG_WEEKS)
+                    .map(|x| x.parse::<i32>().expect("Failed to parse weeks.") * 7)
+                    .unwrap_or(DEFAULT_AUDIT_RETENTION_DAYS);
+                let archive = submatches.value_of(ARG_ARCHIVE).map(PathBuf::from);
+                let task = AuditRetentionTask::new(days).archive(archive);
+                driver.audit_retention(&task).map(|result| {
+                    println!("{}", result);
                     0
                 })
             }
+            (CMD_USER_EXPORT, Some(submatches)) => {
+                let id = user_id_parse(submatches.value_of(ARG_ID).unwrap());
+                let audit = cli_audit(AuditType::UserExport);
+                let export = driver.user_export(&id)?;
+                audit.create_data::<Value>(&driver, 0, Some(id.to_string()), None)?;
+                match export {
+                    Some(export) => {
+                        let data = serde_json::to_string_pretty(&export.to_value()).unwrap();
+                        println!("{}", data);
+                        Ok(0)
+                    }
+                    None => Ok(1),
+                }
+            }
+            (CMD_USER_ERASE, Some(submatches)) => {
+                let id = user_id_parse(submatches.value_of(ARG_ID).unwrap());
+                let audit = cli_audit(AuditType::UserErase);
+                match driver.user_read(&UserRead::Id(id))? {
+                    Some(_) => {
+                        let erased = driver.user_erase(&id)?;
+                        audit.create_data::<Value>(&driver, 0, Some(id.to_string()), None)?;
+                        println!("{}", erased);
+                        Ok(0)
+                    }
+                    None => Ok(1),
+                }
+            }
             _ => {
                 println!("{}", matches.usage());
                 Ok(1)
@@ -189,3 +521,57 @@ fn main() {
         }
     }
 }
+
+/// Parse comma separated key scope argument.
+fn scope_parse(scope: Option<&str>) -> Vec<KeyScope> {
+    match scope {
+        Some(scope) => scope
+            .split(',')
+            .map(|x| KeyScope::from_str(x.trim()).expect("Failed to parse key scope."))
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Parse comma separated allowed remote CIDR argument.
+fn allow_remote_parse(allow_remote: Option<&str>) -> Vec<Cidr> {
+    match allow_remote {
+        Some(allow_remote) => allow_remote
+            .split(',')
+            .map(|x| Cidr::from_str(x.trim()).expect("Failed to parse allowed remote CIDR."))
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Parse comma separated user claims argument.
+fn user_claims_parse(user_claims: Option<&str>) -> Vec<String> {
+    match user_claims {
+        Some(user_claims) => user_claims
+            .split(',')
+            .map(|x| x.trim().to_owned())
+            .filter(|x| !x.is_empty())
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Parse organisation UUID argument.
+fn organisation_parse(organisation: Option<&str>) -> Option<Uuid> {
+    organisation.map(|x| Uuid::parse_str(x.trim()).expect("Failed to parse organisation UUID."))
+}
+
+/// Parse service UUID argument.
+fn service_parse(service: Option<&str>) -> Option<Uuid> {
+    service.map(|x| Uuid::parse_str(x.trim()).expect("Failed to parse service UUID."))
+}
+
+/// Parse user UUID argument.
+fn user_id_parse(id: &str) -> Uuid {
+    Uuid::parse_str(id.trim()).expect("Failed to parse user UUID.")
+}
+
+/// Audit log builder for command line operations.
+fn cli_audit(type_: AuditType) -> AuditBuilder {
+    AuditBuilder::new(AuditMeta::new(CRATE_NAME, "", None, None), type_)
+}
@@ -18,6 +18,11 @@
 //!
 //! Postgres connections, optional.
 //!
+//! ### SSO_KEY_SECRET
+//!
+//! Secret used to hash and encrypt key values, encrypt webhook secrets and sign audit log chain
+//! points, required.
+//!
 //! ### SSO_USER_AGENT
 //!
 //! HTTP client user agent header, optional, defaults to `sso`.
@@ -30,6 +35,15 @@
 //!
 //! Traefik forward authentcation integration enabled, optional, defaults to false.
 //!
+//! ### SSO_TRUSTED_PROXY
+//!
+//! Comma separated trusted proxy CIDR ranges, optional. Client address of requests
+//! from these ranges is resolved from `Forwarded` or `X-Forwarded-For` headers.
+//!
+//! ### SSO_LOGIN_NOTIFY
+//!
+//! Email users when they login from a new device or location, optional, defaults to false.
+//!
 //! ### SSO_TLS_CERT
 //!
 //! Path to TLS certificate in PEM format, optional.
@@ -78,6 +92,38 @@
 //!
 //! Microsoft OAuth2 provider client secret, optional.
 //!
+//! ### SSO_AUDIT_EXPORT_TARGET
+//!
+//! Audit export target URL, optional, exports audit logs in background if defined
+//! (e.g. `udp://host:514`, `tcp://host:514`, `tls://host:6514`, `file:///path`).
+//!
+//! ### SSO_AUDIT_EXPORT_FORMAT
+//!
+//! Audit export format, one of `syslog`, `cef` or `jsonl`, optional, defaults to `jsonl`.
+//!
+//! ### SSO_AUDIT_EXPORT_NAME
+//!
+//! Audit export position name, optional, defaults to `default`.
+//!
+//! ### SSO_AUDIT_EXPORT_MAX_BYTES
+//!
+//! Audit export file target size in bytes before rotation, optional.
+//!
+//! ### SSO_AUDIT_GEOIP_CITY
+//!
+//! Path to MaxMind format city or country database file, optional. Audit logs
+//! are enriched with country and city of remote IP address if defined.
+//!
+//! ### SSO_AUDIT_GEOIP_ASN
+//!
+//! Path to MaxMind format ASN database file, optional. Audit logs are enriched
+//! with autonomous system number and organisation of remote IP address if defined.
+//!
+//! ### SSO_AUDIT_USER_AGENT
+//!
+//! Audit logs are enriched with browser, OS and device family parsed from user agent,
+//! optional, defaults to false.
+//!
 #[macro_use]
 extern crate log;
 
@@ -86,7 +132,10 @@ use hyper::{
     server::conn::AddrStream,
     service::{make_service_fn, service_fn},
 };
-use sso::{log_init, GrpcServer, GrpcServerOptions, HttpServer, Postgres};
+use sso::{
+    env, log_init, AuditEnrich, AuditExportFormat, AuditExportSink, AuditExportTarget, GrpcServer,
+    GrpcServerOptions, HttpServer, Postgres, DEFAULT_AUDIT_EXPORT_NAME,
+};
 use std::sync::Arc;
 use tonic::transport::Server;
 
@@ -103,12 +152,23 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let _guard = log_init("SSO_SENTRY_DSN", "SSO_LOG_PRETTY");
 
     // Postgres connection.
-    let driver = Postgres::from_env("SSO_POSTGRES_URL", "SSO_POSTGRES_CONNECTIONS");
+    let driver = Postgres::from_env(
+        "SSO_POSTGRES_URL",
+        "SSO_POSTGRES_CONNECTIONS",
+        "SSO_KEY_SECRET",
+    )
+    .audit_enrich(AuditEnrich::from_env(
+        "SSO_AUDIT_GEOIP_CITY",
+        "SSO_AUDIT_GEOIP_ASN",
+        "SSO_AUDIT_USER_AGENT",
+    ));
 
     // gRPC, HTTP server options.
     let grpc_options =
         GrpcServerOptions::from_env("SSO_USER_AGENT", "SSO_PWNED_PASSWORDS", "SSO_TRAEFIK")
             .tls_from_env("SSO_TLS_CERT", "SSO_TLS_KEY", "SSO_TLS_CLIENT_CA_CERT")
+            .trusted_proxy_from_env("SSO_TRUSTED_PROXY")
+            .webhook_allow_from_env("SSO_WEBHOOK_ALLOW")
             .smtp_transport_from_env(
                 "SSO_SMTP_HOST",
                 "SSO_SMTP_PORT",
@@ -116,6 +176,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 "SSO_SMTP_PASSWORD",
             )
             .smtp_file_transport_from_env("SSO_SMTP_FILE")
+            .login_notify_from_env("SSO_LOGIN_NOTIFY")
             .github_from_env("SSO_GITHUB_CLIENT_ID", "SSO_GITHUB_CLIENT_SECRET")
             .microsoft_from_env("SSO_MICROSOFT_CLIENT_ID", "SSO_MICROSOFT_CLIENT_SECRET");
     let grpc_tls_config = grpc_options.tls_config();
@@ -124,6 +185,33 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let sso = GrpcServer::new(driver, grpc_options);
     let http_sso = Arc::new(sso.clone());
 
+    // Webhook delivery worker.
+    tokio::spawn(sso.clone().webhook_worker());
+
+    // Audit export sink, optional.
+    let audit_export_target: Option<AuditExportTarget> =
+        env::value_opt("SSO_AUDIT_EXPORT_TARGET").expect("Audit export target is invalid.");
+    if let Some(target) = audit_export_target {
+        let format: AuditExportFormat = env::value_opt("SSO_AUDIT_EXPORT_FORMAT")
+            .expect("Audit export format is invalid.")
+            .unwrap_or(AuditExportFormat::Jsonl);
+        let name = env::string_opt("SSO_AUDIT_EXPORT_NAME")
+            .unwrap_or_else(|| DEFAULT_AUDIT_EXPORT_NAME.to_owned());
+        let max_bytes: Option<u64> = env::value_opt("SSO_AUDIT_EXPORT_MAX_BYTES")
+            .expect("Audit export max bytes is invalid.");
+        let mut sink = AuditExportSink::new(target, format)
+            .hostname(env::string_opt("HOSTNAME"))
+            .file_max_bytes(max_bytes);
+        let driver = sso.driver();
+        info!("Exporting audit logs as {}", format);
+        tokio::task::spawn_blocking(move || loop {
+            if let Err(e) = driver.audit_export(&name, None, &mut sink) {
+                warn!("{}", e);
+            }
+            std::thread::sleep(std::time::Duration::from_secs(5));
+        });
+    }
+
     // gRPC server.
     let grpc = {
         let addr = "0.0.0.0:7042".parse()?;
@@ -2,7 +2,11 @@ use crate::prelude::*;
 use http::{HeaderMap, HeaderValue};
 use serde::ser::Serialize;
 use serde_json::Value;
-use std::fmt;
+use std::{
+    convert::TryFrom,
+    fmt,
+    net::{IpAddr, SocketAddr},
+};
 
 /// Audit type maximum length.
 pub const MAX_AUDIT_TYPE: usize = 200;
@@ -10,6 +14,21 @@ pub const MAX_AUDIT_TYPE: usize = 200;
 /// Audit subject maximum length.
 pub const MAX_AUDIT_SUBJECT: usize = 200;
 
+/// Audit data filter maximum path length.
+pub const MAX_AUDIT_DATA_PATH: usize = 10;
+
+/// Audit report maximum number of time buckets.
+pub const MAX_AUDIT_REPORT_BUCKETS: i64 = 10_080;
+
+/// Audit report maximum number of rows.
+pub const MAX_AUDIT_REPORT_ROWS: i64 = 10_000;
+
+/// Number of days of login history compared to detect new login fingerprints.
+pub const AUDIT_LOGIN_HISTORY_DAYS: i64 = 90;
+
+/// Maximum number of logins in history compared to detect new login fingerprints.
+pub const AUDIT_LOGIN_HISTORY_LIMIT: i64 = 1_000;
+
 /// Audit types.
 #[derive(Debug, Copy, PartialEq, Clone, Serialize, Deserialize)]
 pub enum AuditType {
@@ -19,22 +38,61 @@ pub enum AuditType {
     AuditCreate,
     AuditRead,
     AuditUpdate,
+    AuditWatch,
+    AuditReport,
+    GroupList,
+    GroupCreate,
+    GroupRead,
+    GroupUpdate,
+    GroupDelete,
+    GroupUserCreate,
+    GroupUserDelete,
+    GroupRoleCreate,
+    GroupRoleDelete,
     KeyList,
     KeyCreate,
     KeyRead,
     KeyUpdate,
+    KeyRotate,
     KeyDelete,
+    OrganisationList,
+    OrganisationCreate,
+    OrganisationRead,
+    OrganisationUpdate,
+    OrganisationDelete,
+    RoleList,
+    RoleCreate,
+    RoleRead,
+    RoleUpdate,
+    RoleDelete,
+    UserRoleCreate,
+    UserRoleDelete,
     ServiceList,
     ServiceCreate,
     ServiceRead,
     ServiceUpdate,
     ServiceDelete,
+    ServiceUserList,
+    ServiceUserUpdate,
     UserList,
     UserCreate,
     UserRead,
     UserUpdate,
     UserDelete,
+    UserExport,
+    UserErase,
+    UserInvite,
+    UserInviteResend,
+    UserInviteRevoke,
+    WebhookList,
+    WebhookCreate,
+    WebhookRead,
+    WebhookUpdate,
+    WebhookDelete,
+    WebhookDeliveryList,
+    WebhookDeliveryRetry,
     AuthLocalLogin,
+    AuthLocalLoginRevoke,
     AuthLocalRegister,
     AuthLocalRegisterConfirm,
     AuthLocalRegisterRevoke,
@@ -42,9 +100,13 @@ pub enum AuditType {
     AuthLocalResetPasswordConfirm,
     AuthLocalResetPasswordRevoke,
     AuthLocalUpdateEmail,
+    AuthLocalUpdateEmailConfirm,
     AuthLocalUpdateEmailRevoke,
     AuthLocalUpdatePassword,
     AuthLocalUpdatePasswordRevoke,
+    AuthLocalVerifyEmail,
+    AuthLocalVerifyEmailConfirm,
+    AuthLocalInviteConfirm,
     AuthGithubOauth2Url,
     AuthGithubOauth2Callback,
     AuthMicrosoftOauth2Url,
@@ -55,6 +117,7 @@ pub enum AuditType {
     AuthTokenVerify,
     AuthTokenRefresh,
     AuthTokenRevoke,
+    AuthImpersonate,
     AuthTotp,
     AuthCsrfCreate,
     AuthCsrfVerify,
@@ -62,6 +125,80 @@ pub enum AuditType {
 
 impl_enum_to_from_string!(AuditType, "sso:");
 
+impl AuditType {
+    /// User login audit types.
+    pub const LOGIN: [AuditType; 3] = [
+        Self::AuthLocalLogin,
+        Self::AuthGithubOauth2Callback,
+        Self::AuthMicrosoftOauth2Callback,
+    ];
+}
+
+/// Audit login fingerprint.
+///
+/// User agent family and remote network of a login, used to detect logins
+/// from new devices or locations. IPv4 addresses are masked to /24 networks,
+/// IPv6 addresses to /48 networks.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AuditLoginFingerprint {
+    pub user_agent_family: String,
+    pub remote_network: String,
+}
+
+impl AuditLoginFingerprint {
+    pub fn new(user_agent: &str, remote: &str) -> Self {
+        Self {
+            user_agent_family: Self::user_agent_family(user_agent).to_owned(),
+            remote_network: Self::remote_network(remote),
+        }
+    }
+
+    /// Returns fingerprint of audit metadata.
+    pub fn from_meta(meta: &AuditMeta) -> Self {
+        Self::new(meta.user_agent(), meta.remote())
+    }
+
+    /// Returns fingerprint of audit log.
+    pub fn from_audit(audit: &Audit) -> Self {
+        Self::new(&audit.user_agent, &audit.remote)
+    }
+
+    /// Returns user agent family, tokens are checked in order as most user
+    /// agents include the tokens of browsers they are derived from.
+    fn user_agent_family(user_agent: &str) -> &'static str {
+        const FAMILY: [(&str, &str); 8] = [
+            ("Edg/", "Edge"),
+            ("Edge/", "Edge"),
+            ("OPR/", "Opera"),
+            ("Firefox/", "Firefox"),
+            ("Chromium/", "Chromium"),
+            ("Chrome/", "Chrome"),
+            ("Safari/", "Safari"),
+            ("curl/", "curl"),
+        ];
+        FAMILY
+            .iter()
+            .find(|(token, _)| user_agent.contains(token))
+            .map(|(_, family)| *family)
+            .unwrap_or("Other")
+    }
+
+    /// Returns remote network, or remote string if it is not an address.
+    fn remote_network(remote: &str) -> String {
+        match AuditMeta::remote_addr_parse(remote) {
+            Some(IpAddr::V4(addr)) => {
+                let o = addr.octets();
+                format!("{}.{}.{}.0/24", o[0], o[1], o[2])
+            }
+            Some(IpAddr::V6(addr)) => {
+                let s = addr.segments();
+                format!("{:x}:{:x}:{:x}::/48", s[0], s[1], s[2])
+            }
+            None => remote.to_owned(),
+        }
+    }
+}
+
 /// Audit.
 #[derive(Debug, Clone)]
 pub struct Audit {
@@ -79,6 +216,10 @@ pub struct Audit {
     pub service_id: Option<Uuid>,
     pub user_id: Option<Uuid>,
     pub user_key_id: Option<Uuid>,
+    pub organisation_id: Option<Uuid>,
+    pub actor_key_id: Option<Uuid>,
+    pub proxy: Option<AuditProxy>,
+    pub enrichment: AuditEnrichment,
 }
 
 impl fmt::Display for Audit {
@@ -107,10 +248,94 @@ impl fmt::Display for Audit {
         if let Some(user_key_id) = &self.user_key_id {
             write!(f, "\n\tuser_key_id {}", user_key_id)?;
         }
+        if let Some(organisation_id) = &self.organisation_id {
+            write!(f, "\n\torganisation_id {}", organisation_id)?;
+        }
+        if let Some(actor_key_id) = &self.actor_key_id {
+            write!(f, "\n\tactor_key_id {}", actor_key_id)?;
+        }
+        if let Some(proxy) = &self.proxy {
+            write!(f, "\n\tproxy {}", proxy)?;
+        }
+        if !self.enrichment.is_empty() {
+            write!(f, "\n\tenrichment {}", json!(self.enrichment))?;
+        }
         Ok(())
     }
 }
 
+impl Audit {
+    /// Returns JSON value of audit log.
+    pub fn to_value(&self) -> Value {
+        json!({
+            "created_at": self.created_at,
+            "updated_at": self.updated_at,
+            "id": self.id,
+            "user_agent": self.user_agent,
+            "remote": self.remote,
+            "forwarded": self.forwarded,
+            "status_code": self.status_code,
+            "type": self.type_,
+            "subject": self.subject,
+            "data": self.data,
+            "key_id": self.key_id,
+            "service_id": self.service_id,
+            "user_id": self.user_id,
+            "user_key_id": self.user_key_id,
+            "organisation_id": self.organisation_id,
+            "actor_key_id": self.actor_key_id,
+            "proxy": self.proxy,
+            "enrichment": self.enrichment,
+        })
+    }
+}
+
+/// Audit proxy.
+///
+/// Peer address and proxy headers of request, peer is only set if
+/// remote was resolved from headers of a trusted proxy.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditProxy {
+    pub peer: Option<String>,
+    pub forwarded: Option<String>,
+    pub real_ip: Option<String>,
+    pub forwarded_host: Option<String>,
+    pub forwarded_uri: Option<String>,
+}
+
+impl fmt::Display for AuditProxy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AuditProxy")?;
+        if let Some(peer) = &self.peer {
+            write!(f, " peer={}", peer)?;
+        }
+        if let Some(forwarded) = &self.forwarded {
+            write!(f, " forwarded={}", forwarded)?;
+        }
+        if let Some(real_ip) = &self.real_ip {
+            write!(f, " real_ip={}", real_ip)?;
+        }
+        if let Some(forwarded_host) = &self.forwarded_host {
+            write!(f, " forwarded_host={}", forwarded_host)?;
+        }
+        if let Some(forwarded_uri) = &self.forwarded_uri {
+            write!(f, " forwarded_uri={}", forwarded_uri)?;
+        }
+        Ok(())
+    }
+}
+
+impl AuditProxy {
+    /// Returns true if no fields are set.
+    pub fn is_empty(&self) -> bool {
+        self.peer.is_none()
+            && self.forwarded.is_none()
+            && self.real_ip.is_none()
+            && self.forwarded_host.is_none()
+            && self.forwarded_uri.is_none()
+    }
+}
+
 /// Audit create.
 #[derive(Debug)]
 pub struct AuditCreate {
@@ -123,6 +348,8 @@ pub struct AuditCreate {
     pub service_id: Option<Uuid>,
     pub user_id: Option<Uuid>,
     pub user_key_id: Option<Uuid>,
+    pub organisation_id: Option<Uuid>,
+    pub actor_key_id: Option<Uuid>,
 }
 
 impl AuditCreate {
@@ -137,6 +364,8 @@ impl AuditCreate {
             service_id: None,
             user_id: None,
             user_key_id: None,
+            organisation_id: None,
+            actor_key_id: None,
         }
     }
 
@@ -174,6 +403,16 @@ impl AuditCreate {
         self.user_key_id = user_key_id;
         self
     }
+
+    pub fn organisation_id(mut self, organisation_id: Option<Uuid>) -> Self {
+        self.organisation_id = organisation_id;
+        self
+    }
+
+    pub fn actor_key_id(mut self, actor_key_id: Option<Uuid>) -> Self {
+        self.actor_key_id = actor_key_id;
+        self
+    }
 }
 
 /// Audit list query.
@@ -188,13 +427,60 @@ pub enum AuditListQuery {
 }
 
 /// Audit list filter.
-#[derive(Debug)]
+///
+/// Remote and forwarded are matched by prefix, user agent by
+/// case insensitive substring. Enrichment fields are matched exactly.
+#[derive(Debug, Default)]
 pub struct AuditListFilter {
     pub id: Option<Vec<Uuid>>,
     pub type_: Option<Vec<String>>,
     pub subject: Option<Vec<String>>,
     pub service_id: Option<Vec<Uuid>>,
     pub user_id: Option<Vec<Uuid>>,
+    pub organisation_id: Option<Vec<Uuid>>,
+    pub status_code_ge: Option<u16>,
+    pub status_code_le: Option<u16>,
+    pub remote: Option<String>,
+    pub remote_cidr: Option<Vec<Cidr>>,
+    pub forwarded: Option<String>,
+    pub user_agent: Option<String>,
+    pub key_id: Option<Vec<Uuid>>,
+    pub user_key_id: Option<Vec<Uuid>>,
+    pub data: Option<Vec<AuditDataFilter>>,
+    pub remote_country: Option<Vec<String>>,
+    pub remote_city: Option<Vec<String>>,
+    pub remote_asn: Option<Vec<i64>>,
+    pub user_agent_browser: Option<Vec<String>>,
+    pub user_agent_os: Option<Vec<String>>,
+    pub user_agent_device: Option<Vec<String>>,
+}
+
+/// Audit list data filter.
+///
+/// Matches logs where the data value at path is equal to value,
+/// or contains value if it is an array or object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditDataFilter {
+    pub path: Vec<String>,
+    pub value: Value,
+}
+
+impl AuditDataFilter {
+    pub fn new(path: Vec<String>, value: Value) -> Self {
+        Self { path, value }
+    }
+
+    /// Returns object containing value at path, used for containment queries.
+    pub fn to_value(&self) -> Value {
+        self.path
+            .iter()
+            .rev()
+            .fold(self.value.clone(), |value, key| {
+                let mut m = serde_json::Map::default();
+                m.insert(key.to_owned(), value);
+                Value::Object(m)
+            })
+    }
 }
 
 /// Audit list.
@@ -222,6 +508,147 @@ impl AuditRead {
     }
 }
 
+/// Audit watch.
+#[derive(Debug)]
+pub struct AuditWatch {
+    pub offset_id: Option<Uuid>,
+    pub filter: AuditListFilter,
+}
+
+/// Audit report time bucket.
+#[derive(Debug, Copy, PartialEq, Clone, Serialize, Deserialize)]
+pub enum AuditReportBucket {
+    Minute,
+    Hour,
+    Day,
+}
+
+impl AuditReportBucket {
+    pub fn from_i32(v: i32) -> Self {
+        match v {
+            0 => Self::Minute,
+            1 => Self::Hour,
+            2 => Self::Day,
+            _ => unimplemented!(),
+        }
+    }
+
+    /// Returns field name used by `date_trunc`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Minute => "minute",
+            Self::Hour => "hour",
+            Self::Day => "day",
+        }
+    }
+
+    /// Returns bucket duration.
+    pub fn duration(self) -> Duration {
+        match self {
+            Self::Minute => Duration::minutes(1),
+            Self::Hour => Duration::hours(1),
+            Self::Day => Duration::days(1),
+        }
+    }
+}
+
+impl TryFrom<i32> for AuditReportBucket {
+    type Error = ();
+
+    fn try_from(v: i32) -> Result<Self, Self::Error> {
+        match v {
+            0 => Ok(Self::Minute),
+            1 => Ok(Self::Hour),
+            2 => Ok(Self::Day),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Audit report group dimension.
+#[derive(Debug, Copy, PartialEq, Clone, Serialize, Deserialize)]
+pub enum AuditReportGroup {
+    Type,
+    StatusCode,
+    ServiceId,
+    UserId,
+    Remote,
+}
+
+impl AuditReportGroup {
+    pub fn from_i32(v: i32) -> Self {
+        match v {
+            0 => Self::Type,
+            1 => Self::StatusCode,
+            2 => Self::ServiceId,
+            3 => Self::UserId,
+            4 => Self::Remote,
+            _ => unimplemented!(),
+        }
+    }
+}
+
+impl TryFrom<i32> for AuditReportGroup {
+    type Error = ();
+
+    fn try_from(v: i32) -> Result<Self, Self::Error> {
+        match v {
+            0 => Ok(Self::Type),
+            1 => Ok(Self::StatusCode),
+            2 => Ok(Self::ServiceId),
+            3 => Ok(Self::UserId),
+            4 => Ok(Self::Remote),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Audit report filter.
+#[derive(Debug, Default)]
+pub struct AuditReportFilter {
+    pub type_: Option<Vec<String>>,
+    pub service_id: Option<Vec<Uuid>>,
+    pub user_id: Option<Vec<Uuid>>,
+}
+
+/// Audit report.
+///
+/// Counts audit logs created in range by time bucket, grouped by dimensions.
+/// Dimensions which are not grouped are none in report rows.
+#[derive(Debug)]
+pub struct AuditReport {
+    pub ge: DateTime<Utc>,
+    pub le: DateTime<Utc>,
+    pub bucket: AuditReportBucket,
+    pub group: Vec<AuditReportGroup>,
+    pub filter: AuditReportFilter,
+}
+
+impl AuditReport {
+    /// Returns true if report is grouped by dimension.
+    pub fn is_grouped(&self, group: AuditReportGroup) -> bool {
+        self.group.contains(&group)
+    }
+
+    /// Returns number of time buckets in report range.
+    pub fn buckets(&self) -> i64 {
+        let range = self.le.signed_duration_since(self.ge).num_seconds();
+        range / self.bucket.duration().num_seconds() + 1
+    }
+}
+
+/// Audit report row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditReportRow {
+    pub bucket: DateTime<Utc>,
+    pub type_: Option<String>,
+    pub status_code: Option<u16>,
+    pub service_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+    pub remote: Option<String>,
+    pub count: i64,
+}
+
 /// Audit update.
 #[derive(Debug)]
 pub struct AuditUpdate {
@@ -231,6 +658,136 @@ pub struct AuditUpdate {
     pub data: Option<Value>,
 }
 
+/// Audit chain point types.
+#[derive(Debug, Copy, PartialEq, Clone, Serialize, Deserialize)]
+pub enum AuditChainPointType {
+    Checkpoint,
+    Truncate,
+    Delete,
+    Supersede,
+}
+
+impl_enum_to_from_string!(AuditChainPointType, "");
+
+/// Audit chain point.
+///
+/// Signed record of the audit log hash chain at a sequence number. Checkpoints
+/// are created periodically, truncation points anchor the start of the chain.
+/// Deletion points are created by the retention task and record the previous
+/// hash of audit logs which follow removed audit logs. Supersession points
+/// record the sequence number and hash of audit logs before they were updated
+/// or erased and appended to the chain again.
+#[derive(Debug, Clone)]
+pub struct AuditChainPoint {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+    pub type_: AuditChainPointType,
+    pub chain_seq: i64,
+    pub chain_hash: String,
+    pub count: i64,
+    pub signature: String,
+}
+
+impl AuditChainPoint {
+    /// Returns value signed by point signature.
+    pub fn signature_value(
+        id: &Uuid,
+        type_: AuditChainPointType,
+        chain_seq: i64,
+        chain_hash: &str,
+        count: i64,
+    ) -> String {
+        format!("{}:{}:{}:{}:{}", id, type_, chain_seq, chain_hash, count)
+    }
+}
+
+impl fmt::Display for AuditChainPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AuditChainPoint {}", self.id)?;
+        write!(f, "\n\tcreated_at {}", self.created_at)?;
+        write!(f, "\n\ttype {}", self.type_)?;
+        write!(f, "\n\tchain_seq {}", self.chain_seq)?;
+        write!(f, "\n\tchain_hash {}", self.chain_hash)?;
+        write!(f, "\n\tcount {}", self.count)
+    }
+}
+
+/// Audit chain verification errors.
+#[derive(Debug, Copy, PartialEq, Clone)]
+pub enum AuditChainErrorType {
+    /// Audit log is not part of chain.
+    Unchained,
+    /// Audit log previous hash does not match preceding audit log.
+    Link,
+    /// Audit log contents do not match hash.
+    Hash,
+    /// Chain point signature is invalid.
+    PointSignature,
+    /// Chain point hash does not match audit log at sequence number.
+    PointHash,
+}
+
+impl fmt::Display for AuditChainErrorType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unchained => write!(f, "audit log is not chained"),
+            Self::Link => write!(f, "previous hash does not match"),
+            Self::Hash => write!(f, "contents do not match hash"),
+            Self::PointSignature => write!(f, "point signature is invalid"),
+            Self::PointHash => write!(f, "point hash does not match"),
+        }
+    }
+}
+
+/// Audit chain verification error, location where the chain breaks.
+#[derive(Debug, Clone)]
+pub struct AuditChainError {
+    pub type_: AuditChainErrorType,
+    pub chain_seq: Option<i64>,
+    pub id: Option<Uuid>,
+}
+
+impl fmt::Display for AuditChainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.type_)?;
+        if let Some(chain_seq) = &self.chain_seq {
+            write!(f, " at chain_seq {}", chain_seq)?;
+        }
+        if let Some(id) = &self.id {
+            write!(f, " id {}", id)?;
+        }
+        Ok(())
+    }
+}
+
+/// Audit chain verification result.
+#[derive(Debug, Default)]
+pub struct AuditChainVerify {
+    pub count: i64,
+    pub points: i64,
+    pub errors: Vec<AuditChainError>,
+}
+
+impl AuditChainVerify {
+    /// Returns true if no errors were found.
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+impl fmt::Display for AuditChainVerify {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AuditChainVerify")?;
+        write!(f, "\n\tcount {}", self.count)?;
+        write!(f, "\n\tpoints {}", self.points)?;
+        write!(f, "\n\terrors {}", self.errors.len())?;
+        for error in self.errors.iter() {
+            write!(f, "\n\t\t{}", error)?;
+        }
+        Ok(())
+    }
+}
+
 /// Audit metadata.
 ///
 /// Request information collected from headers or other sources.
@@ -238,8 +795,11 @@ pub struct AuditUpdate {
 pub struct AuditMeta {
     user_agent: String,
     remote: String,
+    remote_addr: Option<IpAddr>,
     forwarded: Option<String>,
+    proxy: Option<AuditProxy>,
     user: Option<HeaderAuthType>,
+    scope: Option<KeyScope>,
 }
 
 impl AuditMeta {
@@ -254,23 +814,47 @@ impl AuditMeta {
         U: Into<String>,
         R: Into<String>,
     {
+        let remote = remote.into();
+        let remote_addr = Self::remote_addr_parse(&remote);
         AuditMeta {
             user_agent: user_agent.into(),
-            remote: remote.into(),
+            remote,
+            remote_addr,
             forwarded,
+            proxy: None,
             user,
+            scope: None,
         }
     }
 
     /// Create audit metadata from header map.
-    pub fn from_header_map<R>(map: &HeaderMap<HeaderValue>, remote: R) -> Self
+    ///
+    /// If remote is in trusted proxy ranges, remote is resolved from
+    /// forwarded headers.
+    pub fn from_header_map<R>(
+        map: &HeaderMap<HeaderValue>,
+        remote: R,
+        trusted_proxy: &[Cidr],
+    ) -> Self
     where
         R: Into<String>,
     {
         let user_agent = header::user_agent(map);
         let forwarded = header::x_forwarded_for(map);
         let user_authorisation = header::user_authorisation(map);
-        Self::new(user_agent, remote, forwarded, user_authorisation)
+        let mut meta = Self::new(user_agent, remote, forwarded, user_authorisation);
+        let proxy = AuditProxy {
+            peer: None,
+            forwarded: header::forwarded(map),
+            real_ip: header::x_real_ip(map),
+            forwarded_host: header::x_forwarded_host(map),
+            forwarded_uri: header::x_forwarded_uri(map),
+        };
+        if !proxy.is_empty() {
+            meta.proxy = Some(proxy);
+        }
+        meta.remote_resolve(trusted_proxy, false);
+        meta
     }
 
     /// User agent string reference.
@@ -279,19 +863,155 @@ impl AuditMeta {
     }
 
     /// Remote IP string reference.
+    ///
+    /// If request was forwarded by a trusted proxy this is the resolved
+    /// client IP address, and the proxy address is stored as peer.
     pub fn remote(&self) -> &str {
         &self.remote
     }
 
+    /// Remote IP address, checked against service and key allowed remote arrays.
+    pub fn remote_addr(&self) -> Option<IpAddr> {
+        self.remote_addr
+    }
+
+    /// Resolve remote IP address from forwarded headers, peer is trusted
+    /// as a proxy even if it is not in trusted proxy ranges.
+    /// This must only be called for requests from a trusted proxy.
+    pub fn set_remote_forwarded(&mut self, trusted_proxy: &[Cidr]) -> &mut Self {
+        self.remote_resolve(trusted_proxy, true);
+        self
+    }
+
     /// Forwarded for header optional string reference.
     pub fn forwarded(&self) -> Option<&str> {
         self.forwarded.as_ref().map(|x| &**x)
     }
 
+    /// Proxy peer address and headers optional reference.
+    pub fn proxy(&self) -> Option<&AuditProxy> {
+        self.proxy.as_ref()
+    }
+
+    /// Returns forwarded for header value used when request is proxied
+    /// to another server, forwarded addresses with peer address appended.
+    pub fn forwarded_for(&self) -> Option<String> {
+        let mut forwarded = self.forwarded_chain();
+        let peer = self.peer();
+        match Self::remote_addr_parse(peer) {
+            Some(peer) => forwarded.push(peer.to_string()),
+            None if !peer.is_empty() => forwarded.push(peer.to_owned()),
+            None => {}
+        }
+        if forwarded.is_empty() {
+            None
+        } else {
+            Some(forwarded.join(", "))
+        }
+    }
+
     /// User authorisation optional reference.
     pub fn user(&self) -> Option<&HeaderAuthType> {
         self.user.as_ref()
     }
+
+    /// Set key scope required by request.
+    pub fn set_scope(&mut self, scope: Option<KeyScope>) -> &mut Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Key scope required by request.
+    pub fn scope(&self) -> Option<KeyScope> {
+        self.scope
+    }
+
+    /// Returns address of connected peer.
+    fn peer(&self) -> &str {
+        match self.proxy.as_ref().and_then(|x| x.peer.as_ref()) {
+            Some(peer) => peer,
+            None => &self.remote,
+        }
+    }
+
+    /// Returns forwarded addresses in order, from `X-Forwarded-For` header
+    /// if present, else from `Forwarded` header. Proxies such as Traefik
+    /// append to `X-Forwarded-For` but pass a client supplied `Forwarded`
+    /// header through unchanged, so it is only used if it is the only header.
+    fn forwarded_chain(&self) -> Vec<String> {
+        match (
+            self.forwarded.as_ref(),
+            self.proxy.as_ref().and_then(|x| x.forwarded.as_ref()),
+        ) {
+            (None, Some(forwarded)) => header::forwarded_for(forwarded),
+            (forwarded_for, _) => forwarded_for
+                .iter()
+                .flat_map(|x| x.split(','))
+                .map(|x| x.trim())
+                .filter(|x| !x.is_empty())
+                .map(|x| x.to_owned())
+                .collect(),
+        }
+    }
+
+    /// Resolve remote IP address if peer is a trusted proxy.
+    ///
+    /// Forwarded addresses are walked from the right, skipping trusted
+    /// proxies, the first untrusted address is the client. If all addresses
+    /// are trusted the leftmost is the client. Walk stops at an address
+    /// which cannot be parsed, such as `unknown` or an obfuscated identifier.
+    /// If no addresses are forwarded, `X-Real-IP` header is used.
+    fn remote_resolve(&mut self, trusted_proxy: &[Cidr], peer_trusted: bool) {
+        let peer = self.peer().to_owned();
+        let is_trusted = |addr: &IpAddr| trusted_proxy.iter().any(|x| x.contains(addr));
+        match Self::remote_addr_parse(&peer) {
+            Some(peer_addr) if peer_trusted || is_trusted(&peer_addr) => {}
+            _ => return,
+        }
+
+        let forwarded = self.forwarded_chain();
+        let mut remote = None;
+        if forwarded.is_empty() {
+            remote = self
+                .proxy
+                .as_ref()
+                .and_then(|x| x.real_ip.as_ref())
+                .and_then(|x| Self::remote_addr_parse(x.trim()));
+        }
+        for addr in forwarded.iter().rev() {
+            match Self::remote_addr_parse(addr) {
+                Some(addr) => {
+                    remote = Some(addr);
+                    if !is_trusted(&addr) {
+                        break;
+                    }
+                }
+                None => break,
+            }
+        }
+
+        if let Some(remote) = remote {
+            self.remote = remote.to_string();
+            self.remote_addr = Some(remote);
+            self.proxy.get_or_insert_with(AuditProxy::default).peer = Some(peer);
+        }
+    }
+
+    /// Parse IP address with or without port, IPv6 addresses may be
+    /// enclosed in brackets.
+    fn remote_addr_parse(remote: &str) -> Option<IpAddr> {
+        remote
+            .parse::<SocketAddr>()
+            .map(|x| x.ip())
+            .or_else(|_e| remote.parse::<IpAddr>())
+            .or_else(|_e| {
+                remote
+                    .trim_start_matches('[')
+                    .trim_end_matches(']')
+                    .parse::<IpAddr>()
+            })
+            .ok()
+    }
 }
 
 /// Audit log builder pattern.
@@ -303,6 +1023,8 @@ pub struct AuditBuilder {
     service: Option<Uuid>,
     user: Option<Uuid>,
     user_key: Option<Uuid>,
+    organisation: Option<Uuid>,
+    actor_key: Option<Uuid>,
 }
 
 impl AuditBuilder {
@@ -315,16 +1037,28 @@ impl AuditBuilder {
             service: None,
             user: None,
             user_key: None,
+            organisation: None,
+            actor_key: None,
         }
     }
 
+    /// Set authenticating key, organisation is set if key is a root key of
+    /// an organisation.
     pub fn key(&mut self, key: Option<&KeyWithValue>) -> &mut Self {
         self.key = key.map(|x| x.id);
+        if let Some(organisation_id) = key.and_then(|x| x.organisation_id) {
+            self.organisation = Some(organisation_id);
+        }
         self
     }
 
+    /// Set authenticating service, organisation is set if service belongs
+    /// to an organisation.
     pub fn service(&mut self, service: Option<&Service>) -> &mut Self {
         self.service = service.map(|x| x.id);
+        if let Some(organisation_id) = service.and_then(|x| x.organisation_id) {
+            self.organisation = Some(organisation_id);
+        }
         self
     }
 
@@ -348,6 +1082,13 @@ impl AuditBuilder {
         self
     }
 
+    /// Set root key which issued impersonation token used to authenticate
+    /// user, audit logs are marked with actor key.
+    pub fn actor_key_id(&mut self, key: Option<Uuid>) -> &mut Self {
+        self.actor_key = key;
+        self
+    }
+
     /// Get reference to metadata.
     pub fn meta(&self) -> &AuditMeta {
         &self.meta
@@ -369,6 +1110,18 @@ impl AuditBuilder {
         self.user_key
     }
 
+    /// Organisation of authenticating key or service, used to mask
+    /// requests to organisation.
+    pub fn get_organisation_id(&self) -> Option<Uuid> {
+        self.organisation
+    }
+
+    /// Root key which issued impersonation token, some if user is
+    /// authenticated by impersonation token.
+    pub fn get_actor_key_id(&self) -> Option<Uuid> {
+        self.actor_key
+    }
+
     /// Create audit log from parameters.
     pub fn create<T>(
         &self,
@@ -386,7 +1139,9 @@ impl AuditBuilder {
             .key_id(self.key)
             .service_id(self.service)
             .user_id(self.user)
-            .user_key_id(self.user_key);
+            .user_key_id(self.user_key)
+            .organisation_id(self.organisation)
+            .actor_key_id(self.actor_key);
         driver.audit_create(&data)
     }
 
@@ -397,7 +1152,9 @@ impl AuditBuilder {
             .key_id(self.key)
             .service_id(self.service)
             .user_id(create.user_id.clone())
-            .user_key_id(create.user_key_id.clone());
+            .user_key_id(create.user_key_id.clone())
+            .organisation_id(self.organisation)
+            .actor_key_id(self.actor_key);
         driver.audit_create(&data)
     }
 
@@ -417,7 +1174,9 @@ impl AuditBuilder {
             .key_id(self.key)
             .service_id(self.service)
             .user_id(self.user)
-            .user_key_id(self.user_key);
+            .user_key_id(self.user_key)
+            .organisation_id(self.organisation)
+            .actor_key_id(self.actor_key);
         driver.audit_create(&audit_data)
     }
 }
@@ -534,3 +1293,150 @@ impl AuditDiffBuilder {
         serde_json::to_value(v).unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn audit_data_filter_to_value() {
+        let filter = AuditDataFilter::new(vec!["a".to_owned(), "b".to_owned()], json!("c"));
+        assert_eq!(filter.to_value(), json!({ "a": { "b": "c" } }));
+        let filter = AuditDataFilter::new(Vec::new(), json!({ "a": 1 }));
+        assert_eq!(filter.to_value(), json!({ "a": 1 }));
+    }
+
+    #[test]
+    fn audit_report_buckets() {
+        let le = Utc::now();
+        let mut report = AuditReport {
+            ge: le - Duration::hours(2),
+            le,
+            bucket: AuditReportBucket::Minute,
+            group: vec![AuditReportGroup::Type],
+            filter: AuditReportFilter::default(),
+        };
+        assert_eq!(report.buckets(), 121);
+        report.bucket = AuditReportBucket::Hour;
+        assert_eq!(report.buckets(), 3);
+        assert!(report.is_grouped(AuditReportGroup::Type));
+        assert!(!report.is_grouped(AuditReportGroup::Remote));
+    }
+
+    #[test]
+    fn audit_login_fingerprint() {
+        let firefox = "Mozilla/5.0 (X11; Linux x86_64; rv:76.0) Gecko/20100101 Firefox/76.0";
+        let chrome = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/81.0.4044.138 Safari/537.36";
+        let edge = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/81.0.4044.138 Safari/537.36 Edg/81.0.416.72";
+
+        let f1 = AuditLoginFingerprint::new(firefox, "192.168.1.10:4000");
+        assert_eq!(f1.user_agent_family, "Firefox");
+        assert_eq!(f1.remote_network, "192.168.1.0/24");
+        assert_eq!(f1, AuditLoginFingerprint::new(firefox, "192.168.1.200"));
+        assert_ne!(f1, AuditLoginFingerprint::new(firefox, "192.168.2.10"));
+        assert_ne!(f1, AuditLoginFingerprint::new(chrome, "192.168.1.10"));
+
+        let f2 = AuditLoginFingerprint::new(edge, "[2001:db8:1:2::1]:443");
+        assert_eq!(f2.user_agent_family, "Edge");
+        assert_eq!(f2.remote_network, "2001:db8:1::/48");
+        assert_eq!(
+            AuditLoginFingerprint::new("", "unknown").remote_network,
+            "unknown"
+        );
+    }
+
+    fn audit_meta_header_map(headers: &[(&'static str, &'static str)]) -> HeaderMap<HeaderValue> {
+        let mut map = HeaderMap::new();
+        for (name, value) in headers {
+            map.append(*name, HeaderValue::from_static(value));
+        }
+        map
+    }
+
+    #[test]
+    fn audit_meta_remote_untrusted_peer() {
+        let trusted: Vec<Cidr> = vec!["10.0.0.0/8".parse().unwrap()];
+        let map = audit_meta_header_map(&[(header::X_FORWARDED_FOR, "203.0.113.1")]);
+        let meta = AuditMeta::from_header_map(&map, "198.51.100.1:4000", &trusted);
+        assert_eq!(meta.remote(), "198.51.100.1:4000");
+        assert!(meta.proxy().is_none());
+    }
+
+    #[test]
+    fn audit_meta_remote_forwarded_for() {
+        let trusted: Vec<Cidr> = vec!["10.0.0.0/8".parse().unwrap()];
+        let map = audit_meta_header_map(&[
+            (header::X_FORWARDED_FOR, "192.0.2.1, 203.0.113.1"),
+            (header::X_FORWARDED_FOR, "10.0.0.2"),
+            (header::X_FORWARDED_HOST, "example.com"),
+        ]);
+        let meta = AuditMeta::from_header_map(&map, "10.0.0.1:4000", &trusted);
+        assert_eq!(meta.remote(), "203.0.113.1");
+        assert_eq!(meta.remote_addr(), Some("203.0.113.1".parse().unwrap()));
+        let proxy = meta.proxy().unwrap();
+        assert_eq!(proxy.peer.as_deref(), Some("10.0.0.1:4000"));
+        assert_eq!(proxy.forwarded_host.as_deref(), Some("example.com"));
+        assert_eq!(
+            meta.forwarded_for().unwrap(),
+            "192.0.2.1, 203.0.113.1, 10.0.0.2, 10.0.0.1"
+        );
+    }
+
+    #[test]
+    fn audit_meta_remote_forwarded() {
+        let trusted: Vec<Cidr> = vec![
+            "10.0.0.0/8".parse().unwrap(),
+            "2001:db8::/32".parse().unwrap(),
+        ];
+        let map = audit_meta_header_map(&[
+            (
+                header::FORWARDED,
+                "for=\"[2001:db8:cafe::17]:4711\";proto=https",
+            ),
+            (header::FORWARDED, "for=10.0.0.2"),
+        ]);
+        let meta = AuditMeta::from_header_map(&map, "10.0.0.1:4000", &trusted);
+        assert_eq!(meta.remote(), "2001:db8:cafe::17");
+
+        let map = audit_meta_header_map(&[(header::FORWARDED, "for=unknown, for=10.0.0.2")]);
+        let meta = AuditMeta::from_header_map(&map, "10.0.0.1:4000", &trusted);
+        assert_eq!(meta.remote(), "10.0.0.2");
+    }
+
+    #[test]
+    fn audit_meta_remote_forwarded_ignored_with_forwarded_for() {
+        let trusted: Vec<Cidr> = vec!["10.0.0.0/8".parse().unwrap()];
+        let map = audit_meta_header_map(&[
+            (header::FORWARDED, "for=198.51.100.7"),
+            (header::X_FORWARDED_FOR, "192.0.2.1"),
+        ]);
+        let meta = AuditMeta::from_header_map(&map, "10.0.0.1:4000", &trusted);
+        assert_eq!(meta.remote(), "192.0.2.1");
+        assert_eq!(meta.forwarded_for().unwrap(), "192.0.2.1, 10.0.0.1");
+    }
+
+    #[test]
+    fn audit_meta_remote_real_ip() {
+        let trusted: Vec<Cidr> = vec!["10.0.0.0/8".parse().unwrap()];
+        let map = audit_meta_header_map(&[(header::X_REAL_IP, "203.0.113.1")]);
+        let meta = AuditMeta::from_header_map(&map, "10.0.0.1:4000", &trusted);
+        assert_eq!(meta.remote(), "203.0.113.1");
+        assert_eq!(
+            meta.proxy().unwrap().real_ip.as_deref(),
+            Some("203.0.113.1")
+        );
+    }
+
+    #[test]
+    fn audit_meta_set_remote_forwarded() {
+        let map = audit_meta_header_map(&[(header::X_FORWARDED_FOR, "192.0.2.1, 203.0.113.1")]);
+        let mut meta = AuditMeta::from_header_map(&map, "198.51.100.1:4000", &[]);
+        assert_eq!(meta.remote(), "198.51.100.1:4000");
+        meta.set_remote_forwarded(&[]);
+        assert_eq!(meta.remote(), "203.0.113.1");
+        assert_eq!(
+            meta.proxy().unwrap().peer.as_deref(),
+            Some("198.51.100.1:4000")
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use crate::{env, DriverError, DriverResult};
+use maxminddb::{geoip2, Reader};
+use std::{fmt, net::IpAddr, path::Path};
+use woothee::parser::Parser;
+
+/// Value returned by user agent parser for unknown fields.
+const USER_AGENT_UNKNOWN: &str = "UNKNOWN";
+
+/// Language of city names.
+const GEOIP_LANGUAGE: &str = "en";
+
+/// Audit enrichment.
+///
+/// Remote location and user agent fields stored with audit log when it
+/// is created, fields are none if enrichment is disabled or lookup fails.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEnrichment {
+    pub remote_country: Option<String>,
+    pub remote_city: Option<String>,
+    pub remote_asn: Option<i64>,
+    pub remote_as_org: Option<String>,
+    pub user_agent_browser: Option<String>,
+    pub user_agent_os: Option<String>,
+    pub user_agent_device: Option<String>,
+}
+
+impl AuditEnrichment {
+    /// Returns true if no fields are set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Audit enrichment sources.
+///
+/// Remote IP addresses are looked up in MaxMind format city and ASN
+/// databases, user agents are parsed into browser, OS and device family.
+/// All sources are optional and disabled by default.
+#[derive(Default)]
+pub struct AuditEnrich {
+    city: Option<Reader<Vec<u8>>>,
+    asn: Option<Reader<Vec<u8>>>,
+    user_agent: bool,
+}
+
+impl fmt::Debug for AuditEnrich {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "AuditEnrich {{ city: {}, asn: {}, user_agent: {} }}",
+            self.city.is_some(),
+            self.asn.is_some(),
+            self.user_agent
+        )
+    }
+}
+
+impl AuditEnrich {
+    /// Returns new enrichment with all sources disabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open MaxMind format city or country database file.
+    pub fn city_database<P: AsRef<Path>>(mut self, path: P) -> DriverResult<Self> {
+        self.city = Some(Self::open(path)?);
+        Ok(self)
+    }
+
+    /// Open MaxMind format ASN database file.
+    pub fn asn_database<P: AsRef<Path>>(mut self, path: P) -> DriverResult<Self> {
+        self.asn = Some(Self::open(path)?);
+        Ok(self)
+    }
+
+    /// Set user agent parsing enabled.
+    pub fn user_agent(mut self, user_agent: bool) -> Self {
+        self.user_agent = user_agent;
+        self
+    }
+
+    /// Read city database, ASN database and user agent parsing enabled
+    /// environment variables into enrichment.
+    pub fn from_env<T: AsRef<str>>(city_name: T, asn_name: T, user_agent_name: T) -> Self {
+        let mut enrich = Self::new();
+        if let Some(city) = env::string_opt(city_name.as_ref()) {
+            enrich = enrich
+                .city_database(city)
+                .expect("Failed to open GeoIP city database.");
+        }
+        if let Some(asn) = env::string_opt(asn_name.as_ref()) {
+            enrich = enrich
+                .asn_database(asn)
+                .expect("Failed to open GeoIP ASN database.");
+        }
+        let user_agent = env::value_opt::<bool>(user_agent_name.as_ref())
+            .expect("Failed to read user agent parsing enabled environment variable.")
+            .unwrap_or(false);
+        enrich.user_agent(user_agent)
+    }
+
+    /// Returns enrichment of remote IP address and user agent.
+    pub fn enrich(&self, remote: Option<IpAddr>, user_agent: &str) -> AuditEnrichment {
+        let mut enrichment = AuditEnrichment::default();
+        if let Some(remote) = remote {
+            self.enrich_city(&mut enrichment, remote);
+            self.enrich_asn(&mut enrichment, remote);
+        }
+        if self.user_agent {
+            Self::enrich_user_agent(&mut enrichment, user_agent);
+        }
+        enrichment
+    }
+
+    fn open<P: AsRef<Path>>(path: P) -> DriverResult<Reader<Vec<u8>>> {
+        Reader::open_readfile(path).map_err(|e| DriverError::MaxMindDb(e.to_string()))
+    }
+
+    fn enrich_city(&self, enrichment: &mut AuditEnrichment, remote: IpAddr) {
+        let city = match &self.city {
+            Some(reader) => match reader.lookup::<geoip2::City>(remote) {
+                Ok(city) => city,
+                Err(_e) => return,
+            },
+            None => return,
+        };
+        enrichment.remote_country = city.country.and_then(|x| x.iso_code);
+        enrichment.remote_city = city
+            .city
+            .and_then(|x| x.names)
+            .and_then(|mut x| x.remove(GEOIP_LANGUAGE));
+    }
+
+    fn enrich_asn(&self, enrichment: &mut AuditEnrichment, remote: IpAddr) {
+        let asn = match &self.asn {
+            Some(reader) => match reader.lookup::<geoip2::Asn>(remote) {
+                Ok(asn) => asn,
+                Err(_e) => return,
+            },
+            None => return,
+        };
+        enrichment.remote_asn = asn.autonomous_system_number.map(i64::from);
+        enrichment.remote_as_org = asn.autonomous_system_organization;
+    }
+
+    fn enrich_user_agent(enrichment: &mut AuditEnrichment, user_agent: &str) {
+        let result = match Parser::new().parse(user_agent) {
+            Some(result) => result,
+            None => return,
+        };
+        let known = |x: &str| {
+            if x.is_empty() || x == USER_AGENT_UNKNOWN {
+                None
+            } else {
+                Some(x.to_owned())
+            }
+        };
+        enrichment.user_agent_browser = known(result.name);
+        enrichment.user_agent_os = known(result.os);
+        enrichment.user_agent_device = known(result.category);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn audit_enrich_disabled() {
+        let enrich = AuditEnrich::new();
+        let enrichment = enrich.enrich(Some("192.0.2.1".parse().unwrap()), "curl/7.68.0");
+        assert!(enrichment.is_empty());
+    }
+
+    #[test]
+    fn audit_enrich_user_agent() {
+        let enrich = AuditEnrich::new().user_agent(true);
+
+        let firefox = "Mozilla/5.0 (X11; Linux x86_64; rv:76.0) Gecko/20100101 Firefox/76.0";
+        let enrichment = enrich.enrich(None, firefox);
+        assert_eq!(enrichment.user_agent_browser.as_deref(), Some("Firefox"));
+        assert_eq!(enrichment.user_agent_os.as_deref(), Some("Linux"));
+        assert_eq!(enrichment.user_agent_device.as_deref(), Some("pc"));
+        assert_eq!(enrichment.remote_country, None);
+
+        let chrome = "Mozilla/5.0 (Linux; Android 10; Pixel 3) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/81.0.4044.138 Mobile Safari/537.36";
+        let enrichment = enrich.enrich(None, chrome);
+        assert_eq!(enrichment.user_agent_browser.as_deref(), Some("Chrome"));
+        assert_eq!(enrichment.user_agent_os.as_deref(), Some("Android"));
+        assert_eq!(enrichment.user_agent_device.as_deref(), Some("smartphone"));
+
+        assert!(enrich.enrich(None, "").is_empty());
+    }
+}
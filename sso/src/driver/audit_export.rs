@@ -0,0 +1,441 @@
+use crate::{impl_enum_to_from_string, Audit, DriverError, DriverResult};
+use native_tls::{TlsConnector, TlsStream};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::Write,
+    net::{TcpStream, UdpSocket},
+    path::PathBuf,
+    str::FromStr,
+};
+use url::Url;
+
+/// Syslog structured data ID, uses the documentation private enterprise number.
+const SYSLOG_SD_ID: &str = "sso@32473";
+
+/// Syslog facility, security/authorisation messages.
+const SYSLOG_FACILITY: u8 = 10;
+
+/// Default audit export position name.
+pub const DEFAULT_AUDIT_EXPORT_NAME: &str = "default";
+
+/// Number of rotated audit export files kept.
+pub const MAX_AUDIT_EXPORT_FILES: usize = 5;
+
+/// Audit export formats.
+#[derive(Debug, Copy, PartialEq, Clone, Serialize, Deserialize)]
+pub enum AuditExportFormat {
+    /// RFC 5424 syslog messages.
+    #[serde(rename = "syslog")]
+    Syslog,
+    /// ArcSight Common Event Format.
+    #[serde(rename = "cef")]
+    Cef,
+    /// Newline delimited JSON.
+    #[serde(rename = "jsonl")]
+    Jsonl,
+}
+
+impl_enum_to_from_string!(AuditExportFormat, "");
+
+impl AuditExportFormat {
+    /// Returns audit log formatted as a single line without trailing newline.
+    pub fn format(self, audit: &Audit, hostname: &str) -> String {
+        match self {
+            Self::Syslog => Self::syslog(audit, hostname),
+            Self::Cef => Self::cef(audit),
+            Self::Jsonl => audit.to_value().to_string(),
+        }
+    }
+
+    fn syslog(audit: &Audit, hostname: &str) -> String {
+        let severity = match audit.status_code {
+            Some(x) if x >= 500 => 3,
+            Some(x) if x >= 400 => 4,
+            _ => 6,
+        };
+        let mut sd = format!("[{} id=\"{}\"", SYSLOG_SD_ID, audit.id);
+        let mut param = |name: &str, value: Option<String>| {
+            if let Some(value) = value {
+                sd.push_str(&format!(" {}=\"{}\"", name, syslog_sd_escape(&value)));
+            }
+        };
+        param("remote", Some(audit.remote.clone()));
+        param("status_code", audit.status_code.map(|x| x.to_string()));
+        param("subject", audit.subject.clone());
+        param("key_id", audit.key_id.map(|x| x.to_string()));
+        param("service_id", audit.service_id.map(|x| x.to_string()));
+        param("user_id", audit.user_id.map(|x| x.to_string()));
+        param("user_key_id", audit.user_key_id.map(|x| x.to_string()));
+        param(
+            "organisation_id",
+            audit.organisation_id.map(|x| x.to_string()),
+        );
+        param("actor_key_id", audit.actor_key_id.map(|x| x.to_string()));
+        sd.push(']');
+
+        format!(
+            "<{}>1 {} {} sso - {} {} {}",
+            SYSLOG_FACILITY * 8 + severity,
+            audit.created_at.to_rfc3339(),
+            syslog_header(hostname, 255),
+            syslog_header(&audit.type_, 32),
+            sd,
+            audit.to_value(),
+        )
+    }
+
+    fn cef(audit: &Audit) -> String {
+        let severity = match audit.status_code {
+            Some(x) if x >= 500 => 8,
+            Some(x) if x >= 400 => 6,
+            _ => 3,
+        };
+        let mut ext = vec![
+            format!("rt={}", audit.created_at.timestamp_millis()),
+            format!("externalId={}", audit.id),
+            format!(
+                "requestClientApplication={}",
+                cef_ext_escape(&audit.user_agent)
+            ),
+        ];
+        if let Some(ip) = audit
+            .remote
+            .parse::<std::net::SocketAddr>()
+            .ok()
+            .map(|x| x.ip())
+        {
+            ext.push(format!("src={}", ip));
+        } else if let Ok(ip) = audit.remote.parse::<std::net::IpAddr>() {
+            ext.push(format!("src={}", ip));
+        }
+        if let Some(status_code) = audit.status_code {
+            let outcome = if status_code >= 400 {
+                "failure"
+            } else {
+                "success"
+            };
+            ext.push(format!("outcome={}", outcome));
+        }
+        if let Some(user_id) = audit.user_id {
+            ext.push(format!("suid={}", user_id));
+        }
+        let mut custom = |n: usize, label: &str, value: Option<String>| {
+            if let Some(value) = value {
+                ext.push(format!("cs{}Label={}", n, label));
+                ext.push(format!("cs{}={}", n, cef_ext_escape(&value)));
+            }
+        };
+        custom(1, "subject", audit.subject.clone());
+        custom(2, "serviceId", audit.service_id.map(|x| x.to_string()));
+        custom(3, "keyId", audit.key_id.map(|x| x.to_string()));
+        custom(
+            4,
+            "organisationId",
+            audit.organisation_id.map(|x| x.to_string()),
+        );
+        custom(5, "actorKeyId", audit.actor_key_id.map(|x| x.to_string()));
+        custom(6, "data", Some(audit.data.to_string()));
+
+        format!(
+            "CEF:0|sso|sso|{}|{}|{}|{}|{}",
+            env!("CARGO_PKG_VERSION"),
+            cef_header_escape(&audit.type_),
+            cef_header_escape(&audit.type_),
+            severity,
+            ext.join(" "),
+        )
+    }
+}
+
+/// Audit export target.
+///
+/// Parsed from URL with scheme `udp`, `tcp`, `tls` or `file`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuditExportTarget {
+    Udp(String),
+    Tcp(String),
+    Tls(String, String),
+    File(PathBuf),
+}
+
+impl FromStr for AuditExportTarget {
+    type Err = DriverError;
+
+    fn from_str(s: &str) -> DriverResult<Self> {
+        let invalid = || DriverError::AuditExportTargetInvalid(s.to_owned());
+        let url = Url::parse(s).map_err(|_e| invalid())?;
+        let addr = || match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => Ok(format!("{}:{}", host, port)),
+            _ => Err(invalid()),
+        };
+        match url.scheme() {
+            "udp" => Ok(Self::Udp(addr()?)),
+            "tcp" => Ok(Self::Tcp(addr()?)),
+            "tls" => Ok(Self::Tls(url.host_str().unwrap_or("").to_owned(), addr()?)),
+            "file" => url.to_file_path().map(Self::File).map_err(|_e| invalid()),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum AuditExportConnection {
+    Udp(UdpSocket),
+    Tcp(TcpStream),
+    Tls(Box<TlsStream<TcpStream>>),
+    File(File, u64),
+}
+
+/// Audit export sink.
+///
+/// Writes formatted audit logs to target. Stream targets use octet counting
+/// framing, file targets are rotated when `file_max_bytes` is exceeded.
+/// Connections are reopened after a write error.
+#[derive(Debug)]
+pub struct AuditExportSink {
+    target: AuditExportTarget,
+    format: AuditExportFormat,
+    hostname: String,
+    file_max_bytes: Option<u64>,
+    connection: Option<AuditExportConnection>,
+}
+
+impl AuditExportSink {
+    /// Returns new sink, connection is opened on first write.
+    pub fn new(target: AuditExportTarget, format: AuditExportFormat) -> Self {
+        Self {
+            target,
+            format,
+            hostname: "-".to_owned(),
+            file_max_bytes: None,
+            connection: None,
+        }
+    }
+
+    /// Set hostname included in syslog messages.
+    pub fn hostname(mut self, hostname: Option<String>) -> Self {
+        if let Some(hostname) = hostname {
+            self.hostname = hostname;
+        }
+        self
+    }
+
+    /// Set maximum size of file before it is rotated.
+    pub fn file_max_bytes(mut self, file_max_bytes: Option<u64>) -> Self {
+        self.file_max_bytes = file_max_bytes;
+        self
+    }
+
+    /// Write audit log to target.
+    pub fn write(&mut self, audit: &Audit) -> DriverResult<()> {
+        let line = self.format.format(audit, &self.hostname);
+        let res = self.write_line(&line);
+        if res.is_err() {
+            self.connection = None;
+        }
+        res
+    }
+
+    fn write_line(&mut self, line: &str) -> DriverResult<()> {
+        if let Some(AuditExportConnection::File(_, size)) = &self.connection {
+            if let Some(max) = self.file_max_bytes {
+                if *size + line.len() as u64 + 1 > max && *size > 0 {
+                    self.connection = None;
+                    self.file_rotate()?;
+                }
+            }
+        }
+        if self.connection.is_none() {
+            self.connection = Some(self.connect()?);
+        }
+
+        match self.connection.as_mut().unwrap() {
+            AuditExportConnection::Udp(socket) => {
+                socket.send(line.as_bytes()).map_err(DriverError::StdIo)?;
+            }
+            AuditExportConnection::Tcp(stream) => {
+                write!(stream, "{} {}", line.len(), line).map_err(DriverError::StdIo)?;
+            }
+            AuditExportConnection::Tls(stream) => {
+                write!(stream, "{} {}", line.len(), line).map_err(DriverError::StdIo)?;
+            }
+            AuditExportConnection::File(file, size) => {
+                writeln!(file, "{}", line).map_err(DriverError::StdIo)?;
+                *size += line.len() as u64 + 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Flush buffered writes to target.
+    pub fn flush(&mut self) -> DriverResult<()> {
+        let res = match self.connection.as_mut() {
+            Some(AuditExportConnection::Tcp(stream)) => stream.flush(),
+            Some(AuditExportConnection::Tls(stream)) => stream.flush(),
+            Some(AuditExportConnection::File(file, _)) => file.sync_data(),
+            _ => Ok(()),
+        };
+        res.map_err(DriverError::StdIo)
+    }
+
+    fn connect(&self) -> DriverResult<AuditExportConnection> {
+        match &self.target {
+            AuditExportTarget::Udp(addr) => {
+                let socket = UdpSocket::bind("0.0.0.0:0").map_err(DriverError::StdIo)?;
+                socket.connect(addr).map_err(DriverError::StdIo)?;
+                Ok(AuditExportConnection::Udp(socket))
+            }
+            AuditExportTarget::Tcp(addr) => TcpStream::connect(addr)
+                .map(AuditExportConnection::Tcp)
+                .map_err(DriverError::StdIo),
+            AuditExportTarget::Tls(domain, addr) => {
+                let stream = TcpStream::connect(addr).map_err(DriverError::StdIo)?;
+                let connector = TlsConnector::new().map_err(DriverError::NativeTls)?;
+                let stream = connector
+                    .connect(domain, stream)
+                    .map_err(|e| DriverError::Message(e.to_string()))?;
+                Ok(AuditExportConnection::Tls(Box::new(stream)))
+            }
+            AuditExportTarget::File(path) => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(DriverError::StdIo)?;
+                let size = file.metadata().map_err(DriverError::StdIo)?.len();
+                Ok(AuditExportConnection::File(file, size))
+            }
+        }
+    }
+
+    /// Rename file to `.1`, shifting existing rotated files and removing the oldest.
+    fn file_rotate(&self) -> DriverResult<()> {
+        if let AuditExportTarget::File(path) = &self.target {
+            let rotated = |i: usize| {
+                let mut p = path.clone().into_os_string();
+                p.push(format!(".{}", i));
+                PathBuf::from(p)
+            };
+            let _ = fs::remove_file(rotated(MAX_AUDIT_EXPORT_FILES));
+            for i in (1..MAX_AUDIT_EXPORT_FILES).rev() {
+                let from = rotated(i);
+                if from.exists() {
+                    fs::rename(&from, rotated(i + 1)).map_err(DriverError::StdIo)?;
+                }
+            }
+            fs::rename(path, rotated(1)).map_err(DriverError::StdIo)?;
+        }
+        Ok(())
+    }
+}
+
+/// Syslog header fields are printable ASCII without spaces, `-` if empty.
+fn syslog_header(value: &str, max: usize) -> String {
+    let value: String = value
+        .chars()
+        .filter(|c| c.is_ascii_graphic())
+        .take(max)
+        .collect();
+    if value.is_empty() {
+        "-".to_owned()
+    } else {
+        value
+    }
+}
+
+fn syslog_sd_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace(']', "\\]")
+}
+
+fn cef_header_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('|', "\\|")
+}
+
+fn cef_ext_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('=', "\\=")
+        .replace('\r', "\\r")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AuditEnrichment;
+    use chrono::{TimeZone, Utc};
+    use uuid::Uuid;
+
+    fn audit() -> Audit {
+        let created_at = Utc.ymd(2020, 5, 17).and_hms(12, 0, 0);
+        Audit {
+            created_at,
+            updated_at: created_at,
+            id: Uuid::nil(),
+            user_agent: "agent=1".to_owned(),
+            remote: "10.0.0.1:1234".to_owned(),
+            forwarded: None,
+            status_code: Some(400),
+            type_: "sso:AuthLocalLogin".to_owned(),
+            subject: Some("a\"b]".to_owned()),
+            data: json!({}),
+            key_id: None,
+            service_id: None,
+            user_id: None,
+            user_key_id: None,
+            organisation_id: None,
+            actor_key_id: None,
+            proxy: None,
+            enrichment: AuditEnrichment::default(),
+        }
+    }
+
+    #[test]
+    fn audit_export_format_syslog() {
+        let s = AuditExportFormat::Syslog.format(&audit(), "host name");
+        assert!(s.starts_with("<84>1 2020-05-17T12:00:00+00:00 hostname sso - sso:AuthLocalLogin "));
+        assert!(s.contains("[sso@32473 id=\"00000000-0000-0000-0000-000000000000\""));
+        assert!(s.contains(" subject=\"a\\\"b\\]\"]"));
+    }
+
+    #[test]
+    fn audit_export_format_cef() {
+        let s = AuditExportFormat::Cef.format(&audit(), "-");
+        assert!(s.starts_with("CEF:0|sso|sso|"));
+        assert!(s.contains("|sso:AuthLocalLogin|sso:AuthLocalLogin|6|rt=1589716800000 "));
+        assert!(s.contains(" requestClientApplication=agent\\=1 "));
+        assert!(s.contains(" src=10.0.0.1 outcome=failure "));
+    }
+
+    #[test]
+    fn audit_export_format_jsonl() {
+        let s = AuditExportFormat::Jsonl.format(&audit(), "-");
+        assert!(!s.contains('\n'));
+        let value: serde_json::Value = serde_json::from_str(&s).unwrap();
+        assert_eq!(value["type"], "sso:AuthLocalLogin");
+    }
+
+    #[test]
+    fn audit_export_target_parse() {
+        assert_eq!(
+            AuditExportTarget::from_str("udp://localhost:514").unwrap(),
+            AuditExportTarget::Udp("localhost:514".to_owned())
+        );
+        assert_eq!(
+            AuditExportTarget::from_str("tls://siem.example.com:6514").unwrap(),
+            AuditExportTarget::Tls(
+                "siem.example.com".to_owned(),
+                "siem.example.com:6514".to_owned()
+            )
+        );
+        assert_eq!(
+            AuditExportTarget::from_str("file:///var/log/sso/audit.log").unwrap(),
+            AuditExportTarget::File(PathBuf::from("/var/log/sso/audit.log"))
+        );
+        assert!(AuditExportTarget::from_str("tcp://localhost").is_err());
+        assert!(AuditExportTarget::from_str("http://localhost:80").is_err());
+    }
+}
@@ -0,0 +1,255 @@
+use crate::prelude::*;
+use chrono::{Datelike, TimeZone};
+use flate2::{write::GzEncoder, Compression};
+use std::{
+    fmt,
+    fs::{File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// Default audit retention in days, if no retention matches audit log.
+pub const DEFAULT_AUDIT_RETENTION_DAYS: i32 = 84;
+
+/// Number of months ahead of current month audit log partitions are created.
+pub const AUDIT_PARTITION_AHEAD_MONTHS: u32 = 2;
+
+/// Audit retention.
+///
+/// Retention period of audit logs by service and type in days. The most
+/// specific retention applies, in order: service and type, service, type,
+/// then retention without service or type which matches all audit logs.
+#[derive(Debug, Clone)]
+pub struct AuditRetention {
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub id: Uuid,
+    pub service_id: Option<Uuid>,
+    pub type_: Option<String>,
+    pub days: i32,
+}
+
+impl fmt::Display for AuditRetention {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AuditRetention {}", self.id)?;
+        write!(f, "\n\tcreated_at {}", self.created_at)?;
+        write!(f, "\n\tupdated_at {}", self.updated_at)?;
+        if let Some(service_id) = &self.service_id {
+            write!(f, "\n\tservice_id {}", service_id)?;
+        }
+        if let Some(type_) = &self.type_ {
+            write!(f, "\n\ttype {}", type_)?;
+        }
+        write!(f, "\n\tdays {}", self.days)
+    }
+}
+
+/// Audit retention set.
+///
+/// Creates or updates retention of service and type.
+#[derive(Debug)]
+pub struct AuditRetentionSet {
+    pub service_id: Option<Uuid>,
+    pub type_: Option<String>,
+    pub days: i32,
+}
+
+/// Audit retention delete.
+#[derive(Debug)]
+pub struct AuditRetentionDelete {
+    pub service_id: Option<Uuid>,
+    pub type_: Option<String>,
+}
+
+/// Audit retention task.
+///
+/// Partitions are dropped when all of their audit logs have expired, expired
+/// audit logs in other partitions are deleted. If archive directory is defined
+/// audit logs are written to compressed JSON Lines files before removal.
+#[derive(Debug)]
+pub struct AuditRetentionTask {
+    pub now: DateTime<Utc>,
+    pub default_days: i32,
+    pub archive: Option<PathBuf>,
+}
+
+impl AuditRetentionTask {
+    pub fn new(default_days: i32) -> Self {
+        Self {
+            now: Utc::now(),
+            default_days,
+            archive: None,
+        }
+    }
+
+    /// Set directory audit logs are archived to before removal.
+    pub fn archive(mut self, archive: Option<PathBuf>) -> Self {
+        self.archive = archive;
+        self
+    }
+}
+
+/// Audit retention task result.
+#[derive(Debug, Default)]
+pub struct AuditRetentionResult {
+    pub created: Vec<String>,
+    pub dropped: Vec<String>,
+    pub deleted: usize,
+    pub archived: Vec<PathBuf>,
+}
+
+impl fmt::Display for AuditRetentionResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AuditRetentionResult")?;
+        write!(f, "\n\tcreated {:?}", self.created)?;
+        write!(f, "\n\tdropped {:?}", self.dropped)?;
+        write!(f, "\n\tdeleted {}", self.deleted)?;
+        write!(f, "\n\tarchived {:?}", self.archived)
+    }
+}
+
+/// Returns name of audit log partition for month of time.
+pub fn audit_partition_name(at: &DateTime<Utc>) -> String {
+    format!("sso_audit_{:04}{:02}", at.year(), at.month())
+}
+
+/// Returns range of audit log partition from name, lower bound is
+/// inclusive and upper bound is exclusive. Returns none if name is not
+/// a monthly partition.
+pub fn audit_partition_range(name: &str) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    if !name.starts_with("sso_audit_") {
+        return None;
+    }
+    let month = &name["sso_audit_".len()..];
+    if month.len() != 6 || !month.chars().all(|x| x.is_ascii_digit()) {
+        return None;
+    }
+    let year: i32 = month[..4].parse().ok()?;
+    let month: u32 = month[4..].parse().ok()?;
+    let lower = Utc.ymd_opt(year, month, 1).single()?;
+    let upper = match month {
+        12 => Utc.ymd_opt(year + 1, 1, 1).single()?,
+        _ => Utc.ymd_opt(year, month + 1, 1).single()?,
+    };
+    Some((lower.and_hms(0, 0, 0), upper.and_hms(0, 0, 0)))
+}
+
+/// Audit archive.
+///
+/// Writes audit logs to gzip compressed JSON Lines file, existing files
+/// are not overwritten.
+pub struct AuditArchive {
+    path: PathBuf,
+    encoder: GzEncoder<BufWriter<File>>,
+}
+
+impl fmt::Debug for AuditArchive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AuditArchive {{ path: {:?} }}", self.path)
+    }
+}
+
+impl AuditArchive {
+    /// Create archive file in directory, name is suffixed with a number
+    /// if a file with the same name exists.
+    pub fn create(dir: &Path, name: &str) -> DriverResult<Self> {
+        let mut n = 0;
+        loop {
+            let path = match n {
+                0 => dir.join(format!("{}.jsonl.gz", name)),
+                n => dir.join(format!("{}.{}.jsonl.gz", name, n)),
+            };
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(file) => {
+                    let encoder = GzEncoder::new(BufWriter::new(file), Compression::default());
+                    return Ok(Self { path, encoder });
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => n += 1,
+                Err(e) => return Err(DriverError::StdIo(e)),
+            }
+        }
+    }
+
+    /// Write audit log to archive.
+    pub fn write(&mut self, audit: &Audit) -> DriverResult<()> {
+        writeln!(self.encoder, "{}", audit.to_value()).map_err(DriverError::StdIo)
+    }
+
+    /// Finish compression and sync file, returns path of archive.
+    pub fn finish(self) -> DriverResult<PathBuf> {
+        let writer = self.encoder.finish().map_err(DriverError::StdIo)?;
+        let file = writer
+            .into_inner()
+            .map_err(|e| DriverError::StdIo(e.into_error()))?;
+        file.sync_all().map_err(DriverError::StdIo)?;
+        Ok(self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::{fs, io::Read};
+
+    #[test]
+    fn audit_partition_name_range() {
+        let at = Utc.ymd(2020, 12, 31).and_hms(23, 59, 59);
+        let name = audit_partition_name(&at);
+        assert_eq!(name, "sso_audit_202012");
+        let (lower, upper) = audit_partition_range(&name).unwrap();
+        assert_eq!(lower, Utc.ymd(2020, 12, 1).and_hms(0, 0, 0));
+        assert_eq!(upper, Utc.ymd(2021, 1, 1).and_hms(0, 0, 0));
+        assert!(lower <= at && at < upper);
+
+        assert!(audit_partition_range("sso_audit_default").is_none());
+        assert!(audit_partition_range("sso_audit_202013").is_none());
+        assert!(audit_partition_range("sso_audit_2020011").is_none());
+    }
+
+    #[test]
+    fn audit_archive_create_write() {
+        let dir = std::env::temp_dir().join(format!("sso-archive-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let now = Utc::now();
+        let audit = Audit {
+            created_at: now,
+            updated_at: now,
+            id: Uuid::new_v4(),
+            user_agent: "agent".to_owned(),
+            remote: "10.0.0.1:1234".to_owned(),
+            forwarded: None,
+            status_code: None,
+            type_: "sso:AuditList".to_owned(),
+            subject: None,
+            data: json!({}),
+            key_id: None,
+            service_id: None,
+            user_id: None,
+            user_key_id: None,
+            organisation_id: None,
+            actor_key_id: None,
+            proxy: None,
+            enrichment: AuditEnrichment::default(),
+        };
+        let mut archive = AuditArchive::create(&dir, "sso_audit_202001").unwrap();
+        archive.write(&audit).unwrap();
+        let path1 = archive.finish().unwrap();
+        assert_eq!(path1, dir.join("sso_audit_202001.jsonl.gz"));
+
+        let archive = AuditArchive::create(&dir, "sso_audit_202001").unwrap();
+        let path2 = archive.finish().unwrap();
+        assert_eq!(path2, dir.join("sso_audit_202001.1.jsonl.gz"));
+
+        let mut s = String::new();
+        GzDecoder::new(File::open(&path1).unwrap())
+            .read_to_string(&mut s)
+            .unwrap();
+        assert_eq!(s.lines().count(), 1);
+        let value: serde_json::Value = serde_json::from_str(s.lines().next().unwrap()).unwrap();
+        assert_eq!(value["id"], audit.id.to_string());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
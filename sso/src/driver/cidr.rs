@@ -0,0 +1,197 @@
+use crate::{DriverError, DriverResult};
+use std::{
+    fmt,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+
+lazy_static! {
+    /// Loopback, private, link local and other address ranges which are not
+    /// publicly routable.
+    static ref CIDR_INTERNAL: Vec<Cidr> = [
+        "0.0.0.0/8",
+        "10.0.0.0/8",
+        "100.64.0.0/10",
+        "127.0.0.0/8",
+        "169.254.0.0/16",
+        "172.16.0.0/12",
+        "192.0.0.0/24",
+        "192.168.0.0/16",
+        "198.18.0.0/15",
+        "224.0.0.0/4",
+        "240.0.0.0/4",
+        "::/128",
+        "::1/128",
+        "fc00::/7",
+        "fe80::/10",
+        "ff00::/8",
+    ]
+    .iter()
+    .map(|x| Cidr::from_str(x).unwrap())
+    .collect();
+}
+
+/// IP address range in CIDR notation.
+///
+/// Used to restrict remote addresses which may authenticate with service or key.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Cidr {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    /// Returns true if address is in range.
+    /// IPv4 mapped IPv6 addresses are compared to IPv4 ranges.
+    pub fn contains(&self, addr: &IpAddr) -> bool {
+        match (self.addr, addr) {
+            (IpAddr::V4(net), IpAddr::V4(addr)) => Self::contains_v4(net, *addr, self.prefix),
+            (IpAddr::V6(net), IpAddr::V6(addr)) => Self::contains_v6(net, *addr, self.prefix),
+            (IpAddr::V4(net), IpAddr::V6(addr)) => match Self::ipv4_mapped(addr) {
+                Some(addr) => Self::contains_v4(net, addr, self.prefix),
+                None => false,
+            },
+            (IpAddr::V6(_), IpAddr::V4(_)) => false,
+        }
+    }
+
+    /// Check remote address is allowed by CIDR array.
+    /// Empty array allows any remote address, otherwise an unknown
+    /// remote address is not allowed.
+    pub fn vec_allows(allow: &[Cidr], remote: Option<IpAddr>) -> bool {
+        if allow.is_empty() {
+            return true;
+        }
+        match remote {
+            Some(remote) => allow.iter().any(|x| x.contains(&remote)),
+            None => false,
+        }
+    }
+
+    /// Returns true if address is loopback, private, link local or
+    /// otherwise not publicly routable.
+    pub fn is_internal(addr: &IpAddr) -> bool {
+        CIDR_INTERNAL.iter().any(|x| x.contains(addr))
+    }
+
+    fn contains_v4(net: Ipv4Addr, addr: Ipv4Addr, prefix: u8) -> bool {
+        let mask = u32::max_value()
+            .checked_shl(32 - u32::from(prefix))
+            .unwrap_or(0);
+        u32::from(net) & mask == u32::from(addr) & mask
+    }
+
+    fn contains_v6(net: Ipv6Addr, addr: Ipv6Addr, prefix: u8) -> bool {
+        let mask = u128::max_value()
+            .checked_shl(128 - u32::from(prefix))
+            .unwrap_or(0);
+        u128::from(net) & mask == u128::from(addr) & mask
+    }
+
+    fn ipv4_mapped(addr: &Ipv6Addr) -> Option<Ipv4Addr> {
+        match addr.segments() {
+            [0, 0, 0, 0, 0, 0xffff, ..] => addr.to_ipv4(),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = DriverError;
+
+    /// Parse CIDR string, an address without prefix length is a single address range.
+    fn from_str(s: &str) -> DriverResult<Self> {
+        let mut parts = s.splitn(2, '/');
+        let addr = parts
+            .next()
+            .unwrap_or("")
+            .parse::<IpAddr>()
+            .map_err(|_e| DriverError::CidrInvalid(s.to_owned()))?;
+        let prefix_max = match addr {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        let prefix = match parts.next() {
+            Some(prefix) => prefix
+                .parse::<u8>()
+                .ok()
+                .filter(|x| *x <= prefix_max)
+                .ok_or_else(|| DriverError::CidrInvalid(s.to_owned()))?,
+            None => prefix_max,
+        };
+        Ok(Self { addr, prefix })
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cidr_parse() {
+        assert_eq!(
+            Cidr::from_str("10.0.0.0/8").unwrap().to_string(),
+            "10.0.0.0/8"
+        );
+        assert_eq!(
+            Cidr::from_str("10.1.2.3").unwrap().to_string(),
+            "10.1.2.3/32"
+        );
+        assert_eq!(Cidr::from_str("::1").unwrap().to_string(), "::1/128");
+        assert!(Cidr::from_str("10.0.0.0/33").is_err());
+        assert!(Cidr::from_str("10.0.0/8").is_err());
+        assert!(Cidr::from_str("").is_err());
+    }
+
+    #[test]
+    fn cidr_contains() {
+        let cidr = Cidr::from_str("10.0.0.0/8").unwrap();
+        assert!(cidr.contains(&"10.1.2.3".parse().unwrap()));
+        assert!(cidr.contains(&"::ffff:10.1.2.3".parse().unwrap()));
+        assert!(!cidr.contains(&"11.1.2.3".parse().unwrap()));
+        assert!(!cidr.contains(&"::1".parse().unwrap()));
+
+        let cidr = Cidr::from_str("fd00::/8").unwrap();
+        assert!(cidr.contains(&"fd12::1".parse().unwrap()));
+        assert!(!cidr.contains(&"fe80::1".parse().unwrap()));
+        assert!(!cidr.contains(&"10.1.2.3".parse().unwrap()));
+
+        let cidr = Cidr::from_str("0.0.0.0/0").unwrap();
+        assert!(cidr.contains(&"192.168.0.1".parse().unwrap()));
+    }
+
+    #[test]
+    fn cidr_is_internal() {
+        assert!(Cidr::is_internal(&"127.0.0.1".parse().unwrap()));
+        assert!(Cidr::is_internal(&"10.1.2.3".parse().unwrap()));
+        assert!(Cidr::is_internal(&"172.31.0.1".parse().unwrap()));
+        assert!(Cidr::is_internal(&"192.168.0.1".parse().unwrap()));
+        assert!(Cidr::is_internal(&"169.254.169.254".parse().unwrap()));
+        assert!(Cidr::is_internal(&"0.0.0.0".parse().unwrap()));
+        assert!(Cidr::is_internal(&"::1".parse().unwrap()));
+        assert!(Cidr::is_internal(&"::ffff:127.0.0.1".parse().unwrap()));
+        assert!(Cidr::is_internal(&"fd12::1".parse().unwrap()));
+        assert!(Cidr::is_internal(&"fe80::1".parse().unwrap()));
+        assert!(!Cidr::is_internal(&"8.8.8.8".parse().unwrap()));
+        assert!(!Cidr::is_internal(&"172.32.0.1".parse().unwrap()));
+        assert!(!Cidr::is_internal(&"2001:4860:4860::8888".parse().unwrap()));
+    }
+
+    #[test]
+    fn cidr_vec_allows() {
+        let allow = vec![Cidr::from_str("192.168.0.0/16").unwrap()];
+        assert!(Cidr::vec_allows(&[], None));
+        assert!(Cidr::vec_allows(
+            &allow,
+            Some("192.168.1.1".parse().unwrap())
+        ));
+        assert!(!Cidr::vec_allows(&allow, Some("10.0.0.1".parse().unwrap())));
+        assert!(!Cidr::vec_allows(&allow, None));
+    }
+}
@@ -4,6 +4,15 @@ pub enum DriverError {
     #[fail(display = "AuditNotFound")]
     AuditNotFound,
 
+    #[fail(display = "GroupNotFound")]
+    GroupNotFound,
+
+    #[fail(display = "GroupNameConstraint")]
+    GroupNameConstraint,
+
+    #[fail(display = "GroupParentInvalid")]
+    GroupParentInvalid,
+
     #[fail(display = "KeyNotFound")]
     KeyNotFound,
 
@@ -25,12 +34,45 @@ pub enum DriverError {
     #[fail(display = "KeyUserTotpConstraint")]
     KeyUserTotpConstraint,
 
+    #[fail(display = "KeyAlreadyRotated")]
+    KeyAlreadyRotated,
+
+    #[fail(display = "KeyScopeForbidden")]
+    KeyScopeForbidden,
+
+    #[fail(display = "KeyScopeInvalid {}", _0)]
+    KeyScopeInvalid(String),
+
+    #[fail(display = "KeyRemoteForbidden")]
+    KeyRemoteForbidden,
+
+    #[fail(display = "KeySecretEncrypt")]
+    KeySecretEncrypt,
+
+    #[fail(display = "KeySecretDecrypt")]
+    KeySecretDecrypt,
+
+    #[fail(display = "OrganisationNotFound")]
+    OrganisationNotFound,
+
+    #[fail(display = "OrganisationDisabled")]
+    OrganisationDisabled,
+
+    #[fail(display = "RoleNotFound")]
+    RoleNotFound,
+
+    #[fail(display = "RoleNameConstraint")]
+    RoleNameConstraint,
+
     #[fail(display = "ServiceNotFound")]
     ServiceNotFound,
 
     #[fail(display = "ServiceDisabled")]
     ServiceDisabled,
 
+    #[fail(display = "ServiceRemoteForbidden")]
+    ServiceRemoteForbidden,
+
     #[fail(display = "ServiceUserRegisterDisabled")]
     ServiceUserRegisterDisabled,
 
@@ -46,6 +88,21 @@ pub enum DriverError {
     #[fail(display = "ServiceCannotCreateServiceKey")]
     ServiceCannotCreateServiceKey,
 
+    #[fail(display = "ServiceUserNotFound")]
+    ServiceUserNotFound,
+
+    #[fail(display = "ServiceUserInvited")]
+    ServiceUserInvited,
+
+    #[fail(display = "ServiceUserSuspended")]
+    ServiceUserSuspended,
+
+    #[fail(display = "ServiceUserNotInvited")]
+    ServiceUserNotInvited,
+
+    #[fail(display = "ServiceUserInviteExpired")]
+    ServiceUserInviteExpired,
+
     #[fail(display = "UserPasswordUpdateRequired")]
     UserPasswordUpdateRequired,
 
@@ -67,6 +124,36 @@ pub enum DriverError {
     #[fail(display = "UserPasswordUndefined")]
     UserPasswordUndefined,
 
+    #[fail(display = "UserImpersonationForbidden")]
+    UserImpersonationForbidden,
+
+    #[fail(display = "UserEmailNotVerified")]
+    UserEmailNotVerified,
+
+    #[fail(display = "UserEmailNotPending")]
+    UserEmailNotPending,
+
+    #[fail(display = "WebhookNotFound")]
+    WebhookNotFound,
+
+    #[fail(display = "WebhookDeliveryNotFound")]
+    WebhookDeliveryNotFound,
+
+    #[fail(display = "WebhookDeliveryNotDead")]
+    WebhookDeliveryNotDead,
+
+    #[fail(display = "WebhookUrlInvalid {}", _0)]
+    WebhookUrlInvalid(String),
+
+    #[fail(display = "WebhookAddressForbidden {}", _0)]
+    WebhookAddressForbidden(String),
+
+    #[fail(display = "CidrInvalid {}", _0)]
+    CidrInvalid(String),
+
+    #[fail(display = "AuditExportTargetInvalid {}", _0)]
+    AuditExportTargetInvalid(String),
+
     #[fail(display = "JwtTypeInvalid")]
     JwtTypeInvalid,
 
@@ -183,6 +270,9 @@ pub enum DriverError {
 
     #[fail(display = "HttpUri {}", _0)]
     HttpUri(#[fail(cause)] http::uri::InvalidUri),
+
+    #[fail(display = "MaxMindDb {}", _0)]
+    MaxMindDb(String),
 }
 
 impl From<libreauth::pass::ErrorCode> for DriverError {
@@ -203,6 +293,12 @@ impl From<serde_urlencoded::ser::Error> for DriverError {
     }
 }
 
+impl From<serde_urlencoded::de::Error> for DriverError {
+    fn from(e: serde_urlencoded::de::Error) -> Self {
+        Self::SerdeUrlencoded(format!("{}", e))
+    }
+}
+
 impl From<diesel::result::Error> for DriverError {
     fn from(e: diesel::result::Error) -> Self {
         Self::DieselResult(e)
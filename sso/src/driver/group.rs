@@ -0,0 +1,130 @@
+use crate::{AuditDiff, AuditDiffBuilder, AuditSubject};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::fmt;
+use uuid::Uuid;
+
+/// Group.
+///
+/// Groups are owned by a service and may have a parent group of the same
+/// service, members of a group are effective members
+/// of its ancestors. Roles assigned to a group apply to its effective members.
+/// Disabled groups and their ancestors do not grant effective membership.
+#[derive(Debug, Clone)]
+pub struct Group {
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub id: Uuid,
+    pub is_enabled: bool,
+    pub name: String,
+    pub parent_id: Option<Uuid>,
+    pub service_id: Uuid,
+}
+
+impl fmt::Display for Group {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Group {}", self.id)?;
+        write!(f, "\n\tcreated_at {}", self.created_at)?;
+        write!(f, "\n\tupdated_at {}", self.updated_at)?;
+        write!(f, "\n\tis_enabled {}", self.is_enabled)?;
+        write!(f, "\n\tname {}", self.name)?;
+        if let Some(parent_id) = &self.parent_id {
+            write!(f, "\n\tparent_id {}", parent_id)?;
+        }
+        write!(f, "\n\tservice_id {}", self.service_id)?;
+        Ok(())
+    }
+}
+
+impl AuditSubject for Group {
+    fn subject(&self) -> String {
+        format!("{}", self.id)
+    }
+}
+
+impl AuditDiff for Group {
+    fn diff(&self, previous: &Self) -> Value {
+        AuditDiffBuilder::default()
+            .compare("is_enabled", &self.is_enabled, &previous.is_enabled)
+            .compare("name", &self.name, &previous.name)
+            .compare_opt(
+                "parent_id",
+                self.parent_id.as_ref(),
+                previous.parent_id.as_ref(),
+            )
+            .into_value()
+    }
+}
+
+/// Group list query.
+#[derive(Debug)]
+pub enum GroupListQuery {
+    Limit,
+    IdGt(Uuid),
+    IdLt(Uuid),
+}
+
+/// Group list filter.
+#[derive(Debug)]
+pub struct GroupListFilter {
+    pub id: Option<Vec<Uuid>>,
+    pub is_enabled: Option<bool>,
+    pub parent_id: Option<Vec<Uuid>>,
+    pub service_id: Option<Vec<Uuid>>,
+    pub user_id: Option<Uuid>,
+    pub limit: i64,
+}
+
+/// Group list.
+#[derive(Debug)]
+pub struct GroupList {
+    pub query: GroupListQuery,
+    pub filter: GroupListFilter,
+}
+
+/// Group create.
+#[derive(Debug)]
+pub struct GroupCreate {
+    pub service_id: Uuid,
+    pub is_enabled: bool,
+    pub name: String,
+    pub parent_id: Option<Uuid>,
+}
+
+/// Group read.
+#[derive(Debug)]
+pub struct GroupRead {
+    pub id: Uuid,
+}
+
+impl GroupRead {
+    pub fn new(id: Uuid) -> Self {
+        Self { id }
+    }
+}
+
+/// Group update.
+///
+/// If parent ID is some, parent of group is updated, a nested none removes
+/// parent of group.
+#[derive(Debug)]
+pub struct GroupUpdate {
+    pub id: Uuid,
+    pub is_enabled: Option<bool>,
+    pub name: Option<String>,
+    pub parent_id: Option<Option<Uuid>>,
+}
+
+/// Group user membership.
+#[derive(Debug)]
+pub struct GroupUser {
+    pub group_id: Uuid,
+    pub user_id: Uuid,
+}
+
+/// Group role assignment.
+#[derive(Debug)]
+pub struct GroupRole {
+    pub group_id: Uuid,
+    pub role_id: Uuid,
+}
@@ -1,14 +1,35 @@
-use crate::{impl_enum_to_from_string, AuditDiff, AuditDiffBuilder, AuditSubject};
-use chrono::{DateTime, Utc};
+use crate::{
+    impl_enum_to_from_string, AuditDiff, AuditDiffBuilder, AuditSubject, Cidr, DriverError,
+    DriverResult,
+};
+use chacha20poly1305::{
+    aead::{generic_array::GenericArray, Aead, NewAead},
+    ChaCha20Poly1305,
+};
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
 use libreauth::key::KeyBuilder;
 use serde_json::Value;
+use sha2::Sha256;
 use std::convert::TryFrom;
 use std::fmt;
+use std::net::IpAddr;
 use uuid::Uuid;
 
 /// Key value size in bytes.
 pub const BYTES_KEY_VALUE: usize = 21;
 
+/// Key value prefix length in characters.
+pub const LEN_KEY_VALUE_PREFIX: usize = 8;
+
+/// Key rotate maximum grace period in seconds.
+pub const MAX_KEY_GRACE_PERIOD_S: i64 = 2_592_000;
+
+/// Key secret nonce size in bytes.
+const BYTES_KEY_SECRET_NONCE: usize = 12;
+
+type HmacSha256 = Hmac<Sha256>;
+
 /// Key types.
 #[derive(Debug, Copy, PartialEq, Clone, Serialize, Deserialize)]
 pub enum KeyType {
@@ -20,6 +41,15 @@ pub enum KeyType {
 impl_enum_to_from_string!(KeyType, "");
 
 impl KeyType {
+    /// Returns true if key value is used as signing or TOTP secret,
+    /// these values are stored encrypted so they can be read internally.
+    pub fn has_secret(self) -> bool {
+        match self {
+            Self::Key => false,
+            Self::Token | Self::Totp => true,
+        }
+    }
+
     pub fn from_i32(v: i32) -> Self {
         match v {
             0 => Self::Key,
@@ -43,6 +73,82 @@ impl TryFrom<i32> for KeyType {
     }
 }
 
+/// Key scopes.
+///
+/// Root and service keys with an empty scope may call any method,
+/// otherwise the method scope must be included in the key scope.
+#[derive(Debug, Copy, PartialEq, Clone, Serialize, Deserialize)]
+pub enum KeyScope {
+    #[serde(rename = "audit:read")]
+    AuditRead,
+    #[serde(rename = "audit:write")]
+    AuditWrite,
+    #[serde(rename = "group:read")]
+    GroupRead,
+    #[serde(rename = "group:write")]
+    GroupWrite,
+    #[serde(rename = "key:read")]
+    KeyRead,
+    #[serde(rename = "key:write")]
+    KeyWrite,
+    #[serde(rename = "organisation:read")]
+    OrganisationRead,
+    #[serde(rename = "organisation:write")]
+    OrganisationWrite,
+    #[serde(rename = "role:read")]
+    RoleRead,
+    #[serde(rename = "role:write")]
+    RoleWrite,
+    #[serde(rename = "service:read")]
+    ServiceRead,
+    #[serde(rename = "service:write")]
+    ServiceWrite,
+    #[serde(rename = "user:read")]
+    UserRead,
+    #[serde(rename = "user:write")]
+    UserWrite,
+    #[serde(rename = "webhook:read")]
+    WebhookRead,
+    #[serde(rename = "webhook:write")]
+    WebhookWrite,
+    #[serde(rename = "auth:key")]
+    AuthKey,
+    #[serde(rename = "auth:token")]
+    AuthToken,
+    #[serde(rename = "auth:totp")]
+    AuthTotp,
+    #[serde(rename = "auth:csrf")]
+    AuthCsrf,
+    #[serde(rename = "auth:local")]
+    AuthLocal,
+    #[serde(rename = "auth:github")]
+    AuthGithub,
+    #[serde(rename = "auth:microsoft")]
+    AuthMicrosoft,
+    #[serde(rename = "auth:impersonate")]
+    AuthImpersonate,
+}
+
+impl_enum_to_from_string!(KeyScope, "");
+
+impl KeyScope {
+    /// Returns true if methods with scope may be called by root keys of an
+    /// organisation, these methods are masked to the organisation.
+    pub fn is_organisation(self) -> bool {
+        match self {
+            Self::AuditRead
+            | Self::OrganisationRead
+            | Self::OrganisationWrite
+            | Self::ServiceRead
+            | Self::ServiceWrite
+            | Self::UserRead
+            | Self::UserWrite
+            | Self::AuthImpersonate => true,
+            _ => false,
+        }
+    }
+}
+
 /// Key.
 #[derive(Debug, Clone)]
 pub struct Key {
@@ -53,8 +159,17 @@ pub struct Key {
     pub is_revoked: bool,
     pub type_: KeyType,
     pub name: String,
+    pub value_prefix: String,
     pub service_id: Option<Uuid>,
     pub user_id: Option<Uuid>,
+    pub scope: Vec<KeyScope>,
+    pub allow_remote: Vec<Cidr>,
+    pub predecessor_id: Option<Uuid>,
+    pub successor_id: Option<Uuid>,
+    pub revoke_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub last_used_remote: Option<String>,
+    pub organisation_id: Option<Uuid>,
 }
 
 impl fmt::Display for Key {
@@ -66,12 +181,40 @@ impl fmt::Display for Key {
         write!(f, "\n\tis_revoked {}", self.is_revoked)?;
         write!(f, "\n\ttype {}", self.type_)?;
         write!(f, "\n\tname {}", self.name)?;
+        write!(f, "\n\tvalue_prefix {}", self.value_prefix)?;
         if let Some(service_id) = &self.service_id {
             write!(f, "\n\tservice_id {}", service_id)?;
         }
         if let Some(user_id) = &self.user_id {
             write!(f, "\n\tuser_id {}", user_id)?;
         }
+        if !self.scope.is_empty() {
+            let scope: Vec<String> = self.scope.iter().map(|x| x.to_string()).collect();
+            write!(f, "\n\tscope {}", scope.join(", "))?;
+        }
+        if !self.allow_remote.is_empty() {
+            let allow_remote: Vec<String> =
+                self.allow_remote.iter().map(|x| x.to_string()).collect();
+            write!(f, "\n\tallow_remote {}", allow_remote.join(", "))?;
+        }
+        if let Some(predecessor_id) = &self.predecessor_id {
+            write!(f, "\n\tpredecessor_id {}", predecessor_id)?;
+        }
+        if let Some(successor_id) = &self.successor_id {
+            write!(f, "\n\tsuccessor_id {}", successor_id)?;
+        }
+        if let Some(revoke_at) = &self.revoke_at {
+            write!(f, "\n\trevoke_at {}", revoke_at)?;
+        }
+        if let Some(last_used_at) = &self.last_used_at {
+            write!(f, "\n\tlast_used_at {}", last_used_at)?;
+        }
+        if let Some(last_used_remote) = &self.last_used_remote {
+            write!(f, "\n\tlast_used_remote {}", last_used_remote)?;
+        }
+        if let Some(organisation_id) = &self.organisation_id {
+            write!(f, "\n\torganisation_id {}", organisation_id)?;
+        }
         Ok(())
     }
 }
@@ -88,6 +231,23 @@ impl AuditDiff for Key {
             .compare("is_enabled", &self.is_enabled, &previous.is_enabled)
             .compare("is_revoked", &self.is_revoked, &previous.is_revoked)
             .compare("name", &self.name, &previous.name)
+            .compare_vec("scope", &self.scope, &previous.scope)
+            .compare_vec("allow_remote", &self.allow_remote, &previous.allow_remote)
+            .compare_opt(
+                "predecessor_id",
+                self.predecessor_id.as_ref(),
+                previous.predecessor_id.as_ref(),
+            )
+            .compare_opt(
+                "successor_id",
+                self.successor_id.as_ref(),
+                previous.successor_id.as_ref(),
+            )
+            .compare_opt(
+                "revoke_at",
+                self.revoke_at.as_ref(),
+                previous.revoke_at.as_ref(),
+            )
             .into_value()
     }
 }
@@ -95,7 +255,11 @@ impl AuditDiff for Key {
 /// Key with value.
 ///
 /// This is split from `Key` to make value private except when created
-/// or read internally.
+/// or read internally. Key values are stored as a hash, the value is
+/// returned when created and for token and TOTP keys when read internally,
+/// else value is an empty string. If a token or TOTP key has been rotated
+/// and the predecessor is within its grace period, the predecessor value is
+/// included so that tokens signed with it can still be verified.
 #[derive(Debug, Clone)]
 pub struct KeyWithValue {
     pub created_at: DateTime<Utc>,
@@ -105,9 +269,19 @@ pub struct KeyWithValue {
     pub is_revoked: bool,
     pub type_: KeyType,
     pub name: String,
+    pub value_prefix: String,
     pub value: String,
     pub service_id: Option<Uuid>,
     pub user_id: Option<Uuid>,
+    pub scope: Vec<KeyScope>,
+    pub allow_remote: Vec<Cidr>,
+    pub predecessor_id: Option<Uuid>,
+    pub successor_id: Option<Uuid>,
+    pub revoke_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub last_used_remote: Option<String>,
+    pub organisation_id: Option<Uuid>,
+    pub predecessor_value: Option<String>,
 }
 
 impl fmt::Display for KeyWithValue {
@@ -119,6 +293,7 @@ impl fmt::Display for KeyWithValue {
         write!(f, "\n\tis_revoked {}", self.is_revoked)?;
         write!(f, "\n\ttype {}", self.type_)?;
         write!(f, "\n\tname {}", self.name)?;
+        write!(f, "\n\tvalue_prefix {}", self.value_prefix)?;
         write!(f, "\n\tvalue {}", self.value)?;
         if let Some(service_id) = &self.service_id {
             write!(f, "\n\tservice_id {}", service_id)?;
@@ -126,6 +301,33 @@ impl fmt::Display for KeyWithValue {
         if let Some(user_id) = &self.user_id {
             write!(f, "\n\tuser_id {}", user_id)?;
         }
+        if !self.scope.is_empty() {
+            let scope: Vec<String> = self.scope.iter().map(|x| x.to_string()).collect();
+            write!(f, "\n\tscope {}", scope.join(", "))?;
+        }
+        if !self.allow_remote.is_empty() {
+            let allow_remote: Vec<String> =
+                self.allow_remote.iter().map(|x| x.to_string()).collect();
+            write!(f, "\n\tallow_remote {}", allow_remote.join(", "))?;
+        }
+        if let Some(predecessor_id) = &self.predecessor_id {
+            write!(f, "\n\tpredecessor_id {}", predecessor_id)?;
+        }
+        if let Some(successor_id) = &self.successor_id {
+            write!(f, "\n\tsuccessor_id {}", successor_id)?;
+        }
+        if let Some(revoke_at) = &self.revoke_at {
+            write!(f, "\n\trevoke_at {}", revoke_at)?;
+        }
+        if let Some(last_used_at) = &self.last_used_at {
+            write!(f, "\n\tlast_used_at {}", last_used_at)?;
+        }
+        if let Some(last_used_remote) = &self.last_used_remote {
+            write!(f, "\n\tlast_used_remote {}", last_used_remote)?;
+        }
+        if let Some(organisation_id) = &self.organisation_id {
+            write!(f, "\n\torganisation_id {}", organisation_id)?;
+        }
         Ok(())
     }
 }
@@ -136,6 +338,77 @@ impl AuditSubject for KeyWithValue {
     }
 }
 
+impl AuditDiff for KeyWithValue {
+    fn diff(&self, previous: &Self) -> Value {
+        Key::from(self.clone()).diff(&Key::from(previous.clone()))
+    }
+}
+
+impl KeyWithValue {
+    /// Returns values which may be used to verify this key, the
+    /// predecessor value is included while in its grace period.
+    pub fn values_verify(&self) -> Vec<&str> {
+        let mut values = vec![self.value.as_str()];
+        if let Some(predecessor_value) = self.predecessor_value.as_ref() {
+            values.push(predecessor_value.as_str());
+        }
+        values
+    }
+
+    /// Check key is enabled and not revoked, keys are revoked once their
+    /// revoke time has passed.
+    pub fn check(self) -> DriverResult<Self> {
+        let revoke_passed = self.revoke_at.map(|x| x <= Utc::now()).unwrap_or(false);
+        if !self.is_enabled {
+            Err(DriverError::KeyDisabled)
+        } else if self.is_revoked || revoke_passed {
+            Err(DriverError::KeyRevoked)
+        } else {
+            Ok(self)
+        }
+    }
+
+    /// Check key scope allows access to method with required scope.
+    /// Keys with an empty scope are not restricted, except root keys of an
+    /// organisation which may only call organisation scoped methods.
+    pub fn check_scope(&self, scope: Option<KeyScope>) -> DriverResult<()> {
+        match scope {
+            Some(scope) => {
+                if self.organisation_id.is_some() && !scope.is_organisation() {
+                    Err(DriverError::KeyScopeForbidden)
+                } else if self.scope.is_empty() || self.scope.contains(&scope) {
+                    Ok(())
+                } else {
+                    Err(DriverError::KeyScopeForbidden)
+                }
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Check scope of a key created by this key is allowed, keys with a scope
+    /// can only create keys with a non-empty subset of their scope.
+    pub fn check_scope_create(&self, scope: &[KeyScope]) -> DriverResult<()> {
+        if self.scope.is_empty()
+            || (!scope.is_empty() && scope.iter().all(|x| self.scope.contains(x)))
+        {
+            Ok(())
+        } else {
+            Err(DriverError::KeyScopeForbidden)
+        }
+    }
+
+    /// Check remote address is allowed, keys with an empty
+    /// allowed remote array are not restricted.
+    pub fn check_remote(&self, remote: Option<IpAddr>) -> DriverResult<()> {
+        if Cidr::vec_allows(&self.allow_remote, remote) {
+            Ok(())
+        } else {
+            Err(DriverError::KeyRemoteForbidden)
+        }
+    }
+}
+
 impl From<KeyWithValue> for Key {
     fn from(k: KeyWithValue) -> Self {
         Self {
@@ -146,8 +419,17 @@ impl From<KeyWithValue> for Key {
             is_revoked: k.is_revoked,
             type_: k.type_,
             name: k.name,
+            value_prefix: k.value_prefix,
             service_id: k.service_id,
             user_id: k.user_id,
+            scope: k.scope,
+            allow_remote: k.allow_remote,
+            predecessor_id: k.predecessor_id,
+            successor_id: k.successor_id,
+            revoke_at: k.revoke_at,
+            last_used_at: k.last_used_at,
+            last_used_remote: k.last_used_remote,
+            organisation_id: k.organisation_id,
         }
     }
 }
@@ -161,6 +443,8 @@ pub enum KeyListQuery {
 }
 
 /// Key list filter.
+///
+/// Keys which have never been used are compared to `last_used_lt` by created at time.
 #[derive(Debug)]
 pub struct KeyListFilter {
     pub id: Option<Vec<Uuid>>,
@@ -169,6 +453,7 @@ pub struct KeyListFilter {
     pub type_: Option<Vec<KeyType>>,
     pub service_id: Option<Vec<Uuid>>,
     pub user_id: Option<Vec<Uuid>>,
+    pub last_used_lt: Option<DateTime<Utc>>,
     pub limit: i64,
 }
 
@@ -196,6 +481,10 @@ pub struct KeyCreate {
     pub value: String,
     pub service_id: Option<Uuid>,
     pub user_id: Option<Uuid>,
+    pub scope: Vec<KeyScope>,
+    pub allow_remote: Vec<Cidr>,
+    pub predecessor_id: Option<Uuid>,
+    pub organisation_id: Option<Uuid>,
 }
 
 impl KeyCreate {
@@ -213,6 +502,10 @@ impl KeyCreate {
             value,
             service_id: None,
             user_id: None,
+            scope: Vec::new(),
+            allow_remote: Vec::new(),
+            predecessor_id: None,
+            organisation_id: None,
         }
     }
 
@@ -230,6 +523,10 @@ impl KeyCreate {
             value,
             service_id: Some(service_id),
             user_id: None,
+            scope: Vec::new(),
+            allow_remote: Vec::new(),
+            predecessor_id: None,
+            organisation_id: None,
         }
     }
 
@@ -253,8 +550,49 @@ impl KeyCreate {
             value,
             service_id: Some(service_id),
             user_id: Some(user_id),
+            scope: Vec::new(),
+            allow_remote: Vec::new(),
+            predecessor_id: None,
+            organisation_id: None,
         }
     }
+
+    /// Create successor of rotated key, type, service, user, scope, allowed
+    /// remote and organisation are copied from predecessor key.
+    pub fn successor(key: &KeyWithValue, name: Option<String>) -> Self {
+        let value = value_generate();
+        Self {
+            is_enabled: key.is_enabled,
+            is_revoked: false,
+            type_: key.type_,
+            name: name.unwrap_or_else(|| key.name.clone()),
+            value,
+            service_id: key.service_id,
+            user_id: key.user_id,
+            scope: key.scope.clone(),
+            allow_remote: key.allow_remote.clone(),
+            predecessor_id: Some(key.id),
+            organisation_id: key.organisation_id,
+        }
+    }
+
+    /// Set key scope, only used by root and service keys.
+    pub fn scope(mut self, scope: Vec<KeyScope>) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Set key allowed remote CIDR ranges.
+    pub fn allow_remote(mut self, allow_remote: Vec<Cidr>) -> Self {
+        self.allow_remote = allow_remote;
+        self
+    }
+
+    /// Set key organisation, only used by root keys.
+    pub fn organisation_id(mut self, organisation_id: Option<Uuid>) -> Self {
+        self.organisation_id = organisation_id;
+        self
+    }
 }
 
 /// Key read by service ID and user ID.
@@ -326,6 +664,17 @@ impl KeyRead {
     }
 }
 
+/// Key rotate data.
+///
+/// Successor key is created with the same type, service, user, scope and allowed remote,
+/// predecessor is revoked after grace period.
+#[derive(Debug)]
+pub struct KeyRotate {
+    pub id: Uuid,
+    pub name: Option<String>,
+    pub grace_period: Duration,
+}
+
 /// Key update data.
 #[derive(Debug)]
 pub struct KeyUpdate {
@@ -342,3 +691,206 @@ fn value_generate() -> String {
         .generate()
         .as_base32()
 }
+
+/// Key secret.
+///
+/// Used to hash key values for storage and lookup, to encrypt values
+/// of token and TOTP keys which are required internally as secrets, and
+/// to sign audit log chain points.
+#[derive(Clone)]
+pub struct KeySecret {
+    hash_key: Vec<u8>,
+    encrypt_key: Vec<u8>,
+    chain_key: Vec<u8>,
+}
+
+impl fmt::Debug for KeySecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "KeySecret {{ ... }}")
+    }
+}
+
+impl KeySecret {
+    /// Create key secret, hash and encryption keys are derived from secret.
+    pub fn new<S: AsRef<[u8]>>(secret: S) -> Self {
+        Self {
+            hash_key: Self::derive(secret.as_ref(), b"sso:key:hash"),
+            encrypt_key: Self::derive(secret.as_ref(), b"sso:key:encrypt"),
+            chain_key: Self::derive(secret.as_ref(), b"sso:audit:chain"),
+        }
+    }
+
+    /// Returns visible prefix of key value, used for lookup.
+    pub fn value_prefix(value: &str) -> String {
+        value.chars().take(LEN_KEY_VALUE_PREFIX).collect()
+    }
+
+    /// Returns keyed hash of key value.
+    pub fn value_hash(&self, value: &str) -> String {
+        let mut mac = self.hash_mac();
+        mac.input(value.as_bytes());
+        hex::encode(mac.result().code())
+    }
+
+    /// Returns true if key value matches hash, compared in constant time.
+    pub fn value_verify(&self, value: &str, hash: &str) -> bool {
+        let hash = match hex::decode(hash) {
+            Ok(hash) => hash,
+            Err(_e) => return false,
+        };
+        let mut mac = self.hash_mac();
+        mac.input(value.as_bytes());
+        mac.verify(&hash).is_ok()
+    }
+
+    /// Encrypt key value, returns nonce and ciphertext encoded as hex.
+    pub fn secret_encrypt(&self, value: &str) -> DriverResult<String> {
+        let nonce = KeyBuilder::new()
+            .size(BYTES_KEY_SECRET_NONCE)
+            .generate()
+            .as_vec();
+        let mut secret = self
+            .cipher()
+            .encrypt(GenericArray::from_slice(&nonce), value.as_bytes())
+            .map_err(|_e| DriverError::KeySecretEncrypt)?;
+        let mut out = nonce;
+        out.append(&mut secret);
+        Ok(hex::encode(out))
+    }
+
+    /// Decrypt key value encrypted by `secret_encrypt`.
+    pub fn secret_decrypt(&self, secret: &str) -> DriverResult<String> {
+        let secret = hex::decode(secret).map_err(|_e| DriverError::KeySecretDecrypt)?;
+        if secret.len() < BYTES_KEY_SECRET_NONCE {
+            return Err(DriverError::KeySecretDecrypt);
+        }
+        let (nonce, secret) = secret.split_at(BYTES_KEY_SECRET_NONCE);
+        let value = self
+            .cipher()
+            .decrypt(GenericArray::from_slice(nonce), secret)
+            .map_err(|_e| DriverError::KeySecretDecrypt)?;
+        String::from_utf8(value).map_err(|_e| DriverError::KeySecretDecrypt)
+    }
+
+    /// Returns signature of audit chain point value.
+    pub fn chain_sign(&self, value: &str) -> String {
+        let mut mac = HmacSha256::new_varkey(&self.chain_key).unwrap();
+        mac.input(value.as_bytes());
+        hex::encode(mac.result().code())
+    }
+
+    /// Returns true if audit chain point value matches signature, compared in constant time.
+    pub fn chain_verify(&self, value: &str, signature: &str) -> bool {
+        let signature = match hex::decode(signature) {
+            Ok(signature) => signature,
+            Err(_e) => return false,
+        };
+        let mut mac = HmacSha256::new_varkey(&self.chain_key).unwrap();
+        mac.input(value.as_bytes());
+        mac.verify(&signature).is_ok()
+    }
+
+    fn derive(secret: &[u8], label: &[u8]) -> Vec<u8> {
+        let mut mac = HmacSha256::new_varkey(secret).unwrap();
+        mac.input(label);
+        mac.result().code().to_vec()
+    }
+
+    fn hash_mac(&self) -> HmacSha256 {
+        HmacSha256::new_varkey(&self.hash_key).unwrap()
+    }
+
+    fn cipher(&self) -> ChaCha20Poly1305 {
+        ChaCha20Poly1305::new(*GenericArray::from_slice(&self.encrypt_key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_secret_chain_sign_verify() {
+        let secret = KeySecret::new("secret");
+        let signature = secret.chain_sign("value");
+        assert!(secret.chain_verify("value", &signature));
+        assert!(!secret.chain_verify("other", &signature));
+        assert!(!secret.chain_verify("value", "invalid"));
+        assert!(!KeySecret::new("other").chain_verify("value", &signature));
+    }
+
+    #[test]
+    fn key_secret_encrypt_decrypt() {
+        let secret = KeySecret::new("secret");
+        let e1 = secret.secret_encrypt("value").unwrap();
+        let e2 = secret.secret_encrypt("value").unwrap();
+        assert!(!e1.contains(&hex::encode("value")));
+        assert_ne!(e1, e2);
+        assert_eq!(secret.secret_decrypt(&e1).unwrap(), "value");
+        assert_eq!(secret.secret_decrypt(&e2).unwrap(), "value");
+        assert!(KeySecret::new("other").secret_decrypt(&e1).is_err());
+        assert!(secret.secret_decrypt("invalid").is_err());
+    }
+
+    fn key_with_scope(scope: Vec<KeyScope>) -> KeyWithValue {
+        let now = Utc::now();
+        KeyWithValue {
+            created_at: now,
+            updated_at: now,
+            id: Uuid::new_v4(),
+            is_enabled: true,
+            is_revoked: false,
+            type_: KeyType::Key,
+            name: "root".to_owned(),
+            value_prefix: "".to_owned(),
+            value: "".to_owned(),
+            service_id: None,
+            user_id: None,
+            scope,
+            allow_remote: Vec::new(),
+            predecessor_id: None,
+            successor_id: None,
+            revoke_at: None,
+            last_used_at: None,
+            last_used_remote: None,
+            organisation_id: None,
+            predecessor_value: None,
+        }
+    }
+
+    #[test]
+    fn key_check() {
+        assert!(key_with_scope(Vec::new()).check().is_ok());
+
+        let mut key = key_with_scope(Vec::new());
+        key.is_enabled = false;
+        assert!(key.check().is_err());
+
+        let mut key = key_with_scope(Vec::new());
+        key.is_revoked = true;
+        assert!(key.check().is_err());
+
+        let mut key = key_with_scope(Vec::new());
+        key.revoke_at = Some(Utc::now() + Duration::seconds(60));
+        assert!(key.check().is_ok());
+        key.revoke_at = Some(Utc::now() - Duration::seconds(1));
+        assert!(key.check().is_err());
+    }
+
+    #[test]
+    fn key_check_scope_create() {
+        let key = key_with_scope(Vec::new());
+        assert!(key.check_scope_create(&[]).is_ok());
+        assert!(key.check_scope_create(&[KeyScope::KeyWrite]).is_ok());
+
+        let key = key_with_scope(vec![KeyScope::UserRead, KeyScope::UserWrite]);
+        assert!(key.check_scope_create(&[KeyScope::UserRead]).is_ok());
+        assert!(key
+            .check_scope_create(&[KeyScope::UserRead, KeyScope::UserWrite])
+            .is_ok());
+        assert!(key.check_scope_create(&[]).is_err());
+        assert!(key
+            .check_scope_create(&[KeyScope::UserRead, KeyScope::KeyWrite])
+            .is_err());
+    }
+}
@@ -43,7 +43,8 @@ lazy_static! {
         let registry = Registry::new();
 
         let audit_count_opts = Opts::new(METRICS_NAME_AUDIT_COUNT, METRICS_HELP_AUDIT_COUNT);
-        let audit_count = IntCounterVec::new(audit_count_opts, &["type", "status"]).unwrap();
+        let audit_count =
+            IntCounterVec::new(audit_count_opts, &["type", "status", "organisation"]).unwrap();
 
         let grpc_count_opts = Opts::new(METRICS_NAME_GRPC_COUNT, METRICS_HELP_GRPC_COUNT);
         let grpc_count = IntCounterVec::new(grpc_count_opts, &["path", "status"]).unwrap();
@@ -77,11 +78,14 @@ impl Metrics {
         let audit_metrics = driver.audit_read_metrics(&metrics.audit_from, None)?;
 
         metrics.audit_from = Utc::now();
-        for (type_, status_code, count) in audit_metrics.iter() {
+        for (type_, status_code, organisation_id, count) in audit_metrics.iter() {
             let status_code = format!("{}", status_code);
+            let organisation_id = organisation_id
+                .map(|x| x.to_string())
+                .unwrap_or_else(String::new);
             metrics
                 .audit_count
-                .with_label_values(&[type_, &status_code])
+                .with_label_values(&[type_, &status_code, &organisation_id])
                 .inc_by(*count);
         }
 
@@ -1,18 +1,34 @@
 mod audit;
+mod audit_enrich;
+mod audit_export;
+mod audit_retention;
+mod cidr;
 mod error;
+mod group;
 mod key;
 mod metrics;
+mod organisation;
 pub(crate) mod pattern;
 mod postgres;
+mod role;
 mod service;
+mod service_user;
 mod template;
 mod user;
+mod webhook;
 
 pub use crate::driver::postgres::{Postgres, PostgresLockFn};
-pub use crate::driver::{audit::*, error::*, key::*, metrics::*, service::*, template::*, user::*};
+pub use crate::driver::{
+    audit::*, audit_enrich::*, audit_export::*, audit_retention::*, cidr::*, error::*, group::*,
+    key::*, metrics::*, organisation::*, role::*, service::*, service_user::*, template::*,
+    user::*, webhook::*,
+};
 
 /// Default limit.
 pub const DEFAULT_LIMIT: i64 = 50;
 
 /// Default CSRF expires seconds.
 pub const DEFAULT_CSRF_EXPIRES_S: i64 = 1000;
+
+/// Default key rotate grace period seconds.
+pub const DEFAULT_KEY_ROTATE_GRACE_PERIOD_S: i64 = 86400;
@@ -0,0 +1,105 @@
+use crate::{AuditDiff, AuditDiffBuilder, AuditSubject, DriverError, DriverResult};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::fmt;
+use uuid::Uuid;
+
+/// Organisation.
+///
+/// Organisations own services, users and root keys, user email addresses
+/// are unique per organisation. Services, users and root keys without an
+/// organisation share a global namespace.
+#[derive(Debug, Clone)]
+pub struct Organisation {
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub id: Uuid,
+    pub is_enabled: bool,
+    pub name: String,
+}
+
+impl fmt::Display for Organisation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Organisation {}", self.id)?;
+        write!(f, "\n\tcreated_at {}", self.created_at)?;
+        write!(f, "\n\tupdated_at {}", self.updated_at)?;
+        write!(f, "\n\tis_enabled {}", self.is_enabled)?;
+        write!(f, "\n\tname {}", self.name)
+    }
+}
+
+impl AuditSubject for Organisation {
+    fn subject(&self) -> String {
+        format!("{}", self.id)
+    }
+}
+
+impl AuditDiff for Organisation {
+    fn diff(&self, previous: &Self) -> Value {
+        AuditDiffBuilder::default()
+            .compare("is_enabled", &self.is_enabled, &previous.is_enabled)
+            .compare("name", &self.name, &previous.name)
+            .into_value()
+    }
+}
+
+impl Organisation {
+    /// Check organisation is enabled.
+    pub fn check(self) -> DriverResult<Self> {
+        if !self.is_enabled {
+            Err(DriverError::OrganisationDisabled)
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+/// Organisation list query.
+#[derive(Debug)]
+pub enum OrganisationListQuery {
+    Limit,
+    IdGt(Uuid),
+    IdLt(Uuid),
+}
+
+/// Organisation list filter.
+#[derive(Debug)]
+pub struct OrganisationListFilter {
+    pub id: Option<Vec<Uuid>>,
+    pub is_enabled: Option<bool>,
+    pub limit: i64,
+}
+
+/// Organisation list.
+#[derive(Debug)]
+pub struct OrganisationList {
+    pub query: OrganisationListQuery,
+    pub filter: OrganisationListFilter,
+}
+
+/// Organisation create.
+#[derive(Debug)]
+pub struct OrganisationCreate {
+    pub is_enabled: bool,
+    pub name: String,
+}
+
+/// Organisation read.
+#[derive(Debug)]
+pub struct OrganisationRead {
+    pub id: Uuid,
+}
+
+impl OrganisationRead {
+    pub fn new(id: Uuid) -> Self {
+        Self { id }
+    }
+}
+
+/// Organisation update.
+#[derive(Debug)]
+pub struct OrganisationUpdate {
+    pub id: Uuid,
+    pub is_enabled: Option<bool>,
+    pub name: Option<String>,
+}
@@ -2,30 +2,33 @@
 use crate::prelude::*;
 use libreauth::oath::TOTPBuilder;
 use reqwest::Client;
+use serde_json::{Map, Value};
 use sha1::{Digest, Sha1};
+use std::net::IpAddr;
 use url::Url;
 use uuid::Uuid;
 
-/// Verify TOTP code using key.
-pub fn totp_verify(key: &str, code: &str) -> DriverResult<()> {
-    let totp = TOTPBuilder::new()
-        .base32_key(key)
-        .finalize()
-        .map_err::<DriverError, _>(Into::into)?;
+/// Verify TOTP code using key, rotated key predecessor is accepted during grace period.
+pub fn totp_verify(key: &KeyWithValue, code: &str) -> DriverResult<()> {
+    for key_value in key.values_verify() {
+        let totp = TOTPBuilder::new()
+            .base32_key(key_value)
+            .finalize()
+            .map_err::<DriverError, _>(Into::into)?;
 
-    if !totp.is_valid(&code) {
-        Err(DriverError::TotpInvalid)
-    } else {
-        Ok(())
+        if totp.is_valid(&code) {
+            return Ok(());
+        }
     }
+    Err(DriverError::TotpInvalid)
 }
 
-/// Authenticate root key.
+/// Authenticate root key, returns authenticated key.
 pub fn key_root_authenticate(
     driver: &Postgres,
     audit: &mut AuditBuilder,
     auth: &HeaderAuth,
-) -> DriverResult<()> {
+) -> DriverResult<KeyWithValue> {
     let key = match auth {
         HeaderAuth::Traefik(x) => match x.key_id {
             Some(key_id) => {
@@ -43,8 +46,14 @@ pub fn key_root_authenticate(
         },
         HeaderAuth::None => Err(DriverError::KeyUndefined),
     }?;
-    key.ok_or_else(|| DriverError::KeyNotFound).map(|key| {
+    key.ok_or_else(|| DriverError::KeyNotFound).and_then(|key| {
         audit.key(Some(&key));
+        let key = key.check()?;
+        key.check_scope(audit.meta().scope())?;
+        check_remote(audit, auth, |remote| key.check_remote(remote))?;
+        check_organisation(driver, key.organisation_id)?;
+        key_last_used_update(driver, audit, &key.id)?;
+        Ok(key)
     })
 }
 
@@ -85,6 +94,7 @@ pub fn user_key_token_authenticate(
                             audit,
                             key.user_id.unwrap(),
                         )?;
+                        key_last_used_update(driver, audit, &key.id)?;
                         Ok(user)
                     }
                     HeaderAuthType::Token(x) => {
@@ -96,8 +106,10 @@ pub fn user_key_token_authenticate(
                         let key =
                             key_read_user_checked(driver, &service, audit, &user, KeyType::Token)?;
 
-                        // Safely decode token with user key.
-                        Jwt::decode_access(&service, &user, &key, x)?;
+                        // Safely decode token with user key, impersonation tokens are
+                        // allowed and actor is returned in headers.
+                        user_token_decode_access(driver, audit, &service, &user, &key, x)?;
+                        key_last_used_update(driver, audit, &key.id)?;
                         Ok(user)
                     }
                 },
@@ -123,7 +135,19 @@ pub fn key_authenticate(
             Ok(service)
         })
         .map(Some)
-        .or_else(|_err| key_root_authenticate(driver, audit, auth).map(|_| None))?;
+        .or_else(|err| match err {
+            // Service key is disabled, revoked or valid but out of scope,
+            // remote address is not allowed, organisation is disabled or
+            // user is authenticated by impersonation token, do not try root key.
+            DriverError::KeyDisabled
+            | DriverError::KeyRevoked
+            | DriverError::KeyScopeForbidden
+            | DriverError::KeyRemoteForbidden
+            | DriverError::ServiceRemoteForbidden
+            | DriverError::OrganisationDisabled
+            | DriverError::UserImpersonationForbidden => Err(err),
+            _ => key_root_authenticate(driver, audit, auth).map(|_| None),
+        })?;
     Ok(service)
 }
 
@@ -152,15 +176,25 @@ fn key_service_authenticate_try(
     key.ok_or_else(|| DriverError::KeyNotFound)
         .and_then(|key| {
             audit.key(Some(&key));
+            let key = key.check()?;
+            key.check_scope(audit.meta().scope())?;
+            check_remote(audit, auth, |remote| key.check_remote(remote))?;
             key.service_id
                 .ok_or_else(|| DriverError::KeyServiceUndefined)
         })
-        .and_then(|service_id| key_service_authenticate_inner(driver, audit, service_id))
+        .and_then(|service_id| key_service_authenticate_inner(driver, audit, auth, service_id))
+        .and_then(|service| {
+            if let Some(key_id) = audit.get_key_id() {
+                key_last_used_update(driver, audit, &key_id)?;
+            }
+            Ok(service)
+        })
 }
 
 fn key_service_authenticate_inner(
     driver: &Postgres,
     audit: &mut AuditBuilder,
+    auth: &HeaderAuth,
     service_id: Uuid,
 ) -> DriverResult<Service> {
     let service = driver
@@ -168,9 +202,103 @@ fn key_service_authenticate_inner(
         .ok_or_else(|| DriverError::ServiceNotFound)?
         .check()?;
     audit.service(Some(&service));
+    check_remote(audit, auth, |remote| service.check_remote(remote))?;
+    check_organisation(driver, service.organisation_id)?;
     Ok(service)
 }
 
+/// Safely decode user access token with key, returns expiry time.
+///
+/// If token is an impersonation token the actor root key must still exist,
+/// be enabled, not revoked and in scope to impersonate users.
+pub fn user_token_decode_access<T: AsRef<str>>(
+    driver: &Postgres,
+    audit: &mut AuditBuilder,
+    service: &Service,
+    user: &User,
+    key: &KeyWithValue,
+    token: T,
+) -> DriverResult<i64> {
+    let (access_token_expires, actor_key_id) = Jwt::decode_access(service, user, key, token)?;
+    audit.actor_key_id(actor_key_id);
+    if let Some(actor_key_id) = actor_key_id {
+        let actor_key = driver
+            .key_read(&KeyRead::RootId(actor_key_id), None)?
+            .ok_or_else(|| DriverError::KeyNotFound)?
+            .check()?;
+        actor_key.check_scope(Some(KeyScope::AuthImpersonate))?;
+        check_organisation(driver, actor_key.organisation_id)?;
+    }
+    Ok(access_token_expires)
+}
+
+/// Check user is not authenticated by an impersonation token, used to
+/// refuse user tokens in requests other than token verify and the Traefik
+/// service hook.
+fn impersonation_check(audit: &AuditBuilder) -> DriverResult<()> {
+    match audit.get_actor_key_id() {
+        Some(_) => Err(DriverError::UserImpersonationForbidden),
+        None => Ok(()),
+    }
+}
+
+/// Check organisation identifier is allowed by organisation mask of request,
+/// requests not masked to an organisation are allowed any organisation.
+pub fn organisation_mask_check(
+    organisation_id_mask: Option<Uuid>,
+    organisation_id: Option<Uuid>,
+) -> DriverResult<()> {
+    match organisation_id_mask {
+        Some(organisation_id_mask) if organisation_id != Some(organisation_id_mask) => {
+            Err(DriverError::OrganisationNotFound)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Check service is allowed by organisation mask of request, the service is
+/// read to compare its organisation. Requests not masked to an organisation
+/// are allowed any service.
+pub fn service_organisation_mask_check(
+    driver: &Postgres,
+    organisation_id_mask: Option<Uuid>,
+    service_id: Uuid,
+) -> DriverResult<()> {
+    match organisation_id_mask {
+        Some(_) => {
+            let service = driver
+                .service_read(&ServiceRead::new(service_id), None)?
+                .ok_or_else(|| DriverError::ServiceNotFound)?;
+            organisation_mask_check(organisation_id_mask, service.organisation_id)
+        }
+        None => Ok(()),
+    }
+}
+
+/// Check organisation of key or service exists and is enabled.
+fn check_organisation(driver: &Postgres, organisation_id: Option<Uuid>) -> DriverResult<()> {
+    match organisation_id {
+        Some(organisation_id) => driver
+            .organisation_read(&OrganisationRead::new(organisation_id))?
+            .ok_or_else(|| DriverError::OrganisationNotFound)?
+            .check()
+            .map(|_| ()),
+        None => Ok(()),
+    }
+}
+
+/// Check remote address of request is allowed, requests authenticated by
+/// Traefik headers have already been checked by forward authentication hook.
+fn check_remote<F>(audit: &AuditBuilder, auth: &HeaderAuth, f: F) -> DriverResult<()>
+where
+    F: FnOnce(Option<IpAddr>) -> DriverResult<()>,
+{
+    match auth {
+        HeaderAuth::Traefik(_) => Ok(()),
+        _ => f(audit.meta().remote_addr()),
+    }
+}
+
 fn check_audit_user(
     driver: &Postgres,
     audit: &mut AuditBuilder,
@@ -184,7 +312,7 @@ fn check_audit_user(
                 let key =
                     key_read_user_value_checked(driver, &service, audit, key_value, KeyType::Key)?;
                 user_read_id_checked(driver, Some(&service), audit, key.user_id.unwrap())?;
-                Ok(())
+                key_last_used_update(driver, audit, &key.id)
             }
             HeaderAuthType::Token(token) => {
                 // Unsafely decode token to get user identifier, used to read key for safe token decode.
@@ -194,20 +322,48 @@ fn check_audit_user(
                 let user = user_read_id_checked(driver, Some(&service), audit, user_id)?;
                 let key = key_read_user_checked(driver, &service, audit, &user, KeyType::Token)?;
 
-                // Safely decode token with user key.
-                Jwt::decode_access(&service, &user, &key, &token)?;
-                Ok(())
+                // Safely decode token with user key, impersonation tokens are forbidden.
+                user_token_decode_access(driver, audit, &service, &user, &key, &token)?;
+                impersonation_check(audit)?;
+                key_last_used_update(driver, audit, &key.id)
             }
         },
         None => Ok(()),
     }
 }
 
+/// Update key last used time and remote, only called after key is authenticated
+/// so failed attempts and audit logs referencing keys do not update it.
+fn key_last_used_update(
+    driver: &Postgres,
+    audit: &AuditBuilder,
+    key_id: &Uuid,
+) -> DriverResult<()> {
+    driver
+        .key_last_used_update(key_id, audit.meta().remote())
+        .map(|_| ())
+}
+
+/// Update user last login time and remote, and last used time of user token
+/// key. Called by login methods after user is authenticated.
+pub fn user_login_update(
+    driver: &Postgres,
+    audit: &AuditBuilder,
+    user: &User,
+    key: &KeyWithValue,
+) -> DriverResult<()> {
+    key_last_used_update(driver, audit, &key.id)?;
+    driver
+        .user_last_login_update(&user.id, audit.meta().remote())
+        .map(|_| ())
+}
+
 /// Read user by ID.
 /// Checks user is enabled, returns bad request if disabled.
+/// If service mask is defined, also checks user is an active member of service.
 pub fn user_read_id_checked(
     driver: &Postgres,
-    _service_mask: Option<&Service>,
+    service_mask: Option<&Service>,
     audit: &mut AuditBuilder,
     id: Uuid,
 ) -> DriverResult<User> {
@@ -219,6 +375,26 @@ pub fn user_read_id_checked(
     if !user.is_enabled {
         return Err(DriverError::UserDisabled);
     }
+    if let Some(service) = service_mask {
+        service_user_checked(driver, service, &user)?;
+    }
+    Ok(user)
+}
+
+/// Read user by ID with an invitation to service.
+/// Checks user is enabled, returns bad request if disabled.
+/// Also checks user membership of service is an invitation which has not expired.
+pub fn user_read_id_invited(
+    driver: &Postgres,
+    service: &Service,
+    audit: &mut AuditBuilder,
+    id: Uuid,
+) -> DriverResult<User> {
+    let user = user_read_id_checked(driver, None, audit, id)?;
+    driver
+        .service_user_read(&service.id, &user.id)?
+        .ok_or_else(|| DriverError::ServiceUserNotFound)?
+        .check_invite()?;
     Ok(user)
 }
 
@@ -238,15 +414,17 @@ pub fn user_read_id_unchecked(
     Ok(user)
 }
 
-/// Read user by email address.
+/// Read user by email address in organisation of service mask.
 /// Also checks user is enabled, returns bad request if disabled.
+/// If service mask is defined, also checks user is an active member of service.
 pub fn user_read_email_checked(
     driver: &Postgres,
-    _service_mask: Option<&Service>,
+    service_mask: Option<&Service>,
     audit: &mut AuditBuilder,
     email: &str,
 ) -> DriverResult<User> {
-    let read = UserRead::Email(email.to_owned());
+    let organisation_id = service_mask.and_then(|x| x.organisation_id);
+    let read = UserRead::Email(email.to_owned(), organisation_id);
     let user = driver
         .user_read(&read)?
         .ok_or_else(|| DriverError::UserNotFound)?;
@@ -254,9 +432,70 @@ pub fn user_read_email_checked(
     if !user.is_enabled {
         return Err(DriverError::UserDisabled);
     }
+    if let Some(service) = service_mask {
+        service_user_checked(driver, service, &user)?;
+    }
     Ok(user)
 }
 
+/// Check user is an active member of service.
+/// Returns bad request if user is not a member, invited or suspended.
+fn service_user_checked(driver: &Postgres, service: &Service, user: &User) -> DriverResult<()> {
+    driver
+        .service_user_read(&service.id, &user.id)?
+        .ok_or_else(|| DriverError::ServiceUserNotFound)?
+        .check()?;
+    Ok(())
+}
+
+/// Returns true if login fingerprint of request is not in recent login history of user.
+/// Returns false if user has no recent successful logins, so first login is not reported.
+pub fn user_login_fingerprint_new(
+    driver: &Postgres,
+    audit: &AuditBuilder,
+    user: &User,
+) -> DriverResult<bool> {
+    let now = Utc::now();
+    let since = now - Duration::days(AUDIT_LOGIN_HISTORY_DAYS);
+    let list = AuditList {
+        query: AuditListQuery::CreatedLe(now, AUDIT_LOGIN_HISTORY_LIMIT, None),
+        filter: AuditListFilter {
+            type_: Some(AuditType::LOGIN.iter().map(|x| x.to_string()).collect()),
+            user_id: Some(vec![user.id]),
+            status_code_le: Some(0),
+            ..AuditListFilter::default()
+        },
+    };
+    let history: Vec<Audit> = driver
+        .audit_list(&list, None, None)?
+        .into_iter()
+        .filter(|x| x.created_at >= since)
+        .collect();
+    if history.is_empty() {
+        return Ok(false);
+    }
+    let fingerprint = AuditLoginFingerprint::from_meta(audit.meta());
+    Ok(!history
+        .iter()
+        .any(|x| AuditLoginFingerprint::from_audit(x) == fingerprint))
+}
+
+/// Read role names and user attributes picked by service for user access token.
+pub fn user_token_claims(
+    driver: &Postgres,
+    service: &Service,
+    user: &User,
+) -> DriverResult<(Vec<String>, Map<String, Value>)> {
+    let roles = driver.user_role_names(&service.id, &user.id)?;
+    let attributes = if service.user_claims.is_empty() {
+        Map::default()
+    } else {
+        let service_attributes = driver.user_service_attributes_read(&user.id, &service.id)?;
+        service.user_claims_pick(&user.attributes, &service_attributes)
+    };
+    Ok((roles, attributes))
+}
+
 /// Read key by user reference and key type.
 /// Also checks key is enabled and not revoked, returns bad request if disabled.
 pub fn key_read_user_checked(
@@ -273,13 +512,7 @@ pub fn key_read_user_checked(
         )?
         .ok_or_else(|| DriverError::KeyNotFound)?;
     audit.user_key(Some(&key));
-    if !key.is_enabled {
-        Err(DriverError::KeyDisabled)
-    } else if key.is_revoked {
-        Err(DriverError::KeyRevoked)
-    } else {
-        Ok(key)
-    }
+    key.check()
 }
 
 /// Unchecked read key by user reference.
@@ -320,13 +553,7 @@ where
         )?
         .ok_or_else(|| DriverError::KeyNotFound)?;
     audit.user_key(Some(&key));
-    if !key.is_enabled {
-        Err(DriverError::KeyDisabled)
-    } else if key.is_revoked {
-        Err(DriverError::KeyRevoked)
-    } else {
-        Ok(key)
-    }
+    key.check()
 }
 
 /// Unchecked read key by user value.
@@ -2,12 +2,16 @@ mod diesel_admin;
 mod model;
 
 use crate::{
-    driver::postgres::model::{ModelAudit, ModelKey, ModelService, ModelUser},
+    driver::postgres::model::{
+        ModelAudit, ModelAuditRetention, ModelGroup, ModelKey, ModelOrganisation, ModelRole,
+        ModelService, ModelUser, ModelUserService, ModelWebhook,
+    },
     prelude::*,
 };
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use diesel::{prelude::*, r2d2::ConnectionManager};
-use std::fmt;
+use serde_json::Value;
+use std::{fmt, sync::Arc};
 use uuid::Uuid;
 
 embed_migrations!("migrations");
@@ -18,11 +22,17 @@ type PooledConnection = r2d2::PooledConnection<ConnectionManager<PgConnection>>;
 #[derive(Clone)]
 pub struct Postgres {
     pool: r2d2::Pool<ConnectionManager<PgConnection>>,
+    key_secret: Arc<KeySecret>,
+    audit_enrich: Arc<AuditEnrich>,
 }
 
 impl fmt::Debug for Postgres {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Postgres {{ pool }}")
+        write!(
+            f,
+            "Postgres {{ pool, key_secret, {:?} }}",
+            self.audit_enrich
+        )
     }
 }
 
@@ -30,20 +40,31 @@ impl fmt::Debug for Postgres {
 pub type PostgresLockFn = Box<dyn FnOnce(&PgConnection) -> DriverResult<bool>>;
 
 impl Postgres {
-    /// Initialise driver with connection URL and number of pooled connections.
-    pub fn initialise(url: &str, connections: Option<u32>) -> DriverResult<Self> {
+    /// Initialise driver with connection URL, number of pooled connections and key secret.
+    pub fn initialise(
+        url: &str,
+        connections: Option<u32>,
+        key_secret: KeySecret,
+    ) -> DriverResult<Self> {
         let manager = ConnectionManager::<PgConnection>::new(url);
         let mut pool = r2d2::Pool::builder();
         if let Some(connections) = connections {
             pool = pool.max_size(connections);
         }
         let pool = pool.build(manager).map_err(DriverError::R2d2)?;
-        let driver = Postgres { pool };
+        let driver = Postgres {
+            pool,
+            key_secret: Arc::new(key_secret),
+            audit_enrich: Arc::new(AuditEnrich::new()),
+        };
         driver.run_migrations()?;
+        driver.run_key_value_migration()?;
+        driver.run_audit_chain_migration()?;
+        driver.run_audit_partition_create()?;
         Ok(driver)
     }
 
-    pub fn from_env<T>(url_name: T, connections_name: T) -> Self
+    pub fn from_env<T>(url_name: T, connections_name: T, key_secret_name: T) -> Self
     where
         T: AsRef<str>,
     {
@@ -51,7 +72,16 @@ impl Postgres {
             .expect("Failed to read postgres URL environment variable.");
         let connections = env::value_opt::<u32>(connections_name.as_ref())
             .expect("Failed to read postgres connections environment variable.");
-        Self::initialise(&url, connections).expect("Failed to initialise postgres connection.")
+        let key_secret = env::string(key_secret_name.as_ref())
+            .expect("Failed to read key secret environment variable.");
+        Self::initialise(&url, connections, KeySecret::new(key_secret))
+            .expect("Failed to initialise postgres connection.")
+    }
+
+    /// Set enrichment of audit logs when they are created.
+    pub fn audit_enrich(mut self, audit_enrich: AuditEnrich) -> Self {
+        self.audit_enrich = Arc::new(audit_enrich);
+        self
     }
 
     pub fn conn(&self) -> DriverResult<PooledConnection> {
@@ -62,6 +92,33 @@ impl Postgres {
         let connection = self.conn()?;
         embedded_migrations::run(&connection).map_err(DriverError::DieselMigrations)
     }
+
+    fn run_key_value_migration(&self) -> DriverResult<()> {
+        let conn = self.conn()?;
+        let count = ModelKey::value_migrate(&conn, &self.key_secret)?;
+        if count > 0 {
+            info!("Migrated {} plaintext key values", count);
+        }
+        Ok(())
+    }
+
+    fn run_audit_chain_migration(&self) -> DriverResult<()> {
+        let conn = self.conn()?;
+        let count = ModelAudit::chain_migrate(&conn, &self.key_secret)?;
+        if count > 0 {
+            info!("Migrated {} unchained audit logs", count);
+        }
+        Ok(())
+    }
+
+    fn run_audit_partition_create(&self) -> DriverResult<()> {
+        let conn = self.conn()?;
+        let created = ModelAudit::partition_create(&conn, &Utc::now())?;
+        if !created.is_empty() {
+            info!("Created audit log partitions {:?}", created);
+        }
+        Ok(())
+    }
 }
 
 impl Postgres {
@@ -104,15 +161,21 @@ impl Postgres {
         &self,
         list: &AuditList,
         service_id: Option<Uuid>,
+        organisation_id: Option<Uuid>,
     ) -> DriverResult<Vec<Audit>> {
         let conn = self.conn()?;
-        ModelAudit::list(&conn, list, service_id)
+        ModelAudit::list(&conn, list, service_id, organisation_id)
     }
 
     /// Create audit log.
+    ///
+    /// Remote and user agent are enriched if enabled.
     pub fn audit_create(&self, create: &AuditCreate) -> DriverResult<Audit> {
+        let enrichment = self
+            .audit_enrich
+            .enrich(create.meta.remote_addr(), create.meta.user_agent());
         let conn = self.conn()?;
-        ModelAudit::create(&conn, create)
+        ModelAudit::create(&conn, &self.key_secret, create, &enrichment)
     }
 
     /// Read audit log.
@@ -120,21 +183,33 @@ impl Postgres {
         &self,
         read: &AuditRead,
         service_id: Option<Uuid>,
+        organisation_id: Option<Uuid>,
     ) -> DriverResult<Option<Audit>> {
         let conn = self.conn()?;
-        ModelAudit::read(&conn, read, service_id)
+        ModelAudit::read(&conn, read, service_id, organisation_id)
     }
 
-    /// Read audit metrics, returns array of counts for distinct audit types.
+    /// Read audit metrics, returns array of counts for distinct audit types and organisations.
     pub fn audit_read_metrics(
         &self,
         from: &DateTime<Utc>,
         service_id_mask: Option<&Uuid>,
-    ) -> DriverResult<Vec<(String, u16, i64)>> {
+    ) -> DriverResult<Vec<(String, u16, Option<Uuid>, i64)>> {
         let conn = self.conn()?;
         ModelAudit::read_metrics(&conn, from, service_id_mask)
     }
 
+    /// Read audit report, returns counts by time bucket and grouped dimensions.
+    pub fn audit_report(
+        &self,
+        report: &AuditReport,
+        service_id_mask: Option<Uuid>,
+        organisation_id_mask: Option<Uuid>,
+    ) -> DriverResult<Vec<AuditReportRow>> {
+        let conn = self.conn()?;
+        ModelAudit::report(&conn, report, service_id_mask, organisation_id_mask)
+    }
+
     /// Update audit log, append data to data array.
     pub fn audit_update(
         &self,
@@ -142,13 +217,166 @@ impl Postgres {
         service_id_mask: Option<Uuid>,
     ) -> DriverResult<Audit> {
         let conn = self.conn()?;
-        ModelAudit::update(&conn, update, service_id_mask)
+        ModelAudit::update(&conn, &self.key_secret, update, service_id_mask)
+    }
+
+    /// Run audit retention task, drops or deletes from partitions of expired
+    /// audit logs and creates partitions ahead of time.
+    pub fn audit_retention(&self, task: &AuditRetentionTask) -> DriverResult<AuditRetentionResult> {
+        let conn = self.conn()?;
+        ModelAudit::retention(&conn, &self.key_secret, task)
+    }
+
+    /// List audit retentions.
+    pub fn audit_retention_list(&self) -> DriverResult<Vec<AuditRetention>> {
+        let conn = self.conn()?;
+        ModelAuditRetention::list(&conn)
+    }
+
+    /// Create or update audit retention of service and type.
+    pub fn audit_retention_set(&self, set: &AuditRetentionSet) -> DriverResult<AuditRetention> {
+        let conn = self.conn()?;
+        ModelAuditRetention::set(&conn, set)
+    }
+
+    /// Delete audit retention of service and type.
+    pub fn audit_retention_delete(&self, delete: &AuditRetentionDelete) -> DriverResult<usize> {
+        let conn = self.conn()?;
+        ModelAuditRetention::delete(&conn, delete)
+    }
+
+    /// Create signed checkpoint of audit log chain.
+    pub fn audit_chain_checkpoint(&self) -> DriverResult<Option<AuditChainPoint>> {
+        let conn = self.conn()?;
+        ModelAudit::chain_checkpoint(&conn, &self.key_secret)
+    }
+
+    /// Verify audit log chain.
+    pub fn audit_chain_verify(&self) -> DriverResult<AuditChainVerify> {
+        let conn = self.conn()?;
+        ModelAudit::chain_verify(&conn, &self.key_secret)
+    }
+
+    /// Returns chain sequence number of audit log, or of chain head if ID is
+    /// none. Returns none if audit log is not found or not chained.
+    pub fn audit_chain_seq(&self, id: Option<&Uuid>) -> DriverResult<Option<i64>> {
+        let conn = self.conn()?;
+        ModelAudit::chain_seq(&conn, id)
+    }
+
+    /// List audit logs after chain sequence number in chain order, returns
+    /// sequence number of each audit log. Used to watch for new audit logs,
+    /// sequence numbers are assigned in commit order unlike creation times.
+    pub fn audit_list_chain(
+        &self,
+        chain_seq_gt: i64,
+        limit: i64,
+        filter: &AuditListFilter,
+        service_id: Option<Uuid>,
+        organisation_id: Option<Uuid>,
+    ) -> DriverResult<Vec<(i64, Audit)>> {
+        let conn = self.conn()?;
+        ModelAudit::list_chain(
+            &conn,
+            chain_seq_gt,
+            limit,
+            filter,
+            service_id.as_ref(),
+            organisation_id.as_ref(),
+        )
+    }
+
+    /// Export audit logs to sink from named export position.
+    pub fn audit_export(
+        &self,
+        name: &str,
+        since: Option<&DateTime<Utc>>,
+        sink: &mut AuditExportSink,
+    ) -> DriverResult<usize> {
+        let conn = self.conn()?;
+        ModelAudit::export(&conn, name, since, sink)
+    }
+
+    // ---------------
+    // Group Functions
+    // ---------------
+
+    /// List groups.
+    pub fn group_list(
+        &self,
+        list: &GroupList,
+        service_id: Option<Uuid>,
+        organisation_id: Option<Uuid>,
+    ) -> DriverResult<Vec<Group>> {
+        let conn = self.conn()?;
+        ModelGroup::list(&conn, list, service_id, organisation_id)
+    }
+
+    /// Create group.
+    ///
+    /// Returns error if name is not unique for service or parent does not
+    /// exist or is a group of another service.
+    pub fn group_create(&self, create: &GroupCreate) -> DriverResult<Group> {
+        let conn = self.conn()?;
+        ModelGroup::create(&conn, create)
+    }
+
+    /// Read group.
+    pub fn group_read(
+        &self,
+        read: &GroupRead,
+        service_id: Option<Uuid>,
+    ) -> DriverResult<Option<Group>> {
+        let conn = self.conn()?;
+        ModelGroup::read(&conn, read, service_id)
+    }
+
+    /// Update group.
+    ///
+    /// Returns error if parent would create a cycle.
+    pub fn group_update(&self, update: &GroupUpdate) -> DriverResult<Group> {
+        let conn = self.conn()?;
+        ModelGroup::update(&conn, update)
+    }
+
+    /// Delete group, child groups are moved to the top level.
+    pub fn group_delete(&self, id: &Uuid) -> DriverResult<usize> {
+        let conn = self.conn()?;
+        ModelGroup::delete(&conn, id)
     }
 
-    /// Delete many audit logs.
-    pub fn audit_delete(&self, created_at: &DateTime<Utc>) -> DriverResult<usize> {
+    /// Add user to group.
+    pub fn group_user_create(&self, group_user: &GroupUser) -> DriverResult<usize> {
         let conn = self.conn()?;
-        ModelAudit::delete(&conn, created_at)
+        ModelGroup::user_create(&conn, group_user)
+    }
+
+    /// Remove user from group.
+    pub fn group_user_delete(&self, group_user: &GroupUser) -> DriverResult<usize> {
+        let conn = self.conn()?;
+        ModelGroup::user_delete(&conn, group_user)
+    }
+
+    /// Read groups of service of which user is an effective member.
+    pub fn group_user_effective(
+        &self,
+        service_id: &Uuid,
+        user_id: &Uuid,
+    ) -> DriverResult<Vec<Group>> {
+        let conn = self.conn()?;
+        ModelGroup::user_effective(&conn, service_id, user_id)
+    }
+
+    /// Assign role to group.
+    pub fn group_role_create(&self, group_role: &GroupRole) -> DriverResult<usize> {
+        let conn = self.conn()?;
+        ModelGroup::role_create(&conn, group_role)
+    }
+
+    /// Remove role from group.
+    pub fn group_role_delete(&self, group_role: &GroupRole) -> DriverResult<usize> {
+        let conn = self.conn()?;
+        ModelGroup::role_delete(&conn, group_role)
     }
 
     // -------------
@@ -156,9 +384,14 @@ impl Postgres {
     // -------------
 
     /// List keys.
-    pub fn key_list(&self, list: &KeyList, service_id: Option<Uuid>) -> DriverResult<Vec<Key>> {
+    pub fn key_list(
+        &self,
+        list: &KeyList,
+        service_id: Option<Uuid>,
+        organisation_id: Option<Uuid>,
+    ) -> DriverResult<Vec<Key>> {
         let conn = self.conn()?;
-        ModelKey::list(&conn, list, service_id)
+        ModelKey::list(&conn, list, service_id, organisation_id)
     }
 
     /// Count keys.
@@ -173,7 +406,7 @@ impl Postgres {
     /// Returns error if related service or user does not exist.
     pub fn key_create(&self, create: &KeyCreate) -> DriverResult<KeyWithValue> {
         let conn = self.conn()?;
-        ModelKey::create(&conn, create)
+        ModelKey::create(&conn, &self.key_secret, create)
     }
 
     /// Read key.
@@ -183,7 +416,17 @@ impl Postgres {
         service_id: Option<Uuid>,
     ) -> DriverResult<Option<KeyWithValue>> {
         let conn = self.conn()?;
-        ModelKey::read(&conn, read, service_id)
+        ModelKey::read(&conn, &self.key_secret, read, service_id)
+    }
+
+    /// Rotate key.
+    ///
+    /// Creates successor key, predecessor key is revoked after grace period.
+    /// Returns predecessor key before rotation and successor key.
+    /// Returns error if key is revoked or has already been rotated.
+    pub fn key_rotate(&self, rotate: &KeyRotate) -> DriverResult<(KeyWithValue, KeyWithValue)> {
+        let conn = self.conn()?;
+        ModelKey::rotate(&conn, &self.key_secret, rotate)
     }
 
     /// Update key.
@@ -198,12 +441,120 @@ impl Postgres {
         ModelKey::update_many(&conn, user_id, update)
     }
 
+    /// Update key last used time and remote, called on successful authentication.
+    pub fn key_last_used_update(&self, id: &Uuid, remote: &str) -> DriverResult<usize> {
+        let conn = self.conn()?;
+        ModelKey::last_used_update(&conn, id, remote)
+    }
+
     /// Delete key.
     pub fn key_delete(&self, id: &Uuid) -> DriverResult<usize> {
         let conn = self.conn()?;
         ModelKey::delete(&conn, id)
     }
 
+    // ----------------------
+    // Organisation Functions
+    // ----------------------
+
+    /// List organisations.
+    pub fn organisation_list(
+        &self,
+        list: &OrganisationList,
+        organisation_id: Option<Uuid>,
+    ) -> DriverResult<Vec<Organisation>> {
+        let conn = self.conn()?;
+        ModelOrganisation::list(&conn, list, organisation_id)
+    }
+
+    /// Create organisation.
+    pub fn organisation_create(&self, create: &OrganisationCreate) -> DriverResult<Organisation> {
+        let conn = self.conn()?;
+        ModelOrganisation::create(&conn, create)
+    }
+
+    /// Read organisation.
+    pub fn organisation_read(&self, read: &OrganisationRead) -> DriverResult<Option<Organisation>> {
+        let conn = self.conn()?;
+        ModelOrganisation::read(&conn, read)
+    }
+
+    /// Update organisation.
+    pub fn organisation_update(&self, update: &OrganisationUpdate) -> DriverResult<Organisation> {
+        let conn = self.conn()?;
+        ModelOrganisation::update(&conn, update)
+    }
+
+    /// Delete organisation.
+    pub fn organisation_delete(&self, id: &Uuid) -> DriverResult<usize> {
+        let conn = self.conn()?;
+        ModelOrganisation::delete(&conn, id)
+    }
+
+    // --------------
+    // Role Functions
+    // --------------
+
+    /// List roles.
+    pub fn role_list(
+        &self,
+        list: &RoleList,
+        service_id: Option<Uuid>,
+        organisation_id: Option<Uuid>,
+    ) -> DriverResult<Vec<Role>> {
+        let conn = self.conn()?;
+        ModelRole::list(&conn, list, service_id, organisation_id)
+    }
+
+    /// Create role.
+    ///
+    /// Returns error if name is not unique for service.
+    pub fn role_create(&self, create: &RoleCreate) -> DriverResult<Role> {
+        let conn = self.conn()?;
+        ModelRole::create(&conn, create)
+    }
+
+    /// Read role.
+    pub fn role_read(
+        &self,
+        read: &RoleRead,
+        service_id: Option<Uuid>,
+    ) -> DriverResult<Option<Role>> {
+        let conn = self.conn()?;
+        ModelRole::read(&conn, read, service_id)
+    }
+
+    /// Update role.
+    pub fn role_update(&self, update: &RoleUpdate) -> DriverResult<Role> {
+        let conn = self.conn()?;
+        ModelRole::update(&conn, update)
+    }
+
+    /// Delete role.
+    pub fn role_delete(&self, id: &Uuid) -> DriverResult<usize> {
+        let conn = self.conn()?;
+        ModelRole::delete(&conn, id)
+    }
+
+    /// Assign role to user.
+    pub fn user_role_create(&self, user_role: &UserRole) -> DriverResult<usize> {
+        let conn = self.conn()?;
+        ModelRole::user_create(&conn, user_role)
+    }
+
+    /// Remove role from user.
+    pub fn user_role_delete(&self, user_role: &UserRole) -> DriverResult<usize> {
+        let conn = self.conn()?;
+        ModelRole::user_delete(&conn, user_role)
+    }
+
+    /// Read names of roles assigned to user for service, including roles
+    /// assigned to groups of which user is an effective member.
+    pub fn user_role_names(&self, service_id: &Uuid, user_id: &Uuid) -> DriverResult<Vec<String>> {
+        let conn = self.conn()?;
+        ModelRole::user_names(&conn, service_id, user_id)
+    }
+
     // -----------------
     // Service Functions
     // -----------------
@@ -213,9 +564,10 @@ impl Postgres {
         &self,
         list: &ServiceList,
         service_id: Option<Uuid>,
+        organisation_id: Option<Uuid>,
     ) -> DriverResult<Vec<Service>> {
         let conn = self.conn()?;
-        ModelService::list(&conn, list, service_id)
+        ModelService::list(&conn, list, service_id, organisation_id)
     }
 
     /// Create service.
@@ -246,14 +598,58 @@ impl Postgres {
         ModelService::delete(&conn, id)
     }
 
+    /// List users of service with membership status.
+    pub fn service_user_list(&self, list: &ServiceUserList) -> DriverResult<Vec<ServiceUser>> {
+        let conn = self.conn()?;
+        ModelUserService::status_list(&conn, list)
+    }
+
+    /// Read membership status of user for service.
+    pub fn service_user_read(
+        &self,
+        service_id: &Uuid,
+        user_id: &Uuid,
+    ) -> DriverResult<Option<ServiceUser>> {
+        let conn = self.conn()?;
+        ModelUserService::status_read(&conn, service_id, user_id)
+    }
+
+    /// Update membership status of user for service, creates membership if
+    /// it does not exist.
+    pub fn service_user_update(&self, update: &ServiceUserUpdate) -> DriverResult<ServiceUser> {
+        let conn = self.conn()?;
+        ModelUserService::status_update(&conn, update)
+    }
+
+    /// Update membership of user for service to invited with expiry time.
+    pub fn service_user_invite(
+        &self,
+        service_id: &Uuid,
+        user_id: &Uuid,
+        invite_expires_at: &DateTime<Utc>,
+    ) -> DriverResult<ServiceUser> {
+        let conn = self.conn()?;
+        ModelUserService::status_invite(&conn, service_id, user_id, invite_expires_at)
+    }
+
+    /// Remove membership status of user for service.
+    pub fn service_user_delete(&self, service_id: &Uuid, user_id: &Uuid) -> DriverResult<usize> {
+        let conn = self.conn()?;
+        ModelUserService::status_delete(&conn, service_id, user_id)
+    }
+
     // --------------
     // User Functions
     // --------------
 
     /// List users.
-    pub fn user_list(&self, list: &UserList) -> DriverResult<Vec<User>> {
+    pub fn user_list(
+        &self,
+        list: &UserList,
+        organisation_id: Option<Uuid>,
+    ) -> DriverResult<Vec<User>> {
         let conn = self.conn()?;
-        ModelUser::list(&conn, list)
+        ModelUser::list(&conn, list, organisation_id)
     }
 
     /// Create user.
@@ -276,9 +672,163 @@ impl Postgres {
         ModelUser::update(&conn, update)
     }
 
+    /// Update user last login time and remote, called on successful login.
+    pub fn user_last_login_update(&self, id: &Uuid, remote: &str) -> DriverResult<usize> {
+        let conn = self.conn()?;
+        ModelUser::last_login_update(&conn, id, remote)
+    }
+
+    /// Invite user, creates user with invited membership of service and token key.
+    pub fn user_invite(&self, invite: &UserInvite) -> DriverResult<UserWithInvite> {
+        let conn = self.conn()?;
+        ModelUser::invite(&conn, &self.key_secret, invite)
+    }
+
+    /// Mark user email address as verified.
+    pub fn user_email_verify(&self, id: &Uuid) -> DriverResult<User> {
+        let conn = self.conn()?;
+        ModelUser::email_verify(&conn, id)
+    }
+
+    /// Update user pending email address.
+    pub fn user_email_pending_update(
+        &self,
+        id: &Uuid,
+        email_pending: Option<&str>,
+    ) -> DriverResult<User> {
+        let conn = self.conn()?;
+        ModelUser::email_pending_update(&conn, id, email_pending)
+    }
+
+    /// Confirm user pending email address.
+    pub fn user_email_pending_confirm(&self, id: &Uuid) -> DriverResult<User> {
+        let conn = self.conn()?;
+        ModelUser::email_pending_confirm(&conn, id)
+    }
+
     /// Delete user.
     pub fn user_delete(&self, id: &Uuid) -> DriverResult<usize> {
         let conn = self.conn()?;
         ModelUser::delete(&conn, id)
     }
+
+    /// Export user data, returns none if user does not exist.
+    pub fn user_export(&self, id: &Uuid) -> DriverResult<Option<UserExport>> {
+        let conn = self.conn()?;
+        ModelUser::export(&conn, id)
+    }
+
+    /// Erase user, audit logs are pseudonymised before keys and user are deleted.
+    pub fn user_erase(&self, id: &Uuid) -> DriverResult<usize> {
+        let conn = self.conn()?;
+        ModelUser::erase(&conn, &self.key_secret, id)
+    }
+
+    /// Read attributes of user for service.
+    ///
+    /// Returns an empty object if attributes have not been set.
+    pub fn user_service_attributes_read(
+        &self,
+        user_id: &Uuid,
+        service_id: &Uuid,
+    ) -> DriverResult<Value> {
+        let conn = self.conn()?;
+        ModelUserService::attributes_read(&conn, user_id, service_id)
+    }
+
+    /// Update attributes of user for service, replaces existing attributes.
+    pub fn user_service_attributes_update(
+        &self,
+        user_id: &Uuid,
+        service_id: &Uuid,
+        attributes: &Value,
+    ) -> DriverResult<Value> {
+        let conn = self.conn()?;
+        ModelUserService::attributes_update(&conn, user_id, service_id, attributes)
+    }
+
+    // -----------------
+    // Webhook Functions
+    // -----------------
+
+    /// List webhooks.
+    pub fn webhook_list(
+        &self,
+        list: &WebhookList,
+        service_id: Option<Uuid>,
+        organisation_id: Option<Uuid>,
+    ) -> DriverResult<Vec<Webhook>> {
+        let conn = self.conn()?;
+        ModelWebhook::list(&conn, list, service_id, organisation_id)
+    }
+
+    /// Create webhook, returns webhook with generated secret.
+    pub fn webhook_create(&self, create: &WebhookCreate) -> DriverResult<WebhookWithSecret> {
+        let conn = self.conn()?;
+        ModelWebhook::create(&conn, &self.key_secret, create)
+    }
+
+    /// Read webhook.
+    pub fn webhook_read(
+        &self,
+        read: &WebhookRead,
+        service_id: Option<Uuid>,
+    ) -> DriverResult<Option<Webhook>> {
+        let conn = self.conn()?;
+        ModelWebhook::read(&conn, read, service_id)
+    }
+
+    /// Update webhook.
+    pub fn webhook_update(&self, update: &WebhookUpdate) -> DriverResult<Webhook> {
+        let conn = self.conn()?;
+        ModelWebhook::update(&conn, update)
+    }
+
+    /// Delete webhook and its deliveries.
+    pub fn webhook_delete(&self, id: &Uuid) -> DriverResult<usize> {
+        let conn = self.conn()?;
+        ModelWebhook::delete(&conn, id)
+    }
+
+    /// List webhook deliveries.
+    pub fn webhook_delivery_list(
+        &self,
+        list: &WebhookDeliveryList,
+    ) -> DriverResult<Vec<WebhookDelivery>> {
+        let conn = self.conn()?;
+        ModelWebhook::delivery_list(&conn, list)
+    }
+
+    /// Read webhook delivery.
+    pub fn webhook_delivery_read(
+        &self,
+        read: &WebhookDeliveryRead,
+    ) -> DriverResult<Option<WebhookDelivery>> {
+        let conn = self.conn()?;
+        ModelWebhook::delivery_read(&conn, read)
+    }
+
+    /// Retry dead lettered webhook delivery.
+    ///
+    /// Returns error if delivery is not dead lettered.
+    pub fn webhook_delivery_retry(&self, id: &Uuid) -> DriverResult<WebhookDelivery> {
+        let conn = self.conn()?;
+        ModelWebhook::delivery_retry(&conn, id)
+    }
+
+    /// Claim pending webhook deliveries which are due for an attempt.
+    pub fn webhook_delivery_claim(
+        &self,
+        limit: i64,
+        lease: Duration,
+    ) -> DriverResult<Vec<WebhookDeliverySend>> {
+        let conn = self.conn()?;
+        ModelWebhook::delivery_claim(&conn, &self.key_secret, limit, lease)
+    }
+
+    /// Record result of webhook delivery attempt.
+    pub fn webhook_delivery_result(&self, result: &WebhookDeliveryResult) -> DriverResult<usize> {
+        let conn = self.conn()?;
+        ModelWebhook::delivery_result(&conn, result)
+    }
 }
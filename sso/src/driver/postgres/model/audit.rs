@@ -1,13 +1,38 @@
 use crate::{
-    schema::sso_audit, Audit, AuditCreate, AuditList, AuditListFilter, AuditListQuery, AuditRead,
-    AuditUpdate, DriverError, DriverResult,
+    audit_partition_range,
+    driver::postgres::{diesel_admin::pg_advisory_xact_lock, model::ModelWebhook},
+    schema::{sso_audit, sso_audit_chain_point, sso_audit_export},
+    Audit, AuditArchive, AuditChainError, AuditChainErrorType, AuditChainPoint,
+    AuditChainPointType, AuditChainVerify, AuditCreate, AuditEnrichment, AuditExportSink,
+    AuditList, AuditListFilter, AuditListQuery, AuditProxy, AuditRead, AuditReport,
+    AuditReportGroup, AuditReportRow, AuditRetentionResult, AuditRetentionTask, AuditUpdate,
+    DriverError, DriverResult, KeySecret, AUDIT_PARTITION_AHEAD_MONTHS, MAX_AUDIT_REPORT_ROWS,
 };
 use chrono::{DateTime, Utc};
 use diesel::{pg::Pg, prelude::*, sql_types};
 use serde_json::Value;
-use std::convert::TryInto;
+use sha2::{Digest, Sha256};
+use std::{collections::HashMap, convert::TryInto, str::FromStr};
 use uuid::Uuid;
 
+/// Advisory lock key used to serialise writes to audit log chain.
+const LOCK_AUDIT_CHAIN: i32 = 1_000;
+
+/// Number of audit logs loaded per query when walking chain.
+const CHAIN_BATCH: i64 = 1_000;
+
+diesel_infix_operator!(JsonbContains, " @> ", backend: Pg);
+diesel_infix_operator!(InetContainedByOrEq, " <<= ", backend: Pg);
+
+sql_function! {
+    /// Returns IP address of audit log remote, null if not an address.
+    fn sso_audit_remote_inet(remote: sql_types::Varchar) -> sql_types::Nullable<sql_types::Inet>;
+}
+sql_function! {
+    /// Returns CIDR string array as IP address array.
+    fn sso_audit_inet_array(cidr: sql_types::Array<sql_types::Varchar>) -> sql_types::Array<sql_types::Inet>;
+}
+
 #[derive(Debug, Identifiable, Queryable, QueryableByName)]
 #[table_name = "sso_audit"]
 #[primary_key(id)]
@@ -26,6 +51,20 @@ pub struct ModelAudit {
     service_id: Option<Uuid>,
     user_id: Option<Uuid>,
     user_key_id: Option<Uuid>,
+    organisation_id: Option<Uuid>,
+    actor_key_id: Option<Uuid>,
+    chain_seq: Option<i64>,
+    chain_previous: Option<String>,
+    chain_hash: Option<String>,
+    chain_superseded: Vec<String>,
+    proxy: Option<Value>,
+    remote_country: Option<String>,
+    remote_city: Option<String>,
+    remote_asn: Option<i64>,
+    remote_as_org: Option<String>,
+    user_agent_browser: Option<String>,
+    user_agent_os: Option<String>,
+    user_agent_device: Option<String>,
 }
 
 impl From<ModelAudit> for Audit {
@@ -45,6 +84,20 @@ impl From<ModelAudit> for Audit {
             service_id: audit.service_id,
             user_id: audit.user_id,
             user_key_id: audit.user_key_id,
+            organisation_id: audit.organisation_id,
+            actor_key_id: audit.actor_key_id,
+            proxy: audit
+                .proxy
+                .and_then(|x| serde_json::from_value::<AuditProxy>(x).ok()),
+            enrichment: AuditEnrichment {
+                remote_country: audit.remote_country,
+                remote_city: audit.remote_city,
+                remote_asn: audit.remote_asn,
+                remote_as_org: audit.remote_as_org,
+                user_agent_browser: audit.user_agent_browser,
+                user_agent_os: audit.user_agent_os,
+                user_agent_device: audit.user_agent_device,
+            },
         }
     }
 }
@@ -55,10 +108,50 @@ struct ModelAuditMetric {
     type_: String,
     #[sql_type = "sql_types::Int2"]
     status_code: i16,
+    #[sql_type = "sql_types::Nullable<sql_types::Uuid>"]
+    organisation_id: Option<Uuid>,
+    #[sql_type = "sql_types::BigInt"]
+    count: i64,
+}
+
+#[derive(Debug, QueryableByName)]
+struct ModelAuditReportRow {
+    #[sql_type = "sql_types::Timestamptz"]
+    bucket: DateTime<Utc>,
+    #[sql_type = "sql_types::Nullable<sql_types::Varchar>"]
+    type_: Option<String>,
+    #[sql_type = "sql_types::Nullable<sql_types::Int2>"]
+    status_code: Option<i16>,
+    #[sql_type = "sql_types::Nullable<sql_types::Uuid>"]
+    service_id: Option<Uuid>,
+    #[sql_type = "sql_types::Nullable<sql_types::Uuid>"]
+    user_id: Option<Uuid>,
+    #[sql_type = "sql_types::Nullable<sql_types::Varchar>"]
+    remote: Option<String>,
     #[sql_type = "sql_types::BigInt"]
     count: i64,
 }
 
+impl From<ModelAuditReportRow> for AuditReportRow {
+    fn from(x: ModelAuditReportRow) -> Self {
+        Self {
+            bucket: x.bucket,
+            type_: x.type_,
+            status_code: x.status_code.map(|x| x as u16),
+            service_id: x.service_id,
+            user_id: x.user_id,
+            remote: x.remote,
+            count: x.count,
+        }
+    }
+}
+
+#[derive(Debug, QueryableByName)]
+struct ModelAuditPartition {
+    #[sql_type = "sql_types::Varchar"]
+    name: String,
+}
+
 #[derive(Debug, Insertable)]
 #[table_name = "sso_audit"]
 struct ModelAuditInsert<'a> {
@@ -76,6 +169,47 @@ struct ModelAuditInsert<'a> {
     service_id: Option<&'a Uuid>,
     user_id: Option<&'a Uuid>,
     user_key_id: Option<&'a Uuid>,
+    organisation_id: Option<&'a Uuid>,
+    actor_key_id: Option<&'a Uuid>,
+    proxy: Option<Value>,
+    remote_country: Option<&'a str>,
+    remote_city: Option<&'a str>,
+    remote_asn: Option<i64>,
+    remote_as_org: Option<&'a str>,
+    user_agent_browser: Option<&'a str>,
+    user_agent_os: Option<&'a str>,
+    user_agent_device: Option<&'a str>,
+}
+
+#[derive(Debug, Queryable)]
+struct ModelAuditChainPoint {
+    id: Uuid,
+    type_: String,
+    chain_seq: i64,
+    chain_hash: String,
+    count: i64,
+    signature: String,
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "sso_audit_chain_point"]
+struct ModelAuditChainPointInsert<'a> {
+    created_at: &'a DateTime<Utc>,
+    id: &'a Uuid,
+    type_: &'a str,
+    chain_seq: i64,
+    chain_hash: &'a str,
+    count: i64,
+    signature: &'a str,
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "sso_audit_export"]
+struct ModelAuditExportUpsert<'a> {
+    created_at: &'a DateTime<Utc>,
+    updated_at: &'a DateTime<Utc>,
+    name: &'a str,
+    chain_seq: i64,
 }
 
 impl ModelAudit {
@@ -83,6 +217,7 @@ impl ModelAudit {
         conn: &PgConnection,
         list: &AuditList,
         service_id: Option<Uuid>,
+        organisation_id: Option<Uuid>,
     ) -> DriverResult<Vec<Audit>> {
         match list.query {
             AuditListQuery::CreatedLe(le, limit, offset_id) => Self::list_where_created_le(
@@ -92,6 +227,7 @@ impl ModelAudit {
                 &offset_id,
                 &list.filter,
                 service_id.as_ref(),
+                organisation_id.as_ref(),
             ),
             AuditListQuery::CreatedGe(ge, limit, offset_id) => Self::list_where_created_ge(
                 conn,
@@ -100,6 +236,7 @@ impl ModelAudit {
                 &offset_id,
                 &list.filter,
                 service_id.as_ref(),
+                organisation_id.as_ref(),
             ),
             AuditListQuery::CreatedLeAndGe(le, ge, limit, offset_id) => {
                 Self::list_where_created_le_and_ge(
@@ -110,12 +247,18 @@ impl ModelAudit {
                     &offset_id,
                     &list.filter,
                     service_id.as_ref(),
+                    organisation_id.as_ref(),
                 )
             }
         }
     }
 
-    pub fn create(conn: &PgConnection, create: &AuditCreate) -> DriverResult<Audit> {
+    pub fn create(
+        conn: &PgConnection,
+        key_secret: &KeySecret,
+        create: &AuditCreate,
+        enrichment: &AuditEnrichment,
+    ) -> DriverResult<Audit> {
         let now = Utc::now();
         let id = Uuid::new_v4();
         let data = create.data.clone().unwrap_or_else(|| json!({}));
@@ -134,18 +277,35 @@ impl ModelAudit {
             service_id: create.service_id.as_ref(),
             user_id: create.user_id.as_ref(),
             user_key_id: create.user_key_id.as_ref(),
+            organisation_id: create.organisation_id.as_ref(),
+            actor_key_id: create.actor_key_id.as_ref(),
+            proxy: create
+                .meta
+                .proxy()
+                .and_then(|x| serde_json::to_value(x).ok()),
+            remote_country: enrichment.remote_country.as_deref(),
+            remote_city: enrichment.remote_city.as_deref(),
+            remote_asn: enrichment.remote_asn,
+            remote_as_org: enrichment.remote_as_org.as_deref(),
+            user_agent_browser: enrichment.user_agent_browser.as_deref(),
+            user_agent_os: enrichment.user_agent_os.as_deref(),
+            user_agent_device: enrichment.user_agent_device.as_deref(),
         };
-        diesel::insert_into(sso_audit::table)
-            .values(&value)
-            .get_result::<ModelAudit>(conn)
-            .map_err(Into::into)
-            .map(Into::into)
+        conn.transaction(|| {
+            let audit = diesel::insert_into(sso_audit::table)
+                .values(&value)
+                .get_result::<ModelAudit>(conn)?;
+            let audit: Audit = Self::chain_append(conn, key_secret, audit)?.into();
+            ModelWebhook::delivery_enqueue(conn, &audit)?;
+            Ok(audit)
+        })
     }
 
     pub fn read(
         conn: &PgConnection,
         read: &AuditRead,
         service_id: Option<Uuid>,
+        organisation_id: Option<Uuid>,
     ) -> DriverResult<Option<Audit>> {
         let mut query = sso_audit::table.into_boxed();
 
@@ -155,6 +315,9 @@ impl ModelAudit {
         if let Some(service_id) = service_id.as_ref() {
             query = query.filter(sso_audit::dsl::service_id.eq(service_id));
         }
+        if let Some(organisation_id) = organisation_id.as_ref() {
+            query = query.filter(sso_audit::dsl::organisation_id.eq(organisation_id));
+        }
 
         query
             .filter(sso_audit::dsl::id.eq(read.id))
@@ -168,7 +331,7 @@ impl ModelAudit {
         conn: &PgConnection,
         from: &DateTime<Utc>,
         service_id_mask: Option<&Uuid>,
-    ) -> DriverResult<Vec<(String, u16, i64)>> {
+    ) -> DriverResult<Vec<(String, u16, Option<Uuid>, i64)>> {
         diesel::sql_query(include_str!("audit_read_metrics.sql"))
             .bind::<sql_types::Timestamptz, _>(from)
             .bind::<sql_types::Nullable<sql_types::Uuid>, _>(service_id_mask)
@@ -176,37 +339,768 @@ impl ModelAudit {
             .map_err(DriverError::DieselResult)
             .map(|x| {
                 x.into_iter()
-                    .map(|x| (x.type_, x.status_code as u16, x.count))
+                    .map(|x| (x.type_, x.status_code as u16, x.organisation_id, x.count))
                     .collect()
             })
     }
 
+    pub fn report(
+        conn: &PgConnection,
+        report: &AuditReport,
+        service_id_mask: Option<Uuid>,
+        organisation_id_mask: Option<Uuid>,
+    ) -> DriverResult<Vec<AuditReportRow>> {
+        diesel::sql_query(include_str!("audit_report.sql"))
+            .bind::<sql_types::Text, _>(report.bucket.as_str())
+            .bind::<sql_types::Bool, _>(report.is_grouped(AuditReportGroup::Type))
+            .bind::<sql_types::Bool, _>(report.is_grouped(AuditReportGroup::StatusCode))
+            .bind::<sql_types::Bool, _>(report.is_grouped(AuditReportGroup::ServiceId))
+            .bind::<sql_types::Bool, _>(report.is_grouped(AuditReportGroup::UserId))
+            .bind::<sql_types::Bool, _>(report.is_grouped(AuditReportGroup::Remote))
+            .bind::<sql_types::Timestamptz, _>(&report.ge)
+            .bind::<sql_types::Timestamptz, _>(&report.le)
+            .bind::<sql_types::Nullable<sql_types::Array<sql_types::Text>>, _>(&report.filter.type_)
+            .bind::<sql_types::Nullable<sql_types::Array<sql_types::Uuid>>, _>(
+                &report.filter.service_id,
+            )
+            .bind::<sql_types::Nullable<sql_types::Array<sql_types::Uuid>>, _>(
+                &report.filter.user_id,
+            )
+            .bind::<sql_types::Nullable<sql_types::Uuid>, _>(service_id_mask)
+            .bind::<sql_types::Nullable<sql_types::Uuid>, _>(organisation_id_mask)
+            .bind::<sql_types::BigInt, _>(MAX_AUDIT_REPORT_ROWS)
+            .load::<ModelAuditReportRow>(conn)
+            .map_err(DriverError::DieselResult)
+            .map(|x| x.into_iter().map(Into::into).collect())
+    }
+
     pub fn update(
         conn: &PgConnection,
+        key_secret: &KeySecret,
         update: &AuditUpdate,
         service_id_mask: Option<Uuid>,
     ) -> DriverResult<Audit> {
         let now = Utc::now();
         let status_code = update.status_code.map(|x| x as i16);
         let data = update.data.clone().unwrap_or_else(|| json!({}));
-        diesel::sql_query(include_str!("audit_update.sql"))
-            .bind::<sql_types::Uuid, _>(&update.id)
+        conn.transaction(|| {
+            let audit = diesel::sql_query(include_str!("audit_update.sql"))
+                .bind::<sql_types::Uuid, _>(&update.id)
+                .bind::<sql_types::Timestamptz, _>(now)
+                .bind::<sql_types::Nullable<sql_types::Int2>, _>(status_code)
+                .bind::<sql_types::Nullable<sql_types::Text>, _>(&update.subject)
+                .bind::<sql_types::Jsonb, _>(data)
+                .bind::<sql_types::Nullable<sql_types::Uuid>, _>(service_id_mask)
+                .get_result::<ModelAudit>(conn)?;
+            Self::chain_append(conn, key_secret, audit).map(Into::into)
+        })
+    }
+
+    /// List audit logs referencing user or keys of user, ordered by creation time.
+    pub fn list_user(
+        conn: &PgConnection,
+        user_id: &Uuid,
+        key_id: &[Uuid],
+    ) -> DriverResult<Vec<Audit>> {
+        use diesel::dsl::any;
+
+        sso_audit::table
+            .filter(
+                sso_audit::dsl::user_id
+                    .eq(user_id)
+                    .or(sso_audit::dsl::user_key_id.eq(any(key_id)))
+                    .or(sso_audit::dsl::subject.eq(user_id.to_string())),
+            )
+            .order(sso_audit::dsl::created_at.asc())
+            .load::<ModelAudit>(conn)
+            .map_err(Into::into)
+            .map(|x| x.into_iter().map(Into::into).collect())
+    }
+
+    /// Pseudonymise audit logs referencing user or keys of user.
+    ///
+    /// References to user and keys are removed, user subjects are replaced
+    /// by pseudonym, and remote, user agent and data are scrubbed.
+    /// Pseudonymised audit logs are appended to the chain again.
+    pub fn erase_user(
+        conn: &PgConnection,
+        key_secret: &KeySecret,
+        user_id: &Uuid,
+        key_id: &[Uuid],
+        pseudonym: &str,
+    ) -> DriverResult<usize> {
+        let now = Utc::now();
+        conn.transaction(|| {
+            let mut audit = diesel::sql_query(include_str!("audit_erase_user.sql"))
+                .bind::<sql_types::Uuid, _>(user_id)
+                .bind::<sql_types::Timestamptz, _>(now)
+                .bind::<sql_types::Text, _>(pseudonym)
+                .bind::<sql_types::Array<sql_types::Uuid>, _>(key_id)
+                .load::<ModelAudit>(conn)?;
+            audit.sort_by_key(|x| x.chain_seq);
+            let count = audit.len();
+            for audit in audit {
+                Self::chain_append(conn, key_secret, audit)?;
+            }
+            Ok(count)
+        })
+    }
+
+    /// Create audit log partitions for months from time up to ahead months,
+    /// and for months of audit logs in default partition. Returns names of
+    /// created partitions.
+    pub fn partition_create(conn: &PgConnection, now: &DateTime<Utc>) -> DriverResult<Vec<String>> {
+        diesel::sql_query(include_str!("audit_partition_create.sql"))
             .bind::<sql_types::Timestamptz, _>(now)
-            .bind::<sql_types::Nullable<sql_types::Int2>, _>(status_code)
-            .bind::<sql_types::Nullable<sql_types::Text>, _>(&update.subject)
-            .bind::<sql_types::Jsonb, _>(data)
-            .bind::<sql_types::Nullable<sql_types::Uuid>, _>(service_id_mask)
-            .get_result::<ModelAudit>(conn)
+            .bind::<sql_types::Integer, _>(AUDIT_PARTITION_AHEAD_MONTHS as i32)
+            .load::<ModelAuditPartition>(conn)
+            .map_err(DriverError::DieselResult)
+            .map(|x| x.into_iter().map(|x| x.name).collect())
+    }
+
+    /// Run audit retention task.
+    ///
+    /// Partitions of past months are dropped if all of their audit logs have
+    /// expired, otherwise expired audit logs are deleted. The head of chain is
+    /// never removed. Signed deletion points are created for audit logs whose
+    /// previous hash refers to a removed audit log, and checkpoints of removed
+    /// audit logs are deleted, so the chain remains verifiable.
+    pub fn retention(
+        conn: &PgConnection,
+        key_secret: &KeySecret,
+        task: &AuditRetentionTask,
+    ) -> DriverResult<AuditRetentionResult> {
+        let mut result = AuditRetentionResult::default();
+        result.created = Self::partition_create(conn, &task.now)?;
+
+        let partitions = diesel::sql_query(include_str!("audit_partition_list.sql"))
+            .load::<ModelAuditPartition>(conn)?;
+        for partition in partitions {
+            let (lower, upper) = match audit_partition_range(&partition.name) {
+                Some((lower, upper)) if upper <= task.now => (lower, upper),
+                _ => continue,
+            };
+            conn.transaction::<_, DriverError, _>(|| {
+                Self::retention_partition(
+                    conn,
+                    key_secret,
+                    task,
+                    &partition.name,
+                    (&lower, &upper),
+                    &mut result,
+                )
+            })?;
+        }
+        Ok(result)
+    }
+
+    fn retention_partition(
+        conn: &PgConnection,
+        key_secret: &KeySecret,
+        task: &AuditRetentionTask,
+        name: &str,
+        range: (&DateTime<Utc>, &DateTime<Utc>),
+        result: &mut AuditRetentionResult,
+    ) -> DriverResult<()> {
+        use diesel::dsl::{count_star, max};
+
+        diesel::select(pg_advisory_xact_lock(1, LOCK_AUDIT_CHAIN)).execute(conn)?;
+
+        let head = sso_audit::table
+            .select(max(sso_audit::dsl::chain_seq))
+            .first::<Option<i64>>(conn)?;
+        let expired = diesel::sql_query(include_str!("audit_retention_expired.sql"))
+            .bind::<sql_types::Timestamptz, _>(range.0)
+            .bind::<sql_types::Timestamptz, _>(range.1)
+            .bind::<sql_types::Timestamptz, _>(&task.now)
+            .bind::<sql_types::Integer, _>(task.default_days)
+            .bind::<sql_types::Nullable<sql_types::BigInt>, _>(head)
+            .load::<ModelAudit>(conn)?;
+        if expired.is_empty() {
+            return Ok(());
+        }
+        let count = sso_audit::table
+            .filter(
+                sso_audit::dsl::created_at
+                    .ge(range.0)
+                    .and(sso_audit::dsl::created_at.lt(range.1)),
+            )
+            .select(count_star())
+            .first::<i64>(conn)?;
+        let deleted = expired.len();
+        let id: Vec<Uuid> = expired.iter().map(|x| x.id).collect();
+        // Hashes of removed audit logs, including hashes they superseded.
+        let removed: Vec<String> = expired
+            .iter()
+            .flat_map(|x| x.chain_hash.iter().chain(x.chain_superseded.iter()))
+            .cloned()
+            .collect();
+
+        if let Some(dir) = &task.archive {
+            let mut archive = AuditArchive::create(dir, name)?;
+            for audit in expired {
+                archive.write(&audit.into())?;
+            }
+            result.archived.push(archive.finish()?);
+        }
+
+        if count == deleted as i64 {
+            // Name is from partition list and matches monthly partition pattern.
+            diesel::sql_query(format!("DROP TABLE {}", name)).execute(conn)?;
+            result.dropped.push(name.to_owned());
+        } else {
+            diesel::delete(
+                sso_audit::table.filter(
+                    sso_audit::dsl::created_at
+                        .ge(range.0)
+                        .and(sso_audit::dsl::created_at.lt(range.1))
+                        .and(sso_audit::dsl::id.eq_any(&id)),
+                ),
+            )
+            .execute(conn)?;
+        }
+        result.deleted += deleted;
+
+        // Checkpoints of removed audit logs can no longer be verified.
+        diesel::delete(
+            sso_audit_chain_point::table.filter(
+                sso_audit_chain_point::dsl::type_
+                    .eq(AuditChainPointType::Checkpoint.to_string())
+                    .and(sso_audit_chain_point::dsl::chain_hash.eq_any(&removed)),
+            ),
+        )
+        .execute(conn)?;
+        let linked = sso_audit::table
+            .select((sso_audit::dsl::chain_seq, sso_audit::dsl::chain_previous))
+            .filter(sso_audit::dsl::chain_previous.eq_any(&removed))
+            .load::<(Option<i64>, Option<String>)>(conn)?;
+        for (chain_seq, chain_previous) in linked {
+            if let (Some(chain_seq), Some(chain_previous)) = (chain_seq, chain_previous) {
+                Self::chain_point_create(
+                    conn,
+                    key_secret,
+                    AuditChainPointType::Delete,
+                    chain_seq - 1,
+                    &chain_previous,
+                    deleted as i64,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Append audit logs which are not part of chain, in order of creation.
+    ///
+    /// Used once to chain audit logs created before the chain was introduced.
+    /// Does nothing if the chain has been started, audit logs inserted
+    /// without a sequence number after that are reported by verify.
+    pub fn chain_migrate(conn: &PgConnection, key_secret: &KeySecret) -> DriverResult<usize> {
+        use diesel::dsl::{count_star, exists};
+
+        conn.transaction(|| {
+            diesel::select(pg_advisory_xact_lock(1, LOCK_AUDIT_CHAIN)).execute(conn)?;
+
+            let chained = diesel::select(exists(
+                sso_audit::table.filter(sso_audit::dsl::chain_seq.is_not_null()),
+            ))
+            .get_result::<bool>(conn)?;
+            let points = sso_audit_chain_point::table
+                .select(count_star())
+                .first::<i64>(conn)?;
+            if chained || points > 0 {
+                return Ok(0);
+            }
+
+            let mut count = 0;
+            loop {
+                let audit = sso_audit::table
+                    .filter(sso_audit::dsl::chain_seq.is_null())
+                    .order(sso_audit::dsl::created_at.asc())
+                    .limit(CHAIN_BATCH)
+                    .load::<ModelAudit>(conn)?;
+                if audit.is_empty() {
+                    return Ok(count);
+                }
+                for audit in audit {
+                    Self::chain_append(conn, key_secret, audit)?;
+                    count += 1;
+                }
+            }
+        })
+    }
+
+    /// Create signed checkpoint of chain head, returns none if chain is empty.
+    pub fn chain_checkpoint(
+        conn: &PgConnection,
+        key_secret: &KeySecret,
+    ) -> DriverResult<Option<AuditChainPoint>> {
+        use diesel::dsl::count_star;
+
+        conn.transaction(|| {
+            diesel::select(pg_advisory_xact_lock(1, LOCK_AUDIT_CHAIN)).execute(conn)?;
+
+            let head = sso_audit::table
+                .filter(sso_audit::dsl::chain_seq.is_not_null())
+                .order(sso_audit::dsl::chain_seq.desc())
+                .first::<ModelAudit>(conn)
+                .optional()?;
+            match head {
+                Some(head) => {
+                    let count = sso_audit::table
+                        .filter(sso_audit::dsl::chain_seq.is_not_null())
+                        .select(count_star())
+                        .first::<i64>(conn)?;
+                    Self::chain_point_create(
+                        conn,
+                        key_secret,
+                        AuditChainPointType::Checkpoint,
+                        head.chain_seq.unwrap_or_default(),
+                        &head.chain_hash.unwrap_or_default(),
+                        count,
+                    )
+                    .map(Some)
+                }
+                None => Ok(None),
+            }
+        })
+    }
+
+    /// Walk chain and verify links, hashes and signed points.
+    ///
+    /// Returns errors at each location where the chain breaks.
+    pub fn chain_verify(
+        conn: &PgConnection,
+        key_secret: &KeySecret,
+    ) -> DriverResult<AuditChainVerify> {
+        let mut verify = AuditChainVerify::default();
+
+        // Verify point signatures, the latest truncation point anchors the chain.
+        let points = sso_audit_chain_point::table
+            .select((
+                sso_audit_chain_point::dsl::id,
+                sso_audit_chain_point::dsl::type_,
+                sso_audit_chain_point::dsl::chain_seq,
+                sso_audit_chain_point::dsl::chain_hash,
+                sso_audit_chain_point::dsl::count,
+                sso_audit_chain_point::dsl::signature,
+            ))
+            .order(sso_audit_chain_point::dsl::chain_seq.asc())
+            .load::<ModelAuditChainPoint>(conn)?;
+        verify.points = points.len() as i64;
+        let mut anchor = (0, String::new());
+        let mut checkpoints = HashMap::new();
+        // Hashes of audit logs which were appended again or removed, by
+        // sequence number they were at. Only signed points are trusted.
+        let mut removed = HashMap::new();
+        for point in points {
+            let type_ = match AuditChainPointType::from_str(&point.type_) {
+                Ok(type_) if point.signature_verify(key_secret, type_) => type_,
+                _ => {
+                    verify.errors.push(AuditChainError {
+                        type_: AuditChainErrorType::PointSignature,
+                        chain_seq: Some(point.chain_seq),
+                        id: Some(point.id),
+                    });
+                    continue;
+                }
+            };
+            match type_ {
+                AuditChainPointType::Checkpoint => {
+                    checkpoints.insert(point.chain_seq, (point.id, point.chain_hash));
+                }
+                AuditChainPointType::Truncate => {
+                    anchor = (point.chain_seq, point.chain_hash);
+                }
+                AuditChainPointType::Delete | AuditChainPointType::Supersede => {
+                    removed.insert(point.chain_hash, point.chain_seq);
+                }
+            }
+        }
+        // Checkpoints at or before anchor refer to deleted audit logs.
+        checkpoints.retain(|chain_seq, _| *chain_seq > anchor.0);
+
+        let unchained = sso_audit::table
+            .select(sso_audit::dsl::id)
+            .filter(sso_audit::dsl::chain_seq.is_null())
+            .load::<Uuid>(conn)?;
+        for id in unchained {
+            verify.errors.push(AuditChainError {
+                type_: AuditChainErrorType::Unchained,
+                chain_seq: None,
+                id: Some(id),
+            });
+        }
+
+        let (mut last_seq, mut last_hash) = anchor;
+        loop {
+            let audit = sso_audit::table
+                .filter(sso_audit::dsl::chain_seq.gt(last_seq))
+                .order(sso_audit::dsl::chain_seq.asc())
+                .limit(CHAIN_BATCH)
+                .load::<ModelAudit>(conn)?;
+            if audit.is_empty() {
+                break;
+            }
+            for audit in audit {
+                let chain_seq = audit.chain_seq.unwrap_or_default();
+                let chain_previous = audit.chain_previous.clone().unwrap_or_default();
+                let chain_hash = audit.chain_hash.clone().unwrap_or_default();
+
+                // A gap in sequence numbers is left when an audit log is
+                // appended again or removed by retention, the previous hash
+                // must then be signed by a supersession or deletion point
+                // at the preceding sequence number.
+                let linked = if chain_seq - 1 == last_seq {
+                    chain_previous == last_hash
+                } else {
+                    removed.get(&chain_previous) == Some(&(chain_seq - 1))
+                };
+                if !linked {
+                    verify.errors.push(AuditChainError {
+                        type_: AuditChainErrorType::Link,
+                        chain_seq: Some(chain_seq),
+                        id: Some(audit.id),
+                    });
+                }
+                let hash =
+                    audit.chain_hash_compute(chain_seq, &chain_previous, &audit.chain_superseded);
+                if hash != chain_hash {
+                    verify.errors.push(AuditChainError {
+                        type_: AuditChainErrorType::Hash,
+                        chain_seq: Some(chain_seq),
+                        id: Some(audit.id),
+                    });
+                }
+                if let Some((_, point_hash)) = checkpoints.remove(&chain_seq) {
+                    if point_hash != chain_hash {
+                        verify.errors.push(AuditChainError {
+                            type_: AuditChainErrorType::PointHash,
+                            chain_seq: Some(chain_seq),
+                            id: Some(audit.id),
+                        });
+                    }
+                }
+
+                verify.count += 1;
+                last_seq = chain_seq;
+                last_hash = chain_hash;
+            }
+        }
+
+        // Remaining checkpoints refer to audit logs which are missing, which
+        // is expected only if they were signed as appended again.
+        let mut checkpoints: Vec<(i64, (Uuid, String))> = checkpoints.into_iter().collect();
+        checkpoints.sort_by_key(|x| x.0);
+        for (chain_seq, (id, point_hash)) in checkpoints {
+            if removed.get(&point_hash) != Some(&chain_seq) {
+                verify.errors.push(AuditChainError {
+                    type_: AuditChainErrorType::PointHash,
+                    chain_seq: Some(chain_seq),
+                    id: Some(id),
+                });
+            }
+        }
+
+        Ok(verify)
+    }
+
+    pub fn chain_seq(conn: &PgConnection, id: Option<&Uuid>) -> DriverResult<Option<i64>> {
+        use diesel::dsl::max;
+
+        match id {
+            Some(id) => sso_audit::table
+                .select(sso_audit::dsl::chain_seq)
+                .filter(sso_audit::dsl::id.eq(id))
+                .first::<Option<i64>>(conn)
+                .optional()
+                .map(Option::flatten),
+            None => sso_audit::table
+                .select(max(sso_audit::dsl::chain_seq))
+                .first::<Option<i64>>(conn),
+        }
+        .map_err(Into::into)
+    }
+
+    pub fn list_chain(
+        conn: &PgConnection,
+        chain_seq_gt: i64,
+        limit: i64,
+        filter: &AuditListFilter,
+        service_id_mask: Option<&Uuid>,
+        organisation_id_mask: Option<&Uuid>,
+    ) -> DriverResult<Vec<(i64, Audit)>> {
+        let mut query = sso_audit::table.into_boxed();
+        query = Self::boxed_query_filter(query, filter, service_id_mask, organisation_id_mask);
+
+        query
+            .filter(sso_audit::dsl::chain_seq.gt(chain_seq_gt))
+            .limit(limit)
+            .order(sso_audit::dsl::chain_seq.asc())
+            .load::<ModelAudit>(conn)
             .map_err(Into::into)
-            .map(Into::into)
+            .map(|x| {
+                x.into_iter()
+                    .map(|x| (x.chain_seq.unwrap_or(chain_seq_gt), x.into()))
+                    .collect()
+            })
+    }
+
+    /// Export audit logs to sink in chain order, returns number of exported logs.
+    ///
+    /// Export position is stored by name and updated after each batch, so
+    /// exports resume after the last written audit log. If `since` is defined
+    /// export starts from the first audit log created at or after that time.
+    /// Updated audit logs are moved to the head of the chain and exported again.
+    /// Audit logs which are not part of chain are not exported, they are
+    /// reported by verify.
+    pub fn export(
+        conn: &PgConnection,
+        name: &str,
+        since: Option<&DateTime<Utc>>,
+        sink: &mut AuditExportSink,
+    ) -> DriverResult<usize> {
+        use diesel::dsl::{max, min};
+
+        // Audit logs appended by chain migration may have been created before
+        // `since`, so start before the first audit log created at or after it.
+        let mut position = match since {
+            Some(since) => match sso_audit::table
+                .select(min(sso_audit::dsl::chain_seq))
+                .filter(sso_audit::dsl::created_at.ge(since))
+                .first::<Option<i64>>(conn)?
+            {
+                Some(first) => Some(first - 1),
+                None => sso_audit::table
+                    .select(max(sso_audit::dsl::chain_seq))
+                    .first::<Option<i64>>(conn)?,
+            },
+            None => sso_audit_export::table
+                .select(sso_audit_export::dsl::chain_seq)
+                .filter(sso_audit_export::dsl::name.eq(name))
+                .first::<i64>(conn)
+                .optional()?,
+        }
+        .unwrap_or(0);
+
+        let mut count = 0;
+        loop {
+            let audits = sso_audit::table
+                .filter(sso_audit::dsl::chain_seq.gt(position))
+                .order(sso_audit::dsl::chain_seq.asc())
+                .limit(CHAIN_BATCH)
+                .load::<ModelAudit>(conn)?;
+            let done = (audits.len() as i64) < CHAIN_BATCH;
+
+            let mut written = position;
+            let mut res = Ok(());
+            for audit in audits {
+                let chain_seq = audit.chain_seq.unwrap_or(written);
+                res = sink.write(&audit.into());
+                if res.is_err() {
+                    break;
+                }
+                written = chain_seq;
+                count += 1;
+            }
+            let res = res.and_then(|_| sink.flush());
+
+            if written != position {
+                Self::export_position_update(conn, name, written)?;
+                position = written;
+            }
+            res?;
+            if done {
+                return Ok(count);
+            }
+        }
+    }
+
+    fn export_position_update(conn: &PgConnection, name: &str, chain_seq: i64) -> DriverResult<()> {
+        let now = Utc::now();
+        let value = ModelAuditExportUpsert {
+            created_at: &now,
+            updated_at: &now,
+            name,
+            chain_seq,
+        };
+        diesel::insert_into(sso_audit_export::table)
+            .values(&value)
+            .on_conflict(sso_audit_export::dsl::name)
+            .do_update()
+            .set((
+                sso_audit_export::dsl::updated_at.eq(&now),
+                sso_audit_export::dsl::chain_seq.eq(chain_seq),
+            ))
+            .execute(conn)?;
+        Ok(())
     }
 
-    pub fn delete(conn: &PgConnection, created_at: &DateTime<Utc>) -> DriverResult<usize> {
-        diesel::delete(sso_audit::table.filter(sso_audit::dsl::created_at.le(created_at)))
-            .execute(conn)
+    /// Append audit log to head of chain, must be called in a transaction.
+    ///
+    /// If audit log is already part of chain its previous hash is superseded
+    /// and it is moved to the head, this is used when audit logs are updated
+    /// or erased. A signed supersession point records the previous sequence
+    /// number and hash, so verify can tell the gap from a removed audit log.
+    fn chain_append(
+        conn: &PgConnection,
+        key_secret: &KeySecret,
+        audit: ModelAudit,
+    ) -> DriverResult<ModelAudit> {
+        use diesel::dsl::max;
+
+        diesel::select(pg_advisory_xact_lock(1, LOCK_AUDIT_CHAIN)).execute(conn)?;
+
+        let head = sso_audit::table
+            .select(max(sso_audit::dsl::chain_seq))
+            .first::<Option<i64>>(conn)?;
+        let (chain_seq, chain_previous) = match head {
+            Some(head) => {
+                let chain_previous = sso_audit::table
+                    .select(sso_audit::dsl::chain_hash)
+                    .filter(sso_audit::dsl::chain_seq.eq(head))
+                    .first::<Option<String>>(conn)?;
+                (head + 1, chain_previous.unwrap_or_default())
+            }
+            None => {
+                // Chain is empty, continue from latest truncation point if any.
+                let anchor = sso_audit_chain_point::table
+                    .select((
+                        sso_audit_chain_point::dsl::chain_seq,
+                        sso_audit_chain_point::dsl::chain_hash,
+                    ))
+                    .filter(
+                        sso_audit_chain_point::dsl::type_
+                            .eq(AuditChainPointType::Truncate.to_string()),
+                    )
+                    .order(sso_audit_chain_point::dsl::chain_seq.desc())
+                    .first::<(i64, String)>(conn)
+                    .optional()?;
+                match anchor {
+                    Some((chain_seq, chain_hash)) => (chain_seq + 1, chain_hash),
+                    None => (1, String::new()),
+                }
+            }
+        };
+        let mut chain_superseded = audit.chain_superseded.clone();
+        if let Some(chain_hash) = &audit.chain_hash {
+            chain_superseded.push(chain_hash.to_owned());
+        }
+        let chain_hash = audit.chain_hash_compute(chain_seq, &chain_previous, &chain_superseded);
+
+        if let (Some(previous_seq), Some(previous_hash)) = (audit.chain_seq, &audit.chain_hash) {
+            Self::chain_point_create(
+                conn,
+                key_secret,
+                AuditChainPointType::Supersede,
+                previous_seq,
+                previous_hash,
+                1,
+            )?;
+        }
+        diesel::update(sso_audit::table.filter(sso_audit::dsl::id.eq(audit.id)))
+            .set((
+                sso_audit::dsl::chain_seq.eq(chain_seq),
+                sso_audit::dsl::chain_previous.eq(&chain_previous),
+                sso_audit::dsl::chain_hash.eq(&chain_hash),
+                sso_audit::dsl::chain_superseded.eq(&chain_superseded),
+            ))
+            .get_result::<ModelAudit>(conn)
             .map_err(Into::into)
     }
 
+    /// Returns hash of audit log contents with chain sequence number,
+    /// previous hash and superseded hashes. Proxy and enrichment are only
+    /// hashed if present, so hashes of audit logs created before they were
+    /// added are unchanged.
+    fn chain_hash_compute(
+        &self,
+        chain_seq: i64,
+        chain_previous: &str,
+        chain_superseded: &[String],
+    ) -> String {
+        let mut value = json!([
+            chain_seq,
+            chain_previous,
+            chain_superseded,
+            self.created_at.to_rfc3339(),
+            self.updated_at.to_rfc3339(),
+            self.id,
+            self.user_agent,
+            self.remote,
+            self.forwarded,
+            self.status_code,
+            self.type_,
+            self.subject,
+            self.data,
+            self.key_id,
+            self.service_id,
+            self.user_id,
+            self.user_key_id,
+            self.organisation_id,
+            self.actor_key_id,
+        ]);
+        if let Some(value) = value.as_array_mut() {
+            let enrichment = json!([
+                self.remote_country,
+                self.remote_city,
+                self.remote_asn,
+                self.remote_as_org,
+                self.user_agent_browser,
+                self.user_agent_os,
+                self.user_agent_device,
+            ]);
+            let enrichment_empty = enrichment
+                .as_array()
+                .map(|x| x.iter().all(|x| x.is_null()))
+                .unwrap_or(true);
+            if self.proxy.is_some() || !enrichment_empty {
+                value.push(self.proxy.clone().unwrap_or(Value::Null));
+            }
+            if !enrichment_empty {
+                value.push(enrichment);
+            }
+        }
+        let mut hash = Sha256::new();
+        hash.input(value.to_string().as_bytes());
+        hex::encode(hash.result())
+    }
+
+    fn chain_point_create(
+        conn: &PgConnection,
+        key_secret: &KeySecret,
+        type_: AuditChainPointType,
+        chain_seq: i64,
+        chain_hash: &str,
+        count: i64,
+    ) -> DriverResult<AuditChainPoint> {
+        let now = Utc::now();
+        let id = Uuid::new_v4();
+        let signature = key_secret.chain_sign(&AuditChainPoint::signature_value(
+            &id, type_, chain_seq, chain_hash, count,
+        ));
+        let value = ModelAuditChainPointInsert {
+            created_at: &now,
+            id: &id,
+            type_: &type_.to_string(),
+            chain_seq,
+            chain_hash,
+            count,
+            signature: &signature,
+        };
+        diesel::insert_into(sso_audit_chain_point::table)
+            .values(&value)
+            .execute(conn)?;
+        Ok(AuditChainPoint {
+            created_at: now,
+            id,
+            type_,
+            chain_seq,
+            chain_hash: chain_hash.to_owned(),
+            count,
+            signature,
+        })
+    }
+
     fn list_where_created_le(
         conn: &PgConnection,
         le: &DateTime<Utc>,
@@ -214,31 +1108,41 @@ impl ModelAudit {
         offset_id: &Option<Uuid>,
         filter: &AuditListFilter,
         service_id_mask: Option<&Uuid>,
+        organisation_id_mask: Option<&Uuid>,
     ) -> DriverResult<Vec<Audit>> {
         let offset: i64 = if offset_id.is_some() { 1 } else { 0 };
-        ModelAudit::list_where_created_le_inner(conn, le, limit, offset, filter, service_id_mask)
-            .and_then(|res| {
-                if let Some(offset_id) = offset_id {
-                    for (i, audit) in res.iter().enumerate() {
-                        if &audit.id == offset_id {
-                            let offset: i64 = (i + 1).try_into().unwrap();
-                            return ModelAudit::list_where_created_le_inner(
-                                conn,
-                                le,
-                                limit,
-                                offset,
-                                filter,
-                                service_id_mask,
-                            );
-                        }
+        ModelAudit::list_where_created_le_inner(
+            conn,
+            le,
+            limit,
+            offset,
+            filter,
+            service_id_mask,
+            organisation_id_mask,
+        )
+        .and_then(|res| {
+            if let Some(offset_id) = offset_id {
+                for (i, audit) in res.iter().enumerate() {
+                    if &audit.id == offset_id {
+                        let offset: i64 = (i + 1).try_into().unwrap();
+                        return ModelAudit::list_where_created_le_inner(
+                            conn,
+                            le,
+                            limit,
+                            offset,
+                            filter,
+                            service_id_mask,
+                            organisation_id_mask,
+                        );
                     }
                 }
-                Ok(res)
-            })
-            .map(|mut v| {
-                v.reverse();
-                v
-            })
+            }
+            Ok(res)
+        })
+        .map(|mut v| {
+            v.reverse();
+            v
+        })
     }
 
     fn list_where_created_le_inner(
@@ -248,9 +1152,10 @@ impl ModelAudit {
         offset: i64,
         filter: &AuditListFilter,
         service_id_mask: Option<&Uuid>,
+        organisation_id_mask: Option<&Uuid>,
     ) -> DriverResult<Vec<Audit>> {
         let mut query = sso_audit::table.into_boxed();
-        query = Self::boxed_query_filter(query, filter, service_id_mask);
+        query = Self::boxed_query_filter(query, filter, service_id_mask, organisation_id_mask);
 
         query
             .filter(sso_audit::dsl::created_at.le(created_le))
@@ -269,27 +1174,37 @@ impl ModelAudit {
         offset_id: &Option<Uuid>,
         filter: &AuditListFilter,
         service_id_mask: Option<&Uuid>,
+        organisation_id_mask: Option<&Uuid>,
     ) -> DriverResult<Vec<Audit>> {
         let offset: i64 = if offset_id.is_some() { 1 } else { 0 };
-        ModelAudit::list_where_created_ge_inner(conn, ge, limit, offset, filter, service_id_mask)
-            .and_then(|res| {
-                if let Some(offset_id) = offset_id {
-                    for (i, audit) in res.iter().enumerate() {
-                        if &audit.id == offset_id {
-                            let offset: i64 = (i + 1).try_into().unwrap();
-                            return ModelAudit::list_where_created_ge_inner(
-                                conn,
-                                ge,
-                                limit,
-                                offset,
-                                filter,
-                                service_id_mask,
-                            );
-                        }
+        ModelAudit::list_where_created_ge_inner(
+            conn,
+            ge,
+            limit,
+            offset,
+            filter,
+            service_id_mask,
+            organisation_id_mask,
+        )
+        .and_then(|res| {
+            if let Some(offset_id) = offset_id {
+                for (i, audit) in res.iter().enumerate() {
+                    if &audit.id == offset_id {
+                        let offset: i64 = (i + 1).try_into().unwrap();
+                        return ModelAudit::list_where_created_ge_inner(
+                            conn,
+                            ge,
+                            limit,
+                            offset,
+                            filter,
+                            service_id_mask,
+                            organisation_id_mask,
+                        );
                     }
                 }
-                Ok(res)
-            })
+            }
+            Ok(res)
+        })
     }
 
     fn list_where_created_ge_inner(
@@ -299,9 +1214,10 @@ impl ModelAudit {
         offset: i64,
         filter: &AuditListFilter,
         service_id_mask: Option<&Uuid>,
+        organisation_id_mask: Option<&Uuid>,
     ) -> DriverResult<Vec<Audit>> {
         let mut query = sso_audit::table.into_boxed();
-        query = Self::boxed_query_filter(query, filter, service_id_mask);
+        query = Self::boxed_query_filter(query, filter, service_id_mask, organisation_id_mask);
 
         query
             .filter(sso_audit::dsl::created_at.ge(created_ge))
@@ -321,6 +1237,7 @@ impl ModelAudit {
         offset_id: &Option<Uuid>,
         filter: &AuditListFilter,
         service_id_mask: Option<&Uuid>,
+        organisation_id_mask: Option<&Uuid>,
     ) -> DriverResult<Vec<Audit>> {
         let offset: i64 = if offset_id.is_some() { 1 } else { 0 };
         ModelAudit::list_where_created_le_and_ge_inner(
@@ -331,6 +1248,7 @@ impl ModelAudit {
             offset,
             filter,
             service_id_mask,
+            organisation_id_mask,
         )
         .and_then(|res| {
             if let Some(offset_id) = offset_id {
@@ -345,6 +1263,7 @@ impl ModelAudit {
                             offset,
                             filter,
                             service_id_mask,
+                            organisation_id_mask,
                         );
                     }
                 }
@@ -361,9 +1280,10 @@ impl ModelAudit {
         offset: i64,
         filter: &AuditListFilter,
         service_id_mask: Option<&Uuid>,
+        organisation_id_mask: Option<&Uuid>,
     ) -> DriverResult<Vec<Audit>> {
         let mut query = sso_audit::table.into_boxed();
-        query = Self::boxed_query_filter(query, filter, service_id_mask);
+        query = Self::boxed_query_filter(query, filter, service_id_mask, organisation_id_mask);
 
         query
             .filter(
@@ -383,6 +1303,7 @@ impl ModelAudit {
         mut query: sso_audit::BoxedQuery<'a, Pg>,
         filter: &'a AuditListFilter,
         service_id_mask: Option<&'a Uuid>,
+        organisation_id_mask: Option<&'a Uuid>,
     ) -> sso_audit::BoxedQuery<'a, Pg> {
         use diesel::dsl::any;
 
@@ -406,10 +1327,105 @@ impl ModelAudit {
             let user_id: Vec<Uuid> = user_id.iter().copied().collect();
             query = query.filter(sso_audit::dsl::user_id.eq(any(user_id)));
         }
+        if let Some(organisation_id) = &filter.organisation_id {
+            let organisation_id: Vec<Uuid> = organisation_id.iter().copied().collect();
+            query = query.filter(sso_audit::dsl::organisation_id.eq(any(organisation_id)));
+        }
+        if let Some(status_code_ge) = filter.status_code_ge {
+            let status_code_ge = status_code_ge as i16;
+            query = query.filter(sso_audit::dsl::status_code.ge(status_code_ge));
+        }
+        if let Some(status_code_le) = filter.status_code_le {
+            let status_code_le = status_code_le as i16;
+            query = query.filter(sso_audit::dsl::status_code.le(status_code_le));
+        }
+        if let Some(remote) = &filter.remote {
+            let remote = format!("{}%", Self::like_escape(remote));
+            query = query.filter(sso_audit::dsl::remote.like(remote));
+        }
+        if let Some(remote_cidr) = &filter.remote_cidr {
+            let remote_cidr: Vec<String> = remote_cidr.iter().map(|x| x.to_string()).collect();
+            query = query.filter(InetContainedByOrEq::new(
+                sso_audit_remote_inet(sso_audit::dsl::remote),
+                any(sso_audit_inet_array(remote_cidr)),
+            ));
+        }
+        if let Some(forwarded) = &filter.forwarded {
+            let forwarded = format!("{}%", Self::like_escape(forwarded));
+            query = query.filter(sso_audit::dsl::forwarded.like(forwarded));
+        }
+        if let Some(user_agent) = &filter.user_agent {
+            let user_agent = format!("%{}%", Self::like_escape(user_agent));
+            query = query.filter(sso_audit::dsl::user_agent.ilike(user_agent));
+        }
+        if let Some(key_id) = &filter.key_id {
+            let key_id: Vec<Uuid> = key_id.iter().copied().collect();
+            query = query.filter(sso_audit::dsl::key_id.eq(any(key_id)));
+        }
+        if let Some(user_key_id) = &filter.user_key_id {
+            let user_key_id: Vec<Uuid> = user_key_id.iter().copied().collect();
+            query = query.filter(sso_audit::dsl::user_key_id.eq(any(user_key_id)));
+        }
+        if let Some(remote_country) = &filter.remote_country {
+            let remote_country: Vec<String> = remote_country.to_vec();
+            query = query.filter(sso_audit::dsl::remote_country.eq(any(remote_country)));
+        }
+        if let Some(remote_city) = &filter.remote_city {
+            let remote_city: Vec<String> = remote_city.to_vec();
+            query = query.filter(sso_audit::dsl::remote_city.eq(any(remote_city)));
+        }
+        if let Some(remote_asn) = &filter.remote_asn {
+            let remote_asn: Vec<i64> = remote_asn.to_vec();
+            query = query.filter(sso_audit::dsl::remote_asn.eq(any(remote_asn)));
+        }
+        if let Some(user_agent_browser) = &filter.user_agent_browser {
+            let user_agent_browser: Vec<String> = user_agent_browser.to_vec();
+            query = query.filter(sso_audit::dsl::user_agent_browser.eq(any(user_agent_browser)));
+        }
+        if let Some(user_agent_os) = &filter.user_agent_os {
+            let user_agent_os: Vec<String> = user_agent_os.to_vec();
+            query = query.filter(sso_audit::dsl::user_agent_os.eq(any(user_agent_os)));
+        }
+        if let Some(user_agent_device) = &filter.user_agent_device {
+            let user_agent_device: Vec<String> = user_agent_device.to_vec();
+            query = query.filter(sso_audit::dsl::user_agent_device.eq(any(user_agent_device)));
+        }
+        if let Some(data) = &filter.data {
+            for data in data {
+                query = query.filter(JsonbContains::new(
+                    sso_audit::dsl::data,
+                    data.to_value().into_sql::<sql_types::Jsonb>(),
+                ));
+            }
+        }
         if let Some(service_id_mask) = service_id_mask {
             query = query.filter(sso_audit::dsl::service_id.eq(service_id_mask));
         }
+        if let Some(organisation_id_mask) = organisation_id_mask {
+            query = query.filter(sso_audit::dsl::organisation_id.eq(organisation_id_mask));
+        }
 
         query
     }
+
+    /// Escape `LIKE` pattern wildcard characters.
+    fn like_escape(value: &str) -> String {
+        value
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    }
+}
+
+impl ModelAuditChainPoint {
+    fn signature_verify(&self, key_secret: &KeySecret, type_: AuditChainPointType) -> bool {
+        let value = AuditChainPoint::signature_value(
+            &self.id,
+            type_,
+            self.chain_seq,
+            &self.chain_hash,
+            self.count,
+        );
+        key_secret.chain_verify(&value, &self.signature)
+    }
 }
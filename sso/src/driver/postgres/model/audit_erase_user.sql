@@ -0,0 +1,57 @@
+UPDATE sso_audit
+SET
+  "updated_at" = $2,
+  "user_agent" = '',
+  "remote" = '',
+  "forwarded" = NULL,
+  "proxy" = NULL,
+  "remote_country" = NULL,
+  "remote_city" = NULL,
+  "remote_asn" = NULL,
+  "remote_as_org" = NULL,
+  "user_agent_browser" = NULL,
+  "user_agent_os" = NULL,
+  "user_agent_device" = NULL,
+  "data" = jsonb_build_object('pseudonym', $3),
+  "subject" = CASE
+    WHEN "subject" = $1::text THEN $3
+    ELSE "subject"
+  END,
+  "key_id" = CASE
+    WHEN "key_id" = ANY($4) THEN NULL
+    ELSE "key_id"
+  END,
+  "user_id" = NULL,
+  "user_key_id" = NULL
+WHERE
+  "user_id" = $1
+  OR "user_key_id" = ANY($4)
+  OR "key_id" = ANY($4)
+  OR "subject" = $1::text RETURNING "created_at",
+  "updated_at",
+  "id",
+  "user_agent",
+  "remote",
+  "forwarded",
+  "status_code",
+  "type" AS "type_",
+  "subject",
+  "data",
+  "key_id",
+  "service_id",
+  "user_id",
+  "user_key_id",
+  "organisation_id",
+  "actor_key_id",
+  "chain_seq",
+  "chain_previous",
+  "chain_hash",
+  "chain_superseded",
+  "proxy",
+  "remote_country",
+  "remote_city",
+  "remote_asn",
+  "remote_as_org",
+  "user_agent_browser",
+  "user_agent_os",
+  "user_agent_device";
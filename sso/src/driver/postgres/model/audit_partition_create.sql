@@ -0,0 +1,18 @@
+SELECT
+  'sso_audit_' || to_char(m."month" AT TIME ZONE 'UTC', 'YYYYMM') AS "name"
+FROM (
+    SELECT
+      DISTINCT date_trunc('month', "created_at" AT TIME ZONE 'UTC') AT TIME ZONE 'UTC' AS "month"
+    FROM sso_audit_default
+    UNION
+    SELECT
+      generate_series(
+        date_trunc('month', $1 AT TIME ZONE 'UTC'),
+        date_trunc('month', $1 AT TIME ZONE 'UTC') + make_interval(months => $2),
+        INTERVAL '1 month'
+      ) AT TIME ZONE 'UTC'
+  ) AS m
+WHERE
+  sso_audit_partition_create(m."month")
+ORDER BY
+  "name" ASC;
@@ -0,0 +1,9 @@
+SELECT
+  c."relname"::VARCHAR AS "name"
+FROM pg_inherits AS i
+INNER JOIN pg_class AS c ON c."oid" = i."inhrelid"
+WHERE
+  i."inhparent" = 'sso_audit'::regclass
+  AND c."relname" ~ '^sso_audit_[0-9]{6}$'
+ORDER BY
+  c."relname" ASC;
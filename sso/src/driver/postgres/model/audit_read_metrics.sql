@@ -1,6 +1,7 @@
 SELECT
   a."type" AS "type_",
   COALESCE(a.status_code, 0 :: SMALLINT) AS "status_code",
+  a."organisation_id" AS "organisation_id",
   count(*) AS "count"
 FROM sso_audit AS a
 WHERE
@@ -11,6 +12,7 @@ WHERE
   END
 GROUP BY
   a."type",
-  a."status_code"
+  a."status_code",
+  a."organisation_id"
 ORDER BY
   a."type" ASC;
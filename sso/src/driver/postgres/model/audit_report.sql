@@ -0,0 +1,54 @@
+SELECT
+  date_trunc($1, a."created_at") AS "bucket",
+  CASE
+    WHEN $2 THEN a."type"
+  END AS "type_",
+  CASE
+    WHEN $3 THEN a."status_code"
+  END AS "status_code",
+  CASE
+    WHEN $4 THEN a."service_id"
+  END AS "service_id",
+  CASE
+    WHEN $5 THEN a."user_id"
+  END AS "user_id",
+  CASE
+    WHEN $6 THEN COALESCE(host(sso_audit_remote_inet(a."remote")), a."remote")
+  END AS "remote",
+  count(*) AS "count"
+FROM sso_audit AS a
+WHERE
+  a."created_at" >= $7
+  AND a."created_at" <= $8
+  AND CASE
+    WHEN $9 IS NULL THEN TRUE
+    ELSE a."type" = ANY($9)
+  END
+  AND CASE
+    WHEN $10 IS NULL THEN TRUE
+    ELSE a."service_id" = ANY($10)
+  END
+  AND CASE
+    WHEN $11 IS NULL THEN TRUE
+    ELSE a."user_id" = ANY($11)
+  END
+  AND CASE
+    WHEN $12 IS NULL THEN TRUE
+    ELSE a."service_id" = $12
+  END
+  AND CASE
+    WHEN $13 IS NULL THEN TRUE
+    ELSE a."organisation_id" = $13
+  END
+GROUP BY
+  1,
+  2,
+  3,
+  4,
+  5,
+  6
+ORDER BY
+  1 ASC,
+  "count" DESC
+LIMIT
+  $14;
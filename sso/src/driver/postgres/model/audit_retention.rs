@@ -0,0 +1,118 @@
+use crate::{
+    schema::sso_audit_retention, AuditRetention, AuditRetentionDelete, AuditRetentionSet,
+    DriverResult,
+};
+use chrono::{DateTime, Utc};
+use diesel::{pg::Pg, prelude::*};
+use uuid::Uuid;
+
+#[derive(Debug, Identifiable, Queryable)]
+#[table_name = "sso_audit_retention"]
+#[primary_key(id)]
+pub struct ModelAuditRetention {
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    id: Uuid,
+    service_id: Option<Uuid>,
+    type_: Option<String>,
+    days: i32,
+}
+
+impl From<ModelAuditRetention> for AuditRetention {
+    fn from(retention: ModelAuditRetention) -> Self {
+        Self {
+            created_at: retention.created_at,
+            updated_at: retention.updated_at,
+            id: retention.id,
+            service_id: retention.service_id,
+            type_: retention.type_,
+            days: retention.days,
+        }
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "sso_audit_retention"]
+struct ModelAuditRetentionInsert<'a> {
+    created_at: &'a DateTime<Utc>,
+    updated_at: &'a DateTime<Utc>,
+    id: &'a Uuid,
+    service_id: Option<&'a Uuid>,
+    type_: Option<&'a str>,
+    days: i32,
+}
+
+impl ModelAuditRetention {
+    pub fn list(conn: &PgConnection) -> DriverResult<Vec<AuditRetention>> {
+        sso_audit_retention::table
+            .order((
+                sso_audit_retention::dsl::service_id.asc(),
+                sso_audit_retention::dsl::type_.asc(),
+            ))
+            .load::<ModelAuditRetention>(conn)
+            .map_err(Into::into)
+            .map(|x| x.into_iter().map(|x| x.into()).collect())
+    }
+
+    /// Create or update retention of service and type.
+    pub fn set(conn: &PgConnection, set: &AuditRetentionSet) -> DriverResult<AuditRetention> {
+        let now = Utc::now();
+        conn.transaction(|| {
+            let existing = Self::query_where(set.service_id.as_ref(), set.type_.as_deref())
+                .select(sso_audit_retention::dsl::id)
+                .first::<Uuid>(conn)
+                .optional()?;
+            match existing {
+                Some(id) => diesel::update(
+                    sso_audit_retention::table.filter(sso_audit_retention::dsl::id.eq(id)),
+                )
+                .set((
+                    sso_audit_retention::dsl::updated_at.eq(&now),
+                    sso_audit_retention::dsl::days.eq(set.days),
+                ))
+                .get_result::<ModelAuditRetention>(conn),
+                None => {
+                    let id = Uuid::new_v4();
+                    let value = ModelAuditRetentionInsert {
+                        created_at: &now,
+                        updated_at: &now,
+                        id: &id,
+                        service_id: set.service_id.as_ref(),
+                        type_: set.type_.as_deref(),
+                        days: set.days,
+                    };
+                    diesel::insert_into(sso_audit_retention::table)
+                        .values(&value)
+                        .get_result::<ModelAuditRetention>(conn)
+                }
+            }
+            .map_err(Into::into)
+            .map(Into::into)
+        })
+    }
+
+    /// Delete retention of service and type, returns number of deleted retentions.
+    pub fn delete(conn: &PgConnection, delete: &AuditRetentionDelete) -> DriverResult<usize> {
+        let id = Self::query_where(delete.service_id.as_ref(), delete.type_.as_deref())
+            .select(sso_audit_retention::dsl::id)
+            .load::<Uuid>(conn)?;
+        diesel::delete(sso_audit_retention::table.filter(sso_audit_retention::dsl::id.eq_any(id)))
+            .execute(conn)
+            .map_err(Into::into)
+    }
+
+    fn query_where<'a>(
+        service_id: Option<&'a Uuid>,
+        type_: Option<&'a str>,
+    ) -> sso_audit_retention::BoxedQuery<'a, Pg> {
+        let mut query = sso_audit_retention::table.into_boxed();
+        query = match service_id {
+            Some(service_id) => query.filter(sso_audit_retention::dsl::service_id.eq(service_id)),
+            None => query.filter(sso_audit_retention::dsl::service_id.is_null()),
+        };
+        match type_ {
+            Some(type_) => query.filter(sso_audit_retention::dsl::type_.eq(type_)),
+            None => query.filter(sso_audit_retention::dsl::type_.is_null()),
+        }
+    }
+}
@@ -0,0 +1,63 @@
+SELECT
+  a."created_at",
+  a."updated_at",
+  a."id",
+  a."user_agent",
+  a."remote",
+  a."forwarded",
+  a."status_code",
+  a."type" AS "type_",
+  a."subject",
+  a."data",
+  a."key_id",
+  a."service_id",
+  a."user_id",
+  a."user_key_id",
+  a."organisation_id",
+  a."actor_key_id",
+  a."chain_seq",
+  a."chain_previous",
+  a."chain_hash",
+  a."chain_superseded",
+  a."proxy",
+  a."remote_country",
+  a."remote_city",
+  a."remote_asn",
+  a."remote_as_org",
+  a."user_agent_browser",
+  a."user_agent_os",
+  a."user_agent_device"
+FROM sso_audit AS a
+WHERE
+  a."created_at" >= $1
+  AND a."created_at" < $2
+  AND (
+    $5::BIGINT IS NULL
+    OR a."chain_seq" IS NULL
+    OR a."chain_seq" <> $5
+  )
+  AND a."created_at" < $3 - make_interval(
+    days => COALESCE(
+      (
+        SELECT
+          r."days"
+        FROM sso_audit_retention AS r
+        WHERE
+          (
+            r."service_id" IS NULL
+            OR r."service_id" = a."service_id"
+          )
+          AND (
+            r."type" IS NULL
+            OR r."type" = a."type"
+          )
+        ORDER BY
+          r."service_id" IS NULL ASC,
+          r."type" IS NULL ASC
+        LIMIT
+          1
+      ), $4
+    )
+  )
+ORDER BY
+  a."chain_seq" ASC NULLS FIRST;
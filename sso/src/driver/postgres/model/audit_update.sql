@@ -22,4 +22,18 @@ WHERE
   "key_id",
   "service_id",
   "user_id",
-  "user_key_id";
+  "user_key_id",
+  "organisation_id",
+  "actor_key_id",
+  "chain_seq",
+  "chain_previous",
+  "chain_hash",
+  "chain_superseded",
+  "proxy",
+  "remote_country",
+  "remote_city",
+  "remote_asn",
+  "remote_as_org",
+  "user_agent_browser",
+  "user_agent_os",
+  "user_agent_device";
@@ -0,0 +1,336 @@
+use crate::{
+    schema::{sso_group, sso_group_role, sso_service, sso_user_group},
+    DriverError, DriverResult, Group, GroupCreate, GroupList, GroupListQuery, GroupRead, GroupRole,
+    GroupUpdate, GroupUser,
+};
+use chrono::{DateTime, Utc};
+use diesel::{prelude::*, sql_types};
+use uuid::Uuid;
+
+#[derive(Debug, Identifiable, Queryable, QueryableByName)]
+#[table_name = "sso_group"]
+#[primary_key(id)]
+pub struct ModelGroup {
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    id: Uuid,
+    is_enabled: bool,
+    name: String,
+    parent_id: Option<Uuid>,
+    service_id: Uuid,
+}
+
+impl From<ModelGroup> for Group {
+    fn from(group: ModelGroup) -> Self {
+        Self {
+            created_at: group.created_at,
+            updated_at: group.updated_at,
+            id: group.id,
+            is_enabled: group.is_enabled,
+            name: group.name,
+            parent_id: group.parent_id,
+            service_id: group.service_id,
+        }
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "sso_group"]
+struct ModelGroupInsert<'a> {
+    created_at: &'a DateTime<Utc>,
+    updated_at: &'a DateTime<Utc>,
+    id: &'a Uuid,
+    is_enabled: bool,
+    name: &'a str,
+    parent_id: Option<&'a Uuid>,
+    service_id: &'a Uuid,
+}
+
+#[derive(AsChangeset)]
+#[table_name = "sso_group"]
+struct ModelGroupUpdate<'a> {
+    updated_at: &'a DateTime<Utc>,
+    is_enabled: Option<bool>,
+    name: Option<&'a str>,
+    parent_id: Option<Option<&'a Uuid>>,
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "sso_user_group"]
+struct ModelUserGroupInsert<'a> {
+    created_at: &'a DateTime<Utc>,
+    user_id: &'a Uuid,
+    group_id: &'a Uuid,
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "sso_group_role"]
+struct ModelGroupRoleInsert<'a> {
+    created_at: &'a DateTime<Utc>,
+    group_id: &'a Uuid,
+    role_id: &'a Uuid,
+}
+
+impl ModelGroup {
+    pub fn list(
+        conn: &PgConnection,
+        list: &GroupList,
+        service_id: Option<Uuid>,
+        organisation_id: Option<Uuid>,
+    ) -> DriverResult<Vec<Group>> {
+        use diesel::dsl::any;
+
+        let mut query = sso_group::table.into_boxed();
+
+        if let Some(id) = &list.filter.id {
+            let id: Vec<Uuid> = id.iter().copied().collect();
+            query = query.filter(sso_group::dsl::id.eq(any(id)));
+        }
+        if let Some(is_enabled) = list.filter.is_enabled {
+            query = query.filter(sso_group::dsl::is_enabled.eq(is_enabled));
+        }
+        if let Some(parent_id) = &list.filter.parent_id {
+            let parent_id: Vec<Uuid> = parent_id.iter().copied().collect();
+            query = query.filter(sso_group::dsl::parent_id.eq(any(parent_id)));
+        }
+        if let Some(service_id) = &list.filter.service_id {
+            let service_id: Vec<Uuid> = service_id.iter().copied().collect();
+            query = query.filter(sso_group::dsl::service_id.eq(any(service_id)));
+        }
+        if let Some(user_id) = list.filter.user_id {
+            query = query.filter(
+                sso_group::dsl::id.eq_any(
+                    sso_user_group::table
+                        .select(sso_user_group::dsl::group_id)
+                        .filter(sso_user_group::dsl::user_id.eq(user_id)),
+                ),
+            );
+        }
+        if let Some(service_id_mask) = service_id {
+            query = query.filter(sso_group::dsl::service_id.eq(service_id_mask));
+        }
+        if let Some(organisation_id_mask) = organisation_id {
+            query = query.filter(
+                sso_group::dsl::service_id.eq_any(
+                    sso_service::table
+                        .select(sso_service::dsl::id)
+                        .filter(sso_service::dsl::organisation_id.eq(organisation_id_mask)),
+                ),
+            );
+        }
+
+        match list.query {
+            GroupListQuery::Limit => query
+                .filter(sso_group::dsl::id.gt(Uuid::nil()))
+                .limit(list.filter.limit)
+                .order(sso_group::dsl::id.asc())
+                .load::<ModelGroup>(conn)
+                .map_err(Into::into)
+                .map(|x| x.into_iter().map(|x| x.into()).collect()),
+            GroupListQuery::IdGt(gt) => query
+                .filter(sso_group::dsl::id.gt(gt))
+                .limit(list.filter.limit)
+                .order(sso_group::dsl::id.asc())
+                .load::<ModelGroup>(conn)
+                .map_err(Into::into)
+                .map(|x| x.into_iter().map(|x| x.into()).collect()),
+            GroupListQuery::IdLt(lt) => query
+                .filter(sso_group::dsl::id.lt(lt))
+                .limit(list.filter.limit)
+                .order(sso_group::dsl::id.desc())
+                .load::<ModelGroup>(conn)
+                .map_err(Into::into)
+                .map(|mut x| {
+                    x.reverse();
+                    x.into_iter().map(|x| x.into()).collect()
+                }),
+        }
+    }
+
+    pub fn create(conn: &PgConnection, create: &GroupCreate) -> DriverResult<Group> {
+        if Self::read_name(conn, &create.service_id, &create.name)?.is_some() {
+            return Err(DriverError::GroupNameConstraint);
+        }
+        // Parent must exist and must be a group of the same service.
+        if let Some(parent_id) = &create.parent_id {
+            let ancestor = Self::read_ancestor(conn, parent_id)?;
+            if ancestor.is_empty() || ancestor.iter().any(|x| x.service_id != create.service_id) {
+                return Err(DriverError::GroupParentInvalid);
+            }
+        }
+
+        let now = Utc::now();
+        let id = Uuid::new_v4();
+        let value = ModelGroupInsert {
+            created_at: &now,
+            updated_at: &now,
+            id: &id,
+            is_enabled: create.is_enabled,
+            name: &create.name,
+            parent_id: create.parent_id.as_ref(),
+            service_id: &create.service_id,
+        };
+        diesel::insert_into(sso_group::table)
+            .values(&value)
+            .get_result::<ModelGroup>(conn)
+            .map_err(Into::into)
+            .map(Into::into)
+    }
+
+    pub fn read(
+        conn: &PgConnection,
+        read: &GroupRead,
+        service_id: Option<Uuid>,
+    ) -> DriverResult<Option<Group>> {
+        let mut query = sso_group::table
+            .filter(sso_group::dsl::id.eq(read.id))
+            .into_boxed();
+
+        if let Some(service_id_mask) = service_id {
+            query = query.filter(sso_group::dsl::service_id.eq(service_id_mask));
+        }
+
+        query
+            .get_result::<ModelGroup>(conn)
+            .optional()
+            .map_err(Into::into)
+            .map(|x| x.map(Into::into))
+    }
+
+    pub fn update(conn: &PgConnection, update: &GroupUpdate) -> DriverResult<Group> {
+        let group = Self::read(conn, &GroupRead::new(update.id), None)?
+            .ok_or_else(|| DriverError::GroupNotFound)?;
+        if let Some(name) = &update.name {
+            if let Some(x) = Self::read_name(conn, &group.service_id, name)? {
+                if x.id != update.id {
+                    return Err(DriverError::GroupNameConstraint);
+                }
+            }
+        }
+        // Parent must exist, must be a group of the same service and must not
+        // be group or one of its descendants.
+        if let Some(Some(parent_id)) = &update.parent_id {
+            let ancestor = Self::read_ancestor(conn, parent_id)?;
+            if ancestor.is_empty()
+                || ancestor
+                    .iter()
+                    .any(|x| x.id == update.id || x.service_id != group.service_id)
+            {
+                return Err(DriverError::GroupParentInvalid);
+            }
+        }
+
+        let now = Utc::now();
+        let value = ModelGroupUpdate {
+            updated_at: &now,
+            is_enabled: update.is_enabled,
+            name: update.name.as_ref().map(|x| &**x),
+            parent_id: update.parent_id.as_ref().map(|x| x.as_ref()),
+        };
+        diesel::update(sso_group::table.filter(sso_group::dsl::id.eq(update.id)))
+            .set(&value)
+            .get_result::<ModelGroup>(conn)
+            .map_err(Into::into)
+            .map(Into::into)
+    }
+
+    pub fn delete(conn: &PgConnection, id: &Uuid) -> DriverResult<usize> {
+        diesel::delete(sso_group::table.filter(sso_group::dsl::id.eq(id)))
+            .execute(conn)
+            .map_err(Into::into)
+    }
+
+    /// Add user to group, does nothing if user is already a member.
+    pub fn user_create(conn: &PgConnection, group_user: &GroupUser) -> DriverResult<usize> {
+        let now = Utc::now();
+        let value = ModelUserGroupInsert {
+            created_at: &now,
+            user_id: &group_user.user_id,
+            group_id: &group_user.group_id,
+        };
+        diesel::insert_into(sso_user_group::table)
+            .values(&value)
+            .on_conflict_do_nothing()
+            .execute(conn)
+            .map_err(Into::into)
+    }
+
+    /// Remove user from group.
+    pub fn user_delete(conn: &PgConnection, group_user: &GroupUser) -> DriverResult<usize> {
+        diesel::delete(
+            sso_user_group::table.filter(
+                sso_user_group::dsl::user_id
+                    .eq(group_user.user_id)
+                    .and(sso_user_group::dsl::group_id.eq(group_user.group_id)),
+            ),
+        )
+        .execute(conn)
+        .map_err(Into::into)
+    }
+
+    /// Returns groups of service of which user is an effective member, sorted by name.
+    pub fn user_effective(
+        conn: &PgConnection,
+        service_id: &Uuid,
+        user_id: &Uuid,
+    ) -> DriverResult<Vec<Group>> {
+        diesel::sql_query(include_str!("group_read_user_effective.sql"))
+            .bind::<sql_types::Uuid, _>(service_id)
+            .bind::<sql_types::Uuid, _>(user_id)
+            .load::<ModelGroup>(conn)
+            .map_err(Into::into)
+            .map(|x| x.into_iter().map(|x| x.into()).collect())
+    }
+
+    /// Assign role to group, does nothing if role is already assigned.
+    pub fn role_create(conn: &PgConnection, group_role: &GroupRole) -> DriverResult<usize> {
+        let now = Utc::now();
+        let value = ModelGroupRoleInsert {
+            created_at: &now,
+            group_id: &group_role.group_id,
+            role_id: &group_role.role_id,
+        };
+        diesel::insert_into(sso_group_role::table)
+            .values(&value)
+            .on_conflict_do_nothing()
+            .execute(conn)
+            .map_err(Into::into)
+    }
+
+    /// Remove role from group.
+    pub fn role_delete(conn: &PgConnection, group_role: &GroupRole) -> DriverResult<usize> {
+        diesel::delete(
+            sso_group_role::table.filter(
+                sso_group_role::dsl::group_id
+                    .eq(group_role.group_id)
+                    .and(sso_group_role::dsl::role_id.eq(group_role.role_id)),
+            ),
+        )
+        .execute(conn)
+        .map_err(Into::into)
+    }
+
+    fn read_name(
+        conn: &PgConnection,
+        service_id: &Uuid,
+        name: &str,
+    ) -> DriverResult<Option<ModelGroup>> {
+        sso_group::table
+            .filter(
+                sso_group::dsl::service_id
+                    .eq(service_id)
+                    .and(sso_group::dsl::name.eq(name)),
+            )
+            .get_result::<ModelGroup>(conn)
+            .optional()
+            .map_err(Into::into)
+    }
+
+    /// Returns group and its ancestors, empty if group does not exist.
+    fn read_ancestor(conn: &PgConnection, id: &Uuid) -> DriverResult<Vec<ModelGroup>> {
+        diesel::sql_query(include_str!("group_read_ancestor.sql"))
+            .bind::<sql_types::Uuid, _>(id)
+            .load::<ModelGroup>(conn)
+            .map_err(Into::into)
+    }
+}
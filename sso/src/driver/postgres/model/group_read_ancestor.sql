@@ -0,0 +1,10 @@
+WITH RECURSIVE ancestor AS (
+  SELECT g.*
+  FROM sso_group AS g
+  WHERE g."id" = $1
+  UNION
+  SELECT g.*
+  FROM sso_group AS g
+  INNER JOIN ancestor AS a ON g."id" = a."parent_id"
+)
+SELECT * FROM ancestor;
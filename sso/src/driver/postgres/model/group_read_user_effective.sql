@@ -0,0 +1,16 @@
+WITH RECURSIVE effective AS (
+  SELECT g.*
+  FROM sso_group AS g
+  INNER JOIN sso_user_group AS ug ON g."id" = ug."group_id"
+  WHERE
+    g."service_id" = $1
+    AND ug."user_id" = $2
+    AND g."is_enabled" = TRUE
+  UNION
+  SELECT g.*
+  FROM sso_group AS g
+  INNER JOIN effective AS e ON g."id" = e."parent_id"
+  WHERE g."is_enabled" = TRUE
+)
+SELECT * FROM effective
+ORDER BY "name" ASC;
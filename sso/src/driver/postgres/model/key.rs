@@ -1,9 +1,10 @@
 use crate::{
-    driver::postgres::model::{ModelService, ModelUser},
+    driver::postgres::model::{ModelOrganisation, ModelService, ModelUser, ModelUserService},
     prelude::*,
-    schema::sso_key,
+    schema::{sso_key, sso_service},
 };
-use diesel::{dsl::sql, prelude::*, sql_types::BigInt, PgConnection};
+use diesel::{dsl::sql, pg::Pg, prelude::*, sql_types::BigInt, PgConnection};
+use std::convert::TryFrom;
 
 #[derive(Debug, Identifiable, Queryable)]
 #[table_name = "sso_key"]
@@ -16,41 +17,130 @@ pub struct ModelKey {
     is_revoked: bool,
     type_: String,
     name: String,
-    value: String,
+    value: Option<String>,
     service_id: Option<Uuid>,
     user_id: Option<Uuid>,
+    scope: Vec<String>,
+    value_prefix: String,
+    value_hash: Option<String>,
+    secret: Option<String>,
+    predecessor_id: Option<Uuid>,
+    successor_id: Option<Uuid>,
+    revoke_at: Option<DateTime<Utc>>,
+    last_used_at: Option<DateTime<Utc>>,
+    last_used_remote: Option<String>,
+    allow_remote: Vec<String>,
+    organisation_id: Option<Uuid>,
 }
 
-impl From<ModelKey> for Key {
-    fn from(key: ModelKey) -> Self {
-        Self {
-            created_at: key.created_at,
-            updated_at: key.updated_at,
-            id: key.id,
-            is_enabled: key.is_enabled,
-            is_revoked: key.is_revoked,
-            type_: KeyType::from_str(&key.type_).unwrap(),
-            name: key.name,
-            service_id: key.service_id,
-            user_id: key.user_id,
+impl ModelKey {
+    /// Returns error if a scope is unknown, keys with an empty scope are not
+    /// restricted so unknown scopes must not be dropped.
+    fn scope_from_model(scope: &[String]) -> DriverResult<Vec<KeyScope>> {
+        scope
+            .iter()
+            .map(|x| KeyScope::from_str(x).map_err(|_e| DriverError::KeyScopeInvalid(x.clone())))
+            .collect()
+    }
+
+    /// Returns true if key is revoked or its revoke time has passed, rotated
+    /// keys are revoked when their grace period expires.
+    fn is_revoked_at(&self, now: &DateTime<Utc>) -> bool {
+        self.is_revoked || self.revoke_at.map(|x| x <= *now).unwrap_or(false)
+    }
+
+    /// Filter query by revoked flag of keys, see `is_revoked_at`.
+    fn filter_revoked(
+        query: sso_key::BoxedQuery<'_, Pg>,
+        is_revoked: bool,
+        now: DateTime<Utc>,
+    ) -> sso_key::BoxedQuery<'_, Pg> {
+        if is_revoked {
+            query.filter(
+                sso_key::dsl::is_revoked
+                    .eq(true)
+                    .or(sso_key::dsl::revoke_at.le(now)),
+            )
+        } else {
+            query.filter(
+                sso_key::dsl::is_revoked.eq(false).and(
+                    sso_key::dsl::revoke_at
+                        .is_null()
+                        .or(sso_key::dsl::revoke_at.gt(now)),
+                ),
+            )
         }
     }
+
+    fn allow_remote_from_model(allow_remote: &[String]) -> Vec<Cidr> {
+        allow_remote
+            .iter()
+            .filter_map(|x| Cidr::from_str(x).ok())
+            .collect()
+    }
+
+    /// Returns key with value, used when key is created.
+    fn into_with_value(self, value: String) -> DriverResult<KeyWithValue> {
+        let is_revoked = self.is_revoked_at(&Utc::now());
+        Ok(KeyWithValue {
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            id: self.id,
+            is_enabled: self.is_enabled,
+            is_revoked,
+            type_: KeyType::from_str(&self.type_).unwrap(),
+            name: self.name,
+            value_prefix: self.value_prefix,
+            value,
+            service_id: self.service_id,
+            user_id: self.user_id,
+            scope: ModelKey::scope_from_model(&self.scope)?,
+            allow_remote: ModelKey::allow_remote_from_model(&self.allow_remote),
+            predecessor_id: self.predecessor_id,
+            successor_id: self.successor_id,
+            revoke_at: self.revoke_at,
+            last_used_at: self.last_used_at,
+            last_used_remote: self.last_used_remote,
+            organisation_id: self.organisation_id,
+            predecessor_value: None,
+        })
+    }
+
+    /// Returns key with value, value is decrypted from secret if defined.
+    fn into_with_secret(self, key_secret: &KeySecret) -> DriverResult<KeyWithValue> {
+        let value = match self.secret.as_ref() {
+            Some(secret) => key_secret.secret_decrypt(secret)?,
+            None => "".to_owned(),
+        };
+        self.into_with_value(value)
+    }
 }
 
-impl From<ModelKey> for KeyWithValue {
-    fn from(key: ModelKey) -> Self {
-        Self {
+impl TryFrom<ModelKey> for Key {
+    type Error = DriverError;
+
+    fn try_from(key: ModelKey) -> DriverResult<Self> {
+        let is_revoked = key.is_revoked_at(&Utc::now());
+        Ok(Self {
             created_at: key.created_at,
             updated_at: key.updated_at,
             id: key.id,
             is_enabled: key.is_enabled,
-            is_revoked: key.is_revoked,
+            is_revoked,
             type_: KeyType::from_str(&key.type_).unwrap(),
             name: key.name,
-            value: key.value,
+            value_prefix: key.value_prefix,
             service_id: key.service_id,
             user_id: key.user_id,
-        }
+            scope: ModelKey::scope_from_model(&key.scope)?,
+            allow_remote: ModelKey::allow_remote_from_model(&key.allow_remote),
+            predecessor_id: key.predecessor_id,
+            successor_id: key.successor_id,
+            revoke_at: key.revoke_at,
+            last_used_at: key.last_used_at,
+            last_used_remote: key.last_used_remote,
+            organisation_id: key.organisation_id,
+        })
     }
 }
 
@@ -64,9 +154,15 @@ struct ModelKeyInsert<'a> {
     is_revoked: bool,
     type_: String,
     name: &'a str,
-    value: &'a str,
     service_id: Option<&'a Uuid>,
     user_id: Option<&'a Uuid>,
+    scope: Vec<String>,
+    value_prefix: String,
+    value_hash: String,
+    secret: Option<String>,
+    predecessor_id: Option<&'a Uuid>,
+    allow_remote: Vec<String>,
+    organisation_id: Option<&'a Uuid>,
 }
 
 #[derive(AsChangeset)]
@@ -94,6 +190,7 @@ impl ModelKey {
         conn: &PgConnection,
         list: &KeyList,
         service_id: Option<Uuid>,
+        organisation_id: Option<Uuid>,
     ) -> DriverResult<Vec<Key>> {
         use diesel::dsl::any;
 
@@ -107,7 +204,7 @@ impl ModelKey {
             query = query.filter(sso_key::dsl::is_enabled.eq(is_enabled));
         }
         if let Some(is_revoked) = list.filter.is_revoked {
-            query = query.filter(sso_key::dsl::is_revoked.eq(is_revoked));
+            query = Self::filter_revoked(query, is_revoked, Utc::now());
         }
         if let Some(type_) = &list.filter.type_ {
             let type_: Vec<String> = type_.iter().map(|x| x.to_string()).collect();
@@ -121,9 +218,30 @@ impl ModelKey {
             let user_id: Vec<Uuid> = user_id.iter().copied().collect();
             query = query.filter(sso_key::dsl::user_id.eq(any(user_id)));
         }
+        if let Some(last_used_lt) = list.filter.last_used_lt {
+            query = query.filter(
+                sso_key::dsl::last_used_at
+                    .lt(last_used_lt)
+                    .or(sso_key::dsl::last_used_at
+                        .is_null()
+                        .and(sso_key::dsl::created_at.lt(last_used_lt))),
+            );
+        }
         if let Some(service_id_mask) = service_id {
             query = query.filter(sso_key::dsl::service_id.eq(service_id_mask));
         }
+        // Root keys of organisation and keys of services owned by organisation.
+        if let Some(organisation_id_mask) = organisation_id {
+            query = query.filter(
+                sso_key::dsl::organisation_id
+                    .eq(organisation_id_mask)
+                    .or(sso_key::dsl::service_id.eq_any(
+                        sso_service::table
+                            .select(sso_service::dsl::id.nullable())
+                            .filter(sso_service::dsl::organisation_id.eq(organisation_id_mask)),
+                    )),
+            );
+        }
 
         match list.query {
             KeyListQuery::Limit => query
@@ -132,23 +250,23 @@ impl ModelKey {
                 .order(sso_key::dsl::id.asc())
                 .load::<ModelKey>(conn)
                 .map_err(Into::into)
-                .map(|x| x.into_iter().map(|x| x.into()).collect()),
+                .and_then(|x| x.into_iter().map(TryInto::try_into).collect()),
             KeyListQuery::IdGt(gt) => query
                 .filter(sso_key::dsl::id.gt(gt))
                 .limit(list.filter.limit)
                 .order(sso_key::dsl::id.asc())
                 .load::<ModelKey>(conn)
                 .map_err(Into::into)
-                .map(|x| x.into_iter().map(|x| x.into()).collect()),
+                .and_then(|x| x.into_iter().map(TryInto::try_into).collect()),
             KeyListQuery::IdLt(lt) => query
                 .filter(sso_key::dsl::id.lt(lt))
                 .limit(list.filter.limit)
                 .order(sso_key::dsl::id.desc())
                 .load::<ModelKey>(conn)
                 .map_err(Into::into)
-                .map(|mut x| {
+                .and_then(|mut x| {
                     x.reverse();
-                    x.into_iter().map(|x| x.into()).collect()
+                    x.into_iter().map(TryInto::try_into).collect()
                 }),
         }
     }
@@ -161,7 +279,11 @@ impl ModelKey {
         .map(|x| x as usize)
     }
 
-    pub fn create(conn: &PgConnection, create: &KeyCreate) -> DriverResult<KeyWithValue> {
+    pub fn create(
+        conn: &PgConnection,
+        key_secret: &KeySecret,
+        create: &KeyCreate,
+    ) -> DriverResult<KeyWithValue> {
         if create.is_enabled {
             if create.type_ == KeyType::Token {
                 let count = Self::count_token(
@@ -192,44 +314,144 @@ impl ModelKey {
             ModelUser::read(conn, &UserRead::Id(*user_id))?
                 .ok_or_else(|| DriverError::UserNotFound)?;
         }
+        if let Some(organisation_id) = &create.organisation_id {
+            ModelOrganisation::read(conn, &OrganisationRead::new(*organisation_id))?
+                .ok_or_else(|| DriverError::OrganisationNotFound)?;
+        }
+
+        conn.transaction(|| {
+            // Users with keys for a service are active members unless
+            // their membership status has already been set.
+            if let (Some(service_id), Some(user_id)) = (&create.service_id, &create.user_id) {
+                ModelUserService::status_create_active(conn, service_id, user_id)?;
+            }
+            Self::insert(conn, key_secret, create, &Uuid::new_v4())
+        })
+    }
 
+    /// Rotate key, creates successor key and sets predecessor revoke time.
+    /// Returns predecessor before rotation and successor key with value.
+    pub fn rotate(
+        conn: &PgConnection,
+        key_secret: &KeySecret,
+        rotate: &KeyRotate,
+    ) -> DriverResult<(KeyWithValue, KeyWithValue)> {
+        conn.transaction(|| {
+            let predecessor = sso_key::table
+                .filter(sso_key::dsl::id.eq(rotate.id))
+                .get_result::<ModelKey>(conn)
+                .optional()?
+                .ok_or_else(|| DriverError::KeyNotFound)?
+                .into_with_secret(key_secret)?;
+            if predecessor.is_revoked {
+                return Err(DriverError::KeyRevoked);
+            }
+            if predecessor.successor_id.is_some() {
+                return Err(DriverError::KeyAlreadyRotated);
+            }
+
+            // Predecessor is updated before successor is inserted so that it is
+            // excluded from user token and TOTP key constraints.
+            let now = Utc::now();
+            let id = Uuid::new_v4();
+            let revoke_at = now + rotate.grace_period;
+            diesel::update(sso_key::table.filter(sso_key::dsl::id.eq(predecessor.id)))
+                .set((
+                    sso_key::dsl::updated_at.eq(now),
+                    sso_key::dsl::is_revoked.eq(revoke_at <= now),
+                    sso_key::dsl::successor_id.eq(id),
+                    sso_key::dsl::revoke_at.eq(revoke_at),
+                ))
+                .execute(conn)?;
+
+            let create = KeyCreate::successor(&predecessor, rotate.name.clone());
+            let successor = Self::insert(conn, key_secret, &create, &id)?;
+            Ok((predecessor, successor))
+        })
+    }
+
+    fn insert(
+        conn: &PgConnection,
+        key_secret: &KeySecret,
+        create: &KeyCreate,
+        id: &Uuid,
+    ) -> DriverResult<KeyWithValue> {
         let now = Utc::now();
-        let id = Uuid::new_v4();
         let value = ModelKeyInsert {
             created_at: &now,
             updated_at: &now,
-            id: &id,
+            id,
             is_enabled: create.is_enabled,
             is_revoked: create.is_revoked,
             type_: create.type_.to_string(),
             name: &create.name,
-            value: &create.value,
             service_id: create.service_id.as_ref(),
             user_id: create.user_id.as_ref(),
+            scope: create.scope.iter().map(|x| x.to_string()).collect(),
+            value_prefix: KeySecret::value_prefix(&create.value),
+            value_hash: key_secret.value_hash(&create.value),
+            secret: Self::secret_encrypt(key_secret, create.type_, &create.value)?,
+            predecessor_id: create.predecessor_id.as_ref(),
+            allow_remote: create.allow_remote.iter().map(|x| x.to_string()).collect(),
+            organisation_id: create.organisation_id.as_ref(),
         };
         diesel::insert_into(sso_key::table)
             .values(&value)
             .get_result::<ModelKey>(conn)
             .map_err(Into::into)
-            .map(Into::into)
+            .and_then(|x| x.into_with_value(create.value.clone()))
     }
 
     pub fn read(
         conn: &PgConnection,
+        key_secret: &KeySecret,
         read: &KeyRead,
         service_id: Option<Uuid>,
     ) -> DriverResult<Option<KeyWithValue>> {
-        match read {
+        let key = match read {
             KeyRead::IdUser(id, user_id) => Self::read_by_id(conn, id, &service_id, user_id),
             KeyRead::RootId(id) => Self::read_by_root_id(conn, *id),
-            KeyRead::RootValue(value) => Self::read_by_root_value(conn, value),
+            KeyRead::RootValue(value) => Self::read_by_root_value(conn, key_secret, value),
             KeyRead::ServiceId(service_id, id) => Self::read_by_service_id(conn, *service_id, *id),
-            KeyRead::ServiceValue(value) => Self::read_by_service_value(conn, value),
+            KeyRead::ServiceValue(value) => Self::read_by_service_value(conn, key_secret, value),
             KeyRead::UserId(r) => Self::read_by_user_id(conn, r),
-            KeyRead::UserValue(r) => Self::read_by_user_value(conn, r),
+            KeyRead::UserValue(r) => Self::read_by_user_value(conn, key_secret, r),
+        }?;
+        match key {
+            Some(key) => {
+                let mut key = key.into_with_secret(key_secret)?;
+                if let KeyRead::UserId(_) = read {
+                    key.predecessor_value = Self::read_predecessor_value(conn, key_secret, &key)?;
+                }
+                Ok(Some(key))
+            }
+            None => Ok(None),
         }
     }
 
+    /// Hash and encrypt plaintext key values, plaintext values are then cleared.
+    /// Returns number of migrated keys, this should be run after migrations.
+    pub fn value_migrate(conn: &PgConnection, key_secret: &KeySecret) -> DriverResult<usize> {
+        conn.transaction(|| {
+            let keys = sso_key::table
+                .filter(sso_key::dsl::value.is_not_null())
+                .load::<ModelKey>(conn)?;
+            for key in keys.iter() {
+                let value = key.value.as_ref().unwrap();
+                let type_ = KeyType::from_str(&key.type_).unwrap();
+                diesel::update(sso_key::table.filter(sso_key::dsl::id.eq(key.id)))
+                    .set((
+                        sso_key::dsl::value.eq(None::<String>),
+                        sso_key::dsl::value_prefix.eq(KeySecret::value_prefix(value)),
+                        sso_key::dsl::value_hash.eq(key_secret.value_hash(value)),
+                        sso_key::dsl::secret.eq(Self::secret_encrypt(key_secret, type_, value)?),
+                    ))
+                    .execute(conn)?;
+            }
+            Ok(keys.len())
+        })
+    }
+
     pub fn update(conn: &PgConnection, update: &KeyUpdate) -> DriverResult<Key> {
         let now = chrono::Utc::now();
         let value = ModelKeyUpdate::from_update(&now, update);
@@ -237,7 +459,7 @@ impl ModelKey {
             .set(&value)
             .get_result::<ModelKey>(conn)
             .map_err(Into::into)
-            .map(Into::into)
+            .and_then(TryInto::try_into)
     }
 
     pub fn update_many(
@@ -253,12 +475,87 @@ impl ModelKey {
             .map_err(Into::into)
     }
 
+    /// Update last used time and remote of key.
+    pub fn last_used_update(conn: &PgConnection, id: &Uuid, remote: &str) -> DriverResult<usize> {
+        diesel::update(sso_key::table.filter(sso_key::dsl::id.eq(id)))
+            .set((
+                sso_key::dsl::last_used_at.eq(Utc::now()),
+                sso_key::dsl::last_used_remote.eq(remote),
+            ))
+            .execute(conn)
+            .map_err(Into::into)
+    }
+
     pub fn delete(conn: &PgConnection, id: &Uuid) -> DriverResult<usize> {
         diesel::delete(sso_key::table.filter(sso_key::dsl::id.eq(id)))
             .execute(conn)
             .map_err(Into::into)
     }
 
+    /// List all keys of user.
+    pub fn list_user(conn: &PgConnection, user_id: &Uuid) -> DriverResult<Vec<Key>> {
+        sso_key::table
+            .filter(sso_key::dsl::user_id.eq(user_id))
+            .order(sso_key::dsl::created_at.asc())
+            .load::<ModelKey>(conn)
+            .map_err(Into::into)
+            .and_then(|x| x.into_iter().map(TryInto::try_into).collect())
+    }
+
+    /// Delete all keys of user.
+    pub fn delete_user(conn: &PgConnection, user_id: &Uuid) -> DriverResult<usize> {
+        diesel::delete(sso_key::table.filter(sso_key::dsl::user_id.eq(user_id)))
+            .execute(conn)
+            .map_err(Into::into)
+    }
+
+    /// Returns predecessor value of token or TOTP key if predecessor is
+    /// enabled and not revoked, used to verify tokens during grace period.
+    fn read_predecessor_value(
+        conn: &PgConnection,
+        key_secret: &KeySecret,
+        key: &KeyWithValue,
+    ) -> DriverResult<Option<String>> {
+        let predecessor_id = match key.predecessor_id {
+            Some(predecessor_id) if key.type_.has_secret() => predecessor_id,
+            _ => return Ok(None),
+        };
+        let query = sso_key::table.into_boxed().filter(
+            sso_key::dsl::id
+                .eq(predecessor_id)
+                .and(sso_key::dsl::is_enabled.eq(true)),
+        );
+        let predecessor = Self::filter_revoked(query, false, Utc::now())
+            .get_result::<ModelKey>(conn)
+            .optional()?;
+        match predecessor {
+            Some(predecessor) => predecessor
+                .into_with_secret(key_secret)
+                .map(|x| Some(x.value)),
+            None => Ok(None),
+        }
+    }
+
+    fn secret_encrypt(
+        key_secret: &KeySecret,
+        type_: KeyType,
+        value: &str,
+    ) -> DriverResult<Option<String>> {
+        if type_.has_secret() {
+            key_secret.secret_encrypt(value).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Returns key with value matching hash from candidates with prefix.
+    fn value_find(keys: Vec<ModelKey>, key_secret: &KeySecret, value: &str) -> Option<ModelKey> {
+        keys.into_iter().find(|key| match key.value_hash.as_ref() {
+            Some(value_hash) => key_secret.value_verify(value, value_hash),
+            None => false,
+        })
+    }
+
     fn count_token(conn: &PgConnection, service_id: &Uuid, user_id: &Uuid) -> DriverResult<i64> {
         sso_key::table
             .select(sql::<BigInt>("count(*)"))
@@ -267,7 +564,8 @@ impl ModelKey {
                     .eq(true)
                     .and(sso_key::dsl::type_.eq("Token"))
                     .and(sso_key::dsl::service_id.eq(service_id))
-                    .and(sso_key::dsl::user_id.eq(user_id)),
+                    .and(sso_key::dsl::user_id.eq(user_id))
+                    .and(sso_key::dsl::successor_id.is_null()),
             )
             .get_result::<i64>(conn)
             .map_err(Into::into)
@@ -281,7 +579,8 @@ impl ModelKey {
                     .eq(true)
                     .and(sso_key::dsl::type_.eq("Totp"))
                     .and(sso_key::dsl::service_id.eq(service_id))
-                    .and(sso_key::dsl::user_id.eq(user_id)),
+                    .and(sso_key::dsl::user_id.eq(user_id))
+                    .and(sso_key::dsl::successor_id.is_null()),
             )
             .get_result::<i64>(conn)
             .map_err(Into::into)
@@ -292,7 +591,7 @@ impl ModelKey {
         id: &Uuid,
         service_id_mask: &Option<Uuid>,
         user_id_mask: &Option<Uuid>,
-    ) -> DriverResult<Option<KeyWithValue>> {
+    ) -> DriverResult<Option<ModelKey>> {
         let mut query = sso_key::table.into_boxed();
 
         if let Some(service_id) = service_id_mask {
@@ -307,10 +606,9 @@ impl ModelKey {
             .get_result::<ModelKey>(conn)
             .optional()
             .map_err(Into::into)
-            .map(|x| x.map(Into::into))
     }
 
-    fn read_by_root_id(conn: &PgConnection, id: Uuid) -> DriverResult<Option<KeyWithValue>> {
+    fn read_by_root_id(conn: &PgConnection, id: Uuid) -> DriverResult<Option<ModelKey>> {
         sso_key::table
             .filter(
                 sso_key::dsl::id
@@ -321,28 +619,30 @@ impl ModelKey {
             .get_result::<ModelKey>(conn)
             .optional()
             .map_err(Into::into)
-            .map(|x| x.map(Into::into))
     }
 
-    fn read_by_root_value(conn: &PgConnection, value: &str) -> DriverResult<Option<KeyWithValue>> {
+    fn read_by_root_value(
+        conn: &PgConnection,
+        key_secret: &KeySecret,
+        value: &str,
+    ) -> DriverResult<Option<ModelKey>> {
         sso_key::table
             .filter(
-                sso_key::dsl::value
-                    .eq(value)
+                sso_key::dsl::value_prefix
+                    .eq(KeySecret::value_prefix(value))
                     .and(sso_key::dsl::service_id.is_null())
                     .and(sso_key::dsl::user_id.is_null()),
             )
-            .get_result::<ModelKey>(conn)
-            .optional()
+            .load::<ModelKey>(conn)
             .map_err(Into::into)
-            .map(|x| x.map(Into::into))
+            .map(|x| Self::value_find(x, key_secret, value))
     }
 
     fn read_by_service_id(
         conn: &PgConnection,
         service_id: Uuid,
         id: Uuid,
-    ) -> DriverResult<Option<KeyWithValue>> {
+    ) -> DriverResult<Option<ModelKey>> {
         sso_key::table
             .filter(
                 sso_key::dsl::id
@@ -353,65 +653,62 @@ impl ModelKey {
             .get_result::<ModelKey>(conn)
             .optional()
             .map_err(Into::into)
-            .map(|x| x.map(Into::into))
     }
 
     fn read_by_service_value(
         conn: &PgConnection,
+        key_secret: &KeySecret,
         value: &str,
-    ) -> DriverResult<Option<KeyWithValue>> {
+    ) -> DriverResult<Option<ModelKey>> {
         sso_key::table
             .filter(
-                sso_key::dsl::value
-                    .eq(value)
+                sso_key::dsl::value_prefix
+                    .eq(KeySecret::value_prefix(value))
                     .and(sso_key::dsl::service_id.is_not_null())
                     .and(sso_key::dsl::user_id.is_null()),
             )
-            .get_result::<ModelKey>(conn)
-            .optional()
+            .load::<ModelKey>(conn)
             .map_err(Into::into)
-            .map(|x| x.map(Into::into))
+            .map(|x| Self::value_find(x, key_secret, value))
     }
 
     fn read_by_user_id(
         conn: &PgConnection,
         read: &KeyReadUserId,
-    ) -> DriverResult<Option<KeyWithValue>> {
+    ) -> DriverResult<Option<ModelKey>> {
         let type_ = read.type_.to_string();
-        sso_key::table
-            .filter(
-                sso_key::dsl::user_id
-                    .eq(read.user_id)
-                    .and(sso_key::dsl::service_id.eq(read.service_id))
-                    .and(sso_key::dsl::is_enabled.eq(read.is_enabled))
-                    .and(sso_key::dsl::is_revoked.eq(read.is_revoked))
-                    .and(sso_key::dsl::type_.eq(type_)),
-            )
+        let query = sso_key::table.into_boxed().filter(
+            sso_key::dsl::user_id
+                .eq(read.user_id)
+                .and(sso_key::dsl::service_id.eq(read.service_id))
+                .and(sso_key::dsl::is_enabled.eq(read.is_enabled))
+                .and(sso_key::dsl::type_.eq(type_))
+                .and(sso_key::dsl::successor_id.is_null()),
+        );
+        Self::filter_revoked(query, read.is_revoked, Utc::now())
             .order(sso_key::dsl::created_at.asc())
             .get_result::<ModelKey>(conn)
             .optional()
             .map_err(Into::into)
-            .map(|x| x.map(Into::into))
     }
 
     fn read_by_user_value(
         conn: &PgConnection,
+        key_secret: &KeySecret,
         read: &KeyReadUserValue,
-    ) -> DriverResult<Option<KeyWithValue>> {
+    ) -> DriverResult<Option<ModelKey>> {
         let type_ = read.type_.to_string();
-        sso_key::table
-            .filter(
-                sso_key::dsl::value
-                    .eq(&read.value)
-                    .and(sso_key::dsl::service_id.eq(read.service_id))
-                    .and(sso_key::dsl::user_id.is_not_null())
-                    .and(sso_key::dsl::is_enabled.eq(read.is_enabled))
-                    .and(sso_key::dsl::is_revoked.eq(read.is_revoked))
-                    .and(sso_key::dsl::type_.eq(type_)),
-            )
-            .get_result::<ModelKey>(conn)
-            .optional()
+        let query = sso_key::table.into_boxed().filter(
+            sso_key::dsl::value_prefix
+                .eq(KeySecret::value_prefix(&read.value))
+                .and(sso_key::dsl::service_id.eq(read.service_id))
+                .and(sso_key::dsl::user_id.is_not_null())
+                .and(sso_key::dsl::is_enabled.eq(read.is_enabled))
+                .and(sso_key::dsl::type_.eq(type_)),
+        );
+        Self::filter_revoked(query, read.is_revoked, Utc::now())
+            .load::<ModelKey>(conn)
             .map_err(Into::into)
-            .map(|x| x.map(Into::into))
+            .map(|x| Self::value_find(x, key_secret, &read.value))
     }
 }
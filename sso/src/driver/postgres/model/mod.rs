@@ -1,6 +1,15 @@
 mod audit;
+mod audit_retention;
+mod group;
 mod key;
+mod organisation;
+mod role;
 mod service;
 mod user;
+mod user_service;
+mod webhook;
 
-pub use crate::driver::postgres::model::{audit::*, key::*, service::*, user::*};
+pub use crate::driver::postgres::model::{
+    audit::*, audit_retention::*, group::*, key::*, organisation::*, role::*, service::*, user::*,
+    user_service::*, webhook::*,
+};
@@ -0,0 +1,147 @@
+use crate::{
+    schema::sso_organisation, DriverResult, Organisation, OrganisationCreate, OrganisationList,
+    OrganisationListQuery, OrganisationRead, OrganisationUpdate,
+};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use uuid::Uuid;
+
+#[derive(Debug, Identifiable, Queryable)]
+#[table_name = "sso_organisation"]
+#[primary_key(id)]
+pub struct ModelOrganisation {
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    id: Uuid,
+    is_enabled: bool,
+    name: String,
+}
+
+impl From<ModelOrganisation> for Organisation {
+    fn from(organisation: ModelOrganisation) -> Self {
+        Self {
+            created_at: organisation.created_at,
+            updated_at: organisation.updated_at,
+            id: organisation.id,
+            is_enabled: organisation.is_enabled,
+            name: organisation.name,
+        }
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "sso_organisation"]
+struct ModelOrganisationInsert<'a> {
+    created_at: &'a DateTime<Utc>,
+    updated_at: &'a DateTime<Utc>,
+    id: &'a Uuid,
+    is_enabled: bool,
+    name: &'a str,
+}
+
+#[derive(AsChangeset)]
+#[table_name = "sso_organisation"]
+struct ModelOrganisationUpdate<'a> {
+    updated_at: &'a DateTime<Utc>,
+    is_enabled: Option<bool>,
+    name: Option<&'a str>,
+}
+
+impl ModelOrganisation {
+    pub fn list(
+        conn: &PgConnection,
+        list: &OrganisationList,
+        organisation_id: Option<Uuid>,
+    ) -> DriverResult<Vec<Organisation>> {
+        use diesel::dsl::any;
+
+        let mut query = sso_organisation::table.into_boxed();
+
+        if let Some(id) = &list.filter.id {
+            let id: Vec<Uuid> = id.iter().copied().collect();
+            query = query.filter(sso_organisation::dsl::id.eq(any(id)));
+        }
+        if let Some(is_enabled) = list.filter.is_enabled {
+            query = query.filter(sso_organisation::dsl::is_enabled.eq(is_enabled));
+        }
+        if let Some(organisation_id_mask) = organisation_id {
+            query = query.filter(sso_organisation::dsl::id.eq(organisation_id_mask));
+        }
+
+        match list.query {
+            OrganisationListQuery::Limit => query
+                .filter(sso_organisation::dsl::id.gt(Uuid::nil()))
+                .limit(list.filter.limit)
+                .order(sso_organisation::dsl::id.asc())
+                .load::<ModelOrganisation>(conn)
+                .map_err(Into::into)
+                .map(|x| x.into_iter().map(|x| x.into()).collect()),
+            OrganisationListQuery::IdGt(gt) => query
+                .filter(sso_organisation::dsl::id.gt(gt))
+                .limit(list.filter.limit)
+                .order(sso_organisation::dsl::id.asc())
+                .load::<ModelOrganisation>(conn)
+                .map_err(Into::into)
+                .map(|x| x.into_iter().map(|x| x.into()).collect()),
+            OrganisationListQuery::IdLt(lt) => query
+                .filter(sso_organisation::dsl::id.lt(lt))
+                .limit(list.filter.limit)
+                .order(sso_organisation::dsl::id.desc())
+                .load::<ModelOrganisation>(conn)
+                .map_err(Into::into)
+                .map(|mut x| {
+                    x.reverse();
+                    x.into_iter().map(|x| x.into()).collect()
+                }),
+        }
+    }
+
+    pub fn create(conn: &PgConnection, create: &OrganisationCreate) -> DriverResult<Organisation> {
+        let now = Utc::now();
+        let id = Uuid::new_v4();
+        let value = ModelOrganisationInsert {
+            created_at: &now,
+            updated_at: &now,
+            id: &id,
+            is_enabled: create.is_enabled,
+            name: &create.name,
+        };
+        diesel::insert_into(sso_organisation::table)
+            .values(&value)
+            .get_result::<ModelOrganisation>(conn)
+            .map_err(Into::into)
+            .map(Into::into)
+    }
+
+    pub fn read(
+        conn: &PgConnection,
+        read: &OrganisationRead,
+    ) -> DriverResult<Option<Organisation>> {
+        sso_organisation::table
+            .filter(sso_organisation::dsl::id.eq(read.id))
+            .get_result::<ModelOrganisation>(conn)
+            .optional()
+            .map_err(Into::into)
+            .map(|x| x.map(Into::into))
+    }
+
+    pub fn update(conn: &PgConnection, update: &OrganisationUpdate) -> DriverResult<Organisation> {
+        let now = Utc::now();
+        let value = ModelOrganisationUpdate {
+            updated_at: &now,
+            is_enabled: update.is_enabled,
+            name: update.name.as_ref().map(|x| &**x),
+        };
+        diesel::update(sso_organisation::table.filter(sso_organisation::dsl::id.eq(update.id)))
+            .set(&value)
+            .get_result::<ModelOrganisation>(conn)
+            .map_err(Into::into)
+            .map(Into::into)
+    }
+
+    pub fn delete(conn: &PgConnection, id: &Uuid) -> DriverResult<usize> {
+        diesel::delete(sso_organisation::table.filter(sso_organisation::dsl::id.eq(id)))
+            .execute(conn)
+            .map_err(Into::into)
+    }
+}
@@ -0,0 +1,260 @@
+use crate::{
+    schema::{sso_role, sso_service, sso_user_role},
+    DriverError, DriverResult, Role, RoleCreate, RoleList, RoleListQuery, RoleRead, RoleUpdate,
+    UserRole,
+};
+use chrono::{DateTime, Utc};
+use diesel::{prelude::*, sql_types};
+use uuid::Uuid;
+
+#[derive(Debug, Identifiable, Queryable)]
+#[table_name = "sso_role"]
+#[primary_key(id)]
+pub struct ModelRole {
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    id: Uuid,
+    service_id: Uuid,
+    name: String,
+}
+
+impl From<ModelRole> for Role {
+    fn from(role: ModelRole) -> Self {
+        Self {
+            created_at: role.created_at,
+            updated_at: role.updated_at,
+            id: role.id,
+            service_id: role.service_id,
+            name: role.name,
+        }
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "sso_role"]
+struct ModelRoleInsert<'a> {
+    created_at: &'a DateTime<Utc>,
+    updated_at: &'a DateTime<Utc>,
+    id: &'a Uuid,
+    service_id: &'a Uuid,
+    name: &'a str,
+}
+
+#[derive(AsChangeset)]
+#[table_name = "sso_role"]
+struct ModelRoleUpdate<'a> {
+    updated_at: &'a DateTime<Utc>,
+    name: Option<&'a str>,
+}
+
+#[derive(Debug, QueryableByName)]
+struct ModelRoleName {
+    #[sql_type = "sql_types::Text"]
+    name: String,
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "sso_user_role"]
+struct ModelUserRoleInsert<'a> {
+    created_at: &'a DateTime<Utc>,
+    user_id: &'a Uuid,
+    role_id: &'a Uuid,
+}
+
+impl ModelRole {
+    pub fn list(
+        conn: &PgConnection,
+        list: &RoleList,
+        service_id: Option<Uuid>,
+        organisation_id: Option<Uuid>,
+    ) -> DriverResult<Vec<Role>> {
+        use diesel::dsl::any;
+
+        let mut query = sso_role::table.into_boxed();
+
+        if let Some(id) = &list.filter.id {
+            let id: Vec<Uuid> = id.iter().copied().collect();
+            query = query.filter(sso_role::dsl::id.eq(any(id)));
+        }
+        if let Some(service_id) = &list.filter.service_id {
+            let service_id: Vec<Uuid> = service_id.iter().copied().collect();
+            query = query.filter(sso_role::dsl::service_id.eq(any(service_id)));
+        }
+        if let Some(user_id) = list.filter.user_id {
+            query = query.filter(
+                sso_role::dsl::id.eq_any(
+                    sso_user_role::table
+                        .select(sso_user_role::dsl::role_id)
+                        .filter(sso_user_role::dsl::user_id.eq(user_id)),
+                ),
+            );
+        }
+        if let Some(service_id_mask) = service_id {
+            query = query.filter(sso_role::dsl::service_id.eq(service_id_mask));
+        }
+        if let Some(organisation_id_mask) = organisation_id {
+            query = query.filter(
+                sso_role::dsl::service_id.eq_any(
+                    sso_service::table
+                        .select(sso_service::dsl::id)
+                        .filter(sso_service::dsl::organisation_id.eq(organisation_id_mask)),
+                ),
+            );
+        }
+
+        match list.query {
+            RoleListQuery::Limit => query
+                .filter(sso_role::dsl::id.gt(Uuid::nil()))
+                .limit(list.filter.limit)
+                .order(sso_role::dsl::id.asc())
+                .load::<ModelRole>(conn)
+                .map_err(Into::into)
+                .map(|x| x.into_iter().map(|x| x.into()).collect()),
+            RoleListQuery::IdGt(gt) => query
+                .filter(sso_role::dsl::id.gt(gt))
+                .limit(list.filter.limit)
+                .order(sso_role::dsl::id.asc())
+                .load::<ModelRole>(conn)
+                .map_err(Into::into)
+                .map(|x| x.into_iter().map(|x| x.into()).collect()),
+            RoleListQuery::IdLt(lt) => query
+                .filter(sso_role::dsl::id.lt(lt))
+                .limit(list.filter.limit)
+                .order(sso_role::dsl::id.desc())
+                .load::<ModelRole>(conn)
+                .map_err(Into::into)
+                .map(|mut x| {
+                    x.reverse();
+                    x.into_iter().map(|x| x.into()).collect()
+                }),
+        }
+    }
+
+    pub fn create(conn: &PgConnection, create: &RoleCreate) -> DriverResult<Role> {
+        if Self::read_name(conn, &create.service_id, &create.name)?.is_some() {
+            return Err(DriverError::RoleNameConstraint);
+        }
+
+        let now = Utc::now();
+        let id = Uuid::new_v4();
+        let value = ModelRoleInsert {
+            created_at: &now,
+            updated_at: &now,
+            id: &id,
+            service_id: &create.service_id,
+            name: &create.name,
+        };
+        diesel::insert_into(sso_role::table)
+            .values(&value)
+            .get_result::<ModelRole>(conn)
+            .map_err(Into::into)
+            .map(Into::into)
+    }
+
+    pub fn read(
+        conn: &PgConnection,
+        read: &RoleRead,
+        service_id: Option<Uuid>,
+    ) -> DriverResult<Option<Role>> {
+        let mut query = sso_role::table
+            .filter(sso_role::dsl::id.eq(read.id))
+            .into_boxed();
+        if let Some(service_id_mask) = service_id {
+            query = query.filter(sso_role::dsl::service_id.eq(service_id_mask));
+        }
+        query
+            .get_result::<ModelRole>(conn)
+            .optional()
+            .map_err(Into::into)
+            .map(|x| x.map(Into::into))
+    }
+
+    pub fn update(conn: &PgConnection, update: &RoleUpdate) -> DriverResult<Role> {
+        if let Some(name) = &update.name {
+            let role = sso_role::table
+                .filter(sso_role::dsl::id.eq(update.id))
+                .get_result::<ModelRole>(conn)?;
+            if let Some(x) = Self::read_name(conn, &role.service_id, name)? {
+                if x.id != update.id {
+                    return Err(DriverError::RoleNameConstraint);
+                }
+            }
+        }
+
+        let now = Utc::now();
+        let value = ModelRoleUpdate {
+            updated_at: &now,
+            name: update.name.as_ref().map(|x| &**x),
+        };
+        diesel::update(sso_role::table.filter(sso_role::dsl::id.eq(update.id)))
+            .set(&value)
+            .get_result::<ModelRole>(conn)
+            .map_err(Into::into)
+            .map(Into::into)
+    }
+
+    pub fn delete(conn: &PgConnection, id: &Uuid) -> DriverResult<usize> {
+        diesel::delete(sso_role::table.filter(sso_role::dsl::id.eq(id)))
+            .execute(conn)
+            .map_err(Into::into)
+    }
+
+    /// Assign role to user, does nothing if role is already assigned.
+    pub fn user_create(conn: &PgConnection, user_role: &UserRole) -> DriverResult<usize> {
+        let now = Utc::now();
+        let value = ModelUserRoleInsert {
+            created_at: &now,
+            user_id: &user_role.user_id,
+            role_id: &user_role.role_id,
+        };
+        diesel::insert_into(sso_user_role::table)
+            .values(&value)
+            .on_conflict_do_nothing()
+            .execute(conn)
+            .map_err(Into::into)
+    }
+
+    /// Remove role from user.
+    pub fn user_delete(conn: &PgConnection, user_role: &UserRole) -> DriverResult<usize> {
+        diesel::delete(
+            sso_user_role::table.filter(
+                sso_user_role::dsl::user_id
+                    .eq(user_role.user_id)
+                    .and(sso_user_role::dsl::role_id.eq(user_role.role_id)),
+            ),
+        )
+        .execute(conn)
+        .map_err(Into::into)
+    }
+
+    /// Returns sorted names of roles assigned to user for service, including
+    /// roles assigned to groups of which user is an effective member.
+    pub fn user_names(
+        conn: &PgConnection,
+        service_id: &Uuid,
+        user_id: &Uuid,
+    ) -> DriverResult<Vec<String>> {
+        diesel::sql_query(include_str!("role_read_user_names.sql"))
+            .bind::<sql_types::Uuid, _>(service_id)
+            .bind::<sql_types::Uuid, _>(user_id)
+            .load::<ModelRoleName>(conn)
+            .map_err(Into::into)
+            .map(|x| x.into_iter().map(|x| x.name).collect())
+    }
+
+    fn read_name(
+        conn: &PgConnection,
+        service_id: &Uuid,
+        name: &str,
+    ) -> DriverResult<Option<ModelRole>> {
+        sso_role::table
+            .filter(
+                sso_role::dsl::service_id
+                    .eq(service_id)
+                    .and(sso_role::dsl::name.eq(name)),
+            )
+            .get_result::<ModelRole>(conn)
+            .optional()
+            .map_err(Into::into)
+    }
+}
@@ -0,0 +1,31 @@
+WITH RECURSIVE effective AS (
+  SELECT g."id", g."parent_id"
+  FROM sso_group AS g
+  INNER JOIN sso_user_group AS ug ON g."id" = ug."group_id"
+  WHERE
+    g."service_id" = $1
+    AND ug."user_id" = $2
+    AND g."is_enabled" = TRUE
+  UNION
+  SELECT g."id", g."parent_id"
+  FROM sso_group AS g
+  INNER JOIN effective AS e ON g."id" = e."parent_id"
+  WHERE g."is_enabled" = TRUE
+)
+SELECT DISTINCT r."name"
+FROM sso_role AS r
+WHERE
+  r."service_id" = $1
+  AND (
+    r."id" IN (
+      SELECT ur."role_id"
+      FROM sso_user_role AS ur
+      WHERE ur."user_id" = $2
+    )
+    OR r."id" IN (
+      SELECT gr."role_id"
+      FROM sso_group_role AS gr
+      INNER JOIN effective AS e ON gr."group_id" = e."id"
+    )
+  )
+ORDER BY r."name" ASC;
@@ -1,9 +1,11 @@
 use crate::{
-    schema::sso_service, DriverResult, Service, ServiceCreate, ServiceList, ServiceListQuery,
+    driver::postgres::model::ModelOrganisation, schema::sso_service, Cidr, DriverError,
+    DriverResult, OrganisationRead, Service, ServiceCreate, ServiceList, ServiceListQuery,
     ServiceRead, ServiceUpdate,
 };
 use chrono::{DateTime, Utc};
 use diesel::prelude::*;
+use std::str::FromStr;
 use uuid::Uuid;
 
 #[derive(Debug, Identifiable, Queryable)]
@@ -21,6 +23,23 @@ pub struct ModelService {
     provider_local_url: Option<String>,
     provider_github_oauth2_url: Option<String>,
     provider_microsoft_oauth2_url: Option<String>,
+    allow_remote: Vec<String>,
+    user_claims: Vec<String>,
+    organisation_id: Option<Uuid>,
+    user_require_email_verified: bool,
+}
+
+impl ModelService {
+    fn allow_remote_from_model(allow_remote: &[String]) -> Vec<Cidr> {
+        allow_remote
+            .iter()
+            .filter_map(|x| Cidr::from_str(x).ok())
+            .collect()
+    }
+
+    fn allow_remote_to_model(allow_remote: &[Cidr]) -> Vec<String> {
+        allow_remote.iter().map(|x| x.to_string()).collect()
+    }
 }
 
 impl From<ModelService> for Service {
@@ -37,6 +56,10 @@ impl From<ModelService> for Service {
             provider_local_url: service.provider_local_url,
             provider_github_oauth2_url: service.provider_github_oauth2_url,
             provider_microsoft_oauth2_url: service.provider_microsoft_oauth2_url,
+            allow_remote: ModelService::allow_remote_from_model(&service.allow_remote),
+            user_claims: service.user_claims,
+            organisation_id: service.organisation_id,
+            user_require_email_verified: service.user_require_email_verified,
         }
     }
 }
@@ -55,6 +78,10 @@ struct ModelServiceInsert<'a> {
     provider_local_url: Option<&'a str>,
     provider_github_oauth2_url: Option<&'a str>,
     provider_microsoft_oauth2_url: Option<&'a str>,
+    allow_remote: Vec<String>,
+    user_claims: &'a [String],
+    organisation_id: Option<&'a Uuid>,
+    user_require_email_verified: bool,
 }
 
 #[derive(AsChangeset)]
@@ -69,6 +96,9 @@ struct ModelServiceUpdate<'a> {
     provider_local_url: Option<&'a str>,
     provider_github_oauth2_url: Option<&'a str>,
     provider_microsoft_oauth2_url: Option<&'a str>,
+    allow_remote: Option<Vec<String>>,
+    user_claims: Option<&'a [String]>,
+    user_require_email_verified: Option<bool>,
 }
 
 impl ModelService {
@@ -76,6 +106,7 @@ impl ModelService {
         conn: &PgConnection,
         list: &ServiceList,
         service_id: Option<Uuid>,
+        organisation_id: Option<Uuid>,
     ) -> DriverResult<Vec<Service>> {
         use diesel::dsl::any;
 
@@ -88,9 +119,16 @@ impl ModelService {
         if let Some(is_enabled) = list.filter.is_enabled {
             query = query.filter(sso_service::dsl::is_enabled.eq(is_enabled));
         }
+        if let Some(organisation_id) = &list.filter.organisation_id {
+            let organisation_id: Vec<Uuid> = organisation_id.iter().copied().collect();
+            query = query.filter(sso_service::dsl::organisation_id.eq(any(organisation_id)));
+        }
         if let Some(service_id) = service_id {
             query = query.filter(sso_service::dsl::id.eq(service_id));
         }
+        if let Some(organisation_id) = organisation_id {
+            query = query.filter(sso_service::dsl::organisation_id.eq(organisation_id));
+        }
 
         match list.query {
             ServiceListQuery::Limit => query
@@ -121,6 +159,11 @@ impl ModelService {
     }
 
     pub fn create(conn: &PgConnection, create: &ServiceCreate) -> DriverResult<Service> {
+        if let Some(organisation_id) = &create.organisation_id {
+            ModelOrganisation::read(conn, &OrganisationRead::new(*organisation_id))?
+                .ok_or_else(|| DriverError::OrganisationNotFound)?;
+        }
+
         let now = Utc::now();
         let id = Uuid::new_v4();
         let value = ModelServiceInsert {
@@ -138,6 +181,10 @@ impl ModelService {
                 .provider_microsoft_oauth2_url
                 .as_ref()
                 .map(|x| &**x),
+            allow_remote: Self::allow_remote_to_model(&create.allow_remote),
+            user_claims: &create.user_claims,
+            organisation_id: create.organisation_id.as_ref(),
+            user_require_email_verified: create.user_require_email_verified,
         };
         diesel::insert_into(sso_service::table)
             .values(value)
@@ -183,6 +230,12 @@ impl ModelService {
                 .provider_microsoft_oauth2_url
                 .as_ref()
                 .map(|x| &**x),
+            allow_remote: update
+                .allow_remote
+                .as_ref()
+                .map(|x| Self::allow_remote_to_model(x)),
+            user_claims: update.user_claims.as_ref().map(|x| &**x),
+            user_require_email_verified: update.user_require_email_verified,
         };
         diesel::update(sso_service::table.filter(sso_service::dsl::id.eq(update.id)))
             .set(value)
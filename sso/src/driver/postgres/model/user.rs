@@ -1,9 +1,13 @@
 use crate::{
-    schema::sso_user, DriverError, DriverResult, User, UserCreate, UserList, UserListFilter,
-    UserListQuery, UserRead, UserUpdate,
+    driver::postgres::model::{ModelAudit, ModelKey, ModelOrganisation, ModelUserService},
+    schema::sso_user,
+    DriverError, DriverResult, KeyCreate, KeySecret, KeyType, OrganisationRead, User, UserCreate,
+    UserExport, UserInvite, UserList, UserListFilter, UserListQuery, UserRead, UserUpdate,
+    UserWithInvite,
 };
 use chrono::{DateTime, Utc};
 use diesel::{pg::Pg, prelude::*};
+use serde_json::Value;
 use std::convert::TryInto;
 use uuid::Uuid;
 
@@ -22,6 +26,12 @@ pub struct ModelUser {
     password_allow_reset: bool,
     password_require_update: bool,
     password_hash: Option<String>,
+    last_login_at: Option<DateTime<Utc>>,
+    last_login_remote: Option<String>,
+    attributes: Value,
+    organisation_id: Option<Uuid>,
+    email_verified_at: Option<DateTime<Utc>>,
+    email_pending: Option<String>,
 }
 
 impl From<ModelUser> for User {
@@ -38,6 +48,12 @@ impl From<ModelUser> for User {
             password_allow_reset: user.password_allow_reset,
             password_require_update: user.password_require_update,
             password_hash: user.password_hash,
+            last_login_at: user.last_login_at,
+            last_login_remote: user.last_login_remote,
+            attributes: user.attributes,
+            organisation_id: user.organisation_id,
+            email_verified_at: user.email_verified_at,
+            email_pending: user.email_pending,
         }
     }
 }
@@ -56,6 +72,8 @@ struct ModelUserInsert<'a> {
     password_allow_reset: bool,
     password_require_update: bool,
     password_hash: Option<&'a str>,
+    attributes: &'a Value,
+    organisation_id: Option<&'a Uuid>,
 }
 
 #[derive(AsChangeset)]
@@ -70,28 +88,40 @@ struct ModelUserUpdate<'a> {
     password_allow_reset: Option<bool>,
     password_require_update: Option<bool>,
     password_hash: Option<&'a str>,
+    attributes: Option<&'a Value>,
+    email_verified_at: Option<Option<&'a DateTime<Utc>>>,
 }
 
 impl ModelUser {
-    pub fn list(conn: &PgConnection, list: &UserList) -> DriverResult<Vec<User>> {
+    pub fn list(
+        conn: &PgConnection,
+        list: &UserList,
+        organisation_id: Option<Uuid>,
+    ) -> DriverResult<Vec<User>> {
+        let organisation_id = organisation_id.as_ref();
         match &list.query {
-            UserListQuery::IdGt(gt) => Self::list_where_id_gt(conn, &gt, &list.filter),
-            UserListQuery::IdLt(lt) => Self::list_where_id_lt(conn, &lt, &list.filter),
+            UserListQuery::IdGt(gt) => {
+                Self::list_where_id_gt(conn, &gt, &list.filter, organisation_id)
+            }
+            UserListQuery::IdLt(lt) => {
+                Self::list_where_id_lt(conn, &lt, &list.filter, organisation_id)
+            }
             UserListQuery::NameGe(name_ge, offset_id) => {
-                Self::list_where_name_ge(conn, &name_ge, &offset_id, &list.filter)
+                Self::list_where_name_ge(conn, &name_ge, &offset_id, &list.filter, organisation_id)
             }
             UserListQuery::NameLe(name_le, offset_id) => {
-                Self::list_where_name_le(conn, &name_le, &offset_id, &list.filter)
+                Self::list_where_name_le(conn, &name_le, &offset_id, &list.filter, organisation_id)
             }
         }
         .map(|x| x.into_iter().map(|x| x.into()).collect())
     }
 
     pub fn create(conn: &PgConnection, create: &UserCreate) -> DriverResult<User> {
-        let user = Self::read_email(conn, &create.email)?;
-        if user.is_some() {
-            return Err(DriverError::UserEmailConstraint);
+        if let Some(organisation_id) = &create.organisation_id {
+            ModelOrganisation::read(conn, &OrganisationRead::new(*organisation_id))?
+                .ok_or_else(|| DriverError::OrganisationNotFound)?;
         }
+        Self::email_check(conn, &create.email, create.organisation_id.as_ref())?;
 
         let now = Utc::now();
         let id = Uuid::new_v4();
@@ -107,6 +137,8 @@ impl ModelUser {
             password_allow_reset: create.password_allow_reset,
             password_require_update: create.password_require_update,
             password_hash: create.password_hash.as_ref().map(|x| &**x),
+            attributes: &create.attributes,
+            organisation_id: create.organisation_id.as_ref(),
         };
         diesel::insert_into(sso_user::table)
             .values(&value)
@@ -115,10 +147,38 @@ impl ModelUser {
             .map(Into::into)
     }
 
+    /// Create user with invited membership of service and token key for service.
+    pub fn invite(
+        conn: &PgConnection,
+        key_secret: &KeySecret,
+        invite: &UserInvite,
+    ) -> DriverResult<UserWithInvite> {
+        conn.transaction(|| {
+            let user = Self::create(conn, &invite.user)?;
+            // Invited membership is created before key so it is not made active.
+            let service_user = ModelUserService::status_invite(
+                conn,
+                &invite.service_id,
+                &user.id,
+                &invite.invite_expires_at,
+            )?;
+            let key_create =
+                KeyCreate::user(true, KeyType::Token, &user.name, invite.service_id, user.id);
+            let key = ModelKey::create(conn, key_secret, &key_create)?;
+            Ok(UserWithInvite {
+                user,
+                key,
+                service_user,
+            })
+        })
+    }
+
     pub fn read(conn: &PgConnection, read: &UserRead) -> DriverResult<Option<User>> {
         match read {
             UserRead::Id(id) => Self::read_id(conn, id),
-            UserRead::Email(email) => Self::read_email(conn, email),
+            UserRead::Email(email, organisation_id) => {
+                Self::read_email(conn, email, organisation_id.as_ref())
+            }
         }
         .map(|r| r.map(|u| u.into()))
     }
@@ -135,6 +195,9 @@ impl ModelUser {
             password_allow_reset: update.password_allow_reset,
             password_require_update: update.password_require_update,
             password_hash: update.password_hash.as_ref().map(|x| &**x),
+            attributes: update.attributes.as_ref(),
+            // Changing email address clears verified state.
+            email_verified_at: update.email.as_ref().map(|_| None),
         };
         diesel::update(sso_user::table.filter(sso_user::dsl::id.eq(update.id)))
             .set(&value)
@@ -143,19 +206,126 @@ impl ModelUser {
             .map(Into::into)
     }
 
+    /// Mark email address of user as verified.
+    pub fn email_verify(conn: &PgConnection, id: &Uuid) -> DriverResult<User> {
+        let now = Utc::now();
+        diesel::update(sso_user::table.filter(sso_user::dsl::id.eq(id)))
+            .set((
+                sso_user::dsl::updated_at.eq(&now),
+                sso_user::dsl::email_verified_at.eq(&now),
+            ))
+            .get_result::<ModelUser>(conn)
+            .map_err(Into::into)
+            .map(Into::into)
+    }
+
+    /// Update pending email address of user, returns error if address is
+    /// already used by another user in organisation.
+    pub fn email_pending_update(
+        conn: &PgConnection,
+        id: &Uuid,
+        email_pending: Option<&str>,
+    ) -> DriverResult<User> {
+        conn.transaction(|| {
+            let user = Self::read_id(conn, id)?.ok_or_else(|| DriverError::UserNotFound)?;
+            if let Some(email_pending) = email_pending {
+                Self::email_check(conn, email_pending, user.organisation_id.as_ref())?;
+            }
+            let now = Utc::now();
+            diesel::update(sso_user::table.filter(sso_user::dsl::id.eq(id)))
+                .set((
+                    sso_user::dsl::updated_at.eq(&now),
+                    sso_user::dsl::email_pending.eq(email_pending),
+                ))
+                .get_result::<ModelUser>(conn)
+                .map_err(Into::into)
+                .map(Into::into)
+        })
+    }
+
+    /// Confirm pending email address of user, email address is replaced and
+    /// marked as verified.
+    pub fn email_pending_confirm(conn: &PgConnection, id: &Uuid) -> DriverResult<User> {
+        conn.transaction(|| {
+            let user = Self::read_id(conn, id)?.ok_or_else(|| DriverError::UserNotFound)?;
+            let email_pending = user
+                .email_pending
+                .ok_or_else(|| DriverError::UserEmailNotPending)?;
+            // Address may have been taken since change was requested.
+            Self::email_check(conn, &email_pending, user.organisation_id.as_ref())?;
+            let now = Utc::now();
+            diesel::update(sso_user::table.filter(sso_user::dsl::id.eq(id)))
+                .set((
+                    sso_user::dsl::updated_at.eq(&now),
+                    sso_user::dsl::email.eq(&email_pending),
+                    sso_user::dsl::email_verified_at.eq(&now),
+                    sso_user::dsl::email_pending.eq(None::<String>),
+                ))
+                .get_result::<ModelUser>(conn)
+                .map_err(Into::into)
+                .map(Into::into)
+        })
+    }
+
+    /// Update last login time and remote of user.
+    pub fn last_login_update(conn: &PgConnection, id: &Uuid, remote: &str) -> DriverResult<usize> {
+        diesel::update(sso_user::table.filter(sso_user::dsl::id.eq(id)))
+            .set((
+                sso_user::dsl::last_login_at.eq(Utc::now()),
+                sso_user::dsl::last_login_remote.eq(remote),
+            ))
+            .execute(conn)
+            .map_err(Into::into)
+    }
+
     pub fn delete(conn: &PgConnection, id: &Uuid) -> DriverResult<usize> {
         diesel::delete(sso_user::table.filter(sso_user::dsl::id.eq(id)))
             .execute(conn)
             .map_err(Into::into)
     }
 
+    /// Export user record, keys, service memberships and audit logs.
+    pub fn export(conn: &PgConnection, id: &Uuid) -> DriverResult<Option<UserExport>> {
+        let user: User = match Self::read_id(conn, id)? {
+            Some(user) => user.into(),
+            None => return Ok(None),
+        };
+        let keys = ModelKey::list_user(conn, id)?;
+        let key_id: Vec<Uuid> = keys.iter().map(|x| x.id).collect();
+        let (services, service_attributes) = ModelUserService::list_user(conn, id)?;
+        let audit = ModelAudit::list_user(conn, id, &key_id)?;
+        Ok(Some(UserExport {
+            user,
+            keys,
+            services,
+            service_attributes,
+            audit,
+        }))
+    }
+
+    /// Erase user, audit logs referencing user or keys of user are
+    /// pseudonymised, then keys and user are deleted.
+    pub fn erase(conn: &PgConnection, key_secret: &KeySecret, id: &Uuid) -> DriverResult<usize> {
+        conn.transaction(|| {
+            let key_id: Vec<Uuid> = ModelKey::list_user(conn, id)?
+                .into_iter()
+                .map(|x| x.id)
+                .collect();
+            let pseudonym = Uuid::new_v4().to_string();
+            ModelAudit::erase_user(conn, key_secret, id, &key_id, &pseudonym)?;
+            ModelKey::delete_user(conn, id)?;
+            Self::delete(conn, id)
+        })
+    }
+
     fn list_where_id_gt(
         conn: &PgConnection,
         gt: &Uuid,
         filter: &UserListFilter,
+        organisation_id: Option<&Uuid>,
     ) -> DriverResult<Vec<ModelUser>> {
         let mut query = sso_user::table.into_boxed();
-        query = Self::boxed_query_filter(query, filter);
+        query = Self::boxed_query_filter(query, filter, organisation_id);
 
         query
             .filter(sso_user::dsl::id.gt(gt))
@@ -169,9 +339,10 @@ impl ModelUser {
         conn: &PgConnection,
         lt: &Uuid,
         filter: &UserListFilter,
+        organisation_id: Option<&Uuid>,
     ) -> DriverResult<Vec<ModelUser>> {
         let mut query = sso_user::table.into_boxed();
-        query = Self::boxed_query_filter(query, filter);
+        query = Self::boxed_query_filter(query, filter, organisation_id);
 
         query
             .filter(sso_user::dsl::id.lt(lt))
@@ -190,19 +361,28 @@ impl ModelUser {
         name_ge: &str,
         offset_id: &Option<Uuid>,
         filter: &UserListFilter,
+        organisation_id: Option<&Uuid>,
     ) -> DriverResult<Vec<ModelUser>> {
         let offset: i64 = if offset_id.is_some() { 1 } else { 0 };
-        Self::list_where_name_ge_inner(conn, name_ge, offset, filter).and_then(|res| {
-            if let Some(offset_id) = offset_id {
-                for (i, user) in res.iter().enumerate() {
-                    if &user.id == offset_id {
-                        let offset: i64 = (i + 1).try_into().unwrap();
-                        return Self::list_where_name_ge_inner(conn, name_ge, offset, filter);
+        Self::list_where_name_ge_inner(conn, name_ge, offset, filter, organisation_id).and_then(
+            |res| {
+                if let Some(offset_id) = offset_id {
+                    for (i, user) in res.iter().enumerate() {
+                        if &user.id == offset_id {
+                            let offset: i64 = (i + 1).try_into().unwrap();
+                            return Self::list_where_name_ge_inner(
+                                conn,
+                                name_ge,
+                                offset,
+                                filter,
+                                organisation_id,
+                            );
+                        }
                     }
                 }
-            }
-            Ok(res)
-        })
+                Ok(res)
+            },
+        )
     }
 
     fn list_where_name_ge_inner(
@@ -210,9 +390,10 @@ impl ModelUser {
         name_ge: &str,
         offset: i64,
         filter: &UserListFilter,
+        organisation_id: Option<&Uuid>,
     ) -> DriverResult<Vec<ModelUser>> {
         let mut query = sso_user::table.into_boxed();
-        query = Self::boxed_query_filter(query, filter);
+        query = Self::boxed_query_filter(query, filter, organisation_id);
 
         query
             .filter(sso_user::dsl::name.ge(name_ge))
@@ -228,20 +409,29 @@ impl ModelUser {
         name_le: &str,
         offset_id: &Option<Uuid>,
         filter: &UserListFilter,
+        organisation_id: Option<&Uuid>,
     ) -> DriverResult<Vec<ModelUser>> {
         let offset: i64 = if offset_id.is_some() { 1 } else { 0 };
-        Self::list_where_name_le_inner(conn, name_le, offset, filter).and_then(|mut res| {
-            if let Some(offset_id) = offset_id {
-                for (i, user) in res.iter().enumerate() {
-                    if &user.id == offset_id {
-                        let offset: i64 = (i + 1).try_into().unwrap();
-                        return Self::list_where_name_le_inner(conn, name_le, offset, filter);
+        Self::list_where_name_le_inner(conn, name_le, offset, filter, organisation_id).and_then(
+            |mut res| {
+                if let Some(offset_id) = offset_id {
+                    for (i, user) in res.iter().enumerate() {
+                        if &user.id == offset_id {
+                            let offset: i64 = (i + 1).try_into().unwrap();
+                            return Self::list_where_name_le_inner(
+                                conn,
+                                name_le,
+                                offset,
+                                filter,
+                                organisation_id,
+                            );
+                        }
                     }
                 }
-            }
-            res.reverse();
-            Ok(res)
-        })
+                res.reverse();
+                Ok(res)
+            },
+        )
     }
 
     fn list_where_name_le_inner(
@@ -249,9 +439,10 @@ impl ModelUser {
         name_le: &str,
         offset: i64,
         filter: &UserListFilter,
+        organisation_id: Option<&Uuid>,
     ) -> DriverResult<Vec<ModelUser>> {
         let mut query = sso_user::table.into_boxed();
-        query = Self::boxed_query_filter(query, filter);
+        query = Self::boxed_query_filter(query, filter, organisation_id);
 
         query
             .filter(sso_user::dsl::name.le(name_le))
@@ -270,17 +461,41 @@ impl ModelUser {
             .map_err(DriverError::DieselResult)
     }
 
-    fn read_email(conn: &PgConnection, email: &str) -> DriverResult<Option<ModelUser>> {
-        sso_user::table
+    fn read_email(
+        conn: &PgConnection,
+        email: &str,
+        organisation_id: Option<&Uuid>,
+    ) -> DriverResult<Option<ModelUser>> {
+        let query = sso_user::table
             .filter(sso_user::dsl::email.eq(email))
+            .into_boxed();
+        let query = match organisation_id {
+            Some(organisation_id) => {
+                query.filter(sso_user::dsl::organisation_id.eq(organisation_id))
+            }
+            None => query.filter(sso_user::dsl::organisation_id.is_null()),
+        };
+        query
             .get_result::<ModelUser>(conn)
             .optional()
             .map_err(DriverError::DieselResult)
     }
 
+    fn email_check(
+        conn: &PgConnection,
+        email: &str,
+        organisation_id: Option<&Uuid>,
+    ) -> DriverResult<()> {
+        match Self::read_email(conn, email, organisation_id)? {
+            Some(_) => Err(DriverError::UserEmailConstraint),
+            None => Ok(()),
+        }
+    }
+
     fn boxed_query_filter<'a>(
         mut query: sso_user::BoxedQuery<'a, Pg>,
         filter: &'a UserListFilter,
+        organisation_id_mask: Option<&'a Uuid>,
     ) -> sso_user::BoxedQuery<'a, Pg> {
         use diesel::dsl::any;
 
@@ -292,6 +507,22 @@ impl ModelUser {
             let email: Vec<String> = email.to_vec();
             query = query.filter(sso_user::dsl::email.eq(any(email)));
         }
+        if let Some(last_login_lt) = filter.last_login_lt {
+            query = query.filter(
+                sso_user::dsl::last_login_at
+                    .lt(last_login_lt)
+                    .or(sso_user::dsl::last_login_at
+                        .is_null()
+                        .and(sso_user::dsl::created_at.lt(last_login_lt))),
+            );
+        }
+        if let Some(organisation_id) = &filter.organisation_id {
+            let organisation_id: Vec<Uuid> = organisation_id.iter().copied().collect();
+            query = query.filter(sso_user::dsl::organisation_id.eq(any(organisation_id)));
+        }
+        if let Some(organisation_id_mask) = organisation_id_mask {
+            query = query.filter(sso_user::dsl::organisation_id.eq(organisation_id_mask));
+        }
 
         query
     }
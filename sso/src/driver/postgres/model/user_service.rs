@@ -0,0 +1,300 @@
+use crate::{
+    schema::sso_user_service, DriverResult, ServiceUser, ServiceUserList, ServiceUserListQuery,
+    ServiceUserStatus, ServiceUserUpdate,
+};
+use chrono::{DateTime, Utc};
+use diesel::{prelude::*, sql_types};
+use serde_json::Value;
+use std::str::FromStr;
+use uuid::Uuid;
+
+#[derive(Debug, Identifiable, Queryable)]
+#[table_name = "sso_user_service"]
+#[primary_key(user_id, service_id)]
+pub struct ModelUserService {
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    user_id: Uuid,
+    service_id: Uuid,
+    attributes: Value,
+    status: Option<String>,
+    invite_expires_at: Option<DateTime<Utc>>,
+}
+
+impl ModelUserService {
+    /// Returns service user if user has a membership status.
+    fn into_service_user(self) -> Option<ServiceUser> {
+        let status = self.status?;
+        Some(ServiceUser {
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            service_id: self.service_id,
+            user_id: self.user_id,
+            status: ServiceUserStatus::from_str(&status).unwrap(),
+            invite_expires_at: self.invite_expires_at,
+        })
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "sso_user_service"]
+struct ModelUserServiceInsert<'a> {
+    created_at: &'a DateTime<Utc>,
+    updated_at: &'a DateTime<Utc>,
+    user_id: &'a Uuid,
+    service_id: &'a Uuid,
+    attributes: &'a Value,
+}
+
+impl ModelUserService {
+    /// Read attributes of user for service, returns an empty object if none are set.
+    pub fn attributes_read(
+        conn: &PgConnection,
+        user_id: &Uuid,
+        service_id: &Uuid,
+    ) -> DriverResult<Value> {
+        sso_user_service::table
+            .select(sso_user_service::dsl::attributes)
+            .filter(
+                sso_user_service::dsl::user_id
+                    .eq(user_id)
+                    .and(sso_user_service::dsl::service_id.eq(service_id)),
+            )
+            .get_result::<Value>(conn)
+            .optional()
+            .map_err(Into::into)
+            .map(|x| x.unwrap_or_else(|| json!({})))
+    }
+
+    /// Create or replace attributes of user for service.
+    pub fn attributes_update(
+        conn: &PgConnection,
+        user_id: &Uuid,
+        service_id: &Uuid,
+        attributes: &Value,
+    ) -> DriverResult<Value> {
+        let now = Utc::now();
+        let value = ModelUserServiceInsert {
+            created_at: &now,
+            updated_at: &now,
+            user_id,
+            service_id,
+            attributes,
+        };
+        diesel::insert_into(sso_user_service::table)
+            .values(&value)
+            .on_conflict((
+                sso_user_service::dsl::user_id,
+                sso_user_service::dsl::service_id,
+            ))
+            .do_update()
+            .set((
+                sso_user_service::dsl::updated_at.eq(&now),
+                sso_user_service::dsl::attributes.eq(attributes),
+            ))
+            .returning(sso_user_service::dsl::attributes)
+            .get_result::<Value>(conn)
+            .map_err(Into::into)
+    }
+
+    /// List users of service with a membership status.
+    pub fn status_list(
+        conn: &PgConnection,
+        list: &ServiceUserList,
+    ) -> DriverResult<Vec<ServiceUser>> {
+        use diesel::dsl::any;
+
+        let mut query = sso_user_service::table
+            .filter(
+                sso_user_service::dsl::service_id
+                    .eq(list.filter.service_id)
+                    .and(sso_user_service::dsl::status.is_not_null()),
+            )
+            .into_boxed();
+
+        if let Some(status) = &list.filter.status {
+            let status: Vec<String> = status.iter().map(|x| x.to_string()).collect();
+            query = query.filter(sso_user_service::dsl::status.eq(any(status)));
+        }
+
+        match list.query {
+            ServiceUserListQuery::Limit => query
+                .filter(sso_user_service::dsl::user_id.gt(Uuid::nil()))
+                .limit(list.filter.limit)
+                .order(sso_user_service::dsl::user_id.asc())
+                .load::<ModelUserService>(conn)
+                .map_err(Into::into)
+                .map(|x| {
+                    x.into_iter()
+                        .filter_map(|x| x.into_service_user())
+                        .collect()
+                }),
+            ServiceUserListQuery::IdGt(gt) => query
+                .filter(sso_user_service::dsl::user_id.gt(gt))
+                .limit(list.filter.limit)
+                .order(sso_user_service::dsl::user_id.asc())
+                .load::<ModelUserService>(conn)
+                .map_err(Into::into)
+                .map(|x| {
+                    x.into_iter()
+                        .filter_map(|x| x.into_service_user())
+                        .collect()
+                }),
+            ServiceUserListQuery::IdLt(lt) => query
+                .filter(sso_user_service::dsl::user_id.lt(lt))
+                .limit(list.filter.limit)
+                .order(sso_user_service::dsl::user_id.desc())
+                .load::<ModelUserService>(conn)
+                .map_err(Into::into)
+                .map(|mut x| {
+                    x.reverse();
+                    x.into_iter()
+                        .filter_map(|x| x.into_service_user())
+                        .collect()
+                }),
+        }
+    }
+
+    /// List membership statuses and attributes of user for all services.
+    pub fn list_user(
+        conn: &PgConnection,
+        user_id: &Uuid,
+    ) -> DriverResult<(Vec<ServiceUser>, Vec<(Uuid, Value)>)> {
+        let rows = sso_user_service::table
+            .filter(sso_user_service::dsl::user_id.eq(user_id))
+            .order(sso_user_service::dsl::service_id.asc())
+            .load::<ModelUserService>(conn)?;
+
+        let attributes = rows
+            .iter()
+            .map(|x| (x.service_id, x.attributes.clone()))
+            .collect();
+        let services = rows
+            .into_iter()
+            .filter_map(|x| x.into_service_user())
+            .collect();
+        Ok((services, attributes))
+    }
+
+    /// Read membership status of user for service.
+    pub fn status_read(
+        conn: &PgConnection,
+        service_id: &Uuid,
+        user_id: &Uuid,
+    ) -> DriverResult<Option<ServiceUser>> {
+        sso_user_service::table
+            .filter(
+                sso_user_service::dsl::user_id
+                    .eq(user_id)
+                    .and(sso_user_service::dsl::service_id.eq(service_id)),
+            )
+            .get_result::<ModelUserService>(conn)
+            .optional()
+            .map_err(Into::into)
+            .map(|x| x.and_then(|x| x.into_service_user()))
+    }
+
+    /// Create or replace membership status of user for service, clears
+    /// invitation expiry time.
+    pub fn status_update(
+        conn: &PgConnection,
+        update: &ServiceUserUpdate,
+    ) -> DriverResult<ServiceUser> {
+        let now = Utc::now();
+        let status = update.status.to_string();
+        diesel::insert_into(sso_user_service::table)
+            .values((
+                sso_user_service::dsl::created_at.eq(&now),
+                sso_user_service::dsl::updated_at.eq(&now),
+                sso_user_service::dsl::user_id.eq(&update.user_id),
+                sso_user_service::dsl::service_id.eq(&update.service_id),
+                sso_user_service::dsl::status.eq(&status),
+            ))
+            .on_conflict((
+                sso_user_service::dsl::user_id,
+                sso_user_service::dsl::service_id,
+            ))
+            .do_update()
+            .set((
+                sso_user_service::dsl::updated_at.eq(&now),
+                sso_user_service::dsl::status.eq(&status),
+                sso_user_service::dsl::invite_expires_at.eq(None::<DateTime<Utc>>),
+            ))
+            .get_result::<ModelUserService>(conn)
+            .map_err(Into::into)
+            .map(|x| x.into_service_user().unwrap())
+    }
+
+    /// Create or replace invited membership of user for service with expiry time.
+    pub fn status_invite(
+        conn: &PgConnection,
+        service_id: &Uuid,
+        user_id: &Uuid,
+        invite_expires_at: &DateTime<Utc>,
+    ) -> DriverResult<ServiceUser> {
+        let now = Utc::now();
+        let status = ServiceUserStatus::Invited.to_string();
+        diesel::insert_into(sso_user_service::table)
+            .values((
+                sso_user_service::dsl::created_at.eq(&now),
+                sso_user_service::dsl::updated_at.eq(&now),
+                sso_user_service::dsl::user_id.eq(user_id),
+                sso_user_service::dsl::service_id.eq(service_id),
+                sso_user_service::dsl::status.eq(&status),
+                sso_user_service::dsl::invite_expires_at.eq(invite_expires_at),
+            ))
+            .on_conflict((
+                sso_user_service::dsl::user_id,
+                sso_user_service::dsl::service_id,
+            ))
+            .do_update()
+            .set((
+                sso_user_service::dsl::updated_at.eq(&now),
+                sso_user_service::dsl::status.eq(&status),
+                sso_user_service::dsl::invite_expires_at.eq(invite_expires_at),
+            ))
+            .get_result::<ModelUserService>(conn)
+            .map_err(Into::into)
+            .map(|x| x.into_service_user().unwrap())
+    }
+
+    /// Remove membership status of user for service, attributes are kept.
+    pub fn status_delete(
+        conn: &PgConnection,
+        service_id: &Uuid,
+        user_id: &Uuid,
+    ) -> DriverResult<usize> {
+        let now = Utc::now();
+        diesel::update(
+            sso_user_service::table.filter(
+                sso_user_service::dsl::user_id
+                    .eq(user_id)
+                    .and(sso_user_service::dsl::service_id.eq(service_id)),
+            ),
+        )
+        .set((
+            sso_user_service::dsl::updated_at.eq(&now),
+            sso_user_service::dsl::status.eq(None::<String>),
+            sso_user_service::dsl::invite_expires_at.eq(None::<DateTime<Utc>>),
+        ))
+        .execute(conn)
+        .map_err(Into::into)
+    }
+
+    /// Create active membership of user for service, does nothing if user
+    /// already has a membership status.
+    pub fn status_create_active(
+        conn: &PgConnection,
+        service_id: &Uuid,
+        user_id: &Uuid,
+    ) -> DriverResult<usize> {
+        let now = Utc::now();
+        diesel::sql_query(include_str!("user_service_status_create_active.sql"))
+            .bind::<sql_types::Timestamptz, _>(&now)
+            .bind::<sql_types::Uuid, _>(user_id)
+            .bind::<sql_types::Uuid, _>(service_id)
+            .bind::<sql_types::Text, _>(ServiceUserStatus::Active.to_string())
+            .execute(conn)
+            .map_err(Into::into)
+    }
+}
@@ -0,0 +1,4 @@
+INSERT INTO sso_user_service ("created_at", "updated_at", "user_id", "service_id", "status")
+VALUES ($1, $1, $2, $3, $4)
+ON CONFLICT ("user_id", "service_id") DO UPDATE SET "updated_at" = $1, "status" = $4
+WHERE sso_user_service."status" IS NULL;
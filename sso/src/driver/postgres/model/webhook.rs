@@ -0,0 +1,481 @@
+use crate::{
+    driver::postgres::model::ModelAudit,
+    schema::{sso_service, sso_user_service, sso_webhook, sso_webhook_delivery},
+    Audit, AuditRead, DriverError, DriverResult, KeySecret, Webhook, WebhookCreate,
+    WebhookDelivery, WebhookDeliveryList, WebhookDeliveryListQuery, WebhookDeliveryRead,
+    WebhookDeliveryResult, WebhookDeliverySend, WebhookDeliveryStatus, WebhookList,
+    WebhookListQuery, WebhookRead, WebhookUpdate, WebhookWithSecret, MAX_WEBHOOK_DELIVERY_ATTEMPTS,
+};
+use chrono::{DateTime, Duration, Utc};
+use diesel::{prelude::*, sql_types};
+use std::str::FromStr;
+use uuid::Uuid;
+
+#[derive(Debug, Identifiable, Queryable)]
+#[table_name = "sso_webhook"]
+#[primary_key(id)]
+pub struct ModelWebhook {
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    id: Uuid,
+    service_id: Uuid,
+    is_enabled: bool,
+    name: String,
+    url: String,
+    // Encrypted by key secret, decrypted when deliveries are claimed.
+    secret: String,
+    audit_type: Vec<String>,
+}
+
+impl From<ModelWebhook> for Webhook {
+    fn from(webhook: ModelWebhook) -> Self {
+        Self {
+            created_at: webhook.created_at,
+            updated_at: webhook.updated_at,
+            id: webhook.id,
+            service_id: webhook.service_id,
+            is_enabled: webhook.is_enabled,
+            name: webhook.name,
+            url: webhook.url,
+            audit_type: webhook.audit_type,
+        }
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "sso_webhook"]
+struct ModelWebhookInsert<'a> {
+    created_at: &'a DateTime<Utc>,
+    updated_at: &'a DateTime<Utc>,
+    id: &'a Uuid,
+    service_id: &'a Uuid,
+    is_enabled: bool,
+    name: &'a str,
+    url: &'a str,
+    secret: &'a str,
+    audit_type: &'a [String],
+}
+
+#[derive(AsChangeset)]
+#[table_name = "sso_webhook"]
+struct ModelWebhookUpdate<'a> {
+    updated_at: &'a DateTime<Utc>,
+    is_enabled: Option<bool>,
+    name: Option<&'a str>,
+    url: Option<&'a str>,
+    audit_type: Option<&'a [String]>,
+}
+
+#[derive(Debug, Identifiable, Queryable, QueryableByName)]
+#[table_name = "sso_webhook_delivery"]
+#[primary_key(id)]
+pub struct ModelWebhookDelivery {
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    id: Uuid,
+    webhook_id: Uuid,
+    audit_id: Uuid,
+    audit_type: String,
+    status: String,
+    attempts: i32,
+    next_attempt_at: DateTime<Utc>,
+    delivered_at: Option<DateTime<Utc>>,
+    status_code: Option<i16>,
+    error: Option<String>,
+}
+
+impl From<ModelWebhookDelivery> for WebhookDelivery {
+    fn from(delivery: ModelWebhookDelivery) -> Self {
+        Self {
+            created_at: delivery.created_at,
+            updated_at: delivery.updated_at,
+            id: delivery.id,
+            webhook_id: delivery.webhook_id,
+            audit_id: delivery.audit_id,
+            audit_type: delivery.audit_type,
+            status: WebhookDeliveryStatus::from_str(&delivery.status).unwrap(),
+            attempts: delivery.attempts,
+            next_attempt_at: delivery.next_attempt_at,
+            delivered_at: delivery.delivered_at,
+            status_code: delivery.status_code.map(|x| x as u16),
+            error: delivery.error,
+        }
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "sso_webhook_delivery"]
+struct ModelWebhookDeliveryInsert<'a> {
+    created_at: &'a DateTime<Utc>,
+    updated_at: &'a DateTime<Utc>,
+    id: &'a Uuid,
+    webhook_id: &'a Uuid,
+    audit_id: &'a Uuid,
+    audit_type: &'a str,
+    status: &'a str,
+    attempts: i32,
+    next_attempt_at: &'a DateTime<Utc>,
+}
+
+#[derive(AsChangeset)]
+#[table_name = "sso_webhook_delivery"]
+#[changeset_options(treat_none_as_null = "true")]
+struct ModelWebhookDeliveryUpdate<'a> {
+    updated_at: &'a DateTime<Utc>,
+    status: &'a str,
+    attempts: i32,
+    next_attempt_at: &'a DateTime<Utc>,
+    delivered_at: Option<&'a DateTime<Utc>>,
+    status_code: Option<i16>,
+    error: Option<&'a str>,
+}
+
+impl ModelWebhook {
+    pub fn list(
+        conn: &PgConnection,
+        list: &WebhookList,
+        service_id: Option<Uuid>,
+        organisation_id: Option<Uuid>,
+    ) -> DriverResult<Vec<Webhook>> {
+        use diesel::dsl::any;
+
+        let mut query = sso_webhook::table.into_boxed();
+
+        if let Some(id) = &list.filter.id {
+            let id: Vec<Uuid> = id.iter().copied().collect();
+            query = query.filter(sso_webhook::dsl::id.eq(any(id)));
+        }
+        if let Some(service_id) = &list.filter.service_id {
+            let service_id: Vec<Uuid> = service_id.iter().copied().collect();
+            query = query.filter(sso_webhook::dsl::service_id.eq(any(service_id)));
+        }
+        if let Some(service_id_mask) = service_id {
+            query = query.filter(sso_webhook::dsl::service_id.eq(service_id_mask));
+        }
+        if let Some(organisation_id_mask) = organisation_id {
+            query = query.filter(
+                sso_webhook::dsl::service_id.eq_any(
+                    sso_service::table
+                        .select(sso_service::dsl::id)
+                        .filter(sso_service::dsl::organisation_id.eq(organisation_id_mask)),
+                ),
+            );
+        }
+
+        match list.query {
+            WebhookListQuery::Limit => query
+                .filter(sso_webhook::dsl::id.gt(Uuid::nil()))
+                .limit(list.filter.limit)
+                .order(sso_webhook::dsl::id.asc())
+                .load::<ModelWebhook>(conn)
+                .map_err(Into::into)
+                .map(|x| x.into_iter().map(|x| x.into()).collect()),
+            WebhookListQuery::IdGt(gt) => query
+                .filter(sso_webhook::dsl::id.gt(gt))
+                .limit(list.filter.limit)
+                .order(sso_webhook::dsl::id.asc())
+                .load::<ModelWebhook>(conn)
+                .map_err(Into::into)
+                .map(|x| x.into_iter().map(|x| x.into()).collect()),
+            WebhookListQuery::IdLt(lt) => query
+                .filter(sso_webhook::dsl::id.lt(lt))
+                .limit(list.filter.limit)
+                .order(sso_webhook::dsl::id.desc())
+                .load::<ModelWebhook>(conn)
+                .map_err(Into::into)
+                .map(|mut x| {
+                    x.reverse();
+                    x.into_iter().map(|x| x.into()).collect()
+                }),
+        }
+    }
+
+    pub fn create(
+        conn: &PgConnection,
+        key_secret: &KeySecret,
+        create: &WebhookCreate,
+    ) -> DriverResult<WebhookWithSecret> {
+        let now = Utc::now();
+        let id = Uuid::new_v4();
+        let secret = Webhook::secret_generate();
+        let secret_encrypted = key_secret.secret_encrypt(&secret)?;
+        let value = ModelWebhookInsert {
+            created_at: &now,
+            updated_at: &now,
+            id: &id,
+            service_id: &create.service_id,
+            is_enabled: create.is_enabled,
+            name: &create.name,
+            url: &create.url,
+            secret: &secret_encrypted,
+            audit_type: &create.audit_type,
+        };
+        diesel::insert_into(sso_webhook::table)
+            .values(&value)
+            .get_result::<ModelWebhook>(conn)
+            .map_err(Into::into)
+            .map(|webhook| WebhookWithSecret {
+                webhook: webhook.into(),
+                secret,
+            })
+    }
+
+    pub fn read(
+        conn: &PgConnection,
+        read: &WebhookRead,
+        service_id: Option<Uuid>,
+    ) -> DriverResult<Option<Webhook>> {
+        let mut query = sso_webhook::table
+            .filter(sso_webhook::dsl::id.eq(read.id))
+            .into_boxed();
+        if let Some(service_id_mask) = service_id {
+            query = query.filter(sso_webhook::dsl::service_id.eq(service_id_mask));
+        }
+        query
+            .get_result::<ModelWebhook>(conn)
+            .optional()
+            .map_err(Into::into)
+            .map(|x| x.map(Into::into))
+    }
+
+    pub fn update(conn: &PgConnection, update: &WebhookUpdate) -> DriverResult<Webhook> {
+        let now = Utc::now();
+        let value = ModelWebhookUpdate {
+            updated_at: &now,
+            is_enabled: update.is_enabled,
+            name: update.name.as_ref().map(|x| &**x),
+            url: update.url.as_ref().map(|x| &**x),
+            audit_type: update.audit_type.as_ref().map(|x| &**x),
+        };
+        diesel::update(sso_webhook::table.filter(sso_webhook::dsl::id.eq(update.id)))
+            .set(&value)
+            .get_result::<ModelWebhook>(conn)
+            .map_err(Into::into)
+            .map(Into::into)
+    }
+
+    pub fn delete(conn: &PgConnection, id: &Uuid) -> DriverResult<usize> {
+        diesel::delete(sso_webhook::table.filter(sso_webhook::dsl::id.eq(id)))
+            .execute(conn)
+            .map_err(Into::into)
+    }
+
+    /// Queue deliveries of audit log to enabled webhooks with matching type.
+    ///
+    /// Audit logs are delivered to webhooks of their service, audit logs
+    /// without a service are delivered to webhooks of services of which the
+    /// user is a member. Called in audit log create transaction.
+    pub fn delivery_enqueue(conn: &PgConnection, audit: &Audit) -> DriverResult<usize> {
+        use diesel::dsl::any;
+
+        let mut query = sso_webhook::table
+            .select(sso_webhook::dsl::id)
+            .filter(sso_webhook::dsl::is_enabled.eq(true))
+            .filter(
+                sso_webhook::dsl::audit_type
+                    .eq(Vec::<String>::new())
+                    .or(audit
+                        .type_
+                        .clone()
+                        .into_sql::<sql_types::Text>()
+                        .eq(any(sso_webhook::dsl::audit_type))),
+            )
+            .into_boxed();
+        match (audit.service_id, audit.user_id) {
+            (Some(service_id), _) => {
+                query = query.filter(sso_webhook::dsl::service_id.eq(service_id));
+            }
+            (None, Some(user_id)) => {
+                query = query.filter(
+                    sso_webhook::dsl::service_id.eq_any(
+                        sso_user_service::table
+                            .select(sso_user_service::dsl::service_id)
+                            .filter(sso_user_service::dsl::user_id.eq(user_id))
+                            .filter(sso_user_service::dsl::status.is_not_null()),
+                    ),
+                );
+            }
+            (None, None) => return Ok(0),
+        }
+        let webhook_id = query.load::<Uuid>(conn)?;
+        if webhook_id.is_empty() {
+            return Ok(0);
+        }
+
+        let now = Utc::now();
+        let status = WebhookDeliveryStatus::Pending.to_string();
+        let id: Vec<Uuid> = webhook_id.iter().map(|_| Uuid::new_v4()).collect();
+        let value: Vec<ModelWebhookDeliveryInsert> = webhook_id
+            .iter()
+            .zip(id.iter())
+            .map(|(webhook_id, id)| ModelWebhookDeliveryInsert {
+                created_at: &now,
+                updated_at: &now,
+                id,
+                webhook_id,
+                audit_id: &audit.id,
+                audit_type: &audit.type_,
+                status: &status,
+                attempts: 0,
+                next_attempt_at: &now,
+            })
+            .collect();
+        diesel::insert_into(sso_webhook_delivery::table)
+            .values(&value)
+            .execute(conn)
+            .map_err(Into::into)
+    }
+
+    pub fn delivery_list(
+        conn: &PgConnection,
+        list: &WebhookDeliveryList,
+    ) -> DriverResult<Vec<WebhookDelivery>> {
+        use diesel::dsl::any;
+
+        let mut query = sso_webhook_delivery::table
+            .filter(sso_webhook_delivery::dsl::webhook_id.eq(list.filter.webhook_id))
+            .into_boxed();
+
+        if let Some(status) = &list.filter.status {
+            let status: Vec<String> = status.iter().map(|x| x.to_string()).collect();
+            query = query.filter(sso_webhook_delivery::dsl::status.eq(any(status)));
+        }
+
+        match list.query {
+            WebhookDeliveryListQuery::Limit => query
+                .filter(sso_webhook_delivery::dsl::id.gt(Uuid::nil()))
+                .limit(list.filter.limit)
+                .order(sso_webhook_delivery::dsl::id.asc())
+                .load::<ModelWebhookDelivery>(conn)
+                .map_err(Into::into)
+                .map(|x| x.into_iter().map(|x| x.into()).collect()),
+            WebhookDeliveryListQuery::IdGt(gt) => query
+                .filter(sso_webhook_delivery::dsl::id.gt(gt))
+                .limit(list.filter.limit)
+                .order(sso_webhook_delivery::dsl::id.asc())
+                .load::<ModelWebhookDelivery>(conn)
+                .map_err(Into::into)
+                .map(|x| x.into_iter().map(|x| x.into()).collect()),
+            WebhookDeliveryListQuery::IdLt(lt) => query
+                .filter(sso_webhook_delivery::dsl::id.lt(lt))
+                .limit(list.filter.limit)
+                .order(sso_webhook_delivery::dsl::id.desc())
+                .load::<ModelWebhookDelivery>(conn)
+                .map_err(Into::into)
+                .map(|mut x| {
+                    x.reverse();
+                    x.into_iter().map(|x| x.into()).collect()
+                }),
+        }
+    }
+
+    pub fn delivery_read(
+        conn: &PgConnection,
+        read: &WebhookDeliveryRead,
+    ) -> DriverResult<Option<WebhookDelivery>> {
+        sso_webhook_delivery::table
+            .filter(sso_webhook_delivery::dsl::id.eq(read.id))
+            .get_result::<ModelWebhookDelivery>(conn)
+            .optional()
+            .map_err(Into::into)
+            .map(|x| x.map(Into::into))
+    }
+
+    /// Queue dead lettered delivery for another series of attempts.
+    pub fn delivery_retry(conn: &PgConnection, id: &Uuid) -> DriverResult<WebhookDelivery> {
+        let now = Utc::now();
+        let value = ModelWebhookDeliveryUpdate {
+            updated_at: &now,
+            status: &WebhookDeliveryStatus::Pending.to_string(),
+            attempts: 0,
+            next_attempt_at: &now,
+            delivered_at: None,
+            status_code: None,
+            error: None,
+        };
+        diesel::update(
+            sso_webhook_delivery::table.filter(sso_webhook_delivery::dsl::id.eq(id).and(
+                sso_webhook_delivery::dsl::status.eq(WebhookDeliveryStatus::Dead.to_string()),
+            )),
+        )
+        .set(&value)
+        .get_result::<ModelWebhookDelivery>(conn)
+        .optional()?
+        .ok_or_else(|| DriverError::WebhookDeliveryNotDead)
+        .map(Into::into)
+    }
+
+    /// Claim pending deliveries which are due, claimed deliveries are leased
+    /// so that they are not claimed again until lease has expired.
+    pub fn delivery_claim(
+        conn: &PgConnection,
+        key_secret: &KeySecret,
+        limit: i64,
+        lease: Duration,
+    ) -> DriverResult<Vec<WebhookDeliverySend>> {
+        let now = Utc::now();
+        let lease_expires = now + lease;
+        let deliveries = diesel::sql_query(include_str!("webhook_delivery_claim.sql"))
+            .bind::<sql_types::Timestamptz, _>(&now)
+            .bind::<sql_types::Timestamptz, _>(&lease_expires)
+            .bind::<sql_types::BigInt, _>(limit)
+            .load::<ModelWebhookDelivery>(conn)?;
+
+        let mut send = Vec::new();
+        for delivery in deliveries {
+            let webhook = sso_webhook::table
+                .filter(sso_webhook::dsl::id.eq(delivery.webhook_id))
+                .get_result::<ModelWebhook>(conn)?;
+            let secret = key_secret.secret_decrypt(&webhook.secret)?;
+            let audit = ModelAudit::read(conn, &AuditRead::new(delivery.audit_id), None, None)?;
+            send.push(WebhookDeliverySend {
+                id: delivery.id,
+                attempts: delivery.attempts,
+                url: webhook.url,
+                secret,
+                body: audit.map(|audit| WebhookDelivery::body(delivery.id, webhook.id, &audit)),
+            });
+        }
+        Ok(send)
+    }
+
+    /// Record result of delivery attempt, failed deliveries are retried after
+    /// backoff or dead lettered after maximum attempts.
+    pub fn delivery_result(
+        conn: &PgConnection,
+        result: &WebhookDeliveryResult,
+    ) -> DriverResult<usize> {
+        let delivery = sso_webhook_delivery::table
+            .filter(sso_webhook_delivery::dsl::id.eq(result.id))
+            .get_result::<ModelWebhookDelivery>(conn)?;
+
+        let now = Utc::now();
+        let attempts = delivery.attempts + 1;
+        let (status, next_attempt_at, delivered_at) = if result.is_delivered() {
+            (WebhookDeliveryStatus::Delivered, now, Some(&now))
+        } else if attempts >= MAX_WEBHOOK_DELIVERY_ATTEMPTS {
+            (WebhookDeliveryStatus::Dead, now, None)
+        } else {
+            (
+                WebhookDeliveryStatus::Pending,
+                now + WebhookDelivery::backoff(attempts),
+                None,
+            )
+        };
+        let value = ModelWebhookDeliveryUpdate {
+            updated_at: &now,
+            status: &status.to_string(),
+            attempts,
+            next_attempt_at: &next_attempt_at,
+            delivered_at,
+            status_code: result.status_code.map(|x| x as i16),
+            error: result.error.as_ref().map(|x| &**x),
+        };
+        diesel::update(
+            sso_webhook_delivery::table.filter(sso_webhook_delivery::dsl::id.eq(result.id)),
+        )
+        .set(&value)
+        .execute(conn)
+        .map_err(Into::into)
+    }
+}
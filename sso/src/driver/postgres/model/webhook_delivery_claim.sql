@@ -0,0 +1,11 @@
+UPDATE sso_webhook_delivery
+SET "updated_at" = $1, "next_attempt_at" = $2
+WHERE "id" IN (
+    SELECT d."id" FROM sso_webhook_delivery AS d
+    INNER JOIN sso_webhook AS w ON w."id" = d."webhook_id"
+    WHERE d."status" = 'Pending' AND d."next_attempt_at" <= $1 AND w."is_enabled"
+    ORDER BY d."next_attempt_at" ASC
+    LIMIT $3
+    FOR UPDATE OF d SKIP LOCKED
+)
+RETURNING *;
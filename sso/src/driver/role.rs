@@ -0,0 +1,101 @@
+use crate::{AuditDiff, AuditDiffBuilder, AuditSubject};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::fmt;
+use uuid::Uuid;
+
+/// Role name maximum length.
+pub const MAX_ROLE_NAME: usize = 100;
+
+/// Role.
+///
+/// Roles are scoped to a service and assigned to users, names are unique per service.
+#[derive(Debug, Clone)]
+pub struct Role {
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub id: Uuid,
+    pub service_id: Uuid,
+    pub name: String,
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Role {}", self.id)?;
+        write!(f, "\n\tcreated_at {}", self.created_at)?;
+        write!(f, "\n\tupdated_at {}", self.updated_at)?;
+        write!(f, "\n\tservice_id {}", self.service_id)?;
+        write!(f, "\n\tname {}", self.name)
+    }
+}
+
+impl AuditSubject for Role {
+    fn subject(&self) -> String {
+        format!("{}", self.id)
+    }
+}
+
+impl AuditDiff for Role {
+    fn diff(&self, previous: &Self) -> Value {
+        AuditDiffBuilder::default()
+            .compare("name", &self.name, &previous.name)
+            .into_value()
+    }
+}
+
+/// Role list query.
+#[derive(Debug)]
+pub enum RoleListQuery {
+    Limit,
+    IdGt(Uuid),
+    IdLt(Uuid),
+}
+
+/// Role list filter.
+#[derive(Debug)]
+pub struct RoleListFilter {
+    pub id: Option<Vec<Uuid>>,
+    pub service_id: Option<Vec<Uuid>>,
+    pub user_id: Option<Uuid>,
+    pub limit: i64,
+}
+
+/// Role list.
+#[derive(Debug)]
+pub struct RoleList {
+    pub query: RoleListQuery,
+    pub filter: RoleListFilter,
+}
+
+/// Role create.
+#[derive(Debug)]
+pub struct RoleCreate {
+    pub service_id: Uuid,
+    pub name: String,
+}
+
+/// Role read.
+#[derive(Debug)]
+pub struct RoleRead {
+    pub id: Uuid,
+}
+
+impl RoleRead {
+    pub fn new(id: Uuid) -> Self {
+        Self { id }
+    }
+}
+
+/// Role update.
+#[derive(Debug)]
+pub struct RoleUpdate {
+    pub id: Uuid,
+    pub name: Option<String>,
+}
+
+/// User role assignment.
+#[derive(Debug)]
+pub struct UserRole {
+    pub user_id: Uuid,
+    pub role_id: Uuid,
+}
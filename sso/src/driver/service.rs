@@ -1,8 +1,8 @@
-use crate::{AuditDiff, AuditDiffBuilder, AuditSubject, DriverError, DriverResult};
+use crate::{AuditDiff, AuditDiffBuilder, AuditSubject, Cidr, DriverError, DriverResult, User};
 use chrono::{DateTime, Utc};
 use serde::ser::Serialize;
-use serde_json::Value;
-use std::fmt;
+use serde_json::{Map, Value};
+use std::{fmt, net::IpAddr};
 use url::Url;
 use uuid::Uuid;
 
@@ -20,6 +20,10 @@ pub struct Service {
     pub provider_local_url: Option<String>,
     pub provider_github_oauth2_url: Option<String>,
     pub provider_microsoft_oauth2_url: Option<String>,
+    pub allow_remote: Vec<Cidr>,
+    pub user_claims: Vec<String>,
+    pub organisation_id: Option<Uuid>,
+    pub user_require_email_verified: bool,
 }
 
 impl Service {
@@ -32,6 +36,43 @@ impl Service {
         }
     }
 
+    /// Check remote address is allowed, services with an empty
+    /// allowed remote array are not restricted.
+    pub fn check_remote(&self, remote: Option<IpAddr>) -> DriverResult<()> {
+        if Cidr::vec_allows(&self.allow_remote, remote) {
+            Ok(())
+        } else {
+            Err(DriverError::ServiceRemoteForbidden)
+        }
+    }
+
+    /// Check user email is verified, services which do not require
+    /// verified email addresses accept any user.
+    pub fn check_user_email_verified(&self, user: &User) -> DriverResult<()> {
+        if !self.user_require_email_verified || user.email_verified_at.is_some() {
+            Ok(())
+        } else {
+            Err(DriverError::UserEmailNotVerified)
+        }
+    }
+
+    /// Returns object of user attributes included as token claims by service,
+    /// service attributes of user override user attributes with the same key.
+    pub fn user_claims_pick(
+        &self,
+        attributes: &Value,
+        service_attributes: &Value,
+    ) -> Map<String, Value> {
+        let mut claims = Map::default();
+        for key in self.user_claims.iter() {
+            let value = service_attributes.get(key).or_else(|| attributes.get(key));
+            if let Some(value) = value {
+                claims.insert(key.to_owned(), value.clone());
+            }
+        }
+        claims
+    }
+
     /// Build a local provider callback URL with type and serialisable data.
     pub fn provider_local_callback_url<T: Into<String>, D: Serialize>(
         &self,
@@ -88,6 +129,22 @@ impl fmt::Display for Service {
                 provider_microsoft_oauth2_url
             )?;
         }
+        if !self.allow_remote.is_empty() {
+            let allow_remote: Vec<String> =
+                self.allow_remote.iter().map(|x| x.to_string()).collect();
+            write!(f, "\n\tallow_remote {}", allow_remote.join(", "))?;
+        }
+        if !self.user_claims.is_empty() {
+            write!(f, "\n\tuser_claims {}", self.user_claims.join(", "))?;
+        }
+        if let Some(organisation_id) = &self.organisation_id {
+            write!(f, "\n\torganisation_id {}", organisation_id)?;
+        }
+        write!(
+            f,
+            "\n\tuser_require_email_verified {}",
+            self.user_require_email_verified
+        )?;
         Ok(())
     }
 }
@@ -156,6 +213,13 @@ impl AuditDiff for Service {
                 &c_provider_microsoft_oauth2_url,
                 &p_provider_microsoft_oauth2_url,
             )
+            .compare_vec("allow_remote", &self.allow_remote, &previous.allow_remote)
+            .compare_vec("user_claims", &self.user_claims, &previous.user_claims)
+            .compare(
+                "user_require_email_verified",
+                &self.user_require_email_verified,
+                &previous.user_require_email_verified,
+            )
             .into_value()
     }
 }
@@ -173,6 +237,7 @@ pub enum ServiceListQuery {
 pub struct ServiceListFilter {
     pub id: Option<Vec<Uuid>>,
     pub is_enabled: Option<bool>,
+    pub organisation_id: Option<Vec<Uuid>>,
     pub limit: i64,
 }
 
@@ -194,6 +259,10 @@ pub struct ServiceCreate {
     pub provider_local_url: Option<String>,
     pub provider_github_oauth2_url: Option<String>,
     pub provider_microsoft_oauth2_url: Option<String>,
+    pub allow_remote: Vec<Cidr>,
+    pub user_claims: Vec<String>,
+    pub organisation_id: Option<Uuid>,
+    pub user_require_email_verified: bool,
 }
 
 /// Service read.
@@ -220,6 +289,9 @@ pub struct ServiceUpdate {
     pub provider_local_url: Option<String>,
     pub provider_github_oauth2_url: Option<String>,
     pub provider_microsoft_oauth2_url: Option<String>,
+    pub allow_remote: Option<Vec<Cidr>>,
+    pub user_claims: Option<Vec<String>>,
+    pub user_require_email_verified: Option<bool>,
 }
 
 #[cfg(test)]
@@ -233,11 +305,10 @@ mod tests {
         token: String,
     }
 
-    #[test]
-    fn service_provider_local_callback_url() {
+    fn service_test() -> Service {
         let id = "6a9c6cfb7e15498b99e057153f0a212b";
         let id = Uuid::parse_str(id).unwrap();
-        let service = Service {
+        Service {
             created_at: Utc::now(),
             updated_at: Utc::now(),
             id,
@@ -249,7 +320,16 @@ mod tests {
             provider_local_url: Some("http://localhost:9000".to_owned()),
             provider_github_oauth2_url: None,
             provider_microsoft_oauth2_url: None,
-        };
+            allow_remote: Vec::new(),
+            user_claims: Vec::new(),
+            organisation_id: None,
+            user_require_email_verified: false,
+        }
+    }
+
+    #[test]
+    fn service_provider_local_callback_url() {
+        let service = service_test();
         let callback_data = CallbackData {
             email: "user@test.com".to_owned(),
             token: "6a9c6cfb7e15498b99e057153f0a212b".to_owned(),
@@ -262,4 +342,14 @@ mod tests {
             "http://localhost:9000/?type=reset_password&email=user%40test.com&token=6a9c6cfb7e15498b99e057153f0a212b"
         );
     }
+
+    #[test]
+    fn service_user_claims_pick() {
+        let mut service = service_test();
+        service.user_claims = vec!["team".to_owned(), "level".to_owned()];
+        let attributes = json!({ "team": "red", "level": 1, "hidden": true });
+        let service_attributes = json!({ "level": 2 });
+        let claims = service.user_claims_pick(&attributes, &service_attributes);
+        assert_eq!(Value::Object(claims), json!({ "team": "red", "level": 2 }));
+    }
 }
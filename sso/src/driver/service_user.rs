@@ -0,0 +1,128 @@
+use crate::{AuditSubject, DriverError, DriverResult};
+use chrono::{DateTime, Utc};
+use std::{convert::TryFrom, fmt};
+use uuid::Uuid;
+
+/// Service user status.
+#[derive(Debug, Copy, PartialEq, Clone, Serialize, Deserialize)]
+pub enum ServiceUserStatus {
+    Active,
+    Invited,
+    Suspended,
+}
+
+impl_enum_to_from_string!(ServiceUserStatus, "");
+
+impl ServiceUserStatus {
+    pub fn from_i32(v: i32) -> Self {
+        match v {
+            0 => Self::Active,
+            1 => Self::Invited,
+            2 => Self::Suspended,
+            _ => unimplemented!(),
+        }
+    }
+}
+
+impl TryFrom<i32> for ServiceUserStatus {
+    type Error = ();
+
+    fn try_from(v: i32) -> Result<Self, Self::Error> {
+        match v {
+            0 => Ok(Self::Active),
+            1 => Ok(Self::Invited),
+            2 => Ok(Self::Suspended),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Service user.
+///
+/// Membership of a user in a service, users can only authenticate with
+/// services of which they are an active member.
+#[derive(Debug, Clone)]
+pub struct ServiceUser {
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub service_id: Uuid,
+    pub user_id: Uuid,
+    pub status: ServiceUserStatus,
+    pub invite_expires_at: Option<DateTime<Utc>>,
+}
+
+impl ServiceUser {
+    /// Check membership is active.
+    pub fn check(self) -> DriverResult<Self> {
+        match self.status {
+            ServiceUserStatus::Active => Ok(self),
+            ServiceUserStatus::Invited => Err(DriverError::ServiceUserInvited),
+            ServiceUserStatus::Suspended => Err(DriverError::ServiceUserSuspended),
+        }
+    }
+
+    /// Check membership is an invitation which has not expired.
+    pub fn check_invite(self) -> DriverResult<Self> {
+        if self.status != ServiceUserStatus::Invited {
+            return Err(DriverError::ServiceUserNotInvited);
+        }
+        match self.invite_expires_at {
+            Some(invite_expires_at) if invite_expires_at > Utc::now() => Ok(self),
+            _ => Err(DriverError::ServiceUserInviteExpired),
+        }
+    }
+}
+
+impl fmt::Display for ServiceUser {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ServiceUser {} {}", self.service_id, self.user_id)?;
+        write!(f, "\n\tcreated_at {}", self.created_at)?;
+        write!(f, "\n\tupdated_at {}", self.updated_at)?;
+        write!(f, "\n\tstatus {}", self.status)?;
+        if let Some(invite_expires_at) = &self.invite_expires_at {
+            write!(f, "\n\tinvite_expires_at {}", invite_expires_at)?;
+        }
+        Ok(())
+    }
+}
+
+impl AuditSubject for ServiceUser {
+    fn subject(&self) -> String {
+        format!("{}", self.user_id)
+    }
+}
+
+/// Service user list query.
+///
+/// Service users are ordered by user ID.
+#[derive(Debug)]
+pub enum ServiceUserListQuery {
+    Limit,
+    IdGt(Uuid),
+    IdLt(Uuid),
+}
+
+/// Service user list filter.
+#[derive(Debug)]
+pub struct ServiceUserListFilter {
+    pub service_id: Uuid,
+    pub status: Option<Vec<ServiceUserStatus>>,
+    pub limit: i64,
+}
+
+/// Service user list.
+#[derive(Debug)]
+pub struct ServiceUserList {
+    pub query: ServiceUserListQuery,
+    pub filter: ServiceUserListFilter,
+}
+
+/// Service user update.
+///
+/// Creates membership if user is not a member of service.
+#[derive(Debug)]
+pub struct ServiceUserUpdate {
+    pub service_id: Uuid,
+    pub user_id: Uuid,
+    pub status: ServiceUserStatus,
+}
@@ -7,7 +7,11 @@ const EMAIL_REGISTER_CONFIRM: &str = "email_register_confirm";
 const EMAIL_RESET_PASSWORD: &str = "email_reset_password";
 const EMAIL_RESET_PASSWORD_CONFIRM: &str = "email_reset_password_confirm";
 const EMAIL_UPDATE_EMAIL: &str = "email_update_email";
+const EMAIL_UPDATE_EMAIL_CONFIRM: &str = "email_update_email_confirm";
 const EMAIL_UPDATE_PASSWORD: &str = "email_update_password";
+const EMAIL_VERIFY_EMAIL: &str = "email_verify_email";
+const EMAIL_INVITE: &str = "email_invite";
+const EMAIL_LOGIN_NOTIFY: &str = "email_login_notify";
 
 lazy_static! {
     static ref HANDLEBARS: Handlebars<'static> = {
@@ -37,12 +41,27 @@ lazy_static! {
         handlebars
             .register_template_string(EMAIL_UPDATE_EMAIL, include_str!("email_update_email.hbs"))
             .unwrap();
+        handlebars
+            .register_template_string(
+                EMAIL_UPDATE_EMAIL_CONFIRM,
+                include_str!("email_update_email_confirm.hbs"),
+            )
+            .unwrap();
         handlebars
             .register_template_string(
                 EMAIL_UPDATE_PASSWORD,
                 include_str!("email_update_password.hbs"),
             )
             .unwrap();
+        handlebars
+            .register_template_string(EMAIL_VERIFY_EMAIL, include_str!("email_verify_email.hbs"))
+            .unwrap();
+        handlebars
+            .register_template_string(EMAIL_INVITE, include_str!("email_invite.hbs"))
+            .unwrap();
+        handlebars
+            .register_template_string(EMAIL_LOGIN_NOTIFY, include_str!("email_login_notify.hbs"))
+            .unwrap();
 
         handlebars
     };
@@ -143,6 +162,42 @@ impl TemplateEmailUpdateEmail {
     }
 }
 
+/// Template email invite parameters.
+#[derive(Debug, Serialize)]
+struct TemplateEmailInvite {
+    user_name: String,
+    user_email: String,
+    url: String,
+    expires_at: DateTime<Utc>,
+    audit: TemplateEmailAudit,
+    service: TemplateEmailService,
+}
+
+impl TemplateEmailInvite {
+    pub fn new<UN, UE, U>(
+        user_name: UN,
+        user_email: UE,
+        url: U,
+        expires_at: DateTime<Utc>,
+        audit: &AuditMeta,
+        service: &Service,
+    ) -> Self
+    where
+        UN: Into<String>,
+        UE: Into<String>,
+        U: Into<String>,
+    {
+        Self {
+            user_name: user_name.into(),
+            user_email: user_email.into(),
+            url: url.into(),
+            expires_at,
+            audit: TemplateEmailAudit::new(audit),
+            service: TemplateEmailService::new(service),
+        }
+    }
+}
+
 /// Template email.
 #[derive(Debug)]
 pub struct TemplateEmail {
@@ -288,19 +343,19 @@ impl TemplateEmail {
         ))
     }
 
-    /// Render update email email template.
+    /// Render update email email template, sent to current email address of user.
     pub fn email_update_email(
         service: &Service,
         user: &User,
-        old_email: &str,
+        new_email: &str,
         token: &str,
         audit: &AuditMeta,
     ) -> DriverResult<Self> {
         let url = service.provider_local_callback_url(
             "update_email",
             json!({
-                "email": user.email,
-                "old_email": old_email,
+                "email": new_email,
+                "old_email": user.email,
                 "token": token,
             }),
         )?;
@@ -309,8 +364,8 @@ impl TemplateEmail {
             .render(
                 EMAIL_UPDATE_EMAIL,
                 &TemplateEmailUpdateEmail::new(
-                    old_email,
                     &user.email,
+                    new_email,
                     url.as_str(),
                     audit,
                     service,
@@ -321,7 +376,45 @@ impl TemplateEmail {
             &user.email,
             &user.name,
             &service.name,
-            "Email Address Updated",
+            "Email Address Update Request",
+            text,
+        ))
+    }
+
+    /// Render update email confirm email template, sent to pending email address of user.
+    pub fn email_update_email_confirm(
+        service: &Service,
+        user: &User,
+        new_email: &str,
+        token: &str,
+        audit: &AuditMeta,
+    ) -> DriverResult<Self> {
+        let url = service.provider_local_callback_url(
+            "update_email_confirm",
+            json!({
+                "email": new_email,
+                "old_email": user.email,
+                "token": token,
+            }),
+        )?;
+
+        let text = HANDLEBARS
+            .render(
+                EMAIL_UPDATE_EMAIL_CONFIRM,
+                &TemplateEmailUpdateEmail::new(
+                    &user.email,
+                    new_email,
+                    url.as_str(),
+                    audit,
+                    service,
+                ),
+            )
+            .map_err(DriverError::HandlebarsRender)?;
+        Ok(Self::new(
+            new_email,
+            user.name.as_str(),
+            service.name.as_str(),
+            "Email Address Update Confirmation",
             text,
         ))
     }
@@ -355,4 +448,102 @@ impl TemplateEmail {
             text,
         ))
     }
+
+    /// Render verify email email template.
+    pub fn email_verify_email(
+        service: &Service,
+        user: &User,
+        token: &str,
+        audit: &AuditMeta,
+    ) -> DriverResult<Self> {
+        let url = service.provider_local_callback_url(
+            "verify_email",
+            json!({
+                "email": user.email,
+                "token": token,
+            }),
+        )?;
+
+        let text = HANDLEBARS
+            .render(
+                EMAIL_VERIFY_EMAIL,
+                &TemplateEmailGeneric::new(&user.email, url.as_str(), audit, service),
+            )
+            .map_err(DriverError::HandlebarsRender)?;
+        Ok(Self::new(
+            &user.email,
+            &user.name,
+            &service.name,
+            "Email Verification Request",
+            text,
+        ))
+    }
+
+    /// Render invite email template.
+    pub fn email_invite(
+        service: &Service,
+        user: &User,
+        token: &str,
+        expires_at: DateTime<Utc>,
+        audit: &AuditMeta,
+    ) -> DriverResult<Self> {
+        let url = service.provider_local_callback_url(
+            "invite",
+            json!({
+                "email": user.email,
+                "token": token,
+            }),
+        )?;
+
+        let text = HANDLEBARS
+            .render(
+                EMAIL_INVITE,
+                &TemplateEmailInvite::new(
+                    &user.name,
+                    &user.email,
+                    url.as_str(),
+                    expires_at,
+                    audit,
+                    service,
+                ),
+            )
+            .map_err(DriverError::HandlebarsRender)?;
+        Ok(Self::new(
+            &user.email,
+            &user.name,
+            &service.name,
+            format!("Invitation to {}", service.name),
+            text,
+        ))
+    }
+
+    /// Render login notify email template, sent when user signs in from a new device or location.
+    pub fn email_login_notify(
+        service: &Service,
+        user: &User,
+        token: &str,
+        audit: &AuditMeta,
+    ) -> DriverResult<Self> {
+        let url = service.provider_local_callback_url(
+            "login_revoke",
+            json!({
+                "email": user.email,
+                "token": token,
+            }),
+        )?;
+
+        let text = HANDLEBARS
+            .render(
+                EMAIL_LOGIN_NOTIFY,
+                &TemplateEmailGeneric::new(&user.email, url.as_str(), audit, service),
+            )
+            .map_err(DriverError::HandlebarsRender)?;
+        Ok(Self::new(
+            &user.email,
+            &user.name,
+            &service.name,
+            "New Sign In",
+            text,
+        ))
+    }
 }
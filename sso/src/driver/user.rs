@@ -1,4 +1,7 @@
-use crate::{AuditDiff, AuditDiffBuilder, AuditSubject, DriverError, DriverResult};
+use crate::{
+    Audit, AuditDiff, AuditDiffBuilder, AuditSubject, DriverError, DriverResult, Key, KeyWithValue,
+    ServiceUser,
+};
 use chrono::{DateTime, Utc};
 use libreauth::pass::HashBuilder;
 use serde_json::Value;
@@ -54,6 +57,12 @@ pub struct User {
     pub password_allow_reset: bool,
     pub password_require_update: bool,
     pub password_hash: Option<String>,
+    pub last_login_at: Option<DateTime<Utc>>,
+    pub last_login_remote: Option<String>,
+    pub attributes: Value,
+    pub organisation_id: Option<Uuid>,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    pub email_pending: Option<String>,
 }
 
 impl fmt::Display for User {
@@ -71,7 +80,24 @@ impl fmt::Display for User {
             f,
             "\n\tpassword_require_update {}",
             self.password_require_update
-        )
+        )?;
+        if let Some(last_login_at) = &self.last_login_at {
+            write!(f, "\n\tlast_login_at {}", last_login_at)?;
+        }
+        if let Some(last_login_remote) = &self.last_login_remote {
+            write!(f, "\n\tlast_login_remote {}", last_login_remote)?;
+        }
+        write!(f, "\n\tattributes {}", self.attributes)?;
+        if let Some(organisation_id) = &self.organisation_id {
+            write!(f, "\n\torganisation_id {}", organisation_id)?;
+        }
+        if let Some(email_verified_at) = &self.email_verified_at {
+            write!(f, "\n\temail_verified_at {}", email_verified_at)?;
+        }
+        if let Some(email_pending) = &self.email_pending {
+            write!(f, "\n\temail_pending {}", email_pending)?;
+        }
+        Ok(())
     }
 }
 
@@ -83,6 +109,12 @@ impl AuditSubject for User {
 
 impl AuditDiff for User {
     fn diff(&self, previous: &Self) -> Value {
+        self.diff_builder(previous).into_value()
+    }
+}
+
+impl User {
+    fn diff_builder(&self, previous: &Self) -> AuditDiffBuilder {
         AuditDiffBuilder::default()
             .compare("is_enabled", &self.is_enabled, &previous.is_enabled)
             .compare("name", &self.name, &previous.name)
@@ -99,10 +131,157 @@ impl AuditDiff for User {
                 &self.password_require_update,
                 &previous.password_require_update,
             )
+            .compare("attributes", &self.attributes, &previous.attributes)
+    }
+}
+
+/// User with attributes for a service.
+///
+/// Service attributes are none if user was not read or updated by a service key.
+#[derive(Debug, Clone)]
+pub struct UserWithService {
+    pub user: User,
+    pub service_attributes: Option<Value>,
+}
+
+impl AuditSubject for UserWithService {
+    fn subject(&self) -> String {
+        self.user.subject()
+    }
+}
+
+impl AuditDiff for UserWithService {
+    fn diff(&self, previous: &Self) -> Value {
+        self.user
+            .diff_builder(&previous.user)
+            .compare_opt(
+                "service_attributes",
+                self.service_attributes.as_ref(),
+                previous.service_attributes.as_ref(),
+            )
             .into_value()
     }
 }
 
+/// User export.
+///
+/// Subject access export of user record, keys without values, service
+/// memberships and attributes, and audit logs of user. Sessions are
+/// represented by user token keys.
+#[derive(Debug)]
+pub struct UserExport {
+    pub user: User,
+    pub keys: Vec<Key>,
+    pub services: Vec<ServiceUser>,
+    pub service_attributes: Vec<(Uuid, Value)>,
+    pub audit: Vec<Audit>,
+}
+
+impl AuditSubject for UserExport {
+    fn subject(&self) -> String {
+        self.user.subject()
+    }
+}
+
+impl UserExport {
+    /// Returns export as JSON value, password hash and key values are not included.
+    pub fn to_value(&self) -> Value {
+        let user = &self.user;
+        let keys: Vec<Value> = self
+            .keys
+            .iter()
+            .map(|x| {
+                json!({
+                    "created_at": x.created_at,
+                    "updated_at": x.updated_at,
+                    "id": x.id,
+                    "is_enabled": x.is_enabled,
+                    "is_revoked": x.is_revoked,
+                    "type": x.type_.to_string(),
+                    "name": x.name,
+                    "service_id": x.service_id,
+                    "scope": x.scope.iter().map(|x| x.to_string()).collect::<Vec<_>>(),
+                    "allow_remote": x.allow_remote.iter().map(|x| x.to_string()).collect::<Vec<_>>(),
+                    "predecessor_id": x.predecessor_id,
+                    "successor_id": x.successor_id,
+                    "revoke_at": x.revoke_at,
+                    "last_used_at": x.last_used_at,
+                    "last_used_remote": x.last_used_remote,
+                })
+            })
+            .collect();
+        let services: Vec<Value> = self
+            .services
+            .iter()
+            .map(|x| {
+                json!({
+                    "created_at": x.created_at,
+                    "updated_at": x.updated_at,
+                    "service_id": x.service_id,
+                    "status": x.status.to_string(),
+                })
+            })
+            .collect();
+        let service_attributes: Vec<Value> = self
+            .service_attributes
+            .iter()
+            .map(|(service_id, attributes)| {
+                json!({
+                    "service_id": service_id,
+                    "attributes": attributes,
+                })
+            })
+            .collect();
+        let audit: Vec<Value> = self
+            .audit
+            .iter()
+            .map(|x| {
+                json!({
+                    "created_at": x.created_at,
+                    "updated_at": x.updated_at,
+                    "id": x.id,
+                    "user_agent": x.user_agent,
+                    "remote": x.remote,
+                    "forwarded": x.forwarded,
+                    "status_code": x.status_code,
+                    "type": x.type_,
+                    "subject": x.subject,
+                    "data": x.data,
+                    "key_id": x.key_id,
+                    "service_id": x.service_id,
+                    "user_id": x.user_id,
+                    "user_key_id": x.user_key_id,
+                    "actor_key_id": x.actor_key_id,
+                })
+            })
+            .collect();
+        json!({
+            "user": {
+                "created_at": user.created_at,
+                "updated_at": user.updated_at,
+                "id": user.id,
+                "is_enabled": user.is_enabled,
+                "name": user.name,
+                "email": user.email,
+                "locale": user.locale,
+                "timezone": user.timezone,
+                "password_allow_reset": user.password_allow_reset,
+                "password_require_update": user.password_require_update,
+                "last_login_at": user.last_login_at,
+                "last_login_remote": user.last_login_remote,
+                "attributes": user.attributes,
+                "organisation_id": user.organisation_id,
+                "email_verified_at": user.email_verified_at,
+                "email_pending": user.email_pending,
+            },
+            "keys": keys,
+            "services": services,
+            "service_attributes": service_attributes,
+            "audit": audit,
+        })
+    }
+}
+
 /// User password metadata.
 #[derive(Debug)]
 pub struct UserPasswordMeta {
@@ -142,10 +321,14 @@ pub enum UserListQuery {
 }
 
 /// User list filter.
+///
+/// Users who have never logged in are compared to `last_login_lt` by created at time.
 #[derive(Debug)]
 pub struct UserListFilter {
     pub id: Option<Vec<Uuid>>,
     pub email: Option<Vec<String>>,
+    pub last_login_lt: Option<DateTime<Utc>>,
+    pub organisation_id: Option<Vec<Uuid>>,
     pub limit: i64,
 }
 
@@ -167,6 +350,8 @@ pub struct UserCreate {
     pub password_allow_reset: bool,
     pub password_require_update: bool,
     pub password_hash: Option<String>,
+    pub attributes: Value,
+    pub organisation_id: Option<Uuid>,
 }
 
 impl UserCreate {
@@ -184,6 +369,8 @@ impl UserCreate {
             password_allow_reset: false,
             password_require_update: false,
             password_hash: None,
+            attributes: json!({}),
+            organisation_id: None,
         }
     }
 
@@ -208,6 +395,16 @@ impl UserCreate {
         self
     }
 
+    pub fn attributes(mut self, attributes: Value) -> Self {
+        self.attributes = attributes;
+        self
+    }
+
+    pub fn organisation_id(mut self, organisation_id: Option<Uuid>) -> Self {
+        self.organisation_id = organisation_id;
+        self
+    }
+
     pub fn with_password<P>(
         mut self,
         allow_reset: bool,
@@ -224,11 +421,39 @@ impl UserCreate {
     }
 }
 
+/// User invite.
+///
+/// Creates user with an invited membership of service which expires,
+/// and a token key for service used to accept the invitation.
+#[derive(Debug)]
+pub struct UserInvite {
+    pub service_id: Uuid,
+    pub user: UserCreate,
+    pub invite_expires_at: DateTime<Utc>,
+}
+
+/// User with invited membership of service and token key.
+#[derive(Debug)]
+pub struct UserWithInvite {
+    pub user: User,
+    pub key: KeyWithValue,
+    pub service_user: ServiceUser,
+}
+
+impl AuditSubject for UserWithInvite {
+    fn subject(&self) -> String {
+        self.user.subject()
+    }
+}
+
 /// User read.
+///
+/// Email addresses are unique per organisation, users without an
+/// organisation are read by email address with none.
 #[derive(Debug)]
 pub enum UserRead {
     Id(Uuid),
-    Email(String),
+    Email(String, Option<Uuid>),
 }
 
 /// User update.
@@ -243,6 +468,7 @@ pub struct UserUpdate {
     pub password_allow_reset: Option<bool>,
     pub password_require_update: Option<bool>,
     pub password_hash: Option<String>,
+    pub attributes: Option<Value>,
 }
 
 impl UserUpdate {
@@ -265,6 +491,7 @@ impl UserUpdate {
             password_allow_reset,
             password_require_update,
             password_hash: None,
+            attributes: None,
         }
     }
 
@@ -279,6 +506,7 @@ impl UserUpdate {
             password_allow_reset: None,
             password_require_update: None,
             password_hash: None,
+            attributes: None,
         }
     }
 
@@ -297,6 +525,7 @@ impl UserUpdate {
             password_allow_reset: None,
             password_require_update: None,
             password_hash: None,
+            attributes: None,
         }
     }
 
@@ -317,6 +546,7 @@ impl UserUpdate {
             password_allow_reset: None,
             password_require_update: Some(false),
             password_hash: Some(hash_password(password.as_ref())?),
+            attributes: None,
         })
     }
 
@@ -329,6 +559,11 @@ impl UserUpdate {
         self.password_allow_reset = Some(password_allow_reset);
         self
     }
+
+    pub fn set_attributes(mut self, attributes: Value) -> Self {
+        self.attributes = Some(attributes);
+        self
+    }
 }
 
 /// User token.
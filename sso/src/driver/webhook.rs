@@ -0,0 +1,339 @@
+use crate::{impl_enum_to_from_string, Audit, AuditDiff, AuditDiffBuilder, AuditSubject};
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use libreauth::key::KeyBuilder;
+use serde_json::Value;
+use sha2::Sha256;
+use std::{convert::TryFrom, fmt};
+use uuid::Uuid;
+
+/// Webhook secret size in bytes.
+pub const BYTES_WEBHOOK_SECRET: usize = 21;
+
+/// Webhook delivery attempts before delivery is dead lettered.
+pub const MAX_WEBHOOK_DELIVERY_ATTEMPTS: i32 = 10;
+
+/// Webhook delivery first retry delay in seconds, doubled for each attempt.
+pub const WEBHOOK_DELIVERY_BACKOFF_S: i64 = 30;
+
+/// Webhook delivery maximum retry delay in seconds.
+pub const MAX_WEBHOOK_DELIVERY_BACKOFF_S: i64 = 21_600;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Webhook.
+///
+/// Webhooks are scoped to a service, audit logs of the service with a type
+/// in `audit_type` are delivered to URL. An empty `audit_type` matches all types.
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub id: Uuid,
+    pub service_id: Uuid,
+    pub is_enabled: bool,
+    pub name: String,
+    pub url: String,
+    pub audit_type: Vec<String>,
+}
+
+impl fmt::Display for Webhook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Webhook {}", self.id)?;
+        write!(f, "\n\tcreated_at {}", self.created_at)?;
+        write!(f, "\n\tupdated_at {}", self.updated_at)?;
+        write!(f, "\n\tservice_id {}", self.service_id)?;
+        write!(f, "\n\tis_enabled {}", self.is_enabled)?;
+        write!(f, "\n\tname {}", self.name)?;
+        write!(f, "\n\turl {}", self.url)?;
+        write!(f, "\n\taudit_type {:?}", self.audit_type)
+    }
+}
+
+impl AuditSubject for Webhook {
+    fn subject(&self) -> String {
+        format!("{}", self.id)
+    }
+}
+
+impl AuditDiff for Webhook {
+    fn diff(&self, previous: &Self) -> Value {
+        AuditDiffBuilder::default()
+            .compare("is_enabled", &self.is_enabled, &previous.is_enabled)
+            .compare("name", &self.name, &previous.name)
+            .compare("url", &self.url, &previous.url)
+            .compare_vec("audit_type", &self.audit_type, &previous.audit_type)
+            .into_value()
+    }
+}
+
+impl Webhook {
+    /// Generate new webhook secret from random bytes.
+    pub fn secret_generate() -> String {
+        KeyBuilder::new()
+            .size(BYTES_WEBHOOK_SECRET)
+            .generate()
+            .as_base32()
+    }
+
+    /// Returns signature header value of delivery body.
+    ///
+    /// Signature is a HMAC-SHA256 of timestamp and body separated by `.`,
+    /// keyed by the webhook secret and encoded as hex.
+    pub fn signature(secret: &str, timestamp: i64, body: &str) -> String {
+        let mut mac = HmacSha256::new_varkey(secret.as_bytes()).unwrap();
+        mac.input(format!("{}.{}", timestamp, body).as_bytes());
+        format!("sha256={}", hex::encode(mac.result().code()))
+    }
+}
+
+/// Webhook with secret.
+///
+/// Secret is only returned when webhook is created.
+#[derive(Debug)]
+pub struct WebhookWithSecret {
+    pub webhook: Webhook,
+    pub secret: String,
+}
+
+impl AuditSubject for WebhookWithSecret {
+    fn subject(&self) -> String {
+        self.webhook.subject()
+    }
+}
+
+/// Webhook list query.
+#[derive(Debug)]
+pub enum WebhookListQuery {
+    Limit,
+    IdGt(Uuid),
+    IdLt(Uuid),
+}
+
+/// Webhook list filter.
+#[derive(Debug)]
+pub struct WebhookListFilter {
+    pub id: Option<Vec<Uuid>>,
+    pub service_id: Option<Vec<Uuid>>,
+    pub limit: i64,
+}
+
+/// Webhook list.
+#[derive(Debug)]
+pub struct WebhookList {
+    pub query: WebhookListQuery,
+    pub filter: WebhookListFilter,
+}
+
+/// Webhook create.
+#[derive(Debug)]
+pub struct WebhookCreate {
+    pub service_id: Uuid,
+    pub is_enabled: bool,
+    pub name: String,
+    pub url: String,
+    pub audit_type: Vec<String>,
+}
+
+/// Webhook read.
+#[derive(Debug)]
+pub struct WebhookRead {
+    pub id: Uuid,
+}
+
+impl WebhookRead {
+    pub fn new(id: Uuid) -> Self {
+        Self { id }
+    }
+}
+
+/// Webhook update.
+#[derive(Debug)]
+pub struct WebhookUpdate {
+    pub id: Uuid,
+    pub is_enabled: Option<bool>,
+    pub name: Option<String>,
+    pub url: Option<String>,
+    pub audit_type: Option<Vec<String>>,
+}
+
+/// Webhook delivery status.
+#[derive(Debug, Copy, PartialEq, Clone, Serialize, Deserialize)]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Delivered,
+    Dead,
+}
+
+impl_enum_to_from_string!(WebhookDeliveryStatus, "");
+
+impl WebhookDeliveryStatus {
+    pub fn from_i32(v: i32) -> Self {
+        match v {
+            0 => Self::Pending,
+            1 => Self::Delivered,
+            2 => Self::Dead,
+            _ => unimplemented!(),
+        }
+    }
+}
+
+impl TryFrom<i32> for WebhookDeliveryStatus {
+    type Error = ();
+
+    fn try_from(v: i32) -> Result<Self, Self::Error> {
+        match v {
+            0 => Ok(Self::Pending),
+            1 => Ok(Self::Delivered),
+            2 => Ok(Self::Dead),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Webhook delivery.
+///
+/// Deliveries are queued when audit logs are created and retried with
+/// exponential backoff, after `MAX_WEBHOOK_DELIVERY_ATTEMPTS` failed
+/// attempts the delivery is dead lettered.
+#[derive(Debug, Clone)]
+pub struct WebhookDelivery {
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub audit_id: Uuid,
+    pub audit_type: String,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+}
+
+impl fmt::Display for WebhookDelivery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "WebhookDelivery {}", self.id)?;
+        write!(f, "\n\tcreated_at {}", self.created_at)?;
+        write!(f, "\n\twebhook_id {}", self.webhook_id)?;
+        write!(f, "\n\taudit_id {}", self.audit_id)?;
+        write!(f, "\n\taudit_type {}", self.audit_type)?;
+        write!(f, "\n\tstatus {}", self.status)?;
+        write!(f, "\n\tattempts {}", self.attempts)?;
+        if let Some(delivered_at) = &self.delivered_at {
+            write!(f, "\n\tdelivered_at {}", delivered_at)?;
+        }
+        if let Some(status_code) = &self.status_code {
+            write!(f, "\n\tstatus_code {}", status_code)?;
+        }
+        if let Some(error) = &self.error {
+            write!(f, "\n\terror {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl AuditSubject for WebhookDelivery {
+    fn subject(&self) -> String {
+        format!("{}", self.id)
+    }
+}
+
+impl WebhookDelivery {
+    /// Returns delay before next attempt after number of failed attempts.
+    pub fn backoff(attempts: i32) -> Duration {
+        let exp = attempts.max(1).min(16) as u32 - 1;
+        let seconds = WEBHOOK_DELIVERY_BACKOFF_S.saturating_mul(2_i64.pow(exp));
+        Duration::seconds(seconds.min(MAX_WEBHOOK_DELIVERY_BACKOFF_S))
+    }
+
+    /// Returns delivery body for audit log.
+    pub fn body(id: Uuid, webhook_id: Uuid, audit: &Audit) -> String {
+        json!({
+            "id": id,
+            "webhook_id": webhook_id,
+            "audit": audit.to_value(),
+        })
+        .to_string()
+    }
+}
+
+/// Webhook delivery list query.
+#[derive(Debug)]
+pub enum WebhookDeliveryListQuery {
+    Limit,
+    IdGt(Uuid),
+    IdLt(Uuid),
+}
+
+/// Webhook delivery list filter.
+#[derive(Debug)]
+pub struct WebhookDeliveryListFilter {
+    pub webhook_id: Uuid,
+    pub status: Option<Vec<WebhookDeliveryStatus>>,
+    pub limit: i64,
+}
+
+/// Webhook delivery list.
+#[derive(Debug)]
+pub struct WebhookDeliveryList {
+    pub query: WebhookDeliveryListQuery,
+    pub filter: WebhookDeliveryListFilter,
+}
+
+/// Webhook delivery read.
+#[derive(Debug)]
+pub struct WebhookDeliveryRead {
+    pub id: Uuid,
+}
+
+/// Webhook delivery send.
+///
+/// Delivery claimed from queue, with decrypted webhook secret and body.
+/// Body is none if the audit log no longer exists.
+#[derive(Debug)]
+pub struct WebhookDeliverySend {
+    pub id: Uuid,
+    pub attempts: i32,
+    pub url: String,
+    pub secret: String,
+    pub body: Option<String>,
+}
+
+/// Webhook delivery result.
+#[derive(Debug)]
+pub struct WebhookDeliveryResult {
+    pub id: Uuid,
+    pub status_code: Option<u16>,
+    pub error: Option<String>,
+}
+
+impl WebhookDeliveryResult {
+    /// Returns true if delivery was successful.
+    pub fn is_delivered(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn webhook_delivery_backoff() {
+        assert_eq!(WebhookDelivery::backoff(1), Duration::seconds(30));
+        assert_eq!(WebhookDelivery::backoff(2), Duration::seconds(60));
+        assert_eq!(WebhookDelivery::backoff(4), Duration::seconds(240));
+        assert_eq!(WebhookDelivery::backoff(50), Duration::seconds(21_600));
+    }
+
+    #[test]
+    fn webhook_signature() {
+        let s1 = Webhook::signature("secret", 1, "{}");
+        assert!(s1.starts_with("sha256="));
+        assert_eq!(s1, Webhook::signature("secret", 1, "{}"));
+        assert_ne!(s1, Webhook::signature("secret", 2, "{}"));
+        assert_ne!(s1, Webhook::signature("other", 1, "{}"));
+    }
+}
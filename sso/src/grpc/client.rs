@@ -370,6 +370,91 @@ impl GrpcClientBlocking {
         self.rt.block_on(self.client.audit_update(request))
     }
 
+    pub fn audit_watch(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::AuditWatchRequest>,
+    ) -> Result<tonic::Response<tonic::codec::Streaming<pb::Audit>>, tonic::Status> {
+        self.rt.block_on(self.client.audit_watch(request))
+    }
+
+    /// Returns next message from audit watch stream, or none if stream ended.
+    pub fn audit_watch_message(
+        &mut self,
+        stream: &mut tonic::codec::Streaming<pb::Audit>,
+    ) -> Result<Option<pb::Audit>, tonic::Status> {
+        self.rt.block_on(stream.message())
+    }
+
+    pub fn audit_report(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::AuditReportRequest>,
+    ) -> Result<tonic::Response<pb::AuditReportReply>, tonic::Status> {
+        self.rt.block_on(self.client.audit_report(request))
+    }
+
+    pub fn group_list(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::GroupListRequest>,
+    ) -> Result<tonic::Response<pb::GroupListReply>, tonic::Status> {
+        self.rt.block_on(self.client.group_list(request))
+    }
+
+    pub fn group_create(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::GroupCreateRequest>,
+    ) -> Result<tonic::Response<pb::GroupReadReply>, tonic::Status> {
+        self.rt.block_on(self.client.group_create(request))
+    }
+
+    pub fn group_read(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::GroupReadRequest>,
+    ) -> Result<tonic::Response<pb::GroupReadReply>, tonic::Status> {
+        self.rt.block_on(self.client.group_read(request))
+    }
+
+    pub fn group_update(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::GroupUpdateRequest>,
+    ) -> Result<tonic::Response<pb::GroupReadReply>, tonic::Status> {
+        self.rt.block_on(self.client.group_update(request))
+    }
+
+    pub fn group_delete(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::GroupReadRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        self.rt.block_on(self.client.group_delete(request))
+    }
+
+    pub fn group_user_create(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::GroupUserRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        self.rt.block_on(self.client.group_user_create(request))
+    }
+
+    pub fn group_user_delete(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::GroupUserRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        self.rt.block_on(self.client.group_user_delete(request))
+    }
+
+    pub fn group_role_create(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::GroupRoleRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        self.rt.block_on(self.client.group_role_create(request))
+    }
+
+    pub fn group_role_delete(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::GroupRoleRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        self.rt.block_on(self.client.group_role_delete(request))
+    }
+
     pub fn key_list(
         &mut self,
         request: impl tonic::IntoRequest<pb::KeyListRequest>,
@@ -398,6 +483,13 @@ impl GrpcClientBlocking {
         self.rt.block_on(self.client.key_update(request))
     }
 
+    pub fn key_rotate(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::KeyRotateRequest>,
+    ) -> Result<tonic::Response<pb::KeyCreateReply>, tonic::Status> {
+        self.rt.block_on(self.client.key_rotate(request))
+    }
+
     pub fn key_delete(
         &mut self,
         request: impl tonic::IntoRequest<pb::KeyReadRequest>,
@@ -405,6 +497,90 @@ impl GrpcClientBlocking {
         self.rt.block_on(self.client.key_delete(request))
     }
 
+    pub fn organisation_list(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::OrganisationListRequest>,
+    ) -> Result<tonic::Response<pb::OrganisationListReply>, tonic::Status> {
+        self.rt.block_on(self.client.organisation_list(request))
+    }
+
+    pub fn organisation_create(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::OrganisationCreateRequest>,
+    ) -> Result<tonic::Response<pb::OrganisationReadReply>, tonic::Status> {
+        self.rt.block_on(self.client.organisation_create(request))
+    }
+
+    pub fn organisation_read(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::OrganisationReadRequest>,
+    ) -> Result<tonic::Response<pb::OrganisationReadReply>, tonic::Status> {
+        self.rt.block_on(self.client.organisation_read(request))
+    }
+
+    pub fn organisation_update(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::OrganisationUpdateRequest>,
+    ) -> Result<tonic::Response<pb::OrganisationReadReply>, tonic::Status> {
+        self.rt.block_on(self.client.organisation_update(request))
+    }
+
+    pub fn organisation_delete(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::OrganisationReadRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        self.rt.block_on(self.client.organisation_delete(request))
+    }
+
+    pub fn role_list(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::RoleListRequest>,
+    ) -> Result<tonic::Response<pb::RoleListReply>, tonic::Status> {
+        self.rt.block_on(self.client.role_list(request))
+    }
+
+    pub fn role_create(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::RoleCreateRequest>,
+    ) -> Result<tonic::Response<pb::RoleReadReply>, tonic::Status> {
+        self.rt.block_on(self.client.role_create(request))
+    }
+
+    pub fn role_read(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::RoleReadRequest>,
+    ) -> Result<tonic::Response<pb::RoleReadReply>, tonic::Status> {
+        self.rt.block_on(self.client.role_read(request))
+    }
+
+    pub fn role_update(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::RoleUpdateRequest>,
+    ) -> Result<tonic::Response<pb::RoleReadReply>, tonic::Status> {
+        self.rt.block_on(self.client.role_update(request))
+    }
+
+    pub fn role_delete(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::RoleReadRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        self.rt.block_on(self.client.role_delete(request))
+    }
+
+    pub fn user_role_create(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::UserRoleRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        self.rt.block_on(self.client.user_role_create(request))
+    }
+
+    pub fn user_role_delete(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::UserRoleRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        self.rt.block_on(self.client.user_role_delete(request))
+    }
+
     pub fn service_list(
         &mut self,
         request: impl tonic::IntoRequest<pb::ServiceListRequest>,
@@ -440,6 +616,20 @@ impl GrpcClientBlocking {
         self.rt.block_on(self.client.service_delete(request))
     }
 
+    pub fn service_user_list(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::ServiceUserListRequest>,
+    ) -> Result<tonic::Response<pb::ServiceUserListReply>, tonic::Status> {
+        self.rt.block_on(self.client.service_user_list(request))
+    }
+
+    pub fn service_user_update(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::ServiceUserUpdateRequest>,
+    ) -> Result<tonic::Response<pb::ServiceUserReadReply>, tonic::Status> {
+        self.rt.block_on(self.client.service_user_update(request))
+    }
+
     pub fn user_list(
         &mut self,
         request: impl tonic::IntoRequest<pb::UserListRequest>,
@@ -475,6 +665,91 @@ impl GrpcClientBlocking {
         self.rt.block_on(self.client.user_delete(request))
     }
 
+    pub fn user_export(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::UserReadRequest>,
+    ) -> Result<tonic::Response<pb::UserExportReply>, tonic::Status> {
+        self.rt.block_on(self.client.user_export(request))
+    }
+
+    pub fn user_erase(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::UserReadRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        self.rt.block_on(self.client.user_erase(request))
+    }
+
+    pub fn user_invite(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::UserInviteRequest>,
+    ) -> Result<tonic::Response<pb::UserInviteReply>, tonic::Status> {
+        self.rt.block_on(self.client.user_invite(request))
+    }
+
+    pub fn user_invite_resend(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::UserInviteResendRequest>,
+    ) -> Result<tonic::Response<pb::ServiceUserReadReply>, tonic::Status> {
+        self.rt.block_on(self.client.user_invite_resend(request))
+    }
+
+    pub fn user_invite_revoke(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::UserInviteRevokeRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        self.rt.block_on(self.client.user_invite_revoke(request))
+    }
+
+    pub fn webhook_list(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::WebhookListRequest>,
+    ) -> Result<tonic::Response<pb::WebhookListReply>, tonic::Status> {
+        self.rt.block_on(self.client.webhook_list(request))
+    }
+
+    pub fn webhook_create(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::WebhookCreateRequest>,
+    ) -> Result<tonic::Response<pb::WebhookCreateReply>, tonic::Status> {
+        self.rt.block_on(self.client.webhook_create(request))
+    }
+
+    pub fn webhook_read(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::WebhookReadRequest>,
+    ) -> Result<tonic::Response<pb::WebhookReadReply>, tonic::Status> {
+        self.rt.block_on(self.client.webhook_read(request))
+    }
+
+    pub fn webhook_update(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::WebhookUpdateRequest>,
+    ) -> Result<tonic::Response<pb::WebhookReadReply>, tonic::Status> {
+        self.rt.block_on(self.client.webhook_update(request))
+    }
+
+    pub fn webhook_delete(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::WebhookReadRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        self.rt.block_on(self.client.webhook_delete(request))
+    }
+
+    pub fn webhook_delivery_list(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::WebhookDeliveryListRequest>,
+    ) -> Result<tonic::Response<pb::WebhookDeliveryListReply>, tonic::Status> {
+        self.rt.block_on(self.client.webhook_delivery_list(request))
+    }
+
+    pub fn webhook_delivery_retry(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::WebhookDeliveryRetryRequest>,
+    ) -> Result<tonic::Response<pb::WebhookDeliveryReadReply>, tonic::Status> {
+        self.rt
+            .block_on(self.client.webhook_delivery_retry(request))
+    }
+
     pub fn auth_key_verify(
         &mut self,
         request: impl tonic::IntoRequest<pb::AuthKeyRequest>,
@@ -510,6 +785,13 @@ impl GrpcClientBlocking {
         self.rt.block_on(self.client.auth_token_revoke(request))
     }
 
+    pub fn auth_impersonate(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::AuthImpersonateRequest>,
+    ) -> Result<tonic::Response<pb::AuthImpersonateReply>, tonic::Status> {
+        self.rt.block_on(self.client.auth_impersonate(request))
+    }
+
     pub fn auth_totp_verify(
         &mut self,
         request: impl tonic::IntoRequest<pb::AuthTotpRequest>,
@@ -538,6 +820,14 @@ impl GrpcClientBlocking {
         self.rt.block_on(self.client.auth_local_login(request))
     }
 
+    pub fn auth_local_login_revoke(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::AuthTokenRequest>,
+    ) -> Result<tonic::Response<pb::AuthAuditReply>, tonic::Status> {
+        self.rt
+            .block_on(self.client.auth_local_login_revoke(request))
+    }
+
     pub fn auth_local_register(
         &mut self,
         request: impl tonic::IntoRequest<pb::AuthRegisterRequest>,
@@ -593,6 +883,14 @@ impl GrpcClientBlocking {
             .block_on(self.client.auth_local_update_email(request))
     }
 
+    pub fn auth_local_update_email_confirm(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::AuthTokenRequest>,
+    ) -> Result<tonic::Response<pb::AuthAuditReply>, tonic::Status> {
+        self.rt
+            .block_on(self.client.auth_local_update_email_confirm(request))
+    }
+
     pub fn auth_local_update_email_revoke(
         &mut self,
         request: impl tonic::IntoRequest<pb::AuthTokenRequest>,
@@ -617,6 +915,30 @@ impl GrpcClientBlocking {
             .block_on(self.client.auth_local_update_password_revoke(request))
     }
 
+    pub fn auth_local_verify_email(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::AuthVerifyEmailRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        self.rt
+            .block_on(self.client.auth_local_verify_email(request))
+    }
+
+    pub fn auth_local_verify_email_confirm(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::AuthTokenRequest>,
+    ) -> Result<tonic::Response<pb::AuthAuditReply>, tonic::Status> {
+        self.rt
+            .block_on(self.client.auth_local_verify_email_confirm(request))
+    }
+
+    pub fn auth_local_invite_confirm(
+        &mut self,
+        request: impl tonic::IntoRequest<pb::AuthRegisterConfirmRequest>,
+    ) -> Result<tonic::Response<pb::AuthPasswordMetaReply>, tonic::Status> {
+        self.rt
+            .block_on(self.client.auth_local_invite_confirm(request))
+    }
+
     pub fn auth_github_oauth2_url(
         &mut self,
         request: impl tonic::IntoRequest<()>,
@@ -1,4 +1,7 @@
 use crate::prelude::*;
+use futures_util::future;
+use std::sync::Arc;
+use tokio::{sync::mpsc, time};
 
 impl validator::Validate for pb::AuditListRequest {
     fn validate(&self) -> Result<(), validator::ValidationErrors> {
@@ -10,6 +13,21 @@ impl validator::Validate for pb::AuditListRequest {
             validate::audit_subject_vec(e, "subject", &self.subject);
             validate::uuid_vec(e, "service_id", &self.service_id);
             validate::uuid_vec(e, "user_id", &self.user_id);
+            validate::uuid_vec(e, "organisation_id", &self.organisation_id);
+            validate::status_code_opt(e, "status_code_ge", self.status_code_ge);
+            validate::status_code_opt(e, "status_code_le", self.status_code_le);
+            validate::text_opt(e, "remote", self.remote.as_ref().map(|x| &**x));
+            validate::cidr_vec(e, "remote_cidr", &self.remote_cidr);
+            validate::text_opt(e, "forwarded", self.forwarded.as_ref().map(|x| &**x));
+            validate::text_opt(e, "user_agent", self.user_agent.as_ref().map(|x| &**x));
+            validate::uuid_vec(e, "key_id", &self.key_id);
+            validate::uuid_vec(e, "user_key_id", &self.user_key_id);
+            validate::audit_data_filter_vec(e, "data", &self.data);
+            validate::text_vec(e, "remote_country", &self.remote_country);
+            validate::text_vec(e, "remote_city", &self.remote_city);
+            validate::text_vec(e, "user_agent_browser", &self.user_agent_browser);
+            validate::text_vec(e, "user_agent_os", &self.user_agent_os);
+            validate::text_vec(e, "user_agent_device", &self.user_agent_device);
         })
     }
 }
@@ -32,6 +50,30 @@ impl From<pb::AuditListRequest> for AuditList {
             subject: pb::string_vec_to_string_vec_opt(x.subject),
             service_id: pb::string_vec_to_uuid_vec_opt(x.service_id),
             user_id: pb::string_vec_to_uuid_vec_opt(x.user_id),
+            organisation_id: pb::string_vec_to_uuid_vec_opt(x.organisation_id),
+            status_code_ge: x.status_code_ge.map(|x| x as u16),
+            status_code_le: x.status_code_le.map(|x| x as u16),
+            remote: x.remote,
+            remote_cidr: pb::string_vec_to_cidr_vec_opt(x.remote_cidr),
+            forwarded: x.forwarded,
+            user_agent: x.user_agent,
+            key_id: pb::string_vec_to_uuid_vec_opt(x.key_id),
+            user_key_id: pb::string_vec_to_uuid_vec_opt(x.user_key_id),
+            data: if x.data.is_empty() {
+                None
+            } else {
+                Some(x.data.into_iter().map(|x| x.into()).collect())
+            },
+            remote_country: pb::string_vec_to_string_vec_opt(x.remote_country),
+            remote_city: pb::string_vec_to_string_vec_opt(x.remote_city),
+            remote_asn: if x.remote_asn.is_empty() {
+                None
+            } else {
+                Some(x.remote_asn)
+            },
+            user_agent_browser: pb::string_vec_to_string_vec_opt(x.user_agent_browser),
+            user_agent_os: pb::string_vec_to_string_vec_opt(x.user_agent_os),
+            user_agent_device: pb::string_vec_to_string_vec_opt(x.user_agent_device),
         };
         AuditList { query, filter }
     }
@@ -51,10 +93,10 @@ pub async fn list(
             AuditType::AuditList,
             |driver, audit| {
                 let service = pattern::key_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
 
                 driver
-                    .audit_list(&req, service.map(|s| s.id))
+                    .audit_list(&req, service.map(|s| s.id), audit.get_organisation_id())
                     .map_err(GrpcMethodError::BadRequest)
             },
         )?;
@@ -67,6 +109,238 @@ pub async fn list(
     })
 }
 
+impl validator::Validate for pb::AuditReportRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::audit_report_bucket(e, "bucket", self.bucket);
+            validate::audit_report_group_vec(e, "group", &self.group);
+            validate::audit_type_vec(e, "type", &self.r#type);
+            validate::uuid_vec(e, "service_id", &self.service_id);
+            validate::uuid_vec(e, "user_id", &self.user_id);
+            if e.is_empty() {
+                validate::audit_report_range(e, "ge", &self.clone().into());
+            }
+        })
+    }
+}
+
+impl From<pb::AuditReportRequest> for AuditReport {
+    fn from(x: pb::AuditReportRequest) -> Self {
+        let le = pb::timestamp_opt_to_datetime_opt(x.le).unwrap_or_else(Utc::now);
+        let ge = pb::timestamp_opt_to_datetime_opt(x.ge).unwrap_or(le - Duration::days(1));
+        AuditReport {
+            ge,
+            le,
+            bucket: AuditReportBucket::from_i32(x.bucket),
+            group: pb::i32_vec_to_audit_report_group_vec(x.group),
+            filter: AuditReportFilter {
+                type_: pb::string_vec_to_string_vec_opt(x.r#type),
+                service_id: pb::string_vec_to_uuid_vec_opt(x.service_id),
+                user_id: pb::string_vec_to_uuid_vec_opt(x.user_id),
+            },
+        }
+    }
+}
+
+pub async fn report(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<AuditReport>,
+) -> GrpcMethodResult<pb::AuditReportReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+    let driver = server.driver();
+
+    blocking_method(move || {
+        let data = audit_result_err(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::AuditReport,
+            |driver, audit| {
+                let service = pattern::key_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                driver
+                    .audit_report(&req, service.map(|s| s.id), audit.get_organisation_id())
+                    .map_err(GrpcMethodError::BadRequest)
+            },
+        )?;
+        Ok((data, req))
+    })
+    .await
+    .map(|(data, req)| pb::AuditReportReply {
+        meta: Some(req.into()),
+        data: data
+            .into_iter()
+            .map::<pb::AuditReportRow, _>(|x| x.into())
+            .collect(),
+    })
+}
+
+impl validator::Validate for pb::AuditWatchRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::uuid_opt(e, "offset_id", self.offset_id.as_ref().map(|x| &**x));
+            validate::audit_type_vec(e, "type", &self.r#type);
+            validate::audit_subject_vec(e, "subject", &self.subject);
+            validate::uuid_vec(e, "service_id", &self.service_id);
+            validate::uuid_vec(e, "user_id", &self.user_id);
+            validate::uuid_vec(e, "organisation_id", &self.organisation_id);
+        })
+    }
+}
+
+impl From<pb::AuditWatchRequest> for AuditWatch {
+    fn from(x: pb::AuditWatchRequest) -> Self {
+        let filter = AuditListFilter {
+            id: None,
+            type_: pb::string_vec_to_string_vec_opt(x.r#type),
+            subject: pb::string_vec_to_string_vec_opt(x.subject),
+            service_id: pb::string_vec_to_uuid_vec_opt(x.service_id),
+            user_id: pb::string_vec_to_uuid_vec_opt(x.user_id),
+            organisation_id: pb::string_vec_to_uuid_vec_opt(x.organisation_id),
+            ..AuditListFilter::default()
+        };
+        AuditWatch {
+            offset_id: pb::string_opt_to_uuid_opt(x.offset_id),
+            filter,
+        }
+    }
+}
+
+/// Audit watch receiver.
+///
+/// Logs are received until the receiver is dropped or an error occurs,
+/// errors are sent before the channel is closed.
+pub type AuditWatchReceiver = mpsc::Receiver<GrpcMethodResult<Audit>>;
+
+pub async fn watch(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<AuditWatch>,
+) -> GrpcMethodResult<AuditWatchReceiver> {
+    let (audit_meta, auth, req) = request.into_inner();
+    let interval = server.options().audit_watch_interval();
+
+    watch_spawn(server.driver(), interval, audit_meta, auth, req).await
+}
+
+/// Authenticate watch request and spawn task which polls for new logs.
+///
+/// Logs are filtered by the same service and organisation masks as list,
+/// the key is authenticated again before each poll so disabled or revoked
+/// keys stop receiving logs.
+pub(crate) async fn watch_spawn(
+    driver: Arc<Postgres>,
+    interval: Duration,
+    audit_meta: AuditMeta,
+    auth: HeaderAuth,
+    req: AuditWatch,
+) -> GrpcMethodResult<AuditWatchReceiver> {
+    let mut chain_seq = {
+        let driver = driver.clone();
+        let audit_meta = audit_meta.clone();
+        let auth = auth.clone();
+        let offset_id = req.offset_id;
+
+        blocking_method(move || {
+            audit_result_err(
+                driver.as_ref(),
+                audit_meta,
+                AuditType::AuditWatch,
+                |driver, audit| {
+                    let service = pattern::key_authenticate(driver, audit, &auth)
+                        .map_err(GrpcMethodError::authenticate)?;
+
+                    match offset_id {
+                        Some(offset_id) => {
+                            let offset = driver
+                                .audit_read(
+                                    &AuditRead::new(offset_id),
+                                    service.map(|x| x.id),
+                                    audit.get_organisation_id(),
+                                )
+                                .map_err(GrpcMethodError::BadRequest)?
+                                .ok_or_else(|| {
+                                    GrpcMethodError::NotFound(DriverError::AuditNotFound)
+                                })?;
+                            driver
+                                .audit_chain_seq(Some(&offset.id))
+                                .map_err(GrpcMethodError::BadRequest)?
+                                .ok_or_else(|| {
+                                    GrpcMethodError::NotFound(DriverError::AuditNotFound)
+                                })
+                        }
+                        None => driver
+                            .audit_chain_seq(None)
+                            .map(|x| x.unwrap_or(0))
+                            .map_err(GrpcMethodError::BadRequest),
+                    }
+                },
+            )
+        })
+        .await?
+    };
+
+    let interval = interval
+        .to_std()
+        .unwrap_or_else(|_e| std::time::Duration::from_secs(1));
+    // Poll in chain order, creation times are not in commit order so logs
+    // committed late would be skipped when polling by creation time.
+    let filter = Arc::new(req.filter);
+    let (mut tx, rx) = mpsc::channel(DEFAULT_LIMIT as usize);
+
+    tokio::spawn(async move {
+        loop {
+            let driver = driver.clone();
+            let audit_meta = audit_meta.clone();
+            let auth = auth.clone();
+            let filter = filter.clone();
+            let res = blocking_method(move || {
+                let mut audit = AuditBuilder::new(audit_meta, AuditType::AuditWatch);
+                let service = pattern::key_authenticate(driver.as_ref(), &mut audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                driver
+                    .audit_list_chain(
+                        chain_seq,
+                        DEFAULT_LIMIT,
+                        &filter,
+                        service.map(|s| s.id),
+                        audit.get_organisation_id(),
+                    )
+                    .map_err(GrpcMethodError::BadRequest)
+            })
+            .await;
+
+            let data = match res {
+                Ok(x) => x,
+                Err(e) => {
+                    let _ = tx.send(Err(e)).await;
+                    return;
+                }
+            };
+
+            let idle = (data.len() as i64) < DEFAULT_LIMIT;
+            if let Some((last, _)) = data.last() {
+                chain_seq = *last;
+            }
+            for (_, audit) in data {
+                if tx.send(Ok(audit)).await.is_err() {
+                    return;
+                }
+            }
+
+            if idle {
+                // Stop polling if receiver was dropped while there were no logs to send.
+                if future::poll_fn(|cx| tx.poll_ready(cx)).await.is_err() {
+                    return;
+                }
+                time::delay_for(interval).await;
+            }
+        }
+    });
+
+    Ok(rx)
+}
+
 impl validator::Validate for pb::AuditCreateRequest {
     fn validate(&self) -> Result<(), validator::ValidationErrors> {
         validate::wrap(|e| {
@@ -98,7 +372,7 @@ pub async fn create(
             AuditType::AuditCreate,
             |driver, audit| {
                 let _service = pattern::key_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
 
                 audit
                     .create2(driver, &req)
@@ -136,10 +410,10 @@ pub async fn read(
             AuditType::AuditRead,
             |driver, audit| {
                 let service = pattern::key_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
 
                 driver
-                    .audit_read(&req, service.map(|x| x.id))
+                    .audit_read(&req, service.map(|x| x.id), audit.get_organisation_id())
                     .map_err(GrpcMethodError::BadRequest)?
                     .ok_or_else(|| GrpcMethodError::NotFound(DriverError::AuditNotFound))
             },
@@ -175,7 +449,7 @@ pub async fn update(
             AuditType::AuditUpdate,
             |driver, audit| {
                 let service = pattern::key_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
 
                 driver
                     .audit_update(&req, service.map(|x| x.id))
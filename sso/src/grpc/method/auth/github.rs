@@ -57,8 +57,9 @@ pub async fn oauth2_callback(
 
     let driver = server.driver();
     let args = server.options().github_oauth2_args();
+    let email = server.smtp_email();
     blocking_method(move || {
-        audit_result_err(
+        let (user_token, template) = audit_result(
             driver.as_ref(),
             audit_meta,
             AuditType::AuthGithubOauth2Callback,
@@ -69,11 +70,16 @@ pub async fn oauth2_callback(
                     &service,
                     service_id,
                     user_email.clone(),
-                    args.access_token_expires,
-                    args.refresh_token_expires,
+                    &args,
                 )
             },
-        )
+        )?;
+        if let Some(template) = template {
+            email(template)
+                .map_err::<DriverError, _>(Into::into)
+                .map_err(GrpcMethodError::BadRequest)?;
+        }
+        Ok(user_token)
     })
     .await
     .map(|user_token| pb::AuthTokenReply {
@@ -99,7 +105,7 @@ mod provider_github {
         args: &ServerProviderOauth2Args,
     ) -> GrpcMethodResult<String> {
         let service =
-            key_service_authenticate(driver, audit, auth).map_err(GrpcMethodError::Unauthorised)?;
+            key_service_authenticate(driver, audit, auth).map_err(GrpcMethodError::authenticate)?;
 
         // Generate the authorisation URL to which we'll redirect the user.
         let client = new_client(&service, &args.provider).map_err(GrpcMethodError::BadRequest)?;
@@ -131,7 +137,7 @@ mod provider_github {
         request: &pb::AuthOauth2CallbackRequest,
     ) -> GrpcMethodResult<(Service, Uuid, String)> {
         let service =
-            key_service_authenticate(driver, audit, auth).map_err(GrpcMethodError::Unauthorised)?;
+            key_service_authenticate(driver, audit, auth).map_err(GrpcMethodError::authenticate)?;
 
         // Read the CSRF key using state value, rebuild code verifier from value.
         let conn = driver.conn().map_err(GrpcMethodError::BadRequest)?;
@@ -23,7 +23,7 @@ pub async fn verify(
             AuditType::AuthKeyVerify,
             |driver, audit| {
                 let service = pattern::key_service_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
 
                 // Key verify requires key key type.
                 let key = pattern::key_read_user_value_checked(
@@ -41,6 +41,13 @@ pub async fn verify(
                     key.user_id.unwrap(),
                 )
                 .map_err(GrpcMethodError::BadRequest)?;
+                let roles = driver
+                    .user_role_names(&service.id, &user.id)
+                    .map_err(GrpcMethodError::BadRequest)?;
+                let groups = driver
+                    .group_user_effective(&service.id, &user.id)
+                    .map_err(GrpcMethodError::BadRequest)?;
+                let groups: Vec<String> = groups.into_iter().map(|x| x.name).collect();
 
                 // Key verified.
                 // Optionally create custom audit log.
@@ -48,19 +55,21 @@ pub async fn verify(
                     let audit = audit
                         .create(driver, x, None, None)
                         .map_err(GrpcMethodError::BadRequest)?;
-                    Ok((user, key, Some(audit)))
+                    Ok((user, key, roles, groups, Some(audit)))
                 } else {
-                    Ok((user, key, None))
+                    Ok((user, key, roles, groups, None))
                 }
             },
         )
         .map_err(Into::into)
     })
     .await
-    .map(|(user, key, audit)| pb::AuthKeyReply {
+    .map(|(user, key, roles, groups, audit)| pb::AuthKeyReply {
         user: Some(user.into()),
         key: Some(key.into()),
         audit: pb::uuid_opt_to_string_opt(audit.map(|x| x.id)),
+        roles,
+        groups,
     })
 }
 
@@ -78,7 +87,7 @@ pub async fn revoke(
             AuditType::AuthKeyRevoke,
             |driver, audit| {
                 let service = pattern::key_service_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
 
                 // Key revoke requires key key type.
                 // Do not check key is enabled or not revoked.
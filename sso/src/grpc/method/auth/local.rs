@@ -1,4 +1,4 @@
-use crate::prelude::*;
+use crate::{grpc::method::auth::login_notify, prelude::*};
 
 impl validator::Validate for pb::AuthLoginRequest {
     fn validate(&self) -> Result<(), validator::ValidationErrors> {
@@ -25,14 +25,17 @@ pub async fn login(
     let driver = server.driver();
     let access_token_expires = server.options().access_token_expires();
     let refresh_token_expires = server.options().refresh_token_expires();
+    let revoke_token_expires = server.options().revoke_token_expires();
+    let login_notify_enabled = server.options().login_notify_enabled();
+    let email = server.smtp_email();
     blocking_method(move || {
-        let user_token = audit_result(
+        let (user_token, template) = audit_result(
             driver.as_ref(),
             audit_meta,
             AuditType::AuthLocalLogin,
             |driver, audit| {
                 let service = pattern::key_service_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
 
                 // Login requires token key type.
                 let user =
@@ -49,23 +52,47 @@ pub async fn login(
                     ));
                 }
 
+                // Forbidden if service requires verified email address.
+                service
+                    .check_user_email_verified(&user)
+                    .map_err(GrpcMethodError::Forbidden)?;
+
                 // Check user password.
                 user.password_check(&req.password)
                     .map_err(GrpcMethodError::BadRequest)?;
 
+                // Notify user if login is from a new device or location.
+                let template = if login_notify_enabled {
+                    login_notify(driver, audit, &service, &user, &key, revoke_token_expires)?
+                } else {
+                    None
+                };
+                pattern::user_login_update(driver, audit, &user, &key)
+                    .map_err(GrpcMethodError::BadRequest)?;
+
                 // Encode user token.
+                let (roles, attributes) = pattern::user_token_claims(driver, &service, &user)
+                    .map_err(GrpcMethodError::BadRequest)?;
                 let conn = driver.conn().map_err(GrpcMethodError::BadRequest)?;
-                Jwt::encode_user(
+                let user_token = Jwt::encode_user(
                     &conn,
                     &service,
                     user,
                     &key,
+                    roles,
+                    attributes,
                     access_token_expires,
                     refresh_token_expires,
                 )
-                .map_err(GrpcMethodError::BadRequest)
+                .map_err(GrpcMethodError::BadRequest)?;
+                Ok((user_token, template))
             },
         )?;
+        if let Some(template) = template {
+            email(template)
+                .map_err::<DriverError, _>(Into::into)
+                .map_err(GrpcMethodError::BadRequest)?;
+        }
         Ok((password_meta, user_token))
     })
     .await
@@ -77,6 +104,28 @@ pub async fn login(
     })
 }
 
+pub async fn login_revoke(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<pb::AuthTokenRequest>,
+) -> GrpcMethodResult<pb::AuthAuditReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::AuthLocalLoginRevoke,
+            |driver, audit| revoke_inner(driver, audit, &auth, &req),
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|audit| pb::AuthAuditReply {
+        audit: pb::uuid_opt_to_string_opt(audit.map(|x| x.id)),
+    })
+}
+
 impl validator::Validate for pb::AuthRegisterRequest {
     fn validate(&self) -> Result<(), validator::ValidationErrors> {
         validate::wrap(|e| {
@@ -104,7 +153,7 @@ pub async fn register(
             AuditType::AuthLocalRegister,
             |driver, audit| {
                 let service = pattern::key_service_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
                 // Bad request if service not allowed to register users.
                 if !service.user_allow_register {
                     return Err(GrpcMethodError::BadRequest(
@@ -114,7 +163,10 @@ pub async fn register(
 
                 // Get user by email if exists, else create now.
                 let user = driver
-                    .user_read(&UserRead::Email(req.email.to_owned()))
+                    .user_read(&UserRead::Email(
+                        req.email.to_owned(),
+                        service.organisation_id,
+                    ))
                     .map_err(GrpcMethodError::BadRequest)?;
                 let user = match user {
                     Some(user) => {
@@ -126,8 +178,9 @@ pub async fn register(
                     }
                     None => {
                         // Create user, is allowed to request password reset in case register token expires.
-                        let mut user_create =
-                            UserCreate::new(true, &req.name, &req.email).password_allow_reset(true);
+                        let mut user_create = UserCreate::new(true, &req.name, &req.email)
+                            .password_allow_reset(true)
+                            .organisation_id(service.organisation_id);
                         if let Some(locale) = &req.locale {
                             user_create = user_create.locale(locale);
                         }
@@ -207,7 +260,7 @@ pub async fn register_confirm(
             AuditType::AuthLocalRegisterConfirm,
             |driver, audit| {
                 let service = pattern::key_service_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
                 // Bad request if service not allowed to register users.
                 if !service.user_allow_register {
                     return Err(GrpcMethodError::BadRequest(
@@ -231,6 +284,11 @@ pub async fn register_confirm(
                 Jwt::decode_register(&conn, &service, &user, &key, &req.token)
                     .map_err(GrpcMethodError::BadRequest)?;
 
+                // Register token was sent to user email address, mark as verified.
+                driver
+                    .user_email_verify(&user.id)
+                    .map_err(GrpcMethodError::BadRequest)?;
+
                 // Encode revoke token.
                 let token = Jwt::encode_revoke(&conn, &service, &user, &key, revoke_token_expires)
                     .map_err(GrpcMethodError::BadRequest)?;
@@ -308,7 +366,7 @@ pub async fn reset_password(
             AuditType::AuthLocalResetPassword,
             |driver, audit| {
                 let service = pattern::key_service_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
 
                 // Reset password requires token key type.
                 let user =
@@ -380,7 +438,7 @@ pub async fn reset_password_confirm(
             AuditType::AuthLocalResetPasswordConfirm,
             |driver, audit| {
                 let service = pattern::key_service_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
 
                 // Unsafely decode token to get user identifier, used to read key for safe token decode.
                 let (user_id, _) = Jwt::decode_unsafe_user(&req.token, service.id)
@@ -471,16 +529,17 @@ pub async fn update_email(
     let (audit_meta, auth, req) = request.into_inner();
 
     let driver = server.driver();
+    let access_token_expires = server.options().access_token_expires();
     let revoke_token_expires = server.options().revoke_token_expires();
     let email = server.smtp_email();
     blocking_method(move || {
-        let template = audit_result(
+        let (template, template_confirm) = audit_result(
             driver.as_ref(),
             audit_meta,
             AuditType::AuthLocalUpdateEmail,
             |driver, audit| {
                 let service = pattern::key_service_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
 
                 // Update email requires token key type.
                 let user =
@@ -499,32 +558,107 @@ pub async fn update_email(
                 // Check user password.
                 user.password_check(&req.password)
                     .map_err(GrpcMethodError::BadRequest)?;
-                // Encode revoke token.
-                let conn = driver.conn().map_err(GrpcMethodError::BadRequest)?;
-                let token = Jwt::encode_revoke(&conn, &service, &user, &key, revoke_token_expires)
+                // Hold new email address as pending until confirmed.
+                driver
+                    .user_email_pending_update(&user.id, Some(&req.new_email))
                     .map_err(GrpcMethodError::BadRequest)?;
 
-                // Update user email.
-                let old_email = user.email.to_owned();
-                driver
-                    .user_update(&UserUpdate::new_email(user.id, &req.new_email))
+                // Encode revoke and update email tokens.
+                let conn = driver.conn().map_err(GrpcMethodError::BadRequest)?;
+                let token = Jwt::encode_revoke(&conn, &service, &user, &key, revoke_token_expires)
                     .map_err(GrpcMethodError::BadRequest)?;
-                let user =
-                    pattern::user_read_email_checked(driver, Some(&service), audit, &req.new_email)
+                let token_confirm =
+                    Jwt::encode_update_email(&conn, &service, &user, &key, access_token_expires)
                         .map_err(GrpcMethodError::BadRequest)?;
-                // Send update email email.
-                TemplateEmail::email_update_email(&service, &user, &old_email, &token, audit.meta())
-                    .map_err(GrpcMethodError::BadRequest)
+
+                // Send update email email to current address, and update email
+                // confirm email to new address.
+                let template = TemplateEmail::email_update_email(
+                    &service,
+                    &user,
+                    &req.new_email,
+                    &token,
+                    audit.meta(),
+                )
+                .map_err(GrpcMethodError::BadRequest)?;
+                let template_confirm = TemplateEmail::email_update_email_confirm(
+                    &service,
+                    &user,
+                    &req.new_email,
+                    &token_confirm,
+                    audit.meta(),
+                )
+                .map_err(GrpcMethodError::BadRequest)?;
+                Ok((template, template_confirm))
             },
         )?;
         email(template)
             .map_err::<DriverError, _>(Into::into)
             .map_err(GrpcMethodError::BadRequest)?;
+        email(template_confirm)
+            .map_err::<DriverError, _>(Into::into)
+            .map_err(GrpcMethodError::BadRequest)?;
         Ok(())
     })
     .await
 }
 
+pub async fn update_email_confirm(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<pb::AuthTokenRequest>,
+) -> GrpcMethodResult<pb::AuthAuditReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::AuthLocalUpdateEmailConfirm,
+            |driver, audit| {
+                let service = pattern::key_service_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                // Unsafely decode token to get user identifier, used to read key for safe token decode.
+                let (user_id, _) = Jwt::decode_unsafe_user(&req.token, service.id)
+                    .map_err(GrpcMethodError::BadRequest)?;
+
+                // Update email confirm requires token key type.
+                let user = pattern::user_read_id_checked(driver, Some(&service), audit, user_id)
+                    .map_err(GrpcMethodError::BadRequest)?;
+                let key =
+                    pattern::key_read_user_checked(driver, &service, audit, &user, KeyType::Token)
+                        .map_err(GrpcMethodError::BadRequest)?;
+
+                // Safely decode token with user key.
+                let conn = driver.conn().map_err(GrpcMethodError::BadRequest)?;
+                Jwt::decode_update_email(&conn, &service, &user, &key, &req.token)
+                    .map_err(GrpcMethodError::BadRequest)?;
+
+                // Replace user email address with pending email address.
+                driver
+                    .user_email_pending_confirm(&user.id)
+                    .map_err(GrpcMethodError::BadRequest)?;
+
+                // Optionally create custom audit log.
+                if let Some(x) = &req.audit {
+                    let audit = audit
+                        .create(driver, x, None, None)
+                        .map_err(GrpcMethodError::BadRequest)?;
+                    Ok(Some(audit))
+                } else {
+                    Ok(None)
+                }
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|audit| pb::AuthAuditReply {
+        audit: pb::uuid_opt_to_string_opt(audit.map(|x| x.id)),
+    })
+}
+
 pub async fn update_email_revoke(
     server: &GrpcServer,
     request: GrpcMethodRequest<pb::AuthTokenRequest>,
@@ -583,7 +717,7 @@ pub async fn update_password(
             AuditType::AuthLocalUpdatePassword,
             |driver, audit| {
                 let service = pattern::key_service_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
 
                 // Update password requires token key type.
                 let user =
@@ -651,6 +785,192 @@ pub async fn update_password_revoke(
     })
 }
 
+pub async fn invite_confirm(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<pb::AuthRegisterConfirmRequest>,
+) -> GrpcMethodResult<pb::AuthPasswordMetaReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let client = server.client();
+    let pwned_passwords = server.options().pwned_passwords_enabled();
+    let password_meta =
+        pattern::password_meta(client.as_ref(), pwned_passwords, req.password.clone())
+            .await
+            .map_err(GrpcMethodError::BadRequest)?;
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::AuthLocalInviteConfirm,
+            |driver, audit| {
+                let service = pattern::key_service_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                // Unsafely decode token to get user identifier, used to read key for safe token decode.
+                let (user_id, _) = Jwt::decode_unsafe_user(&req.token, service.id)
+                    .map_err(GrpcMethodError::BadRequest)?;
+
+                // Invite confirm requires token key type.
+                let user = pattern::user_read_id_invited(driver, &service, audit, user_id)
+                    .map_err(GrpcMethodError::BadRequest)?;
+                let key =
+                    pattern::key_read_user_checked(driver, &service, audit, &user, KeyType::Token)
+                        .map_err(GrpcMethodError::BadRequest)?;
+
+                // Safely decode token with user key.
+                let conn = driver.conn().map_err(GrpcMethodError::BadRequest)?;
+                Jwt::decode_invite(&conn, &service, &user, &key, &req.token)
+                    .map_err(GrpcMethodError::BadRequest)?;
+
+                // Update user password and allow reset flag if provided.
+                if let Some(password) = &req.password {
+                    let mut user_update = UserUpdate::new_password(user.id, password)
+                        .map_err(GrpcMethodError::BadRequest)?;
+                    if let Some(password_allow_reset) = req.password_allow_reset {
+                        user_update = user_update.set_password_allow_reset(password_allow_reset);
+                    }
+                    driver
+                        .user_update(&user_update)
+                        .map_err(GrpcMethodError::BadRequest)?;
+                }
+
+                // Invite token was sent to user email address, mark as verified.
+                driver
+                    .user_email_verify(&user.id)
+                    .map_err(GrpcMethodError::BadRequest)?;
+                // User is now an active member of service.
+                driver
+                    .service_user_update(&ServiceUserUpdate {
+                        service_id: service.id,
+                        user_id: user.id,
+                        status: ServiceUserStatus::Active,
+                    })
+                    .map_err(GrpcMethodError::BadRequest)
+            },
+        )?;
+        Ok(password_meta)
+    })
+    .await
+    .map(|password_meta| pb::AuthPasswordMetaReply {
+        meta: Some(password_meta.into()),
+    })
+}
+
+impl validator::Validate for pb::AuthVerifyEmailRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::email(e, "email", &self.email);
+        })
+    }
+}
+
+pub async fn verify_email(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<pb::AuthVerifyEmailRequest>,
+) -> GrpcMethodResult<()> {
+    let (audit_meta, auth, req) = request.into_inner();
+    let driver = server.driver();
+    let access_token_expires = server.options().access_token_expires();
+    let email = server.smtp_email();
+
+    blocking_method(move || {
+        let template = audit_result(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::AuthLocalVerifyEmail,
+            |driver, audit| {
+                let service = pattern::key_service_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                // Verify email requires token key type.
+                let user =
+                    pattern::user_read_email_checked(driver, Some(&service), audit, &req.email)
+                        .map_err(GrpcMethodError::BadRequest)?;
+                let key =
+                    pattern::key_read_user_checked(driver, &service, audit, &user, KeyType::Token)
+                        .map_err(GrpcMethodError::BadRequest)?;
+
+                // Encode verify email token.
+                let conn = driver.conn().map_err(GrpcMethodError::BadRequest)?;
+                let token =
+                    Jwt::encode_verify_email(&conn, &service, &user, &key, access_token_expires)
+                        .map_err(GrpcMethodError::BadRequest)?;
+                // Send verify email email.
+                TemplateEmail::email_verify_email(&service, &user, &token, audit.meta())
+                    .map_err(GrpcMethodError::BadRequest)
+            },
+        );
+        // Catch Err result so this function returns Ok to prevent the caller
+        // from inferring a users existence.
+        match template {
+            Ok(template) => email(template)
+                .map_err::<DriverError, _>(Into::into)
+                .map_err(GrpcMethodError::BadRequest)
+                .or_else(|_| Ok(())),
+            Err(_e) => Ok(()),
+        }
+    })
+    .await
+}
+
+pub async fn verify_email_confirm(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<pb::AuthTokenRequest>,
+) -> GrpcMethodResult<pb::AuthAuditReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::AuthLocalVerifyEmailConfirm,
+            |driver, audit| {
+                let service = pattern::key_service_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                // Unsafely decode token to get user identifier, used to read key for safe token decode.
+                let (user_id, _) = Jwt::decode_unsafe_user(&req.token, service.id)
+                    .map_err(GrpcMethodError::BadRequest)?;
+
+                // Verify email confirm requires token key type.
+                let user = pattern::user_read_id_checked(driver, Some(&service), audit, user_id)
+                    .map_err(GrpcMethodError::BadRequest)?;
+                let key =
+                    pattern::key_read_user_checked(driver, &service, audit, &user, KeyType::Token)
+                        .map_err(GrpcMethodError::BadRequest)?;
+
+                // Safely decode token with user key.
+                let conn = driver.conn().map_err(GrpcMethodError::BadRequest)?;
+                Jwt::decode_verify_email(&conn, &service, &user, &key, &req.token)
+                    .map_err(GrpcMethodError::BadRequest)?;
+
+                // Mark user email address as verified.
+                driver
+                    .user_email_verify(&user.id)
+                    .map_err(GrpcMethodError::BadRequest)?;
+
+                // Optionally create custom audit log.
+                if let Some(x) = &req.audit {
+                    let audit = audit
+                        .create(driver, x, None, None)
+                        .map_err(GrpcMethodError::BadRequest)?;
+                    Ok(Some(audit))
+                } else {
+                    Ok(None)
+                }
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|audit| pb::AuthAuditReply {
+        audit: pb::uuid_opt_to_string_opt(audit.map(|x| x.id)),
+    })
+}
+
 fn revoke_inner(
     driver: &Postgres,
     audit: &mut AuditBuilder,
@@ -658,7 +978,7 @@ fn revoke_inner(
     req: &pb::AuthTokenRequest,
 ) -> GrpcMethodResult<Option<Audit>> {
     let service = pattern::key_service_authenticate(driver, audit, auth)
-        .map_err(GrpcMethodError::Unauthorised)?;
+        .map_err(GrpcMethodError::authenticate)?;
 
     // Unsafely decode token to get user identifier, used to read key for safe token decode.
     let (user_id, _) =
@@ -676,10 +996,16 @@ fn revoke_inner(
     Jwt::decode_revoke(&conn, &service, &user, &key, &req.token)
         .map_err(GrpcMethodError::BadRequest)?;
 
-    // Disable user and disable and revoke all keys associated with user.
+    // Disable user, discard any pending email address change, and disable
+    // and revoke all keys associated with user.
     driver
         .user_update(&UserUpdate::new_id(user.id).set_is_enabled(false))
         .map_err(GrpcMethodError::BadRequest)?;
+    if user.email_pending.is_some() {
+        driver
+            .user_email_pending_update(&user.id, None)
+            .map_err(GrpcMethodError::BadRequest)?;
+    }
     driver
         .key_update_many(
             &user.id,
@@ -48,11 +48,12 @@ pub async fn oauth2_callback(
 
     let driver = server.driver();
     let args = server.options().microsoft_oauth2_args();
+    let email = server.smtp_email();
     blocking_method(move || {
-        audit_result_err(
+        let (user_token, template) = audit_result(
             driver.as_ref(),
             audit_meta,
-            AuditType::AuthGithubOauth2Callback,
+            AuditType::AuthMicrosoftOauth2Callback,
             |driver, audit| {
                 oauth2_login(
                     driver,
@@ -60,11 +61,16 @@ pub async fn oauth2_callback(
                     &service,
                     service_id,
                     user_email.clone(),
-                    args.access_token_expires,
-                    args.refresh_token_expires,
+                    &args,
                 )
             },
-        )
+        )?;
+        if let Some(template) = template {
+            email(template)
+                .map_err::<DriverError, _>(Into::into)
+                .map_err(GrpcMethodError::BadRequest)?;
+        }
+        Ok(user_token)
     })
     .await
     .map(|user_token| pb::AuthTokenReply {
@@ -92,7 +98,7 @@ mod provider_microsoft {
         args: &ServerProviderOauth2Args,
     ) -> GrpcMethodResult<String> {
         let service =
-            key_service_authenticate(driver, audit, auth).map_err(GrpcMethodError::Unauthorised)?;
+            key_service_authenticate(driver, audit, auth).map_err(GrpcMethodError::authenticate)?;
 
         // Microsoft Graph supports Proof Key for Code Exchange (PKCE - https://oauth.net/2/pkce/).
         // Create a PKCE code verifier and SHA-256 encode it as a code challenge.
@@ -132,7 +138,7 @@ mod provider_microsoft {
         request: &pb::AuthOauth2CallbackRequest,
     ) -> GrpcMethodResult<(Service, Uuid, String)> {
         let service =
-            key_service_authenticate(driver, audit, auth).map_err(GrpcMethodError::Unauthorised)?;
+            key_service_authenticate(driver, audit, auth).map_err(GrpcMethodError::authenticate)?;
 
         // Read the CSRF key using state value, rebuild code verifier from value.
         let conn = driver.conn().map_err(GrpcMethodError::BadRequest)?;
@@ -29,7 +29,7 @@ pub async fn totp_verify(
             AuditType::AuthTotp,
             |driver, audit| {
                 let service = pattern::key_service_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
                 // TOTP requires token key type.
                 let user = pattern::user_read_id_checked(
                     driver,
@@ -42,7 +42,7 @@ pub async fn totp_verify(
                     pattern::key_read_user_checked(driver, &service, audit, &user, KeyType::Totp)
                         .map_err(GrpcMethodError::BadRequest)?;
                 // Verify TOTP code.
-                pattern::totp_verify(&key.value, &req.totp).map_err(GrpcMethodError::BadRequest)
+                pattern::totp_verify(&key, &req.totp).map_err(GrpcMethodError::BadRequest)
             },
         )
         .map_err(Into::into)
@@ -65,7 +65,7 @@ pub async fn csrf_create(
             AuditType::AuthCsrfCreate,
             |driver, audit| {
                 let service = pattern::key_service_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
 
                 let conn = driver.conn().map_err(GrpcMethodError::BadRequest)?;
                 CsrfCreate::request(&conn, &req, service.id).map_err(GrpcMethodError::BadRequest)
@@ -91,7 +91,7 @@ pub async fn csrf_verify(
             AuditType::AuthCsrfVerify,
             |driver, audit| {
                 let service = pattern::key_service_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
 
                 let conn = driver.conn().map_err(GrpcMethodError::BadRequest)?;
                 CsrfVerify::request(&conn, &req, service.id).map_err(GrpcMethodError::BadRequest)
@@ -109,9 +109,8 @@ fn oauth2_login(
     service: &Service,
     service_id: Uuid,
     email: String,
-    access_token_expires: Duration,
-    refresh_token_expires: Duration,
-) -> GrpcMethodResult<UserToken> {
+    args: &ServerProviderOauth2Args,
+) -> GrpcMethodResult<(UserToken, Option<TemplateEmail>)> {
     // Check service making url and callback requests match.
     if service.id != service_id {
         return Err(GrpcMethodError::BadRequest(
@@ -125,15 +124,69 @@ fn oauth2_login(
     let key = pattern::key_read_user_checked(driver, &service, audit, &user, KeyType::Token)
         .map_err(GrpcMethodError::BadRequest)?;
 
+    // Forbidden if service requires verified email address.
+    service
+        .check_user_email_verified(&user)
+        .map_err(GrpcMethodError::Forbidden)?;
+
+    // Notify user if login is from a new device or location.
+    let template = if args.login_notify_enabled {
+        login_notify(
+            driver,
+            audit,
+            service,
+            &user,
+            &key,
+            args.revoke_token_expires,
+        )?
+    } else {
+        None
+    };
+    pattern::user_login_update(driver, audit, &user, &key).map_err(GrpcMethodError::BadRequest)?;
+
     // Encode user token.
+    let (roles, attributes) =
+        pattern::user_token_claims(driver, &service, &user).map_err(GrpcMethodError::BadRequest)?;
     let conn = driver.conn().map_err(GrpcMethodError::BadRequest)?;
-    Jwt::encode_user(
+    let user_token = Jwt::encode_user(
         &conn,
         &service,
         user,
         &key,
-        access_token_expires,
-        refresh_token_expires,
+        roles,
+        attributes,
+        args.access_token_expires,
+        args.refresh_token_expires,
     )
-    .map_err(GrpcMethodError::BadRequest)
+    .map_err(GrpcMethodError::BadRequest)?;
+    Ok((user_token, template))
+}
+
+/// Returns new sign in email if login fingerprint is new for user.
+/// Services without a local provider callback URL are skipped, as the
+/// revoke link would have nowhere to go.
+fn login_notify(
+    driver: &Postgres,
+    audit: &AuditBuilder,
+    service: &Service,
+    user: &User,
+    key: &KeyWithValue,
+    revoke_token_expires: Duration,
+) -> GrpcMethodResult<Option<TemplateEmail>> {
+    if service.provider_local_url.is_none() {
+        return Ok(None);
+    }
+    let fingerprint_new = pattern::user_login_fingerprint_new(driver, audit, user)
+        .map_err(GrpcMethodError::BadRequest)?;
+    if !fingerprint_new {
+        return Ok(None);
+    }
+
+    // Encode revoke token.
+    let conn = driver.conn().map_err(GrpcMethodError::BadRequest)?;
+    let token = Jwt::encode_revoke(&conn, service, user, key, revoke_token_expires)
+        .map_err(GrpcMethodError::BadRequest)?;
+    TemplateEmail::email_login_notify(service, user, &token, audit.meta())
+        .map(Some)
+        .map_err(GrpcMethodError::BadRequest)
 }
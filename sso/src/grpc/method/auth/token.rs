@@ -1,4 +1,4 @@
-use crate::prelude::*;
+use crate::{grpc::method, prelude::*};
 
 impl validator::Validate for pb::AuthTokenRequest {
     fn validate(&self) -> Result<(), validator::ValidationErrors> {
@@ -23,7 +23,7 @@ pub async fn verify(
             AuditType::AuthTokenVerify,
             |driver, audit| {
                 let service = pattern::key_service_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
 
                 // Unsafely decode token to get user identifier, used to read key for safe token decode.
                 let (user_id, _) = Jwt::decode_unsafe_user(&req.token, service.id)
@@ -36,9 +36,19 @@ pub async fn verify(
                     pattern::key_read_user_checked(driver, &service, audit, &user, KeyType::Token)
                         .map_err(GrpcMethodError::BadRequest)?;
 
-                // Safely decode token with user key.
-                let access_token_expires = Jwt::decode_access(&service, &user, &key, &req.token)
+                // Safely decode token with user key, impersonation tokens are allowed.
+                let access_token_expires = pattern::user_token_decode_access(
+                    driver, audit, &service, &user, &key, &req.token,
+                )
+                .map_err(GrpcMethodError::BadRequest)?;
+                let actor_key_id = audit.get_actor_key_id();
+                let roles = driver
+                    .user_role_names(&service.id, &user.id)
                     .map_err(GrpcMethodError::BadRequest)?;
+                let groups = driver
+                    .group_user_effective(&service.id, &user.id)
+                    .map_err(GrpcMethodError::BadRequest)?;
+                let groups: Vec<String> = groups.into_iter().map(|x| x.name).collect();
 
                 // Token verified.
                 let user_token = UserTokenAccess {
@@ -52,20 +62,25 @@ pub async fn verify(
                     let audit = audit
                         .create(driver, x, None, None)
                         .map_err(GrpcMethodError::BadRequest)?;
-                    Ok((user, user_token, Some(audit)))
+                    Ok((user, user_token, roles, groups, Some(audit), actor_key_id))
                 } else {
-                    Ok((user, user_token, None))
+                    Ok((user, user_token, roles, groups, None, actor_key_id))
                 }
             },
         )
         .map_err(Into::into)
     })
     .await
-    .map(|(user, token, audit)| pb::AuthTokenVerifyReply {
-        user: Some(user.into()),
-        access: Some(token.into()),
-        audit: pb::uuid_opt_to_string_opt(audit.map(|x| x.id)),
-    })
+    .map(
+        |(user, token, roles, groups, audit, actor_key_id)| pb::AuthTokenVerifyReply {
+            user: Some(user.into()),
+            access: Some(token.into()),
+            audit: pb::uuid_opt_to_string_opt(audit.map(|x| x.id)),
+            roles,
+            groups,
+            actor_key_id: pb::uuid_opt_to_string_opt(actor_key_id),
+        },
+    )
 }
 
 pub async fn refresh(
@@ -84,7 +99,7 @@ pub async fn refresh(
             AuditType::AuthTokenRefresh,
             |driver, audit| {
                 let service = pattern::key_service_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
 
                 // Unsafely decode token to get user identifier, used to read key for safe token decode.
                 let (user_id, _) = Jwt::decode_unsafe_user(&req.token, service.id)
@@ -103,11 +118,15 @@ pub async fn refresh(
                     .map_err(GrpcMethodError::BadRequest)?;
 
                 // Encode user token.
+                let (roles, attributes) = pattern::user_token_claims(driver, &service, &user)
+                    .map_err(GrpcMethodError::BadRequest)?;
                 let user_token = Jwt::encode_user(
                     &conn,
                     &service,
                     user,
                     &key,
+                    roles,
+                    attributes,
                     access_token_expires,
                     refresh_token_expires,
                 )
@@ -149,7 +168,7 @@ pub async fn revoke(
             AuditType::AuthTokenRevoke,
             |driver, audit| {
                 let service = pattern::key_service_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
 
                 // Unsafely decode token to get user identifier, used to read key for safe token decode.
                 let (user_id, token_type) = Jwt::decode_unsafe_user(&req.token, service.id)
@@ -201,3 +220,74 @@ pub async fn revoke(
         audit: pb::uuid_opt_to_string_opt(audit.map(|x| x.id)),
     })
 }
+
+impl validator::Validate for pb::AuthImpersonateRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::uuid(e, "user_id", &self.user_id);
+            validate::uuid(e, "service_id", &self.service_id);
+        })
+    }
+}
+
+pub async fn impersonate(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<pb::AuthImpersonateRequest>,
+) -> GrpcMethodResult<pb::AuthImpersonateReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    let access_token_expires = server.options().impersonate_token_expires();
+    blocking_method(move || {
+        audit_result(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::AuthImpersonate,
+            |driver, audit| {
+                pattern::key_root_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+                let actor_key_id = audit.get_key_id().unwrap();
+                audit.actor_key_id(Some(actor_key_id));
+
+                // Service and user must be in organisation of root key.
+                let read = ServiceRead::new(pb::string_to_uuid(req.service_id.clone()));
+                let service =
+                    method::service::read_inner(driver, &read, None, audit.get_organisation_id())?
+                        .check()
+                        .map_err(GrpcMethodError::BadRequest)?;
+                audit.service(Some(&service));
+                let user_id = pb::string_to_uuid(req.user_id.clone());
+                let read = UserRead::Id(user_id);
+                method::user::read_inner(driver, &read, audit.get_organisation_id())?;
+
+                // Impersonation requires user to be an active member of service
+                // with a token key.
+                let user = pattern::user_read_id_checked(driver, Some(&service), audit, user_id)
+                    .map_err(GrpcMethodError::BadRequest)?;
+                let key =
+                    pattern::key_read_user_checked(driver, &service, audit, &user, KeyType::Token)
+                        .map_err(GrpcMethodError::BadRequest)?;
+
+                // Encode impersonation access token.
+                let (roles, attributes) = pattern::user_token_claims(driver, &service, &user)
+                    .map_err(GrpcMethodError::BadRequest)?;
+                Jwt::encode_impersonate(
+                    &service,
+                    user,
+                    &key,
+                    actor_key_id,
+                    roles,
+                    attributes,
+                    access_token_expires,
+                )
+                .map_err(GrpcMethodError::BadRequest)
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|user_token| pb::AuthImpersonateReply {
+        user: Some(user_token.user.clone().into()),
+        access: Some(user_token.into()),
+    })
+}
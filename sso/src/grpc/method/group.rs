@@ -0,0 +1,412 @@
+use crate::prelude::*;
+
+impl validator::Validate for pb::GroupListRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::uuid_opt(e, "gt", self.gt.as_ref().map(|x| &**x));
+            validate::uuid_opt(e, "lt", self.lt.as_ref().map(|x| &**x));
+            validate::limit_opt(e, "limit", self.limit);
+            validate::uuid_vec(e, "id", &self.id);
+            validate::uuid_vec(e, "parent_id", &self.parent_id);
+            validate::uuid_vec(e, "service_id", &self.service_id);
+            validate::uuid_opt(e, "user_id", self.user_id.as_ref().map(|x| &**x));
+        })
+    }
+}
+
+pub async fn list(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<GroupList>,
+) -> GrpcMethodResult<pb::GroupListReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        let data = audit_result_err(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::GroupList,
+            |driver, audit| {
+                let service = pattern::key_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                driver
+                    .group_list(&req, service.map(|x| x.id), audit.get_organisation_id())
+                    .map_err(GrpcMethodError::BadRequest)
+            },
+        )?;
+        Ok((req, data))
+    })
+    .await
+    .map(|(req, data)| pb::GroupListReply {
+        meta: Some(req.into()),
+        data: data.into_iter().map::<pb::Group, _>(|x| x.into()).collect(),
+    })
+}
+
+impl validator::Validate for pb::GroupCreateRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::uuid(e, "service_id", &self.service_id);
+            validate::name(e, "name", &self.name);
+            validate::uuid_opt(e, "parent_id", self.parent_id.as_ref().map(|x| &**x));
+        })
+    }
+}
+
+pub async fn create(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<GroupCreate>,
+) -> GrpcMethodResult<pb::GroupReadReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result_subject(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::GroupCreate,
+            |driver, audit| {
+                let service = pattern::key_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                // Service keys can only create groups for their own service.
+                let read = ServiceRead::new(req.service_id);
+                super::service::read_inner(
+                    driver,
+                    &read,
+                    service.map(|x| x.id),
+                    audit.get_organisation_id(),
+                )?;
+
+                driver
+                    .group_create(&req)
+                    .map_err(GrpcMethodError::BadRequest)
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|data| pb::GroupReadReply {
+        data: Some(data.into()),
+    })
+}
+
+impl validator::Validate for pb::GroupReadRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::uuid(e, "id", &self.id);
+        })
+    }
+}
+
+pub async fn read(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<GroupRead>,
+) -> GrpcMethodResult<pb::GroupReadReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result_err(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::GroupRead,
+            |driver, audit| {
+                let service = pattern::key_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                read_inner(
+                    driver,
+                    &req,
+                    service.map(|x| x.id),
+                    audit.get_organisation_id(),
+                )
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|data| pb::GroupReadReply {
+        data: Some(data.into()),
+    })
+}
+
+impl validator::Validate for pb::GroupUpdateRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::uuid(e, "id", &self.id);
+            validate::name_opt(e, "name", self.name.as_ref().map(|x| &**x));
+            validate::uuid_opt(
+                e,
+                "parent",
+                self.parent
+                    .as_ref()
+                    .and_then(|x| x.id.as_ref())
+                    .map(|x| &**x),
+            );
+        })
+    }
+}
+
+pub async fn update(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<GroupUpdate>,
+) -> GrpcMethodResult<pb::GroupReadReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result_diff(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::GroupUpdate,
+            |driver, audit| {
+                let service = pattern::key_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                let read = GroupRead::new(req.id);
+                let previous_group = read_inner(
+                    driver,
+                    &read,
+                    service.map(|x| x.id),
+                    audit.get_organisation_id(),
+                )?;
+                let group = driver
+                    .group_update(&req)
+                    .map_err(GrpcMethodError::BadRequest)?;
+                Ok((previous_group, group))
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|data| pb::GroupReadReply {
+        data: Some(data.into()),
+    })
+}
+
+pub async fn delete(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<GroupRead>,
+) -> GrpcMethodResult<()> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result_subject(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::GroupDelete,
+            |driver, audit| {
+                let service = pattern::key_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                let group = read_inner(
+                    driver,
+                    &req,
+                    service.map(|x| x.id),
+                    audit.get_organisation_id(),
+                )?;
+                driver
+                    .group_delete(&group.id)
+                    .map_err(GrpcMethodError::BadRequest)
+                    .map(|_| group)
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|_data| ())
+}
+
+impl validator::Validate for pb::GroupUserRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::uuid(e, "group_id", &self.group_id);
+            validate::uuid(e, "user_id", &self.user_id);
+        })
+    }
+}
+
+pub async fn user_create(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<GroupUser>,
+) -> GrpcMethodResult<()> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result_subject(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::GroupUserCreate,
+            |driver, audit| {
+                let group = user_inner(driver, audit, &auth, &req)?;
+                driver
+                    .group_user_create(&req)
+                    .map_err(GrpcMethodError::BadRequest)
+                    .map(|_| group)
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|_data| ())
+}
+
+pub async fn user_delete(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<GroupUser>,
+) -> GrpcMethodResult<()> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result_subject(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::GroupUserDelete,
+            |driver, audit| {
+                let group = user_inner(driver, audit, &auth, &req)?;
+                driver
+                    .group_user_delete(&req)
+                    .map_err(GrpcMethodError::BadRequest)
+                    .map(|_| group)
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|_data| ())
+}
+
+impl validator::Validate for pb::GroupRoleRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::uuid(e, "group_id", &self.group_id);
+            validate::uuid(e, "role_id", &self.role_id);
+        })
+    }
+}
+
+pub async fn role_create(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<GroupRole>,
+) -> GrpcMethodResult<()> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result_subject(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::GroupRoleCreate,
+            |driver, audit| {
+                let group = role_inner(driver, audit, &auth, &req)?;
+                driver
+                    .group_role_create(&req)
+                    .map_err(GrpcMethodError::BadRequest)
+                    .map(|_| group)
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|_data| ())
+}
+
+pub async fn role_delete(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<GroupRole>,
+) -> GrpcMethodResult<()> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result_subject(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::GroupRoleDelete,
+            |driver, audit| {
+                let group = role_inner(driver, audit, &auth, &req)?;
+                driver
+                    .group_role_delete(&req)
+                    .map_err(GrpcMethodError::BadRequest)
+                    .map(|_| group)
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|_data| ())
+}
+
+/// Read group, groups are masked to service of service key and to
+/// organisation of root key by organisation of service.
+fn read_inner(
+    driver: &Postgres,
+    read: &GroupRead,
+    service_id: Option<Uuid>,
+    organisation_id: Option<Uuid>,
+) -> GrpcMethodResult<Group> {
+    driver
+        .group_read(read, service_id)
+        .map_err(GrpcMethodError::BadRequest)?
+        .filter(|x| {
+            pattern::service_organisation_mask_check(driver, organisation_id, x.service_id).is_ok()
+        })
+        .ok_or_else(|| DriverError::GroupNotFound)
+        .map_err(GrpcMethodError::NotFound)
+}
+
+/// Authenticate and read group and user of membership.
+fn user_inner(
+    driver: &Postgres,
+    audit: &mut AuditBuilder,
+    auth: &HeaderAuth,
+    group_user: &GroupUser,
+) -> GrpcMethodResult<Group> {
+    let service =
+        pattern::key_authenticate(driver, audit, auth).map_err(GrpcMethodError::authenticate)?;
+
+    let read = GroupRead::new(group_user.group_id);
+    let group = read_inner(
+        driver,
+        &read,
+        service.map(|x| x.id),
+        audit.get_organisation_id(),
+    )?;
+    let read = UserRead::Id(group_user.user_id);
+    let user = super::user::read_inner(driver, &read, audit.get_organisation_id())?;
+    audit.user(Some(&user));
+    Ok(group)
+}
+
+/// Authenticate and read group and role of assignment, roles can only be
+/// assigned to groups of the same service.
+fn role_inner(
+    driver: &Postgres,
+    audit: &mut AuditBuilder,
+    auth: &HeaderAuth,
+    group_role: &GroupRole,
+) -> GrpcMethodResult<Group> {
+    let service =
+        pattern::key_authenticate(driver, audit, auth).map_err(GrpcMethodError::authenticate)?;
+
+    let read = GroupRead::new(group_role.group_id);
+    let group = read_inner(
+        driver,
+        &read,
+        service.map(|x| x.id),
+        audit.get_organisation_id(),
+    )?;
+    let read = RoleRead::new(group_role.role_id);
+    super::role::read_inner(
+        driver,
+        &read,
+        Some(group.service_id),
+        audit.get_organisation_id(),
+    )?;
+    Ok(group)
+}
@@ -28,10 +28,10 @@ pub async fn list(
             AuditType::KeyList,
             |driver, audit| {
                 let service = pattern::key_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
 
                 driver
-                    .key_list(&req, service.map(|s| s.id))
+                    .key_list(&req, service.map(|s| s.id), audit.get_organisation_id())
                     .map_err(GrpcMethodError::BadRequest)
             },
         )?;
@@ -51,6 +51,8 @@ impl validator::Validate for pb::KeyCreateRequest {
             validate::name(e, "name", &self.name);
             validate::uuid_opt(e, "service_id", self.service_id.as_ref().map(|x| &**x));
             validate::uuid_opt(e, "user_id", self.user_id.as_ref().map(|x| &**x));
+            validate::key_scope_vec(e, "scope", &self.scope);
+            validate::cidr_vec(e, "allow_remote", &self.allow_remote);
         })
     }
 }
@@ -71,31 +73,49 @@ pub async fn create(
                 // If service ID is some, root key is required to create service keys.
                 match req.service_id {
                     Some(service_id) => {
-                        pattern::key_root_authenticate(driver, audit, &auth)
-                            .map_err(GrpcMethodError::Unauthorised)
-                            .and_then(|_| {
-                                match req.user_id {
-                                    // User ID is defined, creating user key for service.
-                                    Some(user_id) => driver.key_create(&KeyCreate::user(
-                                        req.is_enabled,
-                                        req.type_,
-                                        &req.name,
-                                        service_id,
-                                        user_id,
-                                    )),
-                                    // Creating service key.
-                                    None => driver.key_create(&KeyCreate::service(
-                                        req.is_enabled,
-                                        &req.name,
-                                        service_id,
-                                    )),
-                                }
-                                .map_err(GrpcMethodError::BadRequest)
-                            })
+                        let root_key = pattern::key_root_authenticate(driver, audit, &auth)
+                            .map_err(GrpcMethodError::authenticate)?;
+
+                        // Service and user must be in organisation of root key.
+                        let read = ServiceRead::new(service_id);
+                        super::service::read_inner(
+                            driver,
+                            &read,
+                            None,
+                            audit.get_organisation_id(),
+                        )?;
+                        if let Some(user_id) = req.user_id {
+                            let read = UserRead::Id(user_id);
+                            super::user::read_inner(driver, &read, audit.get_organisation_id())?;
+                        }
+
+                        match req.user_id {
+                            // User ID is defined, creating user key for service.
+                            Some(user_id) => driver.key_create(&KeyCreate::user(
+                                req.is_enabled,
+                                req.type_,
+                                &req.name,
+                                service_id,
+                                user_id,
+                            )),
+                            // Creating service key, scoped root keys can only
+                            // create service keys within their scope.
+                            None => {
+                                root_key
+                                    .check_scope_create(&req.scope)
+                                    .map_err(GrpcMethodError::Forbidden)?;
+                                driver.key_create(
+                                    &KeyCreate::service(req.is_enabled, &req.name, service_id)
+                                        .scope(req.scope.clone())
+                                        .allow_remote(req.allow_remote.clone()),
+                                )
+                            }
+                        }
+                        .map_err(GrpcMethodError::BadRequest)
                     }
                     None => {
                         pattern::key_service_authenticate(driver, audit, &auth)
-                            .map_err(GrpcMethodError::Unauthorised)
+                            .map_err(GrpcMethodError::authenticate)
                             .and_then(|service| {
                                 match req.user_id {
                                     // User ID is defined, creating user key for service.
@@ -146,9 +166,9 @@ pub async fn read(
             AuditType::KeyRead,
             |driver, audit| {
                 let service = pattern::key_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
 
-                read_inner(driver, &req, service.as_ref())
+                read_inner(driver, &req, service.as_ref(), audit.get_organisation_id())
             },
         )
         .map_err(Into::into)
@@ -182,10 +202,11 @@ pub async fn update(
             AuditType::KeyUpdate,
             |driver, audit| {
                 let service = pattern::key_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
 
                 let read = KeyRead::IdUser(req.id, None);
-                let previous_key = read_inner(driver, &read, service.as_ref())?;
+                let previous_key =
+                    read_inner(driver, &read, service.as_ref(), audit.get_organisation_id())?;
                 let key = driver
                     .key_update(&KeyUpdate {
                         id: req.id,
@@ -205,6 +226,68 @@ pub async fn update(
     })
 }
 
+impl validator::Validate for pb::KeyRotateRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::uuid(e, "id", &self.id);
+            validate::name_opt(e, "name", self.name.as_ref().map(|x| &**x));
+            validate::key_grace_period_s_opt(e, "grace_period_s", self.grace_period_s);
+        })
+    }
+}
+
+pub async fn rotate(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<KeyRotate>,
+) -> GrpcMethodResult<pb::KeyCreateReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result_diff(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::KeyRotate,
+            |driver, audit| {
+                let service = pattern::key_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                // Root key is required to rotate root and service keys.
+                let read = KeyRead::IdUser(req.id, None);
+                let key = read_inner(driver, &read, service.as_ref(), audit.get_organisation_id())?;
+                if key.user_id.is_none() {
+                    if service.is_some() {
+                        return Err(GrpcMethodError::BadRequest(
+                            DriverError::ServiceCannotCreateServiceKey,
+                        ));
+                    }
+
+                    // Successor inherits scope of key, scoped root keys can
+                    // only rotate root and service keys within their scope.
+                    let root_key_id = audit
+                        .get_key_id()
+                        .ok_or_else(|| DriverError::KeyUndefined)
+                        .map_err(GrpcMethodError::Unauthorised)?;
+                    driver
+                        .key_read(&KeyRead::RootId(root_key_id), None)
+                        .map_err(GrpcMethodError::BadRequest)?
+                        .ok_or_else(|| DriverError::KeyNotFound)
+                        .map_err(GrpcMethodError::Unauthorised)?
+                        .check_scope_create(&key.scope)
+                        .map_err(GrpcMethodError::Forbidden)?;
+                }
+
+                driver.key_rotate(&req).map_err(GrpcMethodError::BadRequest)
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|data| pb::KeyCreateReply {
+        data: Some(data.into()),
+    })
+}
+
 pub async fn delete(
     server: &GrpcServer,
     request: GrpcMethodRequest<KeyRead>,
@@ -219,9 +302,9 @@ pub async fn delete(
             AuditType::KeyDelete,
             |driver, audit| {
                 let service = pattern::key_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
 
-                let key = read_inner(driver, &req, service.as_ref())?;
+                let key = read_inner(driver, &req, service.as_ref(), audit.get_organisation_id())?;
                 driver
                     .key_delete(&key.id)
                     .map_err(GrpcMethodError::BadRequest)
@@ -234,14 +317,25 @@ pub async fn delete(
     .map(|_data| ())
 }
 
+/// Read key, keys are masked to service of service key and to organisation
+/// of root key, keys of services are masked by organisation of service.
 fn read_inner(
     driver: &Postgres,
     read: &KeyRead,
     service: Option<&Service>,
+    organisation_id: Option<Uuid>,
 ) -> GrpcMethodResult<Key> {
-    driver
+    let key: Key = driver
         .key_read(&read, service.map(|x| x.id))
         .map_err(GrpcMethodError::BadRequest)?
-        .ok_or_else(|| GrpcMethodError::NotFound(DriverError::KeyNotFound))
-        .map(|x| x.into())
+        .ok_or_else(|| GrpcMethodError::NotFound(DriverError::KeyNotFound))?
+        .into();
+    match key.service_id {
+        Some(service_id) => {
+            pattern::service_organisation_mask_check(driver, organisation_id, service_id)
+        }
+        None => pattern::organisation_mask_check(organisation_id, key.organisation_id),
+    }
+    .map_err(|_e| GrpcMethodError::NotFound(DriverError::KeyNotFound))?;
+    Ok(key)
 }
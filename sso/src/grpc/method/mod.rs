@@ -1,5 +1,9 @@
 pub mod audit;
 pub mod auth;
+pub mod group;
 pub mod key;
+pub mod organisation;
+pub mod role;
 pub mod service;
 pub mod user;
+pub mod webhook;
@@ -0,0 +1,211 @@
+use crate::prelude::*;
+
+impl validator::Validate for pb::OrganisationListRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::uuid_opt(e, "gt", self.gt.as_ref().map(|x| &**x));
+            validate::uuid_opt(e, "lt", self.lt.as_ref().map(|x| &**x));
+            validate::limit_opt(e, "limit", self.limit);
+            validate::uuid_vec(e, "id", &self.id);
+        })
+    }
+}
+
+pub async fn list(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<OrganisationList>,
+) -> GrpcMethodResult<pb::OrganisationListReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        let data = audit_result_err(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::OrganisationList,
+            |driver, audit| {
+                pattern::key_root_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                driver
+                    .organisation_list(&req, audit.get_organisation_id())
+                    .map_err(GrpcMethodError::BadRequest)
+            },
+        )?;
+        Ok((req, data))
+    })
+    .await
+    .map(|(req, data)| pb::OrganisationListReply {
+        meta: Some(req.into()),
+        data: data
+            .into_iter()
+            .map::<pb::Organisation, _>(|x| x.into())
+            .collect(),
+    })
+}
+
+impl validator::Validate for pb::OrganisationCreateRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::name(e, "name", &self.name);
+        })
+    }
+}
+
+pub async fn create(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<OrganisationCreate>,
+) -> GrpcMethodResult<pb::OrganisationReadReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result_subject(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::OrganisationCreate,
+            |driver, audit| {
+                unscoped_inner(driver, audit, &auth)?;
+
+                driver
+                    .organisation_create(&req)
+                    .map_err(GrpcMethodError::BadRequest)
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|data| pb::OrganisationReadReply {
+        data: Some(data.into()),
+    })
+}
+
+impl validator::Validate for pb::OrganisationReadRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::uuid(e, "id", &self.id);
+        })
+    }
+}
+
+pub async fn read(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<OrganisationRead>,
+) -> GrpcMethodResult<pb::OrganisationReadReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result_err(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::OrganisationRead,
+            |driver, audit| {
+                pattern::key_root_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                read_inner(driver, &req, audit.get_organisation_id())
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|data| pb::OrganisationReadReply {
+        data: Some(data.into()),
+    })
+}
+
+impl validator::Validate for pb::OrganisationUpdateRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::uuid(e, "id", &self.id);
+            validate::name_opt(e, "name", self.name.as_ref().map(|x| &**x));
+        })
+    }
+}
+
+pub async fn update(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<OrganisationUpdate>,
+) -> GrpcMethodResult<pb::OrganisationReadReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result_diff(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::OrganisationUpdate,
+            |driver, audit| {
+                pattern::key_root_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                let read = OrganisationRead::new(req.id);
+                let previous_organisation = read_inner(driver, &read, audit.get_organisation_id())?;
+                let organisation = driver
+                    .organisation_update(&req)
+                    .map_err(GrpcMethodError::BadRequest)?;
+                Ok((previous_organisation, organisation))
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|data| pb::OrganisationReadReply {
+        data: Some(data.into()),
+    })
+}
+
+pub async fn delete(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<OrganisationRead>,
+) -> GrpcMethodResult<()> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result_subject(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::OrganisationDelete,
+            |driver, audit| {
+                unscoped_inner(driver, audit, &auth)?;
+
+                let organisation = read_inner(driver, &req, None)?;
+                driver
+                    .organisation_delete(&organisation.id)
+                    .map_err(GrpcMethodError::BadRequest)
+                    .map(|_| organisation)
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|_data| ())
+}
+
+/// Authenticate root key which is not scoped to an organisation.
+fn unscoped_inner(
+    driver: &Postgres,
+    audit: &mut AuditBuilder,
+    auth: &HeaderAuth,
+) -> GrpcMethodResult<()> {
+    pattern::key_root_authenticate(driver, audit, auth).map_err(GrpcMethodError::authenticate)?;
+    match audit.get_organisation_id() {
+        Some(_) => Err(GrpcMethodError::Forbidden(DriverError::KeyScopeForbidden)),
+        None => Ok(()),
+    }
+}
+
+fn read_inner(
+    driver: &Postgres,
+    read: &OrganisationRead,
+    organisation_id: Option<Uuid>,
+) -> GrpcMethodResult<Organisation> {
+    driver
+        .organisation_read(read)
+        .map_err(GrpcMethodError::BadRequest)?
+        .filter(|x| pattern::organisation_mask_check(organisation_id, Some(x.id)).is_ok())
+        .ok_or_else(|| DriverError::OrganisationNotFound)
+        .map_err(GrpcMethodError::NotFound)
+}
@@ -0,0 +1,314 @@
+use crate::prelude::*;
+
+impl validator::Validate for pb::RoleListRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::uuid_opt(e, "gt", self.gt.as_ref().map(|x| &**x));
+            validate::uuid_opt(e, "lt", self.lt.as_ref().map(|x| &**x));
+            validate::limit_opt(e, "limit", self.limit);
+            validate::uuid_vec(e, "id", &self.id);
+            validate::uuid_vec(e, "service_id", &self.service_id);
+            validate::uuid_opt(e, "user_id", self.user_id.as_ref().map(|x| &**x));
+        })
+    }
+}
+
+pub async fn list(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<RoleList>,
+) -> GrpcMethodResult<pb::RoleListReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        let data = audit_result_err(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::RoleList,
+            |driver, audit| {
+                let service = pattern::key_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                driver
+                    .role_list(&req, service.map(|x| x.id), audit.get_organisation_id())
+                    .map_err(GrpcMethodError::BadRequest)
+            },
+        )?;
+        Ok((req, data))
+    })
+    .await
+    .map(|(req, data)| pb::RoleListReply {
+        meta: Some(req.into()),
+        data: data.into_iter().map::<pb::Role, _>(|x| x.into()).collect(),
+    })
+}
+
+impl validator::Validate for pb::RoleCreateRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::uuid(e, "service_id", &self.service_id);
+            validate::role_name(e, "name", &self.name);
+        })
+    }
+}
+
+pub async fn create(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<RoleCreate>,
+) -> GrpcMethodResult<pb::RoleReadReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result_subject(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::RoleCreate,
+            |driver, audit| {
+                let service = pattern::key_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                // Service keys can only create roles for their own service.
+                let read = ServiceRead::new(req.service_id);
+                super::service::read_inner(
+                    driver,
+                    &read,
+                    service.map(|x| x.id),
+                    audit.get_organisation_id(),
+                )?;
+
+                driver
+                    .role_create(&req)
+                    .map_err(GrpcMethodError::BadRequest)
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|data| pb::RoleReadReply {
+        data: Some(data.into()),
+    })
+}
+
+impl validator::Validate for pb::RoleReadRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::uuid(e, "id", &self.id);
+        })
+    }
+}
+
+pub async fn read(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<RoleRead>,
+) -> GrpcMethodResult<pb::RoleReadReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result_err(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::RoleRead,
+            |driver, audit| {
+                let service = pattern::key_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                read_inner(
+                    driver,
+                    &req,
+                    service.map(|x| x.id),
+                    audit.get_organisation_id(),
+                )
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|data| pb::RoleReadReply {
+        data: Some(data.into()),
+    })
+}
+
+impl validator::Validate for pb::RoleUpdateRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::uuid(e, "id", &self.id);
+            validate::role_name_opt(e, "name", self.name.as_ref().map(|x| &**x));
+        })
+    }
+}
+
+pub async fn update(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<RoleUpdate>,
+) -> GrpcMethodResult<pb::RoleReadReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result_diff(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::RoleUpdate,
+            |driver, audit| {
+                let service = pattern::key_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                let read = RoleRead::new(req.id);
+                let previous_role = read_inner(
+                    driver,
+                    &read,
+                    service.map(|x| x.id),
+                    audit.get_organisation_id(),
+                )?;
+                let role = driver
+                    .role_update(&req)
+                    .map_err(GrpcMethodError::BadRequest)?;
+                Ok((previous_role, role))
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|data| pb::RoleReadReply {
+        data: Some(data.into()),
+    })
+}
+
+pub async fn delete(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<RoleRead>,
+) -> GrpcMethodResult<()> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result_subject(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::RoleDelete,
+            |driver, audit| {
+                let service = pattern::key_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                let role = read_inner(
+                    driver,
+                    &req,
+                    service.map(|x| x.id),
+                    audit.get_organisation_id(),
+                )?;
+                driver
+                    .role_delete(&role.id)
+                    .map_err(GrpcMethodError::BadRequest)
+                    .map(|_| role)
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|_data| ())
+}
+
+impl validator::Validate for pb::UserRoleRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::uuid(e, "user_id", &self.user_id);
+            validate::uuid(e, "role_id", &self.role_id);
+        })
+    }
+}
+
+pub async fn user_create(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<UserRole>,
+) -> GrpcMethodResult<()> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result_subject(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::UserRoleCreate,
+            |driver, audit| {
+                let role = user_inner(driver, audit, &auth, &req)?;
+                driver
+                    .user_role_create(&req)
+                    .map_err(GrpcMethodError::BadRequest)
+                    .map(|_| role)
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|_data| ())
+}
+
+pub async fn user_delete(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<UserRole>,
+) -> GrpcMethodResult<()> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result_subject(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::UserRoleDelete,
+            |driver, audit| {
+                let role = user_inner(driver, audit, &auth, &req)?;
+                driver
+                    .user_role_delete(&req)
+                    .map_err(GrpcMethodError::BadRequest)
+                    .map(|_| role)
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|_data| ())
+}
+
+/// Read role, roles are masked to service of service key and to
+/// organisation of root key by organisation of service.
+pub(crate) fn read_inner(
+    driver: &Postgres,
+    read: &RoleRead,
+    service_id: Option<Uuid>,
+    organisation_id: Option<Uuid>,
+) -> GrpcMethodResult<Role> {
+    driver
+        .role_read(read, service_id)
+        .map_err(GrpcMethodError::BadRequest)?
+        .filter(|x| {
+            pattern::service_organisation_mask_check(driver, organisation_id, x.service_id).is_ok()
+        })
+        .ok_or_else(|| DriverError::RoleNotFound)
+        .map_err(GrpcMethodError::NotFound)
+}
+
+/// Authenticate and read role and user of assignment, service keys can only
+/// assign roles of their own service.
+fn user_inner(
+    driver: &Postgres,
+    audit: &mut AuditBuilder,
+    auth: &HeaderAuth,
+    user_role: &UserRole,
+) -> GrpcMethodResult<Role> {
+    let service =
+        pattern::key_authenticate(driver, audit, auth).map_err(GrpcMethodError::authenticate)?;
+
+    let read = RoleRead::new(user_role.role_id);
+    let role = read_inner(
+        driver,
+        &read,
+        service.map(|x| x.id),
+        audit.get_organisation_id(),
+    )?;
+    let read = UserRead::Id(user_role.user_id);
+    let user = super::user::read_inner(driver, &read, audit.get_organisation_id())?;
+    audit.user(Some(&user));
+    Ok(role)
+}
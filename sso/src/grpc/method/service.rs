@@ -7,6 +7,7 @@ impl validator::Validate for pb::ServiceListRequest {
             validate::uuid_opt(e, "lt", self.lt.as_ref().map(|x| &**x));
             validate::limit_opt(e, "limit", self.limit);
             validate::uuid_vec(e, "id", &self.id);
+            validate::uuid_vec(e, "organisation_id", &self.organisation_id);
         })
     }
 }
@@ -25,10 +26,10 @@ pub async fn list(
             AuditType::ServiceList,
             |driver, audit| {
                 let service = pattern::key_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
 
                 driver
-                    .service_list(&req, service.map(|x| x.id))
+                    .service_list(&req, service.map(|x| x.id), audit.get_organisation_id())
                     .map_err(GrpcMethodError::BadRequest)
             },
         )?;
@@ -69,6 +70,13 @@ impl validator::Validate for pb::ServiceCreateRequest {
                 "provider_microsoft_oauth2_url",
                 self.provider_microsoft_oauth2_url.as_ref().map(|x| &**x),
             );
+            validate::cidr_vec(e, "allow_remote", &self.allow_remote);
+            validate::user_claim_vec(e, "user_claims", &self.user_claims);
+            validate::uuid_opt(
+                e,
+                "organisation_id",
+                self.organisation_id.as_ref().map(|x| &**x),
+            );
         })
     }
 }
@@ -87,7 +95,9 @@ pub async fn create(
             AuditType::ServiceCreate,
             |driver, audit| {
                 pattern::key_root_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
+                pattern::organisation_mask_check(audit.get_organisation_id(), req.organisation_id)
+                    .map_err(GrpcMethodError::NotFound)?;
 
                 driver
                     .service_create(&req)
@@ -124,9 +134,14 @@ pub async fn read(
             AuditType::ServiceRead,
             |driver, audit| {
                 let service = pattern::key_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
 
-                read_inner(driver, &req, service.map(|x| x.id))
+                read_inner(
+                    driver,
+                    &req,
+                    service.map(|x| x.id),
+                    audit.get_organisation_id(),
+                )
             },
         )
         .map_err(Into::into)
@@ -163,6 +178,16 @@ impl validator::Validate for pb::ServiceUpdateRequest {
                 "provider_microsoft_oauth2_url",
                 self.provider_microsoft_oauth2_url.as_ref().map(|x| &**x),
             );
+            validate::cidr_vec_opt(
+                e,
+                "allow_remote",
+                self.allow_remote.as_ref().map(|x| &*x.cidr),
+            );
+            validate::user_claim_vec_opt(
+                e,
+                "user_claims",
+                self.user_claims.as_ref().map(|x| &*x.key),
+            );
         })
     }
 }
@@ -181,10 +206,15 @@ pub async fn update(
             AuditType::ServiceUpdate,
             |driver, audit| {
                 let service = pattern::key_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
 
                 let read = ServiceRead::new(req.id);
-                let previous_service = read_inner(driver, &read, service.map(|x| x.id))?;
+                let previous_service = read_inner(
+                    driver,
+                    &read,
+                    service.map(|x| x.id),
+                    audit.get_organisation_id(),
+                )?;
                 let service = driver
                     .service_update(&req)
                     .map_err(GrpcMethodError::BadRequest)?;
@@ -213,9 +243,14 @@ pub async fn delete(
             AuditType::ServiceDelete,
             |driver, audit| {
                 let service = pattern::key_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
 
-                let service = read_inner(driver, &req, service.map(|x| x.id))?;
+                let service = read_inner(
+                    driver,
+                    &req,
+                    service.map(|x| x.id),
+                    audit.get_organisation_id(),
+                )?;
                 driver
                     .service_delete(&service.id)
                     .map_err(GrpcMethodError::BadRequest)
@@ -228,14 +263,128 @@ pub async fn delete(
     .map(|_data| ())
 }
 
-fn read_inner(
+impl validator::Validate for pb::ServiceUserListRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::uuid(e, "service_id", &self.service_id);
+            validate::uuid_opt(e, "gt", self.gt.as_ref().map(|x| &**x));
+            validate::uuid_opt(e, "lt", self.lt.as_ref().map(|x| &**x));
+            validate::limit_opt(e, "limit", self.limit);
+            validate::service_user_status_vec(e, "status", &self.status);
+        })
+    }
+}
+
+pub async fn user_list(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<ServiceUserList>,
+) -> GrpcMethodResult<pb::ServiceUserListReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        let data = audit_result_err(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::ServiceUserList,
+            |driver, audit| {
+                let service = pattern::key_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                let read = ServiceRead::new(req.filter.service_id);
+                read_inner(
+                    driver,
+                    &read,
+                    service.map(|x| x.id),
+                    audit.get_organisation_id(),
+                )?;
+                driver
+                    .service_user_list(&req)
+                    .map_err(GrpcMethodError::BadRequest)
+            },
+        )?;
+        Ok((req, data))
+    })
+    .await
+    .map(|(req, data)| pb::ServiceUserListReply {
+        meta: Some(req.into()),
+        data: data
+            .into_iter()
+            .map::<pb::ServiceUser, _>(|x| x.into())
+            .collect(),
+    })
+}
+
+impl validator::Validate for pb::ServiceUserUpdateRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::uuid(e, "service_id", &self.service_id);
+            validate::uuid(e, "user_id", &self.user_id);
+            validate::service_user_status(e, "status", self.status);
+        })
+    }
+}
+
+pub async fn user_update(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<ServiceUserUpdate>,
+) -> GrpcMethodResult<pb::ServiceUserReadReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result_subject(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::ServiceUserUpdate,
+            |driver, audit| {
+                let service = pattern::key_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                let read = ServiceRead::new(req.service_id);
+                read_inner(
+                    driver,
+                    &read,
+                    service.map(|x| x.id),
+                    audit.get_organisation_id(),
+                )?;
+                let user = driver
+                    .user_read(&UserRead::Id(req.user_id))
+                    .map_err(GrpcMethodError::BadRequest)?
+                    .filter(|x| {
+                        pattern::organisation_mask_check(
+                            audit.get_organisation_id(),
+                            x.organisation_id,
+                        )
+                        .is_ok()
+                    })
+                    .ok_or_else(|| DriverError::UserNotFound)
+                    .map_err(GrpcMethodError::NotFound)?;
+                audit.user(Some(&user));
+
+                driver
+                    .service_user_update(&req)
+                    .map_err(GrpcMethodError::BadRequest)
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|data| pb::ServiceUserReadReply {
+        data: Some(data.into()),
+    })
+}
+
+pub(crate) fn read_inner(
     driver: &Postgres,
     read: &ServiceRead,
     service_id: Option<Uuid>,
+    organisation_id: Option<Uuid>,
 ) -> GrpcMethodResult<Service> {
     driver
         .service_read(read, service_id)
         .map_err(GrpcMethodError::BadRequest)?
+        .filter(|x| pattern::organisation_mask_check(organisation_id, x.organisation_id).is_ok())
         .ok_or_else(|| DriverError::ServiceNotFound)
         .map_err(GrpcMethodError::NotFound)
 }
@@ -11,6 +11,7 @@ impl validator::Validate for pb::UserListRequest {
             validate::uuid_opt(e, "offset_id", self.offset_id.as_ref().map(|x| &**x));
             validate::uuid_vec(e, "id", &self.id);
             validate::email_vec(e, "email", &self.email);
+            validate::uuid_vec(e, "organisation_id", &self.organisation_id);
         })
     }
 }
@@ -29,9 +30,11 @@ pub async fn list(
             AuditType::UserList,
             |driver, audit| {
                 let _service = pattern::key_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
 
-                driver.user_list(&req).map_err(GrpcMethodError::BadRequest)
+                driver
+                    .user_list(&req, audit.get_organisation_id())
+                    .map_err(GrpcMethodError::BadRequest)
             },
         )?;
         Ok((req, data))
@@ -51,6 +54,11 @@ impl validator::Validate for pb::UserCreateRequest {
             validate::locale_opt(e, "locale", self.locale.as_ref().map(|x| &**x));
             validate::timezone_opt(e, "timezone", self.timezone.as_ref().map(|x| &**x));
             validate::password_opt(e, "password", self.password.as_ref().map(|x| &**x));
+            validate::uuid_opt(
+                e,
+                "organisation_id",
+                self.organisation_id.as_ref().map(|x| &**x),
+            );
         })
     }
 }
@@ -77,7 +85,9 @@ pub async fn create(
             AuditType::UserCreate,
             |driver, audit| {
                 let _service = pattern::key_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
+                pattern::organisation_mask_check(audit.get_organisation_id(), req.organisation_id)
+                    .map_err(GrpcMethodError::NotFound)?;
 
                 driver
                     .user_create(&req)
@@ -114,18 +124,17 @@ pub async fn read(
             audit_meta,
             AuditType::UserRead,
             |driver, audit| {
-                let _service = pattern::key_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                let service = pattern::key_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
 
-                read_inner(driver, &req)
+                let user = read_inner(driver, &req, audit.get_organisation_id())?;
+                read_service_inner(driver, user, service.as_ref())
             },
         )
         .map_err(Into::into)
     })
     .await
-    .map(|data| pb::UserReadReply {
-        data: Some(data.into()),
-    })
+    .map(|data| data.into())
 }
 
 impl validator::Validate for pb::UserUpdateRequest {
@@ -141,9 +150,11 @@ impl validator::Validate for pb::UserUpdateRequest {
 
 pub async fn update(
     server: &GrpcServer,
-    request: GrpcMethodRequest<UserUpdate>,
+    request: GrpcMethodRequest<pb::UserUpdateRequest>,
 ) -> GrpcMethodResult<pb::UserReadReply> {
     let (audit_meta, auth, req) = request.into_inner();
+    let service_attributes = pb::struct_opt_to_value_opt(req.service_attributes.clone());
+    let req: UserUpdate = req.into();
 
     let driver = server.driver();
     blocking_method(move || {
@@ -152,24 +163,46 @@ pub async fn update(
             audit_meta,
             AuditType::UserUpdate,
             |driver, audit| {
-                let _service = pattern::key_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                let service = pattern::key_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                // Service attributes can only be updated by service keys.
+                if service.is_none() && service_attributes.is_some() {
+                    return Err(GrpcMethodError::BadRequest(
+                        DriverError::KeyServiceUndefined,
+                    ));
+                }
 
                 let read = UserRead::Id(req.id);
-                let previous_user = read_inner(driver, &read)?;
+                let previous_user = read_inner(driver, &read, audit.get_organisation_id())?;
+                let previous_user = read_service_inner(driver, previous_user, service.as_ref())?;
 
                 let user = driver
                     .user_update(&req)
                     .map_err(GrpcMethodError::BadRequest)?;
+                let user = match (service.as_ref(), service_attributes.as_ref()) {
+                    (Some(service), Some(service_attributes)) => {
+                        let service_attributes = driver
+                            .user_service_attributes_update(
+                                &user.id,
+                                &service.id,
+                                service_attributes,
+                            )
+                            .map_err(GrpcMethodError::BadRequest)?;
+                        UserWithService {
+                            user,
+                            service_attributes: Some(service_attributes),
+                        }
+                    }
+                    _ => read_service_inner(driver, user, service.as_ref())?,
+                };
                 Ok((previous_user, user))
             },
         )
         .map_err(Into::into)
     })
     .await
-    .map(|data| pb::UserReadReply {
-        data: Some(data.into()),
-    })
+    .map(|data| data.into())
 }
 
 pub async fn delete(
@@ -186,9 +219,9 @@ pub async fn delete(
             AuditType::UserDelete,
             |driver, audit| {
                 let _service = pattern::key_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
 
-                let user = read_inner(driver, &req)?;
+                let user = read_inner(driver, &req, audit.get_organisation_id())?;
                 driver
                     .user_delete(&user.id)
                     .map_err(GrpcMethodError::BadRequest)
@@ -201,10 +234,346 @@ pub async fn delete(
     .map(|_data| ())
 }
 
-fn read_inner(driver: &Postgres, read: &UserRead) -> GrpcMethodResult<User> {
+pub async fn export(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<UserRead>,
+) -> GrpcMethodResult<pb::UserExportReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result_subject(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::UserExport,
+            |driver, audit| {
+                pattern::key_root_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                let user = read_inner(driver, &req, audit.get_organisation_id())?;
+                driver
+                    .user_export(&user.id)
+                    .map_err(GrpcMethodError::BadRequest)?
+                    .ok_or_else(|| DriverError::UserNotFound)
+                    .map_err(GrpcMethodError::NotFound)
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|data| pb::UserExportReply {
+        data: pb::value_to_struct_opt(data.to_value()),
+    })
+}
+
+pub async fn erase(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<UserRead>,
+) -> GrpcMethodResult<()> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result_subject(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::UserErase,
+            |driver, audit| {
+                pattern::key_root_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                // User is not referenced by audit log as user row is deleted.
+                let user = read_inner(driver, &req, audit.get_organisation_id())?;
+                driver
+                    .user_erase(&user.id)
+                    .map_err(GrpcMethodError::BadRequest)
+                    .map(|_| user)
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|_data| ())
+}
+
+impl validator::Validate for pb::UserInviteRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::uuid(e, "service_id", &self.service_id);
+            validate::name(e, "name", &self.name);
+            validate::email(e, "email", &self.email);
+            validate::locale_opt(e, "locale", self.locale.as_ref().map(|x| &**x));
+            validate::timezone_opt(e, "timezone", self.timezone.as_ref().map(|x| &**x));
+        })
+    }
+}
+
+pub async fn invite(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<pb::UserInviteRequest>,
+) -> GrpcMethodResult<pb::UserInviteReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    let invite_token_expires = server.options().invite_token_expires();
+    let email = server.smtp_email();
+    blocking_method(move || {
+        let (data, template) = audit_result(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::UserInvite,
+            |driver, audit| {
+                let service = pattern::key_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                let read = ServiceRead::new(pb::string_to_uuid(req.service_id.clone()));
+                let invite_service = super::service::read_inner(
+                    driver,
+                    &read,
+                    service.map(|x| x.id),
+                    audit.get_organisation_id(),
+                )?;
+
+                // Create user in organisation of service.
+                let mut user_create = UserCreate::new(true, &req.name, &req.email)
+                    .organisation_id(invite_service.organisation_id);
+                if let Some(locale) = &req.locale {
+                    user_create = user_create.locale(locale);
+                }
+                if let Some(timezone) = &req.timezone {
+                    user_create = user_create.timezone(timezone);
+                }
+                let invite = UserInvite {
+                    service_id: invite_service.id,
+                    user: user_create,
+                    invite_expires_at: Utc::now() + invite_token_expires,
+                };
+                let data = driver
+                    .user_invite(&invite)
+                    .map_err(GrpcMethodError::BadRequest)?;
+                audit.user(Some(&data.user));
+
+                let template = invite_template_inner(
+                    driver,
+                    audit,
+                    &invite_service,
+                    &data.user,
+                    &data.key,
+                    invite.invite_expires_at,
+                    invite_token_expires,
+                )?;
+                Ok((data, template))
+            },
+        )?;
+        email(template)
+            .map_err::<DriverError, _>(Into::into)
+            .map_err(GrpcMethodError::BadRequest)?;
+        Ok(data)
+    })
+    .await
+    .map(|data| pb::UserInviteReply {
+        user: Some(data.user.into()),
+        data: Some(data.service_user.into()),
+    })
+}
+
+impl validator::Validate for pb::UserInviteResendRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::uuid(e, "service_id", &self.service_id);
+            validate::uuid(e, "user_id", &self.user_id);
+        })
+    }
+}
+
+pub async fn invite_resend(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<pb::UserInviteResendRequest>,
+) -> GrpcMethodResult<pb::ServiceUserReadReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    let invite_token_expires = server.options().invite_token_expires();
+    let email = server.smtp_email();
+    blocking_method(move || {
+        let (data, template) = audit_result(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::UserInviteResend,
+            |driver, audit| {
+                let (invite_service, user) =
+                    invite_read_inner(driver, audit, &auth, &req.service_id, &req.user_id)?;
+                let key = driver
+                    .key_read(
+                        &KeyRead::user_id(invite_service.id, user.id, true, false, KeyType::Token),
+                        None,
+                    )
+                    .map_err(GrpcMethodError::BadRequest)?
+                    .ok_or_else(|| DriverError::KeyNotFound)
+                    .map_err(GrpcMethodError::BadRequest)?;
+
+                // Extend invitation expiry time and send new token.
+                let invite_expires_at = Utc::now() + invite_token_expires;
+                let data = driver
+                    .service_user_invite(&invite_service.id, &user.id, &invite_expires_at)
+                    .map_err(GrpcMethodError::BadRequest)?;
+                let template = invite_template_inner(
+                    driver,
+                    audit,
+                    &invite_service,
+                    &user,
+                    &key,
+                    invite_expires_at,
+                    invite_token_expires,
+                )?;
+                Ok((data, template))
+            },
+        )?;
+        email(template)
+            .map_err::<DriverError, _>(Into::into)
+            .map_err(GrpcMethodError::BadRequest)?;
+        Ok(data)
+    })
+    .await
+    .map(|data| pb::ServiceUserReadReply {
+        data: Some(data.into()),
+    })
+}
+
+impl validator::Validate for pb::UserInviteRevokeRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::uuid(e, "service_id", &self.service_id);
+            validate::uuid(e, "user_id", &self.user_id);
+        })
+    }
+}
+
+pub async fn invite_revoke(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<pb::UserInviteRevokeRequest>,
+) -> GrpcMethodResult<()> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::UserInviteRevoke,
+            |driver, audit| {
+                let (invite_service, user) =
+                    invite_read_inner(driver, audit, &auth, &req.service_id, &req.user_id)?;
+
+                // Remove invited membership and revoke user key for service.
+                driver
+                    .service_user_delete(&invite_service.id, &user.id)
+                    .map_err(GrpcMethodError::BadRequest)?;
+                let key = driver
+                    .key_read(
+                        &KeyRead::user_id(invite_service.id, user.id, true, false, KeyType::Token),
+                        None,
+                    )
+                    .map_err(GrpcMethodError::BadRequest)?;
+                if let Some(key) = key {
+                    driver
+                        .key_update(&KeyUpdate {
+                            id: key.id,
+                            is_enabled: Some(false),
+                            is_revoked: Some(true),
+                            name: None,
+                        })
+                        .map_err(GrpcMethodError::BadRequest)?;
+                }
+                Ok(())
+            },
+        )
+    })
+    .await
+}
+
+/// Authenticate and read service and user of invitation, returns bad request
+/// if membership of user is not an invitation.
+fn invite_read_inner(
+    driver: &Postgres,
+    audit: &mut AuditBuilder,
+    auth: &HeaderAuth,
+    service_id: &str,
+    user_id: &str,
+) -> GrpcMethodResult<(Service, User)> {
+    let service =
+        pattern::key_authenticate(driver, audit, auth).map_err(GrpcMethodError::authenticate)?;
+
+    let read = ServiceRead::new(pb::string_to_uuid(service_id.to_owned()));
+    let invite_service = super::service::read_inner(
+        driver,
+        &read,
+        service.map(|x| x.id),
+        audit.get_organisation_id(),
+    )?;
+    let read = UserRead::Id(pb::string_to_uuid(user_id.to_owned()));
+    let user = read_inner(driver, &read, audit.get_organisation_id())?;
+    audit.user(Some(&user));
+
+    let service_user = driver
+        .service_user_read(&invite_service.id, &user.id)
+        .map_err(GrpcMethodError::BadRequest)?
+        .ok_or_else(|| DriverError::ServiceUserNotFound)
+        .map_err(GrpcMethodError::BadRequest)?;
+    if service_user.status != ServiceUserStatus::Invited {
+        return Err(GrpcMethodError::BadRequest(
+            DriverError::ServiceUserNotInvited,
+        ));
+    }
+    Ok((invite_service, user))
+}
+
+/// Encode invite token and render invitation email.
+fn invite_template_inner(
+    driver: &Postgres,
+    audit: &AuditBuilder,
+    service: &Service,
+    user: &User,
+    key: &KeyWithValue,
+    invite_expires_at: DateTime<Utc>,
+    invite_token_expires: Duration,
+) -> GrpcMethodResult<TemplateEmail> {
+    let conn = driver.conn().map_err(GrpcMethodError::BadRequest)?;
+    let token = Jwt::encode_invite(&conn, service, user, key, invite_token_expires)
+        .map_err(GrpcMethodError::BadRequest)?;
+    TemplateEmail::email_invite(service, user, &token, invite_expires_at, audit.meta())
+        .map_err(GrpcMethodError::BadRequest)
+}
+
+pub(crate) fn read_inner(
+    driver: &Postgres,
+    read: &UserRead,
+    organisation_id: Option<Uuid>,
+) -> GrpcMethodResult<User> {
     driver
         .user_read(read)
         .map_err(GrpcMethodError::BadRequest)?
+        .filter(|x| pattern::organisation_mask_check(organisation_id, x.organisation_id).is_ok())
         .ok_or_else(|| DriverError::UserNotFound)
         .map_err(GrpcMethodError::NotFound)
 }
+
+/// Read attributes of user for service if authenticated as a service.
+fn read_service_inner(
+    driver: &Postgres,
+    user: User,
+    service: Option<&Service>,
+) -> GrpcMethodResult<UserWithService> {
+    let service_attributes = match service {
+        Some(service) => Some(
+            driver
+                .user_service_attributes_read(&user.id, &service.id)
+                .map_err(GrpcMethodError::BadRequest)?,
+        ),
+        None => None,
+    };
+    Ok(UserWithService {
+        user,
+        service_attributes,
+    })
+}
@@ -0,0 +1,499 @@
+use crate::prelude::*;
+use futures_util::future;
+use std::{net::IpAddr, sync::Arc};
+use tokio::{net, time};
+use url::{Host, Url};
+
+/// Maximum number of deliveries claimed by worker per poll.
+const WEBHOOK_DELIVERY_CLAIM_LIMIT: i64 = 10;
+
+impl validator::Validate for pb::WebhookListRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::uuid_opt(e, "gt", self.gt.as_ref().map(|x| &**x));
+            validate::uuid_opt(e, "lt", self.lt.as_ref().map(|x| &**x));
+            validate::limit_opt(e, "limit", self.limit);
+            validate::uuid_vec(e, "id", &self.id);
+            validate::uuid_vec(e, "service_id", &self.service_id);
+        })
+    }
+}
+
+pub async fn list(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<WebhookList>,
+) -> GrpcMethodResult<pb::WebhookListReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        let data = audit_result_err(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::WebhookList,
+            |driver, audit| {
+                let service = pattern::key_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                driver
+                    .webhook_list(&req, service.map(|x| x.id), audit.get_organisation_id())
+                    .map_err(GrpcMethodError::BadRequest)
+            },
+        )?;
+        Ok((req, data))
+    })
+    .await
+    .map(|(req, data)| pb::WebhookListReply {
+        meta: Some(req.into()),
+        data: data
+            .into_iter()
+            .map::<pb::Webhook, _>(|x| x.into())
+            .collect(),
+    })
+}
+
+impl validator::Validate for pb::WebhookCreateRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::uuid(e, "service_id", &self.service_id);
+            validate::name(e, "name", &self.name);
+            validate::url_http(e, "url", &self.url);
+            validate::audit_type_vec(e, "audit_type", &self.audit_type);
+        })
+    }
+}
+
+pub async fn create(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<WebhookCreate>,
+) -> GrpcMethodResult<pb::WebhookCreateReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result_subject(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::WebhookCreate,
+            |driver, audit| {
+                let service = pattern::key_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                // Service keys can only create webhooks for their own service.
+                let read = ServiceRead::new(req.service_id);
+                super::service::read_inner(
+                    driver,
+                    &read,
+                    service.map(|x| x.id),
+                    audit.get_organisation_id(),
+                )?;
+
+                driver
+                    .webhook_create(&req)
+                    .map_err(GrpcMethodError::BadRequest)
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|data| pb::WebhookCreateReply {
+        data: Some(data.webhook.into()),
+        secret: data.secret,
+    })
+}
+
+impl validator::Validate for pb::WebhookReadRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::uuid(e, "id", &self.id);
+        })
+    }
+}
+
+pub async fn read(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<WebhookRead>,
+) -> GrpcMethodResult<pb::WebhookReadReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result_err(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::WebhookRead,
+            |driver, audit| {
+                let service = pattern::key_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                read_inner(
+                    driver,
+                    &req,
+                    service.map(|x| x.id),
+                    audit.get_organisation_id(),
+                )
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|data| pb::WebhookReadReply {
+        data: Some(data.into()),
+    })
+}
+
+impl validator::Validate for pb::WebhookUpdateRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::uuid(e, "id", &self.id);
+            validate::name_opt(e, "name", self.name.as_ref().map(|x| &**x));
+            validate::url_http_opt(e, "url", self.url.as_ref().map(|x| &**x));
+            if let Some(audit_type) = self.audit_type.as_ref() {
+                validate::audit_type_vec(e, "audit_type", &audit_type.audit_type);
+            }
+        })
+    }
+}
+
+pub async fn update(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<WebhookUpdate>,
+) -> GrpcMethodResult<pb::WebhookReadReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result_diff(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::WebhookUpdate,
+            |driver, audit| {
+                let service = pattern::key_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                let read = WebhookRead::new(req.id);
+                let previous_webhook = read_inner(
+                    driver,
+                    &read,
+                    service.map(|x| x.id),
+                    audit.get_organisation_id(),
+                )?;
+                let webhook = driver
+                    .webhook_update(&req)
+                    .map_err(GrpcMethodError::BadRequest)?;
+                Ok((previous_webhook, webhook))
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|data| pb::WebhookReadReply {
+        data: Some(data.into()),
+    })
+}
+
+pub async fn delete(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<WebhookRead>,
+) -> GrpcMethodResult<()> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result_subject(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::WebhookDelete,
+            |driver, audit| {
+                let service = pattern::key_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                let webhook = read_inner(
+                    driver,
+                    &req,
+                    service.map(|x| x.id),
+                    audit.get_organisation_id(),
+                )?;
+                driver
+                    .webhook_delete(&webhook.id)
+                    .map_err(GrpcMethodError::BadRequest)
+                    .map(|_| webhook)
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|_data| ())
+}
+
+impl validator::Validate for pb::WebhookDeliveryListRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::uuid(e, "webhook_id", &self.webhook_id);
+            validate::uuid_opt(e, "gt", self.gt.as_ref().map(|x| &**x));
+            validate::uuid_opt(e, "lt", self.lt.as_ref().map(|x| &**x));
+            validate::limit_opt(e, "limit", self.limit);
+            validate::webhook_delivery_status_vec(e, "status", &self.status);
+        })
+    }
+}
+
+pub async fn delivery_list(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<WebhookDeliveryList>,
+) -> GrpcMethodResult<pb::WebhookDeliveryListReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        let data = audit_result_err(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::WebhookDeliveryList,
+            |driver, audit| {
+                let service = pattern::key_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                let read = WebhookRead::new(req.filter.webhook_id);
+                read_inner(
+                    driver,
+                    &read,
+                    service.map(|x| x.id),
+                    audit.get_organisation_id(),
+                )?;
+                driver
+                    .webhook_delivery_list(&req)
+                    .map_err(GrpcMethodError::BadRequest)
+            },
+        )?;
+        Ok((req, data))
+    })
+    .await
+    .map(|(req, data)| pb::WebhookDeliveryListReply {
+        meta: Some(req.into()),
+        data: data
+            .into_iter()
+            .map::<pb::WebhookDelivery, _>(|x| x.into())
+            .collect(),
+    })
+}
+
+impl validator::Validate for pb::WebhookDeliveryRetryRequest {
+    fn validate(&self) -> Result<(), validator::ValidationErrors> {
+        validate::wrap(|e| {
+            validate::uuid(e, "id", &self.id);
+        })
+    }
+}
+
+pub async fn delivery_retry(
+    server: &GrpcServer,
+    request: GrpcMethodRequest<WebhookDeliveryRead>,
+) -> GrpcMethodResult<pb::WebhookDeliveryReadReply> {
+    let (audit_meta, auth, req) = request.into_inner();
+
+    let driver = server.driver();
+    blocking_method(move || {
+        audit_result_subject(
+            driver.as_ref(),
+            audit_meta,
+            AuditType::WebhookDeliveryRetry,
+            |driver, audit| {
+                let service = pattern::key_authenticate(driver, audit, &auth)
+                    .map_err(GrpcMethodError::authenticate)?;
+
+                let delivery = driver
+                    .webhook_delivery_read(&req)
+                    .map_err(GrpcMethodError::BadRequest)?
+                    .ok_or_else(|| DriverError::WebhookDeliveryNotFound)
+                    .map_err(GrpcMethodError::NotFound)?;
+                let read = WebhookRead::new(delivery.webhook_id);
+                read_inner(
+                    driver,
+                    &read,
+                    service.map(|x| x.id),
+                    audit.get_organisation_id(),
+                )?;
+
+                driver
+                    .webhook_delivery_retry(&delivery.id)
+                    .map_err(GrpcMethodError::BadRequest)
+            },
+        )
+        .map_err(Into::into)
+    })
+    .await
+    .map(|data| pb::WebhookDeliveryReadReply {
+        data: Some(data.into()),
+    })
+}
+
+/// Webhook delivery worker, polls queue for pending deliveries which are
+/// due and sends them concurrently. Runs until the process exits.
+pub async fn worker(server: GrpcServer) {
+    let driver = server.driver();
+    let client = server.webhook_client();
+    let allow = server.options().webhook_allow_cidr().to_vec();
+    let interval = server
+        .options()
+        .webhook_delivery_interval()
+        .to_std()
+        .unwrap_or_else(|_e| std::time::Duration::from_secs(5));
+    let timeout = server.options().webhook_delivery_timeout();
+    // Lease outlives request timeout so deliveries in flight are not claimed again.
+    let lease = timeout + Duration::seconds(60);
+    let timeout = timeout
+        .to_std()
+        .unwrap_or_else(|_e| std::time::Duration::from_secs(10));
+
+    loop {
+        let claim = {
+            let driver = driver.clone();
+            blocking_method(move || {
+                driver
+                    .webhook_delivery_claim(WEBHOOK_DELIVERY_CLAIM_LIMIT, lease)
+                    .map_err(GrpcMethodError::BadRequest)
+            })
+            .await
+        };
+
+        let idle = match claim {
+            Ok(sends) => {
+                let idle = (sends.len() as i64) < WEBHOOK_DELIVERY_CLAIM_LIMIT;
+                let results = future::join_all(
+                    sends
+                        .into_iter()
+                        .map(|send| delivery_send(client.clone(), &allow, timeout, send)),
+                )
+                .await;
+
+                let driver = driver.clone();
+                let res = blocking_method(move || {
+                    for result in results.iter() {
+                        driver
+                            .webhook_delivery_result(result)
+                            .map_err(GrpcMethodError::BadRequest)?;
+                    }
+                    Ok(())
+                })
+                .await;
+                if let Err(e) = res {
+                    warn!("{}", e);
+                }
+                idle
+            }
+            Err(e) => {
+                warn!("{}", e);
+                true
+            }
+        };
+
+        if idle {
+            time::delay_for(interval).await;
+        }
+    }
+}
+
+/// Send delivery body to webhook URL with signature headers.
+async fn delivery_send(
+    client: Arc<reqwest::Client>,
+    allow: &[Cidr],
+    timeout: std::time::Duration,
+    send: WebhookDeliverySend,
+) -> WebhookDeliveryResult {
+    let body = match send.body {
+        Some(body) => body,
+        None => {
+            return WebhookDeliveryResult {
+                id: send.id,
+                status_code: None,
+                error: Some(DriverError::AuditNotFound.to_string()),
+            }
+        }
+    };
+    if let Err(e) = delivery_url_check(&send.url, allow).await {
+        return WebhookDeliveryResult {
+            id: send.id,
+            status_code: None,
+            error: Some(e.to_string()),
+        };
+    }
+
+    let timestamp = Utc::now().timestamp();
+    let signature = Webhook::signature(&send.secret, timestamp, &body);
+    let res = client
+        .post(&send.url)
+        .timeout(timeout)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(header::SSO_WEBHOOK_ID, send.id.to_string())
+        .header(header::SSO_WEBHOOK_TIMESTAMP, timestamp.to_string())
+        .header(header::SSO_WEBHOOK_SIGNATURE, signature)
+        .body(body)
+        .send()
+        .await
+        .and_then(|res| res.error_for_status());
+
+    match res {
+        Ok(res) => WebhookDeliveryResult {
+            id: send.id,
+            status_code: Some(res.status().as_u16()),
+            error: None,
+        },
+        Err(e) => WebhookDeliveryResult {
+            id: send.id,
+            status_code: e.status().map(|x| x.as_u16()),
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+/// Check webhook URL scheme is HTTP or HTTPS and that host does not resolve
+/// to an internal address, unless address is in allowed ranges.
+/// Host is resolved again by client when sending, so this does not prevent
+/// DNS rebinding, but redirects are not followed.
+async fn delivery_url_check(url: &str, allow: &[Cidr]) -> DriverResult<()> {
+    let invalid = || DriverError::WebhookUrlInvalid(url.to_owned());
+    let parsed = Url::parse(url).map_err(|_e| invalid())?;
+    match parsed.scheme() {
+        "http" | "https" => {}
+        _ => return Err(invalid()),
+    }
+    let port = parsed.port_or_known_default().ok_or_else(invalid)?;
+    let addrs: Vec<IpAddr> = match parsed.host() {
+        Some(Host::Domain(domain)) => net::lookup_host((domain, port))
+            .await
+            .map_err(DriverError::StdIo)?
+            .map(|x| x.ip())
+            .collect(),
+        Some(Host::Ipv4(addr)) => vec![IpAddr::V4(addr)],
+        Some(Host::Ipv6(addr)) => vec![IpAddr::V6(addr)],
+        None => return Err(invalid()),
+    };
+    if addrs.is_empty() {
+        return Err(invalid());
+    }
+    for addr in addrs {
+        if Cidr::is_internal(&addr) && !allow.iter().any(|x| x.contains(&addr)) {
+            return Err(DriverError::WebhookAddressForbidden(addr.to_string()));
+        }
+    }
+    Ok(())
+}
+
+/// Read webhook, webhooks are masked to service of service key and to
+/// organisation of root key by organisation of service.
+fn read_inner(
+    driver: &Postgres,
+    read: &WebhookRead,
+    service_id: Option<Uuid>,
+    organisation_id: Option<Uuid>,
+) -> GrpcMethodResult<Webhook> {
+    driver
+        .webhook_read(read, service_id)
+        .map_err(GrpcMethodError::BadRequest)?
+        .filter(|x| {
+            pattern::service_organisation_mask_check(driver, organisation_id, x.service_id).is_ok()
+        })
+        .ok_or_else(|| DriverError::WebhookNotFound)
+        .map_err(GrpcMethodError::NotFound)
+}
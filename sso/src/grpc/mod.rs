@@ -6,13 +6,19 @@ mod util;
 
 pub use crate::grpc::{client::*, options::*, server::*, util::*};
 
+pub(crate) use crate::grpc::method::audit::watch_spawn as audit_watch_spawn;
+
 pub mod pb {
     //! Generated protobuf server and client items.
     tonic::include_proto!("sso");
 
-    use crate::KeyType as DriverKeyType;
+    use crate::{
+        AuditReportGroup as DriverAuditReportGroup, Cidr as DriverCidr, KeyScope as DriverKeyScope,
+        KeyType as DriverKeyType, ServiceUserStatus as DriverServiceUserStatus,
+        WebhookDeliveryStatus as DriverWebhookDeliveryStatus,
+    };
     use chrono::{DateTime, Utc};
-    use std::convert::TryInto;
+    use std::{convert::TryInto, str::FromStr};
     use uuid::Uuid;
 
     pub fn timestamp_opt_to_datetime_opt(
@@ -96,6 +102,86 @@ pub mod pb {
         }
     }
 
+    pub fn i32_vec_to_service_user_status_vec_opt(
+        s: Vec<i32>,
+    ) -> Option<Vec<DriverServiceUserStatus>> {
+        if s.is_empty() {
+            None
+        } else {
+            Some(
+                s.into_iter()
+                    .map(DriverServiceUserStatus::from_i32)
+                    .collect(),
+            )
+        }
+    }
+
+    pub fn service_user_status_vec_opt_to_i32_vec(
+        s: Option<Vec<DriverServiceUserStatus>>,
+    ) -> Vec<i32> {
+        match s {
+            Some(s) => s.into_iter().map(|x| x as i32).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn i32_vec_to_audit_report_group_vec(s: Vec<i32>) -> Vec<DriverAuditReportGroup> {
+        s.into_iter()
+            .map(DriverAuditReportGroup::from_i32)
+            .collect()
+    }
+
+    pub fn audit_report_group_vec_to_i32_vec(s: Vec<DriverAuditReportGroup>) -> Vec<i32> {
+        s.into_iter().map(|x| x as i32).collect()
+    }
+
+    pub fn i32_vec_to_webhook_delivery_status_vec_opt(
+        s: Vec<i32>,
+    ) -> Option<Vec<DriverWebhookDeliveryStatus>> {
+        if s.is_empty() {
+            None
+        } else {
+            Some(
+                s.into_iter()
+                    .map(DriverWebhookDeliveryStatus::from_i32)
+                    .collect(),
+            )
+        }
+    }
+
+    pub fn webhook_delivery_status_vec_opt_to_i32_vec(
+        s: Option<Vec<DriverWebhookDeliveryStatus>>,
+    ) -> Vec<i32> {
+        match s {
+            Some(s) => s.into_iter().map(|x| x as i32).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn string_vec_to_key_scope_vec(s: Vec<String>) -> Vec<DriverKeyScope> {
+        s.into_iter()
+            .map(|x| DriverKeyScope::from_str(&x).unwrap())
+            .collect()
+    }
+
+    pub fn string_vec_to_cidr_vec(s: Vec<String>) -> Vec<DriverCidr> {
+        s.into_iter()
+            .map(|x| DriverCidr::from_str(&x).unwrap())
+            .collect()
+    }
+
+    pub fn string_vec_to_cidr_vec_opt(s: Vec<String>) -> Option<Vec<DriverCidr>> {
+        if s.is_empty() {
+            None
+        } else {
+            Some(string_vec_to_cidr_vec(s))
+        }
+    }
+
+    pub fn cidr_vec_to_string_vec(c: &[DriverCidr]) -> Vec<String> {
+        c.iter().map(|x| x.to_string()).collect()
+    }
+
     pub fn uuid_to_string(u: Uuid) -> String {
         format!("{}", u)
     }
@@ -156,7 +242,14 @@ pub mod pb {
         }
     }
 
-    fn value_to_struct_value(value: serde_json::Value) -> prost_types::Value {
+    pub fn struct_value_opt_to_value(s: Option<prost_types::Value>) -> serde_json::Value {
+        match s.and_then(|x| x.kind) {
+            Some(kind) => struct_kind_to_value(kind),
+            None => serde_json::Value::Null,
+        }
+    }
+
+    pub fn value_to_struct_value(value: serde_json::Value) -> prost_types::Value {
         let kind: prost_types::value::Kind = match value {
             serde_json::Value::Null => prost_types::value::Kind::NullValue(0),
             serde_json::Value::Bool(x) => prost_types::value::Kind::BoolValue(x),
@@ -75,12 +75,29 @@ pub struct GrpcServerOptions {
     pwned_passwords_enabled: bool,
     /// Enabled Traefik forward authentication.
     traefik_enabled: bool,
+    /// Trusted proxy address ranges, remote address of requests from these
+    /// ranges is resolved from forwarded headers.
+    trusted_proxy: Vec<Cidr>,
+    /// Enable login notification emails for new devices or locations.
+    login_notify_enabled: bool,
     /// Access token expiry time duration.
     access_token_expires: Duration,
     /// Refresh token expiry time duration.
     refresh_token_expires: Duration,
     /// Revoke token expiry time duration.
     revoke_token_expires: Duration,
+    /// Impersonation access token expiry time duration.
+    impersonate_token_expires: Duration,
+    /// User invite token expiry time duration.
+    invite_token_expires: Duration,
+    /// Audit watch polling interval duration.
+    audit_watch_interval: Duration,
+    /// Webhook delivery queue polling interval duration.
+    webhook_delivery_interval: Duration,
+    /// Webhook delivery request timeout duration.
+    webhook_delivery_timeout: Duration,
+    /// Webhook target address ranges allowed in addition to public addresses.
+    webhook_allow: Vec<Cidr>,
     /// SMTP transport.
     smtp_transport: Option<GrpcServerOptionsSmtp>,
     /// SMTP file transport.
@@ -105,9 +122,17 @@ impl GrpcServerOptions {
             user_agent: user_agent.into(),
             pwned_passwords_enabled,
             traefik_enabled,
+            trusted_proxy: Vec::new(),
+            login_notify_enabled: false,
             access_token_expires: Duration::seconds(3_600),
             refresh_token_expires: Duration::seconds(86_400),
             revoke_token_expires: Duration::seconds(604_800),
+            impersonate_token_expires: Duration::seconds(900),
+            invite_token_expires: Duration::seconds(604_800),
+            audit_watch_interval: Duration::seconds(1),
+            webhook_delivery_interval: Duration::seconds(5),
+            webhook_delivery_timeout: Duration::seconds(10),
+            webhook_allow: Vec::new(),
             smtp_transport: None,
             smtp_file_transport: None,
             github: None,
@@ -216,6 +241,46 @@ impl GrpcServerOptions {
         self.smtp_file_transport(Some(transport))
     }
 
+    /// Set trusted proxy address ranges.
+    pub fn trusted_proxy(mut self, trusted_proxy: Vec<Cidr>) -> Self {
+        self.trusted_proxy = trusted_proxy;
+        self
+    }
+
+    /// Read comma separated trusted proxy address ranges environment variable into options.
+    pub fn trusted_proxy_from_env<T: AsRef<str>>(self, trusted_proxy_name: T) -> Self {
+        let trusted_proxy = Self::cidr_vec_from_env(trusted_proxy_name.as_ref())
+            .expect("Failed to read trusted proxy environment variable.");
+        self.trusted_proxy(trusted_proxy)
+    }
+
+    /// Set webhook target address ranges allowed in addition to public addresses.
+    pub fn webhook_allow(mut self, webhook_allow: Vec<Cidr>) -> Self {
+        self.webhook_allow = webhook_allow;
+        self
+    }
+
+    /// Read comma separated webhook allowed address ranges environment variable into options.
+    pub fn webhook_allow_from_env<T: AsRef<str>>(self, webhook_allow_name: T) -> Self {
+        let webhook_allow = Self::cidr_vec_from_env(webhook_allow_name.as_ref())
+            .expect("Failed to read webhook allow environment variable.");
+        self.webhook_allow(webhook_allow)
+    }
+
+    /// Set login notification emails enabled.
+    pub fn login_notify(mut self, login_notify_enabled: bool) -> Self {
+        self.login_notify_enabled = login_notify_enabled;
+        self
+    }
+
+    /// Read login notification emails enabled environment variable into options.
+    pub fn login_notify_from_env<T: AsRef<str>>(self, login_notify_enabled_name: T) -> Self {
+        let login_notify_enabled = env::value_opt::<bool>(login_notify_enabled_name.as_ref())
+            .expect("Failed to read login notify enabled environment variable.")
+            .unwrap_or(false);
+        self.login_notify(login_notify_enabled)
+    }
+
     /// Set Github provider.
     pub fn github(mut self, github: Option<GrpcServerOptionsProvider>) -> Self {
         self.github = github;
@@ -300,6 +365,20 @@ impl GrpcServerOptions {
             .map_err(DriverError::Reqwest)
     }
 
+    /// Returns asynchronous reqwest `Client` for webhook deliveries.
+    /// Redirects are not followed, a redirect response could point
+    /// deliveries at an internal address.
+    pub fn webhook_client(&self) -> DriverResult<Client> {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::USER_AGENT, self.user_agent.parse().unwrap());
+        Client::builder()
+            .use_rustls_tls()
+            .default_headers(headers)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(DriverError::Reqwest)
+    }
+
     /// Returns Pwned Passwords integration enabled flag.
     pub fn pwned_passwords_enabled(&self) -> bool {
         self.pwned_passwords_enabled
@@ -310,6 +389,16 @@ impl GrpcServerOptions {
         self.traefik_enabled
     }
 
+    /// Returns trusted proxy address ranges.
+    pub fn trusted_proxy_cidr(&self) -> &[Cidr] {
+        &self.trusted_proxy
+    }
+
+    /// Returns login notification emails enabled flag.
+    pub fn login_notify_enabled(&self) -> bool {
+        self.login_notify_enabled
+    }
+
     /// Returns access token expiry value.
     pub fn access_token_expires(&self) -> Duration {
         self.access_token_expires
@@ -325,6 +414,36 @@ impl GrpcServerOptions {
         self.revoke_token_expires
     }
 
+    /// Returns impersonation access token expiry value.
+    pub fn impersonate_token_expires(&self) -> Duration {
+        self.impersonate_token_expires
+    }
+
+    /// Returns user invite token expiry value.
+    pub fn invite_token_expires(&self) -> Duration {
+        self.invite_token_expires
+    }
+
+    /// Returns audit watch polling interval value.
+    pub fn audit_watch_interval(&self) -> Duration {
+        self.audit_watch_interval
+    }
+
+    /// Returns webhook delivery queue polling interval value.
+    pub fn webhook_delivery_interval(&self) -> Duration {
+        self.webhook_delivery_interval
+    }
+
+    /// Returns webhook delivery request timeout value.
+    pub fn webhook_delivery_timeout(&self) -> Duration {
+        self.webhook_delivery_timeout
+    }
+
+    /// Returns webhook allowed address ranges.
+    pub fn webhook_allow_cidr(&self) -> &[Cidr] {
+        &self.webhook_allow
+    }
+
     /// Returns `SmtpClient` built from options.
     pub fn smtp_client(&self) -> DriverResult<Option<SmtpClient>> {
         if let Some(smtp) = self.smtp_transport.as_ref() {
@@ -367,6 +486,8 @@ impl GrpcServerOptions {
             self.github.clone(),
             self.access_token_expires(),
             self.refresh_token_expires(),
+            self.revoke_token_expires(),
+            self.login_notify_enabled(),
         )
     }
 
@@ -376,8 +497,20 @@ impl GrpcServerOptions {
             self.microsoft.clone(),
             self.access_token_expires(),
             self.refresh_token_expires(),
+            self.revoke_token_expires(),
+            self.login_notify_enabled(),
         )
     }
+
+    fn cidr_vec_from_env(name: &str) -> DriverResult<Vec<Cidr>> {
+        env::string_opt(name)
+            .unwrap_or_else(String::new)
+            .split(',')
+            .map(|x| x.trim())
+            .filter(|x| !x.is_empty())
+            .map(Cidr::from_str)
+            .collect()
+    }
 }
 
 /// Authentication provider OAuth2 common arguments.
@@ -386,6 +519,8 @@ pub(crate) struct ServerProviderOauth2Args {
     pub provider: Option<GrpcServerOptionsProvider>,
     pub access_token_expires: Duration,
     pub refresh_token_expires: Duration,
+    pub revoke_token_expires: Duration,
+    pub login_notify_enabled: bool,
 }
 
 impl ServerProviderOauth2Args {
@@ -393,11 +528,15 @@ impl ServerProviderOauth2Args {
         provider: Option<GrpcServerOptionsProvider>,
         access_token_expires: Duration,
         refresh_token_expires: Duration,
+        revoke_token_expires: Duration,
+        login_notify_enabled: bool,
     ) -> Self {
         Self {
             provider,
             access_token_expires,
             refresh_token_expires,
+            revoke_token_expires,
+            login_notify_enabled,
         }
     }
 }
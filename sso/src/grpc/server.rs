@@ -1,4 +1,5 @@
 use crate::{grpc::method, prelude::*};
+use futures_util::stream::{self, StreamExt};
 use lettre::{file::FileTransport, SmtpClient, Transport};
 use lettre_email::Email;
 use prometheus::{HistogramTimer, HistogramVec, IntCounterVec};
@@ -43,6 +44,7 @@ pub struct GrpcServer {
     options: GrpcServerOptions,
     driver: Arc<Postgres>,
     client: Arc<reqwest::Client>,
+    webhook_client: Arc<reqwest::Client>,
     smtp_client: Arc<Option<SmtpClient>>,
     count: IntCounterVec,
     latency: HistogramVec,
@@ -58,12 +60,14 @@ impl GrpcServer {
     /// Returns new server.
     pub fn new(driver: Postgres, options: GrpcServerOptions) -> Self {
         let client = options.client().unwrap();
+        let webhook_client = options.webhook_client().unwrap();
         let smtp_client = options.smtp_client().unwrap();
         let (count, latency) = Metrics::grpc_metrics();
         Self {
             options,
             driver: Arc::new(driver),
             client: Arc::new(client),
+            webhook_client: Arc::new(webhook_client),
             smtp_client: Arc::new(smtp_client),
             count,
             latency,
@@ -90,6 +94,16 @@ impl GrpcServer {
         self.client.clone()
     }
 
+    /// Returns reference to webhook delivery HTTP client.
+    pub(crate) fn webhook_client(&self) -> Arc<reqwest::Client> {
+        self.webhook_client.clone()
+    }
+
+    /// Run webhook delivery worker, must be spawned on runtime.
+    pub async fn webhook_worker(self) {
+        method::webhook::worker(self).await
+    }
+
     /// Build email callback function. Must be called from blocking context.
     /// If client is None and file directory path is provided, file transport is used.
     pub(crate) fn smtp_email(&self) -> Box<dyn FnOnce(TemplateEmail) -> DriverResult<()> + Send> {
@@ -132,21 +146,32 @@ impl GrpcServer {
         })
     }
 
+    /// Start metrics and extract request, key used to authenticate
+    /// request is required to have `scope` unless unrestricted.
     fn pre(
         &self,
         path: &str,
+        scope: KeyScope,
         req: tonic::Request<()>,
     ) -> Result<(GrpcServerMetrics, GrpcMethodRequest<()>), tonic::Status> {
         let metrics = GrpcServerMetrics::start(path, &self.count, &self.latency);
         Ok((
             metrics,
-            GrpcMethodRequest::from_unit(req, self.options().traefik_enabled())?,
+            GrpcMethodRequest::from_unit(
+                req,
+                self.options().traefik_enabled(),
+                self.options().trusted_proxy_cidr(),
+            )?
+            .scope(scope),
         ))
     }
 
+    /// Start metrics and extract validated request, key used to authenticate
+    /// request is required to have `scope` unless unrestricted.
     fn pre_validate<R, T>(
         &self,
         path: &str,
+        scope: KeyScope,
         req: tonic::Request<R>,
     ) -> Result<(GrpcServerMetrics, GrpcMethodRequest<T>), tonic::Status>
     where
@@ -156,7 +181,12 @@ impl GrpcServer {
         let metrics = GrpcServerMetrics::start(path, &self.count, &self.latency);
         Ok((
             metrics,
-            GrpcMethodRequest::from_request(req, self.options().traefik_enabled())?,
+            GrpcMethodRequest::from_request(
+                req,
+                self.options().traefik_enabled(),
+                self.options().trusted_proxy_cidr(),
+            )?
+            .scope(scope),
         ))
     }
 
@@ -212,140 +242,449 @@ impl pb::sso_server::Sso for GrpcServer {
         &self,
         request: tonic::Request<pb::AuditListRequest>,
     ) -> Result<tonic::Response<pb::AuditListReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("audit_list", request)?;
+        let (metrics, request) = self.pre_validate("audit_list", KeyScope::AuditRead, request)?;
         self.post(metrics, method::audit::list(self, request).await)
     }
     async fn audit_create(
         &self,
         request: tonic::Request<pb::AuditCreateRequest>,
     ) -> Result<tonic::Response<pb::AuditReadReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("audit_create", request)?;
+        let (metrics, request) =
+            self.pre_validate("audit_create", KeyScope::AuditWrite, request)?;
         self.post(metrics, method::audit::create(self, request).await)
     }
     async fn audit_read(
         &self,
         request: tonic::Request<pb::AuditReadRequest>,
     ) -> Result<tonic::Response<pb::AuditReadReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("audit_read", request)?;
+        let (metrics, request) = self.pre_validate("audit_read", KeyScope::AuditRead, request)?;
         self.post(metrics, method::audit::read(self, request).await)
     }
     async fn audit_update(
         &self,
         request: tonic::Request<pb::AuditUpdateRequest>,
     ) -> Result<tonic::Response<pb::AuditReadReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("audit_update", request)?;
+        let (metrics, request) =
+            self.pre_validate("audit_update", KeyScope::AuditWrite, request)?;
         self.post(metrics, method::audit::update(self, request).await)
     }
+    type AuditWatchStream = stream::Map<
+        method::audit::AuditWatchReceiver,
+        fn(GrpcMethodResult<Audit>) -> Result<pb::Audit, tonic::Status>,
+    >;
+    async fn audit_watch(
+        &self,
+        request: tonic::Request<pb::AuditWatchRequest>,
+    ) -> Result<tonic::Response<Self::AuditWatchStream>, tonic::Status> {
+        let (metrics, request) = self.pre_validate("audit_watch", KeyScope::AuditRead, request)?;
+        let res = method::audit::watch(self, request)
+            .await
+            .map(|rx| rx.map(audit_watch_item as fn(_) -> _));
+        self.post(metrics, res)
+    }
+    async fn audit_report(
+        &self,
+        request: tonic::Request<pb::AuditReportRequest>,
+    ) -> Result<tonic::Response<pb::AuditReportReply>, tonic::Status> {
+        let (metrics, request) = self.pre_validate("audit_report", KeyScope::AuditRead, request)?;
+        self.post(metrics, method::audit::report(self, request).await)
+    }
+    async fn group_list(
+        &self,
+        request: tonic::Request<pb::GroupListRequest>,
+    ) -> Result<tonic::Response<pb::GroupListReply>, tonic::Status> {
+        let (metrics, request) = self.pre_validate("group_list", KeyScope::GroupRead, request)?;
+        self.post(metrics, method::group::list(self, request).await)
+    }
+    async fn group_create(
+        &self,
+        request: tonic::Request<pb::GroupCreateRequest>,
+    ) -> Result<tonic::Response<pb::GroupReadReply>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("group_create", KeyScope::GroupWrite, request)?;
+        self.post(metrics, method::group::create(self, request).await)
+    }
+    async fn group_read(
+        &self,
+        request: tonic::Request<pb::GroupReadRequest>,
+    ) -> Result<tonic::Response<pb::GroupReadReply>, tonic::Status> {
+        let (metrics, request) = self.pre_validate("group_read", KeyScope::GroupRead, request)?;
+        self.post(metrics, method::group::read(self, request).await)
+    }
+    async fn group_update(
+        &self,
+        request: tonic::Request<pb::GroupUpdateRequest>,
+    ) -> Result<tonic::Response<pb::GroupReadReply>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("group_update", KeyScope::GroupWrite, request)?;
+        self.post(metrics, method::group::update(self, request).await)
+    }
+    async fn group_delete(
+        &self,
+        request: tonic::Request<pb::GroupReadRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("group_delete", KeyScope::GroupWrite, request)?;
+        self.post(metrics, method::group::delete(self, request).await)
+    }
+    async fn group_user_create(
+        &self,
+        request: tonic::Request<pb::GroupUserRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("group_user_create", KeyScope::GroupWrite, request)?;
+        self.post(metrics, method::group::user_create(self, request).await)
+    }
+    async fn group_user_delete(
+        &self,
+        request: tonic::Request<pb::GroupUserRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("group_user_delete", KeyScope::GroupWrite, request)?;
+        self.post(metrics, method::group::user_delete(self, request).await)
+    }
+    async fn group_role_create(
+        &self,
+        request: tonic::Request<pb::GroupRoleRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("group_role_create", KeyScope::GroupWrite, request)?;
+        self.post(metrics, method::group::role_create(self, request).await)
+    }
+    async fn group_role_delete(
+        &self,
+        request: tonic::Request<pb::GroupRoleRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("group_role_delete", KeyScope::GroupWrite, request)?;
+        self.post(metrics, method::group::role_delete(self, request).await)
+    }
     async fn key_list(
         &self,
         request: tonic::Request<pb::KeyListRequest>,
     ) -> Result<tonic::Response<pb::KeyListReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("key_list", request)?;
+        let (metrics, request) = self.pre_validate("key_list", KeyScope::KeyRead, request)?;
         self.post(metrics, method::key::list(self, request).await)
     }
     async fn key_create(
         &self,
         request: tonic::Request<pb::KeyCreateRequest>,
     ) -> Result<tonic::Response<pb::KeyCreateReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("key_create", request)?;
+        let (metrics, request) = self.pre_validate("key_create", KeyScope::KeyWrite, request)?;
         self.post(metrics, method::key::create(self, request).await)
     }
     async fn key_read(
         &self,
         request: tonic::Request<pb::KeyReadRequest>,
     ) -> Result<tonic::Response<pb::KeyReadReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("key_read", request)?;
+        let (metrics, request) = self.pre_validate("key_read", KeyScope::KeyRead, request)?;
         self.post(metrics, method::key::read(self, request).await)
     }
     async fn key_update(
         &self,
         request: tonic::Request<pb::KeyUpdateRequest>,
     ) -> Result<tonic::Response<pb::KeyReadReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("key_update", request)?;
+        let (metrics, request) = self.pre_validate("key_update", KeyScope::KeyWrite, request)?;
         self.post(metrics, method::key::update(self, request).await)
     }
+    async fn key_rotate(
+        &self,
+        request: tonic::Request<pb::KeyRotateRequest>,
+    ) -> Result<tonic::Response<pb::KeyCreateReply>, tonic::Status> {
+        let (metrics, request) = self.pre_validate("key_rotate", KeyScope::KeyWrite, request)?;
+        self.post(metrics, method::key::rotate(self, request).await)
+    }
     async fn key_delete(
         &self,
         request: tonic::Request<pb::KeyReadRequest>,
     ) -> Result<tonic::Response<()>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("key_delete", request)?;
+        let (metrics, request) = self.pre_validate("key_delete", KeyScope::KeyWrite, request)?;
         self.post(metrics, method::key::delete(self, request).await)
     }
+    async fn organisation_list(
+        &self,
+        request: tonic::Request<pb::OrganisationListRequest>,
+    ) -> Result<tonic::Response<pb::OrganisationListReply>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("organisation_list", KeyScope::OrganisationRead, request)?;
+        self.post(metrics, method::organisation::list(self, request).await)
+    }
+    async fn organisation_create(
+        &self,
+        request: tonic::Request<pb::OrganisationCreateRequest>,
+    ) -> Result<tonic::Response<pb::OrganisationReadReply>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("organisation_create", KeyScope::OrganisationWrite, request)?;
+        self.post(metrics, method::organisation::create(self, request).await)
+    }
+    async fn organisation_read(
+        &self,
+        request: tonic::Request<pb::OrganisationReadRequest>,
+    ) -> Result<tonic::Response<pb::OrganisationReadReply>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("organisation_read", KeyScope::OrganisationRead, request)?;
+        self.post(metrics, method::organisation::read(self, request).await)
+    }
+    async fn organisation_update(
+        &self,
+        request: tonic::Request<pb::OrganisationUpdateRequest>,
+    ) -> Result<tonic::Response<pb::OrganisationReadReply>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("organisation_update", KeyScope::OrganisationWrite, request)?;
+        self.post(metrics, method::organisation::update(self, request).await)
+    }
+    async fn organisation_delete(
+        &self,
+        request: tonic::Request<pb::OrganisationReadRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("organisation_delete", KeyScope::OrganisationWrite, request)?;
+        self.post(metrics, method::organisation::delete(self, request).await)
+    }
+    async fn role_list(
+        &self,
+        request: tonic::Request<pb::RoleListRequest>,
+    ) -> Result<tonic::Response<pb::RoleListReply>, tonic::Status> {
+        let (metrics, request) = self.pre_validate("role_list", KeyScope::RoleRead, request)?;
+        self.post(metrics, method::role::list(self, request).await)
+    }
+    async fn role_create(
+        &self,
+        request: tonic::Request<pb::RoleCreateRequest>,
+    ) -> Result<tonic::Response<pb::RoleReadReply>, tonic::Status> {
+        let (metrics, request) = self.pre_validate("role_create", KeyScope::RoleWrite, request)?;
+        self.post(metrics, method::role::create(self, request).await)
+    }
+    async fn role_read(
+        &self,
+        request: tonic::Request<pb::RoleReadRequest>,
+    ) -> Result<tonic::Response<pb::RoleReadReply>, tonic::Status> {
+        let (metrics, request) = self.pre_validate("role_read", KeyScope::RoleRead, request)?;
+        self.post(metrics, method::role::read(self, request).await)
+    }
+    async fn role_update(
+        &self,
+        request: tonic::Request<pb::RoleUpdateRequest>,
+    ) -> Result<tonic::Response<pb::RoleReadReply>, tonic::Status> {
+        let (metrics, request) = self.pre_validate("role_update", KeyScope::RoleWrite, request)?;
+        self.post(metrics, method::role::update(self, request).await)
+    }
+    async fn role_delete(
+        &self,
+        request: tonic::Request<pb::RoleReadRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        let (metrics, request) = self.pre_validate("role_delete", KeyScope::RoleWrite, request)?;
+        self.post(metrics, method::role::delete(self, request).await)
+    }
+    async fn user_role_create(
+        &self,
+        request: tonic::Request<pb::UserRoleRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("user_role_create", KeyScope::RoleWrite, request)?;
+        self.post(metrics, method::role::user_create(self, request).await)
+    }
+    async fn user_role_delete(
+        &self,
+        request: tonic::Request<pb::UserRoleRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("user_role_delete", KeyScope::RoleWrite, request)?;
+        self.post(metrics, method::role::user_delete(self, request).await)
+    }
     async fn service_list(
         &self,
         request: tonic::Request<pb::ServiceListRequest>,
     ) -> Result<tonic::Response<pb::ServiceListReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("service_list", request)?;
+        let (metrics, request) =
+            self.pre_validate("service_list", KeyScope::ServiceRead, request)?;
         self.post(metrics, method::service::list(self, request).await)
     }
     async fn service_create(
         &self,
         request: tonic::Request<pb::ServiceCreateRequest>,
     ) -> Result<tonic::Response<pb::ServiceReadReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("service_create", request)?;
+        let (metrics, request) =
+            self.pre_validate("service_create", KeyScope::ServiceWrite, request)?;
         self.post(metrics, method::service::create(self, request).await)
     }
     async fn service_read(
         &self,
         request: tonic::Request<pb::ServiceReadRequest>,
     ) -> Result<tonic::Response<pb::ServiceReadReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("service_read", request)?;
+        let (metrics, request) =
+            self.pre_validate("service_read", KeyScope::ServiceRead, request)?;
         self.post(metrics, method::service::read(self, request).await)
     }
     async fn service_update(
         &self,
         request: tonic::Request<pb::ServiceUpdateRequest>,
     ) -> Result<tonic::Response<pb::ServiceReadReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("service_update", request)?;
+        let (metrics, request) =
+            self.pre_validate("service_update", KeyScope::ServiceWrite, request)?;
         self.post(metrics, method::service::update(self, request).await)
     }
     async fn service_delete(
         &self,
         request: tonic::Request<pb::ServiceReadRequest>,
     ) -> Result<tonic::Response<()>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("service_delete", request)?;
+        let (metrics, request) =
+            self.pre_validate("service_delete", KeyScope::ServiceWrite, request)?;
         self.post(metrics, method::service::delete(self, request).await)
     }
+    async fn service_user_list(
+        &self,
+        request: tonic::Request<pb::ServiceUserListRequest>,
+    ) -> Result<tonic::Response<pb::ServiceUserListReply>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("service_user_list", KeyScope::ServiceRead, request)?;
+        self.post(metrics, method::service::user_list(self, request).await)
+    }
+    async fn service_user_update(
+        &self,
+        request: tonic::Request<pb::ServiceUserUpdateRequest>,
+    ) -> Result<tonic::Response<pb::ServiceUserReadReply>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("service_user_update", KeyScope::ServiceWrite, request)?;
+        self.post(metrics, method::service::user_update(self, request).await)
+    }
     async fn user_list(
         &self,
         request: tonic::Request<pb::UserListRequest>,
     ) -> Result<tonic::Response<pb::UserListReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("user_list", request)?;
+        let (metrics, request) = self.pre_validate("user_list", KeyScope::UserRead, request)?;
         self.post(metrics, method::user::list(self, request).await)
     }
     async fn user_create(
         &self,
         request: tonic::Request<pb::UserCreateRequest>,
     ) -> Result<tonic::Response<pb::UserCreateReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("user_create", request)?;
+        let (metrics, request) = self.pre_validate("user_create", KeyScope::UserWrite, request)?;
         self.post(metrics, method::user::create(self, request).await)
     }
     async fn user_read(
         &self,
         request: tonic::Request<pb::UserReadRequest>,
     ) -> Result<tonic::Response<pb::UserReadReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("user_read", request)?;
+        let (metrics, request) = self.pre_validate("user_read", KeyScope::UserRead, request)?;
         self.post(metrics, method::user::read(self, request).await)
     }
     async fn user_update(
         &self,
         request: tonic::Request<pb::UserUpdateRequest>,
     ) -> Result<tonic::Response<pb::UserReadReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("user_update", request)?;
+        let (metrics, request) = self.pre_validate("user_update", KeyScope::UserWrite, request)?;
         self.post(metrics, method::user::update(self, request).await)
     }
     async fn user_delete(
         &self,
         request: tonic::Request<pb::UserReadRequest>,
     ) -> Result<tonic::Response<()>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("user_delete", request)?;
+        let (metrics, request) = self.pre_validate("user_delete", KeyScope::UserWrite, request)?;
         self.post(metrics, method::user::delete(self, request).await)
     }
+    async fn user_export(
+        &self,
+        request: tonic::Request<pb::UserReadRequest>,
+    ) -> Result<tonic::Response<pb::UserExportReply>, tonic::Status> {
+        let (metrics, request) = self.pre_validate("user_export", KeyScope::UserRead, request)?;
+        self.post(metrics, method::user::export(self, request).await)
+    }
+    async fn user_erase(
+        &self,
+        request: tonic::Request<pb::UserReadRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        let (metrics, request) = self.pre_validate("user_erase", KeyScope::UserWrite, request)?;
+        self.post(metrics, method::user::erase(self, request).await)
+    }
+    async fn user_invite(
+        &self,
+        request: tonic::Request<pb::UserInviteRequest>,
+    ) -> Result<tonic::Response<pb::UserInviteReply>, tonic::Status> {
+        let (metrics, request) = self.pre_validate("user_invite", KeyScope::UserWrite, request)?;
+        self.post(metrics, method::user::invite(self, request).await)
+    }
+    async fn user_invite_resend(
+        &self,
+        request: tonic::Request<pb::UserInviteResendRequest>,
+    ) -> Result<tonic::Response<pb::ServiceUserReadReply>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("user_invite_resend", KeyScope::UserWrite, request)?;
+        self.post(metrics, method::user::invite_resend(self, request).await)
+    }
+    async fn user_invite_revoke(
+        &self,
+        request: tonic::Request<pb::UserInviteRevokeRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("user_invite_revoke", KeyScope::UserWrite, request)?;
+        self.post(metrics, method::user::invite_revoke(self, request).await)
+    }
+    async fn webhook_list(
+        &self,
+        request: tonic::Request<pb::WebhookListRequest>,
+    ) -> Result<tonic::Response<pb::WebhookListReply>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("webhook_list", KeyScope::WebhookRead, request)?;
+        self.post(metrics, method::webhook::list(self, request).await)
+    }
+    async fn webhook_create(
+        &self,
+        request: tonic::Request<pb::WebhookCreateRequest>,
+    ) -> Result<tonic::Response<pb::WebhookCreateReply>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("webhook_create", KeyScope::WebhookWrite, request)?;
+        self.post(metrics, method::webhook::create(self, request).await)
+    }
+    async fn webhook_read(
+        &self,
+        request: tonic::Request<pb::WebhookReadRequest>,
+    ) -> Result<tonic::Response<pb::WebhookReadReply>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("webhook_read", KeyScope::WebhookRead, request)?;
+        self.post(metrics, method::webhook::read(self, request).await)
+    }
+    async fn webhook_update(
+        &self,
+        request: tonic::Request<pb::WebhookUpdateRequest>,
+    ) -> Result<tonic::Response<pb::WebhookReadReply>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("webhook_update", KeyScope::WebhookWrite, request)?;
+        self.post(metrics, method::webhook::update(self, request).await)
+    }
+    async fn webhook_delete(
+        &self,
+        request: tonic::Request<pb::WebhookReadRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("webhook_delete", KeyScope::WebhookWrite, request)?;
+        self.post(metrics, method::webhook::delete(self, request).await)
+    }
+    async fn webhook_delivery_list(
+        &self,
+        request: tonic::Request<pb::WebhookDeliveryListRequest>,
+    ) -> Result<tonic::Response<pb::WebhookDeliveryListReply>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("webhook_delivery_list", KeyScope::WebhookRead, request)?;
+        self.post(metrics, method::webhook::delivery_list(self, request).await)
+    }
+    async fn webhook_delivery_retry(
+        &self,
+        request: tonic::Request<pb::WebhookDeliveryRetryRequest>,
+    ) -> Result<tonic::Response<pb::WebhookDeliveryReadReply>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("webhook_delivery_retry", KeyScope::WebhookWrite, request)?;
+        self.post(
+            metrics,
+            method::webhook::delivery_retry(self, request).await,
+        )
+    }
     async fn auth_key_verify(
         &self,
         request: tonic::Request<pb::AuthKeyRequest>,
     ) -> Result<tonic::Response<pb::AuthKeyReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("auth_key_verify", request)?;
+        let (metrics, request) =
+            self.pre_validate("auth_key_verify", KeyScope::AuthKey, request)?;
         self.post(metrics, method::auth::key::verify(self, request).await)
             .map_err(|e| tonic::Status::new(e.code(), ERR_REDACTED))
     }
@@ -353,7 +692,8 @@ impl pb::sso_server::Sso for GrpcServer {
         &self,
         request: tonic::Request<pb::AuthKeyRequest>,
     ) -> Result<tonic::Response<pb::AuthAuditReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("auth_key_revoke", request)?;
+        let (metrics, request) =
+            self.pre_validate("auth_key_revoke", KeyScope::AuthKey, request)?;
         self.post(metrics, method::auth::key::revoke(self, request).await)
             .map_err(|e| tonic::Status::new(e.code(), ERR_REDACTED))
     }
@@ -361,7 +701,8 @@ impl pb::sso_server::Sso for GrpcServer {
         &self,
         request: tonic::Request<pb::AuthTokenRequest>,
     ) -> Result<tonic::Response<pb::AuthTokenVerifyReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("auth_token_verify", request)?;
+        let (metrics, request) =
+            self.pre_validate("auth_token_verify", KeyScope::AuthToken, request)?;
         self.post(metrics, method::auth::token::verify(self, request).await)
             .map_err(|e| tonic::Status::new(e.code(), ERR_REDACTED))
     }
@@ -369,7 +710,8 @@ impl pb::sso_server::Sso for GrpcServer {
         &self,
         request: tonic::Request<pb::AuthTokenRequest>,
     ) -> Result<tonic::Response<pb::AuthTokenReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("auth_token_refresh", request)?;
+        let (metrics, request) =
+            self.pre_validate("auth_token_refresh", KeyScope::AuthToken, request)?;
         self.post(metrics, method::auth::token::refresh(self, request).await)
             .map_err(|e| tonic::Status::new(e.code(), ERR_REDACTED))
     }
@@ -377,15 +719,28 @@ impl pb::sso_server::Sso for GrpcServer {
         &self,
         request: tonic::Request<pb::AuthTokenRequest>,
     ) -> Result<tonic::Response<pb::AuthAuditReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("auth_token_revoke", request)?;
+        let (metrics, request) =
+            self.pre_validate("auth_token_revoke", KeyScope::AuthToken, request)?;
         self.post(metrics, method::auth::token::revoke(self, request).await)
             .map_err(|e| tonic::Status::new(e.code(), ERR_REDACTED))
     }
+    async fn auth_impersonate(
+        &self,
+        request: tonic::Request<pb::AuthImpersonateRequest>,
+    ) -> Result<tonic::Response<pb::AuthImpersonateReply>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("auth_impersonate", KeyScope::AuthImpersonate, request)?;
+        self.post(
+            metrics,
+            method::auth::token::impersonate(self, request).await,
+        )
+    }
     async fn auth_totp_verify(
         &self,
         request: tonic::Request<pb::AuthTotpRequest>,
     ) -> Result<tonic::Response<pb::AuthAuditReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("auth_totp_verify", request)?;
+        let (metrics, request) =
+            self.pre_validate("auth_totp_verify", KeyScope::AuthTotp, request)?;
         self.post(metrics, method::auth::totp_verify(self, request).await)
             .map_err(|e| tonic::Status::new(e.code(), ERR_REDACTED))
     }
@@ -393,7 +748,8 @@ impl pb::sso_server::Sso for GrpcServer {
         &self,
         request: tonic::Request<pb::AuthCsrfCreateRequest>,
     ) -> Result<tonic::Response<pb::AuthCsrfCreateReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("auth_csrf_create", request)?;
+        let (metrics, request) =
+            self.pre_validate("auth_csrf_create", KeyScope::AuthCsrf, request)?;
         self.post(metrics, method::auth::csrf_create(self, request).await)
             .map_err(|e| tonic::Status::new(e.code(), ERR_REDACTED))
     }
@@ -401,7 +757,8 @@ impl pb::sso_server::Sso for GrpcServer {
         &self,
         request: tonic::Request<pb::AuthCsrfVerifyRequest>,
     ) -> Result<tonic::Response<pb::AuthAuditReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("auth_csrf_verify", request)?;
+        let (metrics, request) =
+            self.pre_validate("auth_csrf_verify", KeyScope::AuthCsrf, request)?;
         self.post(metrics, method::auth::csrf_verify(self, request).await)
             .map_err(|e| tonic::Status::new(e.code(), ERR_REDACTED))
     }
@@ -409,15 +766,29 @@ impl pb::sso_server::Sso for GrpcServer {
         &self,
         request: tonic::Request<pb::AuthLoginRequest>,
     ) -> Result<tonic::Response<pb::AuthLoginReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("auth_local_login", request)?;
+        let (metrics, request) =
+            self.pre_validate("auth_local_login", KeyScope::AuthLocal, request)?;
         self.post(metrics, method::auth::local::login(self, request).await)
             .map_err(|e| tonic::Status::new(e.code(), ERR_REDACTED))
     }
+    async fn auth_local_login_revoke(
+        &self,
+        request: tonic::Request<pb::AuthTokenRequest>,
+    ) -> Result<tonic::Response<pb::AuthAuditReply>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("auth_local_login_revoke", KeyScope::AuthLocal, request)?;
+        self.post(
+            metrics,
+            method::auth::local::login_revoke(self, request).await,
+        )
+        .map_err(|e| tonic::Status::new(e.code(), ERR_REDACTED))
+    }
     async fn auth_local_register(
         &self,
         request: tonic::Request<pb::AuthRegisterRequest>,
     ) -> Result<tonic::Response<()>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("auth_local_register", request)?;
+        let (metrics, request) =
+            self.pre_validate("auth_local_register", KeyScope::AuthLocal, request)?;
         self.post(metrics, method::auth::local::register(self, request).await)
             .map_err(|e| tonic::Status::new(e.code(), ERR_REDACTED))
     }
@@ -425,7 +796,8 @@ impl pb::sso_server::Sso for GrpcServer {
         &self,
         request: tonic::Request<pb::AuthRegisterConfirmRequest>,
     ) -> Result<tonic::Response<pb::AuthPasswordMetaReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("auth_local_register_confirm", request)?;
+        let (metrics, request) =
+            self.pre_validate("auth_local_register_confirm", KeyScope::AuthLocal, request)?;
         self.post(
             metrics,
             method::auth::local::register_confirm(self, request).await,
@@ -436,7 +808,8 @@ impl pb::sso_server::Sso for GrpcServer {
         &self,
         request: tonic::Request<pb::AuthTokenRequest>,
     ) -> Result<tonic::Response<pb::AuthAuditReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("auth_local_register_revoke", request)?;
+        let (metrics, request) =
+            self.pre_validate("auth_local_register_revoke", KeyScope::AuthLocal, request)?;
         self.post(
             metrics,
             method::auth::local::register_revoke(self, request).await,
@@ -447,7 +820,8 @@ impl pb::sso_server::Sso for GrpcServer {
         &self,
         request: tonic::Request<pb::AuthResetPasswordRequest>,
     ) -> Result<tonic::Response<()>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("auth_local_reset_password", request)?;
+        let (metrics, request) =
+            self.pre_validate("auth_local_reset_password", KeyScope::AuthLocal, request)?;
         self.post(
             metrics,
             method::auth::local::reset_password(self, request).await,
@@ -458,7 +832,11 @@ impl pb::sso_server::Sso for GrpcServer {
         &self,
         request: tonic::Request<pb::AuthResetPasswordConfirmRequest>,
     ) -> Result<tonic::Response<pb::AuthPasswordMetaReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("auth_local_reset_password_confirm", request)?;
+        let (metrics, request) = self.pre_validate(
+            "auth_local_reset_password_confirm",
+            KeyScope::AuthLocal,
+            request,
+        )?;
         self.post(
             metrics,
             method::auth::local::reset_password_confirm(self, request).await,
@@ -469,7 +847,11 @@ impl pb::sso_server::Sso for GrpcServer {
         &self,
         request: tonic::Request<pb::AuthTokenRequest>,
     ) -> Result<tonic::Response<pb::AuthAuditReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("auth_local_reset_password_revoke", request)?;
+        let (metrics, request) = self.pre_validate(
+            "auth_local_reset_password_revoke",
+            KeyScope::AuthLocal,
+            request,
+        )?;
         self.post(
             metrics,
             method::auth::local::reset_password_revoke(self, request).await,
@@ -480,18 +862,38 @@ impl pb::sso_server::Sso for GrpcServer {
         &self,
         request: tonic::Request<pb::AuthUpdateEmailRequest>,
     ) -> Result<tonic::Response<()>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("auth_local_update_email", request)?;
+        let (metrics, request) =
+            self.pre_validate("auth_local_update_email", KeyScope::AuthLocal, request)?;
         self.post(
             metrics,
             method::auth::local::update_email(self, request).await,
         )
         .map_err(|e| tonic::Status::new(e.code(), ERR_REDACTED))
     }
+    async fn auth_local_update_email_confirm(
+        &self,
+        request: tonic::Request<pb::AuthTokenRequest>,
+    ) -> Result<tonic::Response<pb::AuthAuditReply>, tonic::Status> {
+        let (metrics, request) = self.pre_validate(
+            "auth_local_update_email_confirm",
+            KeyScope::AuthLocal,
+            request,
+        )?;
+        self.post(
+            metrics,
+            method::auth::local::update_email_confirm(self, request).await,
+        )
+        .map_err(|e| tonic::Status::new(e.code(), ERR_REDACTED))
+    }
     async fn auth_local_update_email_revoke(
         &self,
         request: tonic::Request<pb::AuthTokenRequest>,
     ) -> Result<tonic::Response<pb::AuthAuditReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("auth_local_update_email_revoke", request)?;
+        let (metrics, request) = self.pre_validate(
+            "auth_local_update_email_revoke",
+            KeyScope::AuthLocal,
+            request,
+        )?;
         self.post(
             metrics,
             method::auth::local::update_email_revoke(self, request).await,
@@ -502,7 +904,8 @@ impl pb::sso_server::Sso for GrpcServer {
         &self,
         request: tonic::Request<pb::AuthUpdatePasswordRequest>,
     ) -> Result<tonic::Response<pb::AuthPasswordMetaReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("auth_local_update_password", request)?;
+        let (metrics, request) =
+            self.pre_validate("auth_local_update_password", KeyScope::AuthLocal, request)?;
         self.post(
             metrics,
             method::auth::local::update_password(self, request).await,
@@ -513,18 +916,62 @@ impl pb::sso_server::Sso for GrpcServer {
         &self,
         request: tonic::Request<pb::AuthTokenRequest>,
     ) -> Result<tonic::Response<pb::AuthAuditReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("auth_local_update_password_revoke", request)?;
+        let (metrics, request) = self.pre_validate(
+            "auth_local_update_password_revoke",
+            KeyScope::AuthLocal,
+            request,
+        )?;
         self.post(
             metrics,
             method::auth::local::update_password_revoke(self, request).await,
         )
         .map_err(|e| tonic::Status::new(e.code(), ERR_REDACTED))
     }
+    async fn auth_local_verify_email(
+        &self,
+        request: tonic::Request<pb::AuthVerifyEmailRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("auth_local_verify_email", KeyScope::AuthLocal, request)?;
+        self.post(
+            metrics,
+            method::auth::local::verify_email(self, request).await,
+        )
+        .map_err(|e| tonic::Status::new(e.code(), ERR_REDACTED))
+    }
+    async fn auth_local_verify_email_confirm(
+        &self,
+        request: tonic::Request<pb::AuthTokenRequest>,
+    ) -> Result<tonic::Response<pb::AuthAuditReply>, tonic::Status> {
+        let (metrics, request) = self.pre_validate(
+            "auth_local_verify_email_confirm",
+            KeyScope::AuthLocal,
+            request,
+        )?;
+        self.post(
+            metrics,
+            method::auth::local::verify_email_confirm(self, request).await,
+        )
+        .map_err(|e| tonic::Status::new(e.code(), ERR_REDACTED))
+    }
+    async fn auth_local_invite_confirm(
+        &self,
+        request: tonic::Request<pb::AuthRegisterConfirmRequest>,
+    ) -> Result<tonic::Response<pb::AuthPasswordMetaReply>, tonic::Status> {
+        let (metrics, request) =
+            self.pre_validate("auth_local_invite_confirm", KeyScope::AuthLocal, request)?;
+        self.post(
+            metrics,
+            method::auth::local::invite_confirm(self, request).await,
+        )
+        .map_err(|e| tonic::Status::new(e.code(), ERR_REDACTED))
+    }
     async fn auth_github_oauth2_url(
         &self,
         request: tonic::Request<()>,
     ) -> Result<tonic::Response<pb::AuthOauth2UrlReply>, tonic::Status> {
-        let (metrics, request) = self.pre("auth_github_oauth2_url", request)?;
+        let (metrics, request) =
+            self.pre("auth_github_oauth2_url", KeyScope::AuthGithub, request)?;
         self.post(
             metrics,
             method::auth::github::oauth2_url(self, request).await,
@@ -535,7 +982,8 @@ impl pb::sso_server::Sso for GrpcServer {
         &self,
         request: tonic::Request<pb::AuthOauth2CallbackRequest>,
     ) -> Result<tonic::Response<pb::AuthTokenReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("auth_github_oauth2_callback", request)?;
+        let (metrics, request) =
+            self.pre_validate("auth_github_oauth2_callback", KeyScope::AuthGithub, request)?;
         self.post(
             metrics,
             method::auth::github::oauth2_callback(self, request).await,
@@ -546,7 +994,11 @@ impl pb::sso_server::Sso for GrpcServer {
         &self,
         request: tonic::Request<()>,
     ) -> Result<tonic::Response<pb::AuthOauth2UrlReply>, tonic::Status> {
-        let (metrics, request) = self.pre("auth_microsoft_oauth2_url", request)?;
+        let (metrics, request) = self.pre(
+            "auth_microsoft_oauth2_url",
+            KeyScope::AuthMicrosoft,
+            request,
+        )?;
         self.post(
             metrics,
             method::auth::microsoft::oauth2_url(self, request).await,
@@ -557,7 +1009,11 @@ impl pb::sso_server::Sso for GrpcServer {
         &self,
         request: tonic::Request<pb::AuthOauth2CallbackRequest>,
     ) -> Result<tonic::Response<pb::AuthTokenReply>, tonic::Status> {
-        let (metrics, request) = self.pre_validate("auth_microsoft_oauth2_callback", request)?;
+        let (metrics, request) = self.pre_validate(
+            "auth_microsoft_oauth2_callback",
+            KeyScope::AuthMicrosoft,
+            request,
+        )?;
         self.post(
             metrics,
             method::auth::microsoft::oauth2_callback(self, request).await,
@@ -565,3 +1021,8 @@ impl pb::sso_server::Sso for GrpcServer {
         .map_err(|e| tonic::Status::new(e.code(), ERR_REDACTED))
     }
 }
+
+/// Convert audit watch receiver item to stream message.
+fn audit_watch_item(x: GrpcMethodResult<Audit>) -> Result<pb::Audit, tonic::Status> {
+    x.map(Into::into).map_err(Into::into)
+}
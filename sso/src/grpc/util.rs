@@ -76,6 +76,20 @@ struct GrpcMethodErrorData {
 }
 
 impl GrpcMethodError {
+    /// Map authentication pattern function errors, returns forbidden
+    /// for keys which are valid but not in scope for method or used
+    /// from a remote address which is not allowed.
+    pub fn authenticate(e: DriverError) -> Self {
+        match e {
+            DriverError::KeyScopeForbidden
+            | DriverError::KeyRemoteForbidden
+            | DriverError::ServiceRemoteForbidden
+            | DriverError::OrganisationDisabled
+            | DriverError::UserImpersonationForbidden => GrpcMethodError::Forbidden(e),
+            _ => GrpcMethodError::Unauthorised(e),
+        }
+    }
+
     pub fn get_status(&self) -> Status {
         match self {
             GrpcMethodError::BadRequest(e) => Status::invalid_argument(self.driver_string(e)),
@@ -136,13 +150,21 @@ pub struct GrpcMethodRequest<T> {
 }
 
 impl<T> GrpcMethodRequest<T> {
-    pub fn from_request<R>(request: Request<R>, traefik_enabled: bool) -> GrpcMethodResult<Self>
+    pub fn from_request<R>(
+        request: Request<R>,
+        traefik_enabled: bool,
+        trusted_proxy: &[Cidr],
+    ) -> GrpcMethodResult<Self>
     where
         R: validator::Validate,
         T: From<R>,
     {
-        let (audit, auth) =
-            request_audit_auth(request.remote_addr(), request.metadata(), traefik_enabled)?;
+        let (audit, auth) = request_audit_auth(
+            request.remote_addr(),
+            request.metadata(),
+            traefik_enabled,
+            trusted_proxy,
+        )?;
         let message =
             validate::validate(request.into_inner()).map_err(GrpcMethodError::BadRequest)?;
         Ok(GrpcMethodRequest {
@@ -152,12 +174,20 @@ impl<T> GrpcMethodRequest<T> {
         })
     }
 
-    pub fn from_unit(request: Request<()>, traefik_enabled: bool) -> GrpcMethodResult<Self>
+    pub fn from_unit(
+        request: Request<()>,
+        traefik_enabled: bool,
+        trusted_proxy: &[Cidr],
+    ) -> GrpcMethodResult<Self>
     where
         T: Default,
     {
-        let (audit, auth) =
-            request_audit_auth(request.remote_addr(), request.metadata(), traefik_enabled)?;
+        let (audit, auth) = request_audit_auth(
+            request.remote_addr(),
+            request.metadata(),
+            traefik_enabled,
+            trusted_proxy,
+        )?;
         Ok(GrpcMethodRequest {
             audit,
             auth,
@@ -165,6 +195,12 @@ impl<T> GrpcMethodRequest<T> {
         })
     }
 
+    /// Set key scope required by request.
+    pub fn scope(mut self, scope: KeyScope) -> Self {
+        self.audit.set_scope(Some(scope));
+        self
+    }
+
     pub fn into_inner(self) -> (AuditMeta, HeaderAuth, T) {
         (self.audit, self.auth, self.message)
     }
@@ -174,9 +210,14 @@ impl GrpcMethodRequest<serde_json::Value> {
     pub fn from_struct(
         request: Request<prost_types::Struct>,
         traefik_enabled: bool,
+        trusted_proxy: &[Cidr],
     ) -> GrpcMethodResult<GrpcMethodRequest<serde_json::Value>> {
-        let (audit, auth) =
-            request_audit_auth(request.remote_addr(), request.metadata(), traefik_enabled)?;
+        let (audit, auth) = request_audit_auth(
+            request.remote_addr(),
+            request.metadata(),
+            traefik_enabled,
+            trusted_proxy,
+        )?;
         let message = pb::struct_opt_to_value_opt(Some(request.into_inner())).unwrap();
         Ok(GrpcMethodRequest {
             audit,
@@ -229,7 +270,15 @@ where
 {
     let mut audit = AuditBuilder::new(audit_meta, audit_type);
     match f(driver, &mut audit) {
-        Ok(res) => Ok(res),
+        Ok(res) => {
+            // Requests authenticated by impersonation tokens are always audited.
+            if audit.get_actor_key_id().is_some() {
+                audit
+                    .create_data::<bool>(driver, 0, None, None)
+                    .map_err(GrpcMethodError::InternalServerError)?;
+            }
+            Ok(res)
+        }
         Err(e) => {
             let data = e.get_data();
             audit
@@ -317,6 +366,7 @@ fn request_audit_auth(
     remote: Option<SocketAddr>,
     metadata: &MetadataMap,
     traefik_enabled: bool,
+    trusted_proxy: &[Cidr],
 ) -> GrpcMethodResult<(AuditMeta, HeaderAuth)> {
     let remote = match remote {
         Some(remote) => format!("{}", remote),
@@ -324,11 +374,139 @@ fn request_audit_auth(
     };
     let header_map = metadata.clone().into_headers();
     Ok((
-        AuditMeta::from_header_map(&header_map, remote),
+        AuditMeta::from_header_map(&header_map, remote, trusted_proxy),
         HeaderAuth::from_header_map(&header_map, traefik_enabled),
     ))
 }
 
+impl From<pb::GroupListRequest> for GroupList {
+    fn from(r: pb::GroupListRequest) -> Self {
+        let limit = r.limit.unwrap_or(DEFAULT_LIMIT);
+        let gt = pb::string_opt_to_uuid_opt(r.gt);
+        let lt = pb::string_opt_to_uuid_opt(r.lt);
+        let query = match (gt, lt) {
+            (Some(gt), Some(_lt)) => GroupListQuery::IdGt(gt),
+            (Some(gt), None) => GroupListQuery::IdGt(gt),
+            (None, Some(lt)) => GroupListQuery::IdLt(lt),
+            (None, None) => GroupListQuery::Limit,
+        };
+        let filter = GroupListFilter {
+            id: pb::string_vec_to_uuid_vec_opt(r.id),
+            is_enabled: r.is_enabled,
+            parent_id: pb::string_vec_to_uuid_vec_opt(r.parent_id),
+            service_id: pb::string_vec_to_uuid_vec_opt(r.service_id),
+            user_id: pb::string_opt_to_uuid_opt(r.user_id),
+            limit,
+        };
+        Self { query, filter }
+    }
+}
+
+impl From<pb::GroupCreateRequest> for GroupCreate {
+    fn from(r: pb::GroupCreateRequest) -> Self {
+        Self {
+            service_id: pb::string_to_uuid(r.service_id),
+            is_enabled: r.is_enabled.unwrap_or(true),
+            name: r.name,
+            parent_id: pb::string_opt_to_uuid_opt(r.parent_id),
+        }
+    }
+}
+
+impl From<pb::GroupReadRequest> for GroupRead {
+    fn from(r: pb::GroupReadRequest) -> Self {
+        Self {
+            id: pb::string_to_uuid(r.id),
+        }
+    }
+}
+
+impl From<pb::GroupUpdateRequest> for GroupUpdate {
+    fn from(r: pb::GroupUpdateRequest) -> Self {
+        Self {
+            id: pb::string_to_uuid(r.id),
+            is_enabled: r.is_enabled,
+            name: r.name,
+            parent_id: r.parent.map(|x| pb::string_opt_to_uuid_opt(x.id)),
+        }
+    }
+}
+
+impl From<GroupList> for pb::GroupListRequest {
+    fn from(l: GroupList) -> Self {
+        let id = pb::uuid_vec_opt_to_string_vec(l.filter.id);
+        let is_enabled = l.filter.is_enabled;
+        let parent_id = pb::uuid_vec_opt_to_string_vec(l.filter.parent_id);
+        let service_id = pb::uuid_vec_opt_to_string_vec(l.filter.service_id);
+        let user_id = pb::uuid_opt_to_string_opt(l.filter.user_id);
+        let limit = l.filter.limit;
+        match l.query {
+            GroupListQuery::Limit => Self {
+                gt: None,
+                lt: None,
+                limit: Some(limit),
+                id,
+                is_enabled,
+                parent_id,
+                service_id,
+                user_id,
+            },
+            GroupListQuery::IdGt(gt) => Self {
+                gt: Some(pb::uuid_to_string(gt)),
+                lt: None,
+                limit: Some(limit),
+                id,
+                is_enabled,
+                parent_id,
+                service_id,
+                user_id,
+            },
+            GroupListQuery::IdLt(lt) => Self {
+                gt: None,
+                lt: Some(pb::uuid_to_string(lt)),
+                limit: Some(limit),
+                id,
+                is_enabled,
+                parent_id,
+                service_id,
+                user_id,
+            },
+        }
+    }
+}
+
+impl From<Group> for pb::Group {
+    fn from(r: Group) -> Self {
+        Self {
+            created_at: pb::datetime_to_timestamp_opt(r.created_at),
+            updated_at: pb::datetime_to_timestamp_opt(r.updated_at),
+            id: pb::uuid_to_string(r.id),
+            is_enabled: r.is_enabled,
+            name: r.name,
+            parent_id: pb::uuid_opt_to_string_opt(r.parent_id),
+            service_id: pb::uuid_to_string(r.service_id),
+        }
+    }
+}
+
+impl From<pb::GroupUserRequest> for GroupUser {
+    fn from(r: pb::GroupUserRequest) -> Self {
+        Self {
+            group_id: pb::string_to_uuid(r.group_id),
+            user_id: pb::string_to_uuid(r.user_id),
+        }
+    }
+}
+
+impl From<pb::GroupRoleRequest> for GroupRole {
+    fn from(r: pb::GroupRoleRequest) -> Self {
+        Self {
+            group_id: pb::string_to_uuid(r.group_id),
+            role_id: pb::string_to_uuid(r.role_id),
+        }
+    }
+}
+
 impl From<pb::KeyListRequest> for KeyList {
     fn from(r: pb::KeyListRequest) -> Self {
         let limit = r.limit.unwrap_or(DEFAULT_LIMIT);
@@ -347,6 +525,7 @@ impl From<pb::KeyListRequest> for KeyList {
             type_: pb::i32_vec_to_key_type_vec_opt(r.r#type),
             service_id: pb::string_vec_to_uuid_vec_opt(r.service_id),
             user_id: pb::string_vec_to_uuid_vec_opt(r.user_id),
+            last_used_lt: pb::timestamp_opt_to_datetime_opt(r.last_used_lt),
             limit,
         };
         KeyList { query, filter }
@@ -361,6 +540,7 @@ impl From<KeyList> for pb::KeyListRequest {
         let type_ = pb::key_type_vec_opt_to_i32_vec(l.filter.type_);
         let service_id = pb::uuid_vec_opt_to_string_vec(l.filter.service_id);
         let user_id = pb::uuid_vec_opt_to_string_vec(l.filter.user_id);
+        let last_used_lt = pb::datetime_opt_to_timestamp_opt(l.filter.last_used_lt);
         let limit = l.filter.limit;
         match l.query {
             KeyListQuery::Limit => Self {
@@ -373,6 +553,7 @@ impl From<KeyList> for pb::KeyListRequest {
                 r#type: type_,
                 service_id,
                 user_id,
+                last_used_lt,
             },
             KeyListQuery::IdGt(gt) => Self {
                 gt: Some(pb::uuid_to_string(gt)),
@@ -384,6 +565,7 @@ impl From<KeyList> for pb::KeyListRequest {
                 r#type: type_,
                 service_id,
                 user_id,
+                last_used_lt,
             },
             KeyListQuery::IdLt(lt) => Self {
                 gt: None,
@@ -395,6 +577,7 @@ impl From<KeyList> for pb::KeyListRequest {
                 r#type: type_,
                 service_id,
                 user_id,
+                last_used_lt,
             },
         }
     }
@@ -412,6 +595,15 @@ impl From<Key> for pb::Key {
             name: r.name,
             service_id: pb::uuid_opt_to_string_opt(r.service_id),
             user_id: pb::uuid_opt_to_string_opt(r.user_id),
+            scope: r.scope.iter().map(|x| x.to_string()).collect(),
+            value_prefix: r.value_prefix,
+            predecessor_id: pb::uuid_opt_to_string_opt(r.predecessor_id),
+            successor_id: pb::uuid_opt_to_string_opt(r.successor_id),
+            revoke_at: pb::datetime_opt_to_timestamp_opt(r.revoke_at),
+            last_used_at: pb::datetime_opt_to_timestamp_opt(r.last_used_at),
+            last_used_remote: r.last_used_remote,
+            allow_remote: pb::cidr_vec_to_string_vec(&r.allow_remote),
+            organisation_id: pb::uuid_opt_to_string_opt(r.organisation_id),
         }
     }
 }
@@ -428,6 +620,15 @@ impl From<KeyWithValue> for pb::Key {
             name: r.name,
             service_id: pb::uuid_opt_to_string_opt(r.service_id),
             user_id: pb::uuid_opt_to_string_opt(r.user_id),
+            scope: r.scope.iter().map(|x| x.to_string()).collect(),
+            value_prefix: r.value_prefix,
+            predecessor_id: pb::uuid_opt_to_string_opt(r.predecessor_id),
+            successor_id: pb::uuid_opt_to_string_opt(r.successor_id),
+            revoke_at: pb::datetime_opt_to_timestamp_opt(r.revoke_at),
+            last_used_at: pb::datetime_opt_to_timestamp_opt(r.last_used_at),
+            last_used_remote: r.last_used_remote,
+            allow_remote: pb::cidr_vec_to_string_vec(&r.allow_remote),
+            organisation_id: pb::uuid_opt_to_string_opt(r.organisation_id),
         }
     }
 }
@@ -442,6 +643,10 @@ impl From<pb::KeyCreateRequest> for KeyCreate {
             value: "".to_owned(),
             service_id: pb::string_opt_to_uuid_opt(r.service_id),
             user_id: pb::string_opt_to_uuid_opt(r.user_id),
+            scope: pb::string_vec_to_key_scope_vec(r.scope),
+            allow_remote: pb::string_vec_to_cidr_vec(r.allow_remote),
+            predecessor_id: None,
+            organisation_id: None,
         }
     }
 }
@@ -476,6 +681,109 @@ impl From<pb::KeyUpdateRequest> for KeyUpdate {
     }
 }
 
+impl From<pb::KeyRotateRequest> for KeyRotate {
+    fn from(r: pb::KeyRotateRequest) -> Self {
+        let grace_period_s = r
+            .grace_period_s
+            .unwrap_or(DEFAULT_KEY_ROTATE_GRACE_PERIOD_S);
+        Self {
+            id: pb::string_to_uuid(r.id),
+            name: r.name,
+            grace_period: Duration::seconds(grace_period_s),
+        }
+    }
+}
+
+impl From<pb::OrganisationListRequest> for OrganisationList {
+    fn from(r: pb::OrganisationListRequest) -> Self {
+        let limit = r.limit.unwrap_or(DEFAULT_LIMIT);
+        let gt = pb::string_opt_to_uuid_opt(r.gt);
+        let lt = pb::string_opt_to_uuid_opt(r.lt);
+        let query = match (gt, lt) {
+            (Some(gt), Some(_lt)) => OrganisationListQuery::IdGt(gt),
+            (Some(gt), None) => OrganisationListQuery::IdGt(gt),
+            (None, Some(lt)) => OrganisationListQuery::IdLt(lt),
+            (None, None) => OrganisationListQuery::Limit,
+        };
+        let filter = OrganisationListFilter {
+            id: pb::string_vec_to_uuid_vec_opt(r.id),
+            is_enabled: r.is_enabled,
+            limit,
+        };
+        Self { query, filter }
+    }
+}
+
+impl From<pb::OrganisationCreateRequest> for OrganisationCreate {
+    fn from(r: pb::OrganisationCreateRequest) -> Self {
+        Self {
+            is_enabled: r.is_enabled.unwrap_or(true),
+            name: r.name,
+        }
+    }
+}
+
+impl From<pb::OrganisationReadRequest> for OrganisationRead {
+    fn from(r: pb::OrganisationReadRequest) -> Self {
+        Self {
+            id: pb::string_to_uuid(r.id),
+        }
+    }
+}
+
+impl From<pb::OrganisationUpdateRequest> for OrganisationUpdate {
+    fn from(r: pb::OrganisationUpdateRequest) -> Self {
+        Self {
+            id: pb::string_to_uuid(r.id),
+            is_enabled: r.is_enabled,
+            name: r.name,
+        }
+    }
+}
+
+impl From<OrganisationList> for pb::OrganisationListRequest {
+    fn from(l: OrganisationList) -> Self {
+        let id = pb::uuid_vec_opt_to_string_vec(l.filter.id);
+        let is_enabled = l.filter.is_enabled;
+        let limit = l.filter.limit;
+        match l.query {
+            OrganisationListQuery::Limit => Self {
+                gt: None,
+                lt: None,
+                limit: Some(limit),
+                id,
+                is_enabled,
+            },
+            OrganisationListQuery::IdGt(gt) => Self {
+                gt: Some(pb::uuid_to_string(gt)),
+                lt: None,
+                limit: Some(limit),
+                id,
+                is_enabled,
+            },
+            OrganisationListQuery::IdLt(lt) => Self {
+                gt: None,
+                lt: Some(pb::uuid_to_string(lt)),
+                limit: Some(limit),
+                id,
+                is_enabled,
+            },
+        }
+    }
+}
+
+impl From<Organisation> for pb::Organisation {
+    fn from(r: Organisation) -> Self {
+        Self {
+            created_at: pb::datetime_to_timestamp_opt(r.created_at),
+            updated_at: pb::datetime_to_timestamp_opt(r.updated_at),
+            id: pb::uuid_to_string(r.id),
+            is_enabled: r.is_enabled,
+            name: r.name,
+        }
+    }
+}
+
 impl From<pb::ServiceListRequest> for ServiceList {
     fn from(r: pb::ServiceListRequest) -> Self {
         let limit = r.limit.unwrap_or(DEFAULT_LIMIT);
@@ -490,6 +798,7 @@ impl From<pb::ServiceListRequest> for ServiceList {
         let filter = ServiceListFilter {
             id: pb::string_vec_to_uuid_vec_opt(r.id),
             is_enabled: r.is_enabled,
+            organisation_id: pb::string_vec_to_uuid_vec_opt(r.organisation_id),
             limit,
         };
         Self { query, filter }
@@ -507,6 +816,10 @@ impl From<pb::ServiceCreateRequest> for ServiceCreate {
             provider_local_url: r.provider_local_url,
             provider_github_oauth2_url: r.provider_github_oauth2_url,
             provider_microsoft_oauth2_url: r.provider_microsoft_oauth2_url,
+            allow_remote: pb::string_vec_to_cidr_vec(r.allow_remote),
+            user_claims: r.user_claims,
+            organisation_id: pb::string_opt_to_uuid_opt(r.organisation_id),
+            user_require_email_verified: r.user_require_email_verified.unwrap_or(false),
         }
     }
 }
@@ -531,6 +844,9 @@ impl From<pb::ServiceUpdateRequest> for ServiceUpdate {
             provider_local_url: r.provider_local_url,
             provider_github_oauth2_url: r.provider_github_oauth2_url,
             provider_microsoft_oauth2_url: r.provider_microsoft_oauth2_url,
+            allow_remote: r.allow_remote.map(|x| pb::string_vec_to_cidr_vec(x.cidr)),
+            user_claims: r.user_claims.map(|x| x.key),
+            user_require_email_verified: r.user_require_email_verified,
         }
     }
 }
@@ -539,47 +855,408 @@ impl From<ServiceList> for pb::ServiceListRequest {
     fn from(l: ServiceList) -> Self {
         let id = pb::uuid_vec_opt_to_string_vec(l.filter.id);
         let is_enabled = l.filter.is_enabled;
+        let organisation_id = pb::uuid_vec_opt_to_string_vec(l.filter.organisation_id);
         let limit = l.filter.limit;
         match l.query {
             ServiceListQuery::Limit => Self {
                 gt: None,
                 lt: None,
                 limit: Some(limit),
-                id,
-                is_enabled,
+                id,
+                is_enabled,
+                organisation_id,
+            },
+            ServiceListQuery::IdGt(gt) => Self {
+                gt: Some(pb::uuid_to_string(gt)),
+                lt: None,
+                limit: Some(limit),
+                id,
+                is_enabled,
+                organisation_id,
+            },
+            ServiceListQuery::IdLt(lt) => Self {
+                gt: None,
+                lt: Some(pb::uuid_to_string(lt)),
+                limit: Some(limit),
+                id,
+                is_enabled,
+                organisation_id,
+            },
+        }
+    }
+}
+
+impl From<Service> for pb::Service {
+    fn from(r: Service) -> Self {
+        Self {
+            created_at: pb::datetime_to_timestamp_opt(r.created_at),
+            updated_at: pb::datetime_to_timestamp_opt(r.updated_at),
+            id: pb::uuid_to_string(r.id),
+            is_enabled: r.is_enabled,
+            name: r.name,
+            url: r.url,
+            user_allow_register: r.user_allow_register,
+            user_email_text: r.user_email_text,
+            provider_local_url: r.provider_local_url,
+            provider_github_oauth2_url: r.provider_github_oauth2_url,
+            provider_microsoft_oauth2_url: r.provider_microsoft_oauth2_url,
+            allow_remote: pb::cidr_vec_to_string_vec(&r.allow_remote),
+            user_claims: r.user_claims,
+            organisation_id: pb::uuid_opt_to_string_opt(r.organisation_id),
+            user_require_email_verified: r.user_require_email_verified,
+        }
+    }
+}
+
+impl From<pb::ServiceUserListRequest> for ServiceUserList {
+    fn from(r: pb::ServiceUserListRequest) -> Self {
+        let limit = r.limit.unwrap_or(DEFAULT_LIMIT);
+        let gt = pb::string_opt_to_uuid_opt(r.gt);
+        let lt = pb::string_opt_to_uuid_opt(r.lt);
+        let query = match (gt, lt) {
+            (Some(gt), Some(_lt)) => ServiceUserListQuery::IdGt(gt),
+            (Some(gt), None) => ServiceUserListQuery::IdGt(gt),
+            (None, Some(lt)) => ServiceUserListQuery::IdLt(lt),
+            (None, None) => ServiceUserListQuery::Limit,
+        };
+        let filter = ServiceUserListFilter {
+            service_id: pb::string_to_uuid(r.service_id),
+            status: pb::i32_vec_to_service_user_status_vec_opt(r.status),
+            limit,
+        };
+        Self { query, filter }
+    }
+}
+
+impl From<ServiceUserList> for pb::ServiceUserListRequest {
+    fn from(l: ServiceUserList) -> Self {
+        let service_id = pb::uuid_to_string(l.filter.service_id);
+        let status = pb::service_user_status_vec_opt_to_i32_vec(l.filter.status);
+        let limit = l.filter.limit;
+        match l.query {
+            ServiceUserListQuery::Limit => Self {
+                service_id,
+                gt: None,
+                lt: None,
+                limit: Some(limit),
+                status,
+            },
+            ServiceUserListQuery::IdGt(gt) => Self {
+                service_id,
+                gt: Some(pb::uuid_to_string(gt)),
+                lt: None,
+                limit: Some(limit),
+                status,
+            },
+            ServiceUserListQuery::IdLt(lt) => Self {
+                service_id,
+                gt: None,
+                lt: Some(pb::uuid_to_string(lt)),
+                limit: Some(limit),
+                status,
+            },
+        }
+    }
+}
+
+impl From<pb::ServiceUserUpdateRequest> for ServiceUserUpdate {
+    fn from(r: pb::ServiceUserUpdateRequest) -> Self {
+        Self {
+            service_id: pb::string_to_uuid(r.service_id),
+            user_id: pb::string_to_uuid(r.user_id),
+            status: ServiceUserStatus::from_i32(r.status),
+        }
+    }
+}
+
+impl From<ServiceUser> for pb::ServiceUser {
+    fn from(r: ServiceUser) -> Self {
+        Self {
+            created_at: pb::datetime_to_timestamp_opt(r.created_at),
+            updated_at: pb::datetime_to_timestamp_opt(r.updated_at),
+            service_id: pb::uuid_to_string(r.service_id),
+            user_id: pb::uuid_to_string(r.user_id),
+            status: r.status as i32,
+            invite_expires_at: pb::datetime_opt_to_timestamp_opt(r.invite_expires_at),
+        }
+    }
+}
+
+impl From<pb::RoleListRequest> for RoleList {
+    fn from(r: pb::RoleListRequest) -> Self {
+        let limit = r.limit.unwrap_or(DEFAULT_LIMIT);
+        let gt = pb::string_opt_to_uuid_opt(r.gt);
+        let lt = pb::string_opt_to_uuid_opt(r.lt);
+        let query = match (gt, lt) {
+            (Some(gt), Some(_lt)) => RoleListQuery::IdGt(gt),
+            (Some(gt), None) => RoleListQuery::IdGt(gt),
+            (None, Some(lt)) => RoleListQuery::IdLt(lt),
+            (None, None) => RoleListQuery::Limit,
+        };
+        let filter = RoleListFilter {
+            id: pb::string_vec_to_uuid_vec_opt(r.id),
+            service_id: pb::string_vec_to_uuid_vec_opt(r.service_id),
+            user_id: pb::string_opt_to_uuid_opt(r.user_id),
+            limit,
+        };
+        Self { query, filter }
+    }
+}
+
+impl From<pb::RoleCreateRequest> for RoleCreate {
+    fn from(r: pb::RoleCreateRequest) -> Self {
+        Self {
+            service_id: pb::string_to_uuid(r.service_id),
+            name: r.name,
+        }
+    }
+}
+
+impl From<pb::RoleReadRequest> for RoleRead {
+    fn from(r: pb::RoleReadRequest) -> Self {
+        Self {
+            id: pb::string_to_uuid(r.id),
+        }
+    }
+}
+
+impl From<pb::RoleUpdateRequest> for RoleUpdate {
+    fn from(r: pb::RoleUpdateRequest) -> Self {
+        Self {
+            id: pb::string_to_uuid(r.id),
+            name: r.name,
+        }
+    }
+}
+
+impl From<RoleList> for pb::RoleListRequest {
+    fn from(l: RoleList) -> Self {
+        let id = pb::uuid_vec_opt_to_string_vec(l.filter.id);
+        let service_id = pb::uuid_vec_opt_to_string_vec(l.filter.service_id);
+        let user_id = pb::uuid_opt_to_string_opt(l.filter.user_id);
+        let limit = l.filter.limit;
+        match l.query {
+            RoleListQuery::Limit => Self {
+                gt: None,
+                lt: None,
+                limit: Some(limit),
+                id,
+                service_id,
+                user_id,
+            },
+            RoleListQuery::IdGt(gt) => Self {
+                gt: Some(pb::uuid_to_string(gt)),
+                lt: None,
+                limit: Some(limit),
+                id,
+                service_id,
+                user_id,
+            },
+            RoleListQuery::IdLt(lt) => Self {
+                gt: None,
+                lt: Some(pb::uuid_to_string(lt)),
+                limit: Some(limit),
+                id,
+                service_id,
+                user_id,
+            },
+        }
+    }
+}
+
+impl From<Role> for pb::Role {
+    fn from(r: Role) -> Self {
+        Self {
+            created_at: pb::datetime_to_timestamp_opt(r.created_at),
+            updated_at: pb::datetime_to_timestamp_opt(r.updated_at),
+            id: pb::uuid_to_string(r.id),
+            service_id: pb::uuid_to_string(r.service_id),
+            name: r.name,
+        }
+    }
+}
+
+impl From<pb::UserRoleRequest> for UserRole {
+    fn from(r: pb::UserRoleRequest) -> Self {
+        Self {
+            user_id: pb::string_to_uuid(r.user_id),
+            role_id: pb::string_to_uuid(r.role_id),
+        }
+    }
+}
+
+impl From<pb::WebhookListRequest> for WebhookList {
+    fn from(r: pb::WebhookListRequest) -> Self {
+        let limit = r.limit.unwrap_or(DEFAULT_LIMIT);
+        let gt = pb::string_opt_to_uuid_opt(r.gt);
+        let lt = pb::string_opt_to_uuid_opt(r.lt);
+        let query = match (gt, lt) {
+            (Some(gt), Some(_lt)) => WebhookListQuery::IdGt(gt),
+            (Some(gt), None) => WebhookListQuery::IdGt(gt),
+            (None, Some(lt)) => WebhookListQuery::IdLt(lt),
+            (None, None) => WebhookListQuery::Limit,
+        };
+        let filter = WebhookListFilter {
+            id: pb::string_vec_to_uuid_vec_opt(r.id),
+            service_id: pb::string_vec_to_uuid_vec_opt(r.service_id),
+            limit,
+        };
+        Self { query, filter }
+    }
+}
+
+impl From<WebhookList> for pb::WebhookListRequest {
+    fn from(l: WebhookList) -> Self {
+        let id = pb::uuid_vec_opt_to_string_vec(l.filter.id);
+        let service_id = pb::uuid_vec_opt_to_string_vec(l.filter.service_id);
+        let limit = l.filter.limit;
+        match l.query {
+            WebhookListQuery::Limit => Self {
+                gt: None,
+                lt: None,
+                limit: Some(limit),
+                id,
+                service_id,
+            },
+            WebhookListQuery::IdGt(gt) => Self {
+                gt: Some(pb::uuid_to_string(gt)),
+                lt: None,
+                limit: Some(limit),
+                id,
+                service_id,
+            },
+            WebhookListQuery::IdLt(lt) => Self {
+                gt: None,
+                lt: Some(pb::uuid_to_string(lt)),
+                limit: Some(limit),
+                id,
+                service_id,
+            },
+        }
+    }
+}
+
+impl From<pb::WebhookCreateRequest> for WebhookCreate {
+    fn from(r: pb::WebhookCreateRequest) -> Self {
+        Self {
+            service_id: pb::string_to_uuid(r.service_id),
+            is_enabled: r.is_enabled.unwrap_or(true),
+            name: r.name,
+            url: r.url,
+            audit_type: r.audit_type,
+        }
+    }
+}
+
+impl From<pb::WebhookReadRequest> for WebhookRead {
+    fn from(r: pb::WebhookReadRequest) -> Self {
+        Self {
+            id: pb::string_to_uuid(r.id),
+        }
+    }
+}
+
+impl From<pb::WebhookUpdateRequest> for WebhookUpdate {
+    fn from(r: pb::WebhookUpdateRequest) -> Self {
+        Self {
+            id: pb::string_to_uuid(r.id),
+            is_enabled: r.is_enabled,
+            name: r.name,
+            url: r.url,
+            audit_type: r.audit_type.map(|x| x.audit_type),
+        }
+    }
+}
+
+impl From<Webhook> for pb::Webhook {
+    fn from(r: Webhook) -> Self {
+        Self {
+            created_at: pb::datetime_to_timestamp_opt(r.created_at),
+            updated_at: pb::datetime_to_timestamp_opt(r.updated_at),
+            id: pb::uuid_to_string(r.id),
+            service_id: pb::uuid_to_string(r.service_id),
+            is_enabled: r.is_enabled,
+            name: r.name,
+            url: r.url,
+            audit_type: r.audit_type,
+        }
+    }
+}
+
+impl From<pb::WebhookDeliveryListRequest> for WebhookDeliveryList {
+    fn from(r: pb::WebhookDeliveryListRequest) -> Self {
+        let limit = r.limit.unwrap_or(DEFAULT_LIMIT);
+        let gt = pb::string_opt_to_uuid_opt(r.gt);
+        let lt = pb::string_opt_to_uuid_opt(r.lt);
+        let query = match (gt, lt) {
+            (Some(gt), Some(_lt)) => WebhookDeliveryListQuery::IdGt(gt),
+            (Some(gt), None) => WebhookDeliveryListQuery::IdGt(gt),
+            (None, Some(lt)) => WebhookDeliveryListQuery::IdLt(lt),
+            (None, None) => WebhookDeliveryListQuery::Limit,
+        };
+        let filter = WebhookDeliveryListFilter {
+            webhook_id: pb::string_to_uuid(r.webhook_id),
+            status: pb::i32_vec_to_webhook_delivery_status_vec_opt(r.status),
+            limit,
+        };
+        Self { query, filter }
+    }
+}
+
+impl From<WebhookDeliveryList> for pb::WebhookDeliveryListRequest {
+    fn from(l: WebhookDeliveryList) -> Self {
+        let webhook_id = pb::uuid_to_string(l.filter.webhook_id);
+        let status = pb::webhook_delivery_status_vec_opt_to_i32_vec(l.filter.status);
+        let limit = l.filter.limit;
+        match l.query {
+            WebhookDeliveryListQuery::Limit => Self {
+                webhook_id,
+                gt: None,
+                lt: None,
+                limit: Some(limit),
+                status,
             },
-            ServiceListQuery::IdGt(gt) => Self {
+            WebhookDeliveryListQuery::IdGt(gt) => Self {
+                webhook_id,
                 gt: Some(pb::uuid_to_string(gt)),
                 lt: None,
                 limit: Some(limit),
-                id,
-                is_enabled,
+                status,
             },
-            ServiceListQuery::IdLt(lt) => Self {
+            WebhookDeliveryListQuery::IdLt(lt) => Self {
+                webhook_id,
                 gt: None,
                 lt: Some(pb::uuid_to_string(lt)),
                 limit: Some(limit),
-                id,
-                is_enabled,
+                status,
             },
         }
     }
 }
 
-impl From<Service> for pb::Service {
-    fn from(r: Service) -> Self {
+impl From<pb::WebhookDeliveryRetryRequest> for WebhookDeliveryRead {
+    fn from(r: pb::WebhookDeliveryRetryRequest) -> Self {
+        Self {
+            id: pb::string_to_uuid(r.id),
+        }
+    }
+}
+
+impl From<WebhookDelivery> for pb::WebhookDelivery {
+    fn from(r: WebhookDelivery) -> Self {
         Self {
             created_at: pb::datetime_to_timestamp_opt(r.created_at),
             updated_at: pb::datetime_to_timestamp_opt(r.updated_at),
             id: pb::uuid_to_string(r.id),
-            is_enabled: r.is_enabled,
-            name: r.name,
-            url: r.url,
-            user_allow_register: r.user_allow_register,
-            user_email_text: r.user_email_text,
-            provider_local_url: r.provider_local_url,
-            provider_github_oauth2_url: r.provider_github_oauth2_url,
-            provider_microsoft_oauth2_url: r.provider_microsoft_oauth2_url,
+            webhook_id: pb::uuid_to_string(r.webhook_id),
+            audit_id: pb::uuid_to_string(r.audit_id),
+            audit_type: r.audit_type,
+            status: r.status as i32,
+            attempts: r.attempts,
+            next_attempt_at: pb::datetime_to_timestamp_opt(r.next_attempt_at),
+            delivered_at: pb::datetime_opt_to_timestamp_opt(r.delivered_at),
+            status_code: r.status_code.map(|x| x as u32),
+            error: r.error,
         }
     }
 }
@@ -609,6 +1286,8 @@ impl From<pb::UserListRequest> for UserList {
         let filter = UserListFilter {
             id: pb::string_vec_to_uuid_vec_opt(r.id),
             email: pb::string_vec_to_string_vec_opt(r.email),
+            last_login_lt: pb::timestamp_opt_to_datetime_opt(r.last_login_lt),
+            organisation_id: pb::string_vec_to_uuid_vec_opt(r.organisation_id),
             limit,
         };
         Self { query, filter }
@@ -624,6 +1303,12 @@ impl From<pb::UserCreateRequest> for UserCreate {
         if let Some(timezone) = r.timezone {
             create = create.timezone(timezone);
         }
+        if let Some(attributes) = pb::struct_opt_to_value_opt(r.attributes) {
+            create = create.attributes(attributes);
+        }
+        if let Some(organisation_id) = pb::string_opt_to_uuid_opt(r.organisation_id) {
+            create = create.organisation_id(Some(organisation_id));
+        }
         if let Some(password) = r.password {
             create = create
                 .with_password(
@@ -653,7 +1338,7 @@ impl From<pb::UserReadRequest> for UserRead {
 
 impl From<pb::UserUpdateRequest> for UserUpdate {
     fn from(r: pb::UserUpdateRequest) -> Self {
-        Self::new(
+        let update = Self::new(
             pb::string_to_uuid(r.id),
             r.is_enabled,
             r.name,
@@ -661,7 +1346,11 @@ impl From<pb::UserUpdateRequest> for UserUpdate {
             r.timezone,
             r.password_allow_reset,
             r.password_require_update,
-        )
+        );
+        match pb::struct_opt_to_value_opt(r.attributes) {
+            Some(attributes) => update.set_attributes(attributes),
+            None => update,
+        }
     }
 }
 
@@ -669,6 +1358,8 @@ impl From<UserList> for pb::UserListRequest {
     fn from(l: UserList) -> Self {
         let id = pb::uuid_vec_opt_to_string_vec(l.filter.id);
         let email = l.filter.email.unwrap_or_default();
+        let last_login_lt = pb::datetime_opt_to_timestamp_opt(l.filter.last_login_lt);
+        let organisation_id = pb::uuid_vec_opt_to_string_vec(l.filter.organisation_id);
         match l.query {
             UserListQuery::IdGt(gt) => Self {
                 gt: Some(pb::uuid_to_string(gt)),
@@ -679,6 +1370,8 @@ impl From<UserList> for pb::UserListRequest {
                 offset_id: None,
                 id,
                 email,
+                last_login_lt,
+                organisation_id,
             },
             UserListQuery::IdLt(lt) => Self {
                 gt: None,
@@ -689,6 +1382,8 @@ impl From<UserList> for pb::UserListRequest {
                 offset_id: None,
                 id,
                 email,
+                last_login_lt,
+                organisation_id,
             },
             UserListQuery::NameGe(name_ge, offset_id) => Self {
                 gt: None,
@@ -699,6 +1394,8 @@ impl From<UserList> for pb::UserListRequest {
                 offset_id: pb::uuid_opt_to_string_opt(offset_id),
                 id,
                 email,
+                last_login_lt,
+                organisation_id,
             },
             UserListQuery::NameLe(name_le, offset_id) => Self {
                 gt: None,
@@ -709,6 +1406,8 @@ impl From<UserList> for pb::UserListRequest {
                 offset_id: pb::uuid_opt_to_string_opt(offset_id),
                 id,
                 email,
+                last_login_lt,
+                organisation_id,
             },
         }
     }
@@ -727,6 +1426,21 @@ impl From<User> for pb::User {
             timezone: r.timezone,
             password_allow_reset: r.password_allow_reset,
             password_require_update: r.password_require_update,
+            last_login_at: pb::datetime_opt_to_timestamp_opt(r.last_login_at),
+            last_login_remote: r.last_login_remote,
+            attributes: pb::value_to_struct_opt(r.attributes),
+            organisation_id: pb::uuid_opt_to_string_opt(r.organisation_id),
+            email_verified_at: pb::datetime_opt_to_timestamp_opt(r.email_verified_at),
+            email_pending: r.email_pending,
+        }
+    }
+}
+
+impl From<UserWithService> for pb::UserReadReply {
+    fn from(r: UserWithService) -> Self {
+        Self {
+            data: Some(r.user.into()),
+            service_attributes: r.service_attributes.and_then(pb::value_to_struct_opt),
         }
     }
 }
@@ -745,6 +1459,12 @@ impl From<pb::User> for User {
             password_allow_reset: r.password_allow_reset,
             password_require_update: r.password_require_update,
             password_hash: None,
+            last_login_at: pb::timestamp_opt_to_datetime_opt(r.last_login_at),
+            last_login_remote: r.last_login_remote,
+            attributes: pb::struct_opt_to_value_opt(r.attributes).unwrap_or_else(|| json!({})),
+            organisation_id: pb::string_opt_to_uuid_opt(r.organisation_id),
+            email_verified_at: pb::timestamp_opt_to_datetime_opt(r.email_verified_at),
+            email_pending: r.email_pending,
         }
     }
 }
@@ -793,45 +1513,95 @@ impl From<pb::AuditUpdateRequest> for AuditUpdate {
 
 impl From<AuditList> for pb::AuditListRequest {
     fn from(l: AuditList) -> Self {
-        let id = pb::uuid_vec_opt_to_string_vec(l.filter.id);
-        let type_ = l.filter.type_.unwrap_or_default();
-        let subject = l.filter.subject.unwrap_or_default();
-        let service_id = pb::uuid_vec_opt_to_string_vec(l.filter.service_id);
-        let user_id = pb::uuid_vec_opt_to_string_vec(l.filter.user_id);
-        match l.query {
-            AuditListQuery::CreatedLe(le, limit, offset_id) => Self {
-                ge: None,
-                le: pb::datetime_to_timestamp_opt(le),
-                limit: Some(limit),
-                offset_id: pb::uuid_opt_to_string_opt(offset_id),
-                id,
-                r#type: type_,
-                subject,
-                service_id,
-                user_id,
-            },
-            AuditListQuery::CreatedGe(ge, limit, offset_id) => Self {
-                ge: pb::datetime_to_timestamp_opt(ge),
-                le: None,
-                limit: Some(limit),
-                offset_id: pb::uuid_opt_to_string_opt(offset_id),
-                id,
-                r#type: type_,
-                subject,
-                service_id,
-                user_id,
-            },
-            AuditListQuery::CreatedLeAndGe(le, ge, limit, offset_id) => Self {
-                ge: pb::datetime_to_timestamp_opt(ge),
-                le: pb::datetime_to_timestamp_opt(le),
-                limit: Some(limit),
-                offset_id: pb::uuid_opt_to_string_opt(offset_id),
-                id,
-                r#type: type_,
-                subject,
-                service_id,
-                user_id,
-            },
+        let (ge, le, limit, offset_id) = match l.query {
+            AuditListQuery::CreatedLe(le, limit, offset_id) => (None, Some(le), limit, offset_id),
+            AuditListQuery::CreatedGe(ge, limit, offset_id) => (Some(ge), None, limit, offset_id),
+            AuditListQuery::CreatedLeAndGe(le, ge, limit, offset_id) => {
+                (Some(ge), Some(le), limit, offset_id)
+            }
+        };
+        Self {
+            ge: pb::datetime_opt_to_timestamp_opt(ge),
+            le: pb::datetime_opt_to_timestamp_opt(le),
+            limit: Some(limit),
+            offset_id: pb::uuid_opt_to_string_opt(offset_id),
+            id: pb::uuid_vec_opt_to_string_vec(l.filter.id),
+            r#type: l.filter.type_.unwrap_or_default(),
+            subject: l.filter.subject.unwrap_or_default(),
+            service_id: pb::uuid_vec_opt_to_string_vec(l.filter.service_id),
+            user_id: pb::uuid_vec_opt_to_string_vec(l.filter.user_id),
+            organisation_id: pb::uuid_vec_opt_to_string_vec(l.filter.organisation_id),
+            status_code_ge: l.filter.status_code_ge.map(|x| x as u32),
+            status_code_le: l.filter.status_code_le.map(|x| x as u32),
+            remote: l.filter.remote,
+            remote_cidr: l
+                .filter
+                .remote_cidr
+                .map(|x| pb::cidr_vec_to_string_vec(&x))
+                .unwrap_or_default(),
+            forwarded: l.filter.forwarded,
+            user_agent: l.filter.user_agent,
+            key_id: pb::uuid_vec_opt_to_string_vec(l.filter.key_id),
+            user_key_id: pb::uuid_vec_opt_to_string_vec(l.filter.user_key_id),
+            data: l
+                .filter
+                .data
+                .unwrap_or_default()
+                .into_iter()
+                .map(|x| x.into())
+                .collect(),
+            remote_country: l.filter.remote_country.unwrap_or_default(),
+            remote_city: l.filter.remote_city.unwrap_or_default(),
+            remote_asn: l.filter.remote_asn.unwrap_or_default(),
+            user_agent_browser: l.filter.user_agent_browser.unwrap_or_default(),
+            user_agent_os: l.filter.user_agent_os.unwrap_or_default(),
+            user_agent_device: l.filter.user_agent_device.unwrap_or_default(),
+        }
+    }
+}
+
+impl From<AuditReport> for pb::AuditReportRequest {
+    fn from(r: AuditReport) -> Self {
+        Self {
+            ge: pb::datetime_to_timestamp_opt(r.ge),
+            le: pb::datetime_to_timestamp_opt(r.le),
+            bucket: r.bucket as i32,
+            group: pb::audit_report_group_vec_to_i32_vec(r.group),
+            r#type: r.filter.type_.unwrap_or_default(),
+            service_id: pb::uuid_vec_opt_to_string_vec(r.filter.service_id),
+            user_id: pb::uuid_vec_opt_to_string_vec(r.filter.user_id),
+        }
+    }
+}
+
+impl From<AuditReportRow> for pb::AuditReportRow {
+    fn from(r: AuditReportRow) -> Self {
+        Self {
+            bucket: pb::datetime_to_timestamp_opt(r.bucket),
+            r#type: r.type_,
+            status_code: r.status_code.map(|x| x as u32),
+            service_id: pb::uuid_opt_to_string_opt(r.service_id),
+            user_id: pb::uuid_opt_to_string_opt(r.user_id),
+            remote: r.remote,
+            count: r.count,
+        }
+    }
+}
+
+impl From<pb::AuditDataFilter> for AuditDataFilter {
+    fn from(r: pb::AuditDataFilter) -> Self {
+        Self {
+            path: r.path,
+            value: pb::struct_value_opt_to_value(r.value),
+        }
+    }
+}
+
+impl From<AuditDataFilter> for pb::AuditDataFilter {
+    fn from(r: AuditDataFilter) -> Self {
+        Self {
+            path: r.path,
+            value: Some(pb::value_to_struct_value(r.value)),
         }
     }
 }
@@ -853,6 +1623,36 @@ impl From<Audit> for pb::Audit {
             service_id: pb::uuid_opt_to_string_opt(r.service_id),
             user_id: pb::uuid_opt_to_string_opt(r.user_id),
             user_key_id: pb::uuid_opt_to_string_opt(r.user_key_id),
+            organisation_id: pb::uuid_opt_to_string_opt(r.organisation_id),
+            actor_key_id: pb::uuid_opt_to_string_opt(r.actor_key_id),
+            proxy: r.proxy.map(|x| x.into()),
+            enrichment: Some(r.enrichment.into()),
+        }
+    }
+}
+
+impl From<AuditProxy> for pb::AuditProxy {
+    fn from(r: AuditProxy) -> Self {
+        Self {
+            peer: r.peer,
+            forwarded: r.forwarded,
+            real_ip: r.real_ip,
+            forwarded_host: r.forwarded_host,
+            forwarded_uri: r.forwarded_uri,
+        }
+    }
+}
+
+impl From<AuditEnrichment> for pb::AuditEnrichment {
+    fn from(r: AuditEnrichment) -> Self {
+        Self {
+            remote_country: r.remote_country,
+            remote_city: r.remote_city,
+            remote_asn: r.remote_asn,
+            remote_as_org: r.remote_as_org,
+            user_agent_browser: r.user_agent_browser,
+            user_agent_os: r.user_agent_os,
+            user_agent_device: r.user_agent_device,
         }
     }
 }
@@ -872,6 +1672,10 @@ impl pb::ServiceCreateRequest {
             provider_local_url: None,
             provider_github_oauth2_url: None,
             provider_microsoft_oauth2_url: None,
+            allow_remote: Vec::new(),
+            user_claims: Vec::new(),
+            organisation_id: None,
+            user_require_email_verified: None,
         }
     }
 
@@ -905,6 +1709,26 @@ impl pb::ServiceCreateRequest {
         self.provider_microsoft_oauth2_url = Some(provider_microsoft_oauth2_url.into());
         self
     }
+
+    pub fn allow_remote(mut self, allow_remote: Vec<Cidr>) -> Self {
+        self.allow_remote = pb::cidr_vec_to_string_vec(&allow_remote);
+        self
+    }
+
+    pub fn user_claims(mut self, user_claims: Vec<String>) -> Self {
+        self.user_claims = user_claims;
+        self
+    }
+
+    pub fn user_require_email_verified(mut self, user_require_email_verified: bool) -> Self {
+        self.user_require_email_verified = Some(user_require_email_verified);
+        self
+    }
+
+    pub fn organisation_id<S: Into<String>>(mut self, organisation_id: S) -> Self {
+        self.organisation_id = Some(organisation_id.into());
+        self
+    }
 }
 
 impl pb::KeyCreateRequest {
@@ -918,6 +1742,8 @@ impl pb::KeyCreateRequest {
             is_enabled: Some(is_enabled),
             service_id: None,
             user_id: None,
+            scope: Vec::new(),
+            allow_remote: Vec::new(),
         }
     }
 
@@ -931,6 +1757,8 @@ impl pb::KeyCreateRequest {
             is_enabled: Some(is_enabled),
             service_id: Some(service_id),
             user_id: None,
+            scope: Vec::new(),
+            allow_remote: Vec::new(),
         }
     }
 
@@ -944,6 +1772,31 @@ impl pb::KeyCreateRequest {
             is_enabled: Some(is_enabled),
             service_id: None,
             user_id: Some(user_id),
+            scope: Vec::new(),
+            allow_remote: Vec::new(),
+        }
+    }
+
+    pub fn scope(mut self, scope: Vec<KeyScope>) -> Self {
+        self.scope = scope.iter().map(|x| x.to_string()).collect();
+        self
+    }
+
+    pub fn allow_remote(mut self, allow_remote: Vec<Cidr>) -> Self {
+        self.allow_remote = pb::cidr_vec_to_string_vec(&allow_remote);
+        self
+    }
+}
+
+impl pb::KeyRotateRequest {
+    pub fn new<I>(id: I, grace_period_s: Option<i64>) -> Self
+    where
+        I: Into<String>,
+    {
+        Self {
+            id: id.into(),
+            name: None,
+            grace_period_s,
         }
     }
 }
@@ -960,6 +1813,7 @@ impl pb::KeyListRequest {
             r#type: Vec::new(),
             service_id: Vec::new(),
             user_id: Vec::new(),
+            last_used_lt: None,
         }
     }
 
@@ -997,6 +1851,8 @@ impl pb::UserCreateRequest {
             password_allow_reset: None,
             password_require_update: None,
             password: None,
+            attributes: None,
+            organisation_id: None,
         }
     }
 
@@ -1030,6 +1886,16 @@ impl pb::UserCreateRequest {
         self.password = Some(password.into());
         self
     }
+
+    pub fn attributes(mut self, attributes: serde_json::Value) -> Self {
+        self.attributes = pb::value_to_struct_opt(attributes);
+        self
+    }
+
+    pub fn organisation_id<S: Into<String>>(mut self, organisation_id: S) -> Self {
+        self.organisation_id = Some(organisation_id.into());
+        self
+    }
 }
 
 impl pb::AuthKeyRequest {
@@ -1078,6 +1944,30 @@ impl pb::AuditCreateRequest {
     }
 }
 
+impl pb::AuditReportRequest {
+    pub fn new(bucket: pb::AuditReportBucket) -> Self {
+        Self {
+            ge: None,
+            le: None,
+            bucket: bucket as i32,
+            group: Vec::new(),
+            r#type: Vec::new(),
+            service_id: Vec::new(),
+            user_id: Vec::new(),
+        }
+    }
+
+    pub fn group(mut self, group: Vec<pb::AuditReportGroup>) -> Self {
+        self.group = group.into_iter().map(|x| x as i32).collect();
+        self
+    }
+
+    pub fn audit_type(mut self, audit_type: Vec<String>) -> Self {
+        self.r#type = audit_type;
+        self
+    }
+}
+
 impl pb::ServiceListRequest {
     pub fn limit_id(limit: i64, id: Vec<String>) -> Self {
         Self {
@@ -1086,6 +1976,7 @@ impl pb::ServiceListRequest {
             limit: Some(limit),
             id,
             is_enabled: None,
+            organisation_id: Vec::new(),
         }
     }
 
@@ -1102,6 +1993,112 @@ impl pb::ServiceListRequest {
     }
 }
 
+impl pb::GroupCreateRequest {
+    pub fn new<S, N>(service_id: S, is_enabled: bool, name: N) -> Self
+    where
+        S: Into<String>,
+        N: Into<String>,
+    {
+        Self {
+            name: name.into(),
+            is_enabled: Some(is_enabled),
+            parent_id: None,
+            service_id: service_id.into(),
+        }
+    }
+
+    pub fn parent_id<S: Into<String>>(mut self, parent_id: S) -> Self {
+        self.parent_id = Some(parent_id.into());
+        self
+    }
+}
+
+impl pb::OrganisationCreateRequest {
+    pub fn new<N>(is_enabled: bool, name: N) -> Self
+    where
+        N: Into<String>,
+    {
+        Self {
+            name: name.into(),
+            is_enabled: Some(is_enabled),
+        }
+    }
+}
+
+impl pb::GroupUserRequest {
+    pub fn new<G, U>(group_id: G, user_id: U) -> Self
+    where
+        G: Into<String>,
+        U: Into<String>,
+    {
+        Self {
+            group_id: group_id.into(),
+            user_id: user_id.into(),
+        }
+    }
+}
+
+impl pb::GroupRoleRequest {
+    pub fn new<G, R>(group_id: G, role_id: R) -> Self
+    where
+        G: Into<String>,
+        R: Into<String>,
+    {
+        Self {
+            group_id: group_id.into(),
+            role_id: role_id.into(),
+        }
+    }
+}
+
+impl pb::RoleCreateRequest {
+    pub fn new<S, N>(service_id: S, name: N) -> Self
+    where
+        S: Into<String>,
+        N: Into<String>,
+    {
+        Self {
+            service_id: service_id.into(),
+            name: name.into(),
+        }
+    }
+}
+
+impl pb::UserRoleRequest {
+    pub fn new<U, R>(user_id: U, role_id: R) -> Self
+    where
+        U: Into<String>,
+        R: Into<String>,
+    {
+        Self {
+            user_id: user_id.into(),
+            role_id: role_id.into(),
+        }
+    }
+}
+
+impl pb::WebhookCreateRequest {
+    pub fn new<S, N, U>(service_id: S, name: N, url: U) -> Self
+    where
+        S: Into<String>,
+        N: Into<String>,
+        U: Into<String>,
+    {
+        Self {
+            service_id: service_id.into(),
+            is_enabled: None,
+            name: name.into(),
+            url: url.into(),
+            audit_type: Vec::new(),
+        }
+    }
+
+    pub fn audit_type(mut self, audit_type: Vec<String>) -> Self {
+        self.audit_type = audit_type;
+        self
+    }
+}
+
 impl pb::AuditListRequest {
     pub fn ge_limit(ge: Option<prost_types::Timestamp>, limit: i64) -> Self {
         Self {
@@ -1114,6 +2111,22 @@ impl pb::AuditListRequest {
             subject: Vec::new(),
             service_id: Vec::new(),
             user_id: Vec::new(),
+            organisation_id: Vec::new(),
+            status_code_ge: None,
+            status_code_le: None,
+            remote: None,
+            remote_cidr: Vec::new(),
+            forwarded: None,
+            user_agent: None,
+            key_id: Vec::new(),
+            user_key_id: Vec::new(),
+            data: Vec::new(),
+            remote_country: Vec::new(),
+            remote_city: Vec::new(),
+            remote_asn: Vec::new(),
+            user_agent_browser: Vec::new(),
+            user_agent_os: Vec::new(),
+            user_agent_device: Vec::new(),
         }
     }
 
@@ -1132,6 +2145,22 @@ impl pb::AuditListRequest {
             subject: Vec::new(),
             service_id: Vec::new(),
             user_id: Vec::new(),
+            organisation_id: Vec::new(),
+            status_code_ge: None,
+            status_code_le: None,
+            remote: None,
+            remote_cidr: Vec::new(),
+            forwarded: None,
+            user_agent: None,
+            key_id: Vec::new(),
+            user_key_id: Vec::new(),
+            data: Vec::new(),
+            remote_country: Vec::new(),
+            remote_city: Vec::new(),
+            remote_asn: Vec::new(),
+            user_agent_browser: Vec::new(),
+            user_agent_os: Vec::new(),
+            user_agent_device: Vec::new(),
         }
     }
 
@@ -1150,6 +2179,22 @@ impl pb::AuditListRequest {
             subject: Vec::new(),
             service_id: Vec::new(),
             user_id: Vec::new(),
+            organisation_id: Vec::new(),
+            status_code_ge: None,
+            status_code_le: None,
+            remote: None,
+            remote_cidr: Vec::new(),
+            forwarded: None,
+            user_agent: None,
+            key_id: Vec::new(),
+            user_key_id: Vec::new(),
+            data: Vec::new(),
+            remote_country: Vec::new(),
+            remote_city: Vec::new(),
+            remote_asn: Vec::new(),
+            user_agent_browser: Vec::new(),
+            user_agent_os: Vec::new(),
+            user_agent_device: Vec::new(),
         }
     }
 
@@ -1169,6 +2214,22 @@ impl pb::AuditListRequest {
             subject: Vec::new(),
             service_id: Vec::new(),
             user_id: Vec::new(),
+            organisation_id: Vec::new(),
+            status_code_ge: None,
+            status_code_le: None,
+            remote: None,
+            remote_cidr: Vec::new(),
+            forwarded: None,
+            user_agent: None,
+            key_id: Vec::new(),
+            user_key_id: Vec::new(),
+            data: Vec::new(),
+            remote_country: Vec::new(),
+            remote_city: Vec::new(),
+            remote_asn: Vec::new(),
+            user_agent_browser: Vec::new(),
+            user_agent_os: Vec::new(),
+            user_agent_device: Vec::new(),
         }
     }
 
@@ -1183,6 +2244,22 @@ impl pb::AuditListRequest {
             subject,
             service_id: Vec::new(),
             user_id: Vec::new(),
+            organisation_id: Vec::new(),
+            status_code_ge: None,
+            status_code_le: None,
+            remote: None,
+            remote_cidr: Vec::new(),
+            forwarded: None,
+            user_agent: None,
+            key_id: Vec::new(),
+            user_key_id: Vec::new(),
+            data: Vec::new(),
+            remote_country: Vec::new(),
+            remote_city: Vec::new(),
+            remote_asn: Vec::new(),
+            user_agent_browser: Vec::new(),
+            user_agent_os: Vec::new(),
+            user_agent_device: Vec::new(),
         }
     }
 }
@@ -1197,6 +2274,8 @@ impl pb::UserUpdateRequest {
             timezone: None,
             password_allow_reset: None,
             password_require_update: None,
+            attributes: None,
+            service_attributes: None,
         }
     }
 
@@ -1212,6 +2291,16 @@ impl pb::UserUpdateRequest {
         self.name = Some(name.into());
         self
     }
+
+    pub fn attributes(mut self, attributes: serde_json::Value) -> Self {
+        self.attributes = pb::value_to_struct_opt(attributes);
+        self
+    }
+
+    pub fn service_attributes(mut self, service_attributes: serde_json::Value) -> Self {
+        self.service_attributes = pb::value_to_struct_opt(service_attributes);
+        self
+    }
 }
 
 impl pb::UserListRequest {
@@ -1301,6 +2390,34 @@ impl pb::AuthResetPasswordRequest {
     }
 }
 
+impl pb::UserInviteRequest {
+    pub fn new<S, N, E>(service_id: S, name: N, email: E) -> Self
+    where
+        S: Into<String>,
+        N: Into<String>,
+        E: Into<String>,
+    {
+        Self {
+            service_id: service_id.into(),
+            name: name.into(),
+            email: email.into(),
+            locale: None,
+            timezone: None,
+        }
+    }
+}
+
+impl pb::AuthVerifyEmailRequest {
+    pub fn new<E>(email: E) -> Self
+    where
+        E: Into<String>,
+    {
+        Self {
+            email: email.into(),
+        }
+    }
+}
+
 impl pb::AuthCsrfVerifyRequest {
     pub fn new<C>(csrf: C) -> Self
     where
@@ -1313,6 +2430,19 @@ impl pb::AuthCsrfVerifyRequest {
     }
 }
 
+impl pb::AuthImpersonateRequest {
+    pub fn new<U, S>(user_id: U, service_id: S) -> Self
+    where
+        U: Into<String>,
+        S: Into<String>,
+    {
+        Self {
+            user_id: user_id.into(),
+            service_id: service_id.into(),
+        }
+    }
+}
+
 impl pb::AuthTotpRequest {
     pub fn new<U, T>(user_id: U, totp: T) -> Self
     where
@@ -13,6 +13,19 @@ pub async fn local_login(
     Ok(res.into())
 }
 
+pub async fn local_login_revoke(
+    server: &GrpcServiceServer,
+    request: GrpcMethodRequest<pb::AuthTokenRequest>,
+) -> GrpcMethodResult<pb::AuthAuditReply> {
+    let (audit_meta, _auth, req) = request.into_inner();
+    let res = server
+        .client(&audit_meta)
+        .auth_local_login_revoke(req)
+        .await?
+        .into_inner();
+    Ok(res.into())
+}
+
 pub async fn local_register(
     server: &GrpcServiceServer,
     request: GrpcMethodRequest<pb::AuthRegisterRequest>,
@@ -104,6 +117,19 @@ pub async fn local_update_email(
     Ok(res.into())
 }
 
+pub async fn local_update_email_confirm(
+    server: &GrpcServiceServer,
+    request: GrpcMethodRequest<pb::AuthTokenRequest>,
+) -> GrpcMethodResult<pb::AuthAuditReply> {
+    let (audit_meta, _auth, req) = request.into_inner();
+    let res = server
+        .client(&audit_meta)
+        .auth_local_update_email_confirm(req)
+        .await?
+        .into_inner();
+    Ok(res.into())
+}
+
 pub async fn local_update_email_revoke(
     server: &GrpcServiceServer,
     request: GrpcMethodRequest<pb::AuthTokenRequest>,
@@ -143,6 +169,45 @@ pub async fn local_update_password_revoke(
     Ok(res.into())
 }
 
+pub async fn local_verify_email(
+    server: &GrpcServiceServer,
+    request: GrpcMethodRequest<pb::AuthVerifyEmailRequest>,
+) -> GrpcMethodResult<()> {
+    let (audit_meta, _auth, req) = request.into_inner();
+    let res = server
+        .client(&audit_meta)
+        .auth_local_verify_email(req)
+        .await?
+        .into_inner();
+    Ok(res.into())
+}
+
+pub async fn local_verify_email_confirm(
+    server: &GrpcServiceServer,
+    request: GrpcMethodRequest<pb::AuthTokenRequest>,
+) -> GrpcMethodResult<pb::AuthAuditReply> {
+    let (audit_meta, _auth, req) = request.into_inner();
+    let res = server
+        .client(&audit_meta)
+        .auth_local_verify_email_confirm(req)
+        .await?
+        .into_inner();
+    Ok(res.into())
+}
+
+pub async fn local_invite_confirm(
+    server: &GrpcServiceServer,
+    request: GrpcMethodRequest<pb::AuthRegisterConfirmRequest>,
+) -> GrpcMethodResult<pb::AuthPasswordMetaReply> {
+    let (audit_meta, _auth, req) = request.into_inner();
+    let res = server
+        .client(&audit_meta)
+        .auth_local_invite_confirm(req)
+        .await?
+        .into_inner();
+    Ok(res.into())
+}
+
 pub async fn microsoft_oauth2_url(
     server: &GrpcServiceServer,
     request: GrpcMethodRequest<()>,
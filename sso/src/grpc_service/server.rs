@@ -8,6 +8,7 @@ pub struct GrpcServiceServer {
     authorisation: String,
     channel: GrpcClientChannel,
     traefik_enabled: bool,
+    trusted_proxy: Vec<Cidr>,
     count: IntCounterVec,
     latency: HistogramVec,
 }
@@ -24,6 +25,7 @@ impl GrpcServiceServer {
         authorisation: A,
         channel: GrpcClientChannel,
         traefik_enabled: bool,
+        trusted_proxy: Vec<Cidr>,
         count: IntCounterVec,
         latency: HistogramVec,
     ) -> Self {
@@ -31,6 +33,7 @@ impl GrpcServiceServer {
             authorisation: authorisation.into(),
             channel,
             traefik_enabled,
+            trusted_proxy,
             count,
             latency,
         }
@@ -50,7 +53,7 @@ impl GrpcServiceServer {
                 .authorisation(authorisation)
                 .user_authorisation(audit_meta.user().map(|x| x.header_value()))
                 .user_agent(Some(audit_meta.user_agent().to_owned()))
-                .forwarded(audit_meta.forwarded().map(|x| x.to_owned())),
+                .forwarded(audit_meta.forwarded_for()),
         )
     }
 
@@ -62,7 +65,7 @@ impl GrpcServiceServer {
         let metrics = GrpcServerMetrics::start(path, &self.count, &self.latency);
         Ok((
             metrics,
-            GrpcMethodRequest::from_unit(req, self.traefik_enabled)?,
+            GrpcMethodRequest::from_unit(req, self.traefik_enabled, &self.trusted_proxy)?,
         ))
     }
 
@@ -78,7 +81,7 @@ impl GrpcServiceServer {
         let metrics = GrpcServerMetrics::start(path, &self.count, &self.latency);
         Ok((
             metrics,
-            GrpcMethodRequest::from_request(req, self.traefik_enabled)?,
+            GrpcMethodRequest::from_request(req, self.traefik_enabled, &self.trusted_proxy)?,
         ))
     }
 
@@ -114,6 +117,14 @@ impl pb::sso_service_server::SsoService for GrpcServiceServer {
         self.post(metrics, method::local_login(self, request).await)
     }
 
+    async fn auth_local_login_revoke(
+        &self,
+        request: tonic::Request<pb::AuthTokenRequest>,
+    ) -> Result<tonic::Response<pb::AuthAuditReply>, tonic::Status> {
+        let (metrics, request) = self.pre_validate("local_login_revoke", request)?;
+        self.post(metrics, method::local_login_revoke(self, request).await)
+    }
+
     async fn auth_local_register(
         &self,
         request: tonic::Request<pb::AuthRegisterRequest>,
@@ -176,6 +187,17 @@ impl pb::sso_service_server::SsoService for GrpcServiceServer {
         self.post(metrics, method::local_update_email(self, request).await)
     }
 
+    async fn auth_local_update_email_confirm(
+        &self,
+        request: tonic::Request<pb::AuthTokenRequest>,
+    ) -> Result<tonic::Response<pb::AuthAuditReply>, tonic::Status> {
+        let (metrics, request) = self.pre_validate("local_update_email_confirm", request)?;
+        self.post(
+            metrics,
+            method::local_update_email_confirm(self, request).await,
+        )
+    }
+
     async fn auth_local_update_email_revoke(
         &self,
         request: tonic::Request<pb::AuthTokenRequest>,
@@ -206,6 +228,33 @@ impl pb::sso_service_server::SsoService for GrpcServiceServer {
         )
     }
 
+    async fn auth_local_verify_email(
+        &self,
+        request: tonic::Request<pb::AuthVerifyEmailRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        let (metrics, request) = self.pre_validate("local_verify_email", request)?;
+        self.post(metrics, method::local_verify_email(self, request).await)
+    }
+
+    async fn auth_local_verify_email_confirm(
+        &self,
+        request: tonic::Request<pb::AuthTokenRequest>,
+    ) -> Result<tonic::Response<pb::AuthAuditReply>, tonic::Status> {
+        let (metrics, request) = self.pre_validate("local_verify_email_confirm", request)?;
+        self.post(
+            metrics,
+            method::local_verify_email_confirm(self, request).await,
+        )
+    }
+
+    async fn auth_local_invite_confirm(
+        &self,
+        request: tonic::Request<pb::AuthRegisterConfirmRequest>,
+    ) -> Result<tonic::Response<pb::AuthPasswordMetaReply>, tonic::Status> {
+        let (metrics, request) = self.pre_validate("local_invite_confirm", request)?;
+        self.post(metrics, method::local_invite_confirm(self, request).await)
+    }
+
     async fn auth_microsoft_oauth2_url(
         &self,
         request: tonic::Request<()>,
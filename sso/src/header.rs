@@ -17,6 +17,18 @@ pub const USER_AGENT: &str = "user-agent";
 /// X-Forwarded-For header.
 pub const X_FORWARDED_FOR: &str = "x-forwarded-for";
 
+/// Forwarded header.
+pub const FORWARDED: &str = "forwarded";
+
+/// X-Real-IP header.
+pub const X_REAL_IP: &str = "x-real-ip";
+
+/// X-Forwarded-Host header.
+pub const X_FORWARDED_HOST: &str = "x-forwarded-host";
+
+/// X-Forwarded-Uri header.
+pub const X_FORWARDED_URI: &str = "x-forwarded-uri";
+
 /// Grpc-Metadata-Sso-Key-Id header.
 pub const GRPC_METADATA_SSO_KEY_ID: &str = "grpc-metadata-sso-key-id";
 
@@ -29,6 +41,12 @@ pub const GRPC_METADATA_SSO_USER_KEY_ID: &str = "grpc-metadata-sso-user-key-id";
 /// Grpc-Metadata-Sso-User-Id header.
 pub const GRPC_METADATA_SSO_USER_ID: &str = "grpc-metadata-sso-user-id";
 
+/// Grpc-Metadata-Sso-User-Roles header.
+pub const GRPC_METADATA_SSO_USER_ROLES: &str = "grpc-metadata-sso-user-roles";
+
+/// Grpc-Metadata-Sso-Actor-Key-Id header.
+pub const GRPC_METADATA_SSO_ACTOR_KEY_ID: &str = "grpc-metadata-sso-actor-key-id";
+
 /// Sso-Key-Id header.
 pub const SSO_KEY_ID: &str = "sso-key-id";
 
@@ -41,6 +59,15 @@ pub const SSO_USER_KEY_ID: &str = "sso-user-key-id";
 /// Sso-User-Id header.
 pub const SSO_USER_ID: &str = "sso-user-id";
 
+/// Sso-Webhook-Id header.
+pub const SSO_WEBHOOK_ID: &str = "sso-webhook-id";
+
+/// Sso-Webhook-Timestamp header.
+pub const SSO_WEBHOOK_TIMESTAMP: &str = "sso-webhook-timestamp";
+
+/// Sso-Webhook-Signature header.
+pub const SSO_WEBHOOK_SIGNATURE: &str = "sso-webhook-signature";
+
 /// Returns Authorization header string.
 pub fn authorisation(map: &HeaderMap<HeaderValue>) -> Option<String> {
     if let Some(x) = map.get(AUTHORISATION) {
@@ -90,15 +117,50 @@ pub fn user_agent(map: &HeaderMap<HeaderValue>) -> String {
 }
 
 /// Returns X-Forwarded-For header string.
+/// Multiple headers are joined into one comma separated list.
 pub fn x_forwarded_for(map: &HeaderMap<HeaderValue>) -> Option<String> {
-    if let Some(x) = map.get(X_FORWARDED_FOR) {
-        match x.to_str() {
-            Ok(x) => Some(x.to_owned()),
-            Err(_e) => None,
-        }
-    } else {
-        None
-    }
+    list(map, X_FORWARDED_FOR)
+}
+
+/// Returns Forwarded header string.
+/// Multiple headers are joined into one comma separated list.
+pub fn forwarded(map: &HeaderMap<HeaderValue>) -> Option<String> {
+    list(map, FORWARDED)
+}
+
+/// Returns X-Real-IP header string.
+pub fn x_real_ip(map: &HeaderMap<HeaderValue>) -> Option<String> {
+    string_opt(map, X_REAL_IP)
+}
+
+/// Returns X-Forwarded-Host header string.
+pub fn x_forwarded_host(map: &HeaderMap<HeaderValue>) -> Option<String> {
+    string_opt(map, X_FORWARDED_HOST)
+}
+
+/// Returns X-Forwarded-Uri header string.
+pub fn x_forwarded_uri(map: &HeaderMap<HeaderValue>) -> Option<String> {
+    string_opt(map, X_FORWARDED_URI)
+}
+
+/// Returns `for` parameters of elements in Forwarded header string, in order.
+/// Quotes are removed, elements without a `for` parameter are skipped.
+pub fn forwarded_for(forwarded: &str) -> Vec<String> {
+    forwarded
+        .split(',')
+        .filter_map(|element| {
+            element.split(';').find_map(|pair| {
+                let mut pair = pair.splitn(2, '=');
+                let key = pair.next().unwrap_or("").trim();
+                let value = pair.next().unwrap_or("").trim();
+                if key.eq_ignore_ascii_case("for") && !value.is_empty() {
+                    Some(value.trim_matches('"').to_owned())
+                } else {
+                    None
+                }
+            })
+        })
+        .collect()
 }
 
 /// Returns Sso-Key-Id header string.
@@ -264,10 +326,55 @@ impl HeaderAuth {
     }
 }
 
+/// Returns header string, or None if header is missing or not valid.
+fn string_opt(map: &HeaderMap<HeaderValue>, name: &str) -> Option<String> {
+    map.get(name)
+        .and_then(|x| x.to_str().ok())
+        .map(|x| x.to_owned())
+}
+
+/// Returns values of all headers with name joined by commas, or None if
+/// no headers are present.
+fn list(map: &HeaderMap<HeaderValue>, name: &str) -> Option<String> {
+    let values: Vec<&str> = map
+        .get_all(name)
+        .iter()
+        .filter_map(|x| x.to_str().ok())
+        .collect();
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.join(", "))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn forwarded_for_parses_elements() {
+        let x = forwarded_for(
+            "for=192.0.2.60;proto=http;by=203.0.113.43, For=\"[2001:db8:cafe::17]:4711\", proto=https",
+        );
+        assert_eq!(x, vec!["192.0.2.60", "[2001:db8:cafe::17]:4711"]);
+    }
+
+    #[test]
+    fn list_joins_headers() {
+        let mut map = HeaderMap::new();
+        map.append(
+            X_FORWARDED_FOR,
+            HeaderValue::from_static("10.0.0.1, 10.0.0.2"),
+        );
+        map.append(X_FORWARDED_FOR, HeaderValue::from_static("10.0.0.3"));
+        assert_eq!(
+            x_forwarded_for(&map).unwrap(),
+            "10.0.0.1, 10.0.0.2, 10.0.0.3"
+        );
+        assert_eq!(forwarded(&map), None);
+    }
+
     #[test]
     fn header_auth_parses_type_none() {
         let x = HeaderAuth::parse_type("abcdefg").unwrap();
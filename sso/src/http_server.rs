@@ -1,10 +1,51 @@
-use crate::prelude::*;
-use hyper::{Body, Method, Request, Response, StatusCode};
+use crate::{grpc::audit_watch_spawn, prelude::*};
+use futures_util::stream::StreamExt;
+use hyper::{
+    header::{CACHE_CONTROL, CONTENT_TYPE},
+    Body, Method, Request, Response, StatusCode,
+};
 use std::{net::SocketAddr, sync::Arc};
 
 static NOT_FOUND: &[u8] = b"NotFound";
 static PONG: &[u8] = b"Pong";
 
+/// Audit watch query parameters, filter arrays are comma separated.
+#[derive(Debug, Default, Deserialize)]
+struct AuditWatchQuery {
+    offset_id: Option<String>,
+    #[serde(rename = "type")]
+    type_: Option<String>,
+    subject: Option<String>,
+    service_id: Option<String>,
+    user_id: Option<String>,
+    organisation_id: Option<String>,
+}
+
+impl AuditWatchQuery {
+    fn split(x: Option<String>) -> Vec<String> {
+        x.map(|x| {
+            x.split(',')
+                .filter(|x| !x.is_empty())
+                .map(|x| x.to_owned())
+                .collect()
+        })
+        .unwrap_or_default()
+    }
+}
+
+impl From<AuditWatchQuery> for pb::AuditWatchRequest {
+    fn from(x: AuditWatchQuery) -> Self {
+        Self {
+            offset_id: x.offset_id,
+            r#type: AuditWatchQuery::split(x.type_),
+            subject: AuditWatchQuery::split(x.subject),
+            service_id: AuditWatchQuery::split(x.service_id),
+            user_id: AuditWatchQuery::split(x.user_id),
+            organisation_id: AuditWatchQuery::split(x.organisation_id),
+        }
+    }
+}
+
 /// HTTP server.
 #[derive(Debug)]
 pub struct HttpServer;
@@ -20,9 +61,10 @@ impl HttpServer {
         match (req.method(), req.uri().path()) {
             (&Method::GET, "/ping") => ping(req).await,
             (&Method::GET, "/metrics") => metrics(driver, req).await,
+            (&Method::GET, "/audit/watch") => audit_watch(options, driver, req, remote).await,
             (&Method::GET, "/hook/traefik/self") => {
                 if options.traefik_enabled() {
-                    traefik_self(driver, req, remote).await
+                    traefik_self(options, driver, req, remote).await
                 } else {
                     // Return 401 unauthorised response.
                     Ok(response_unauthorised())
@@ -30,7 +72,7 @@ impl HttpServer {
             }
             (&Method::GET, "/hook/traefik/service") => {
                 if options.traefik_enabled() {
-                    traefik_service(driver, req, remote).await
+                    traefik_service(options, driver, req, remote).await
                 } else {
                     // Return 401 unauthorised response.
                     Ok(response_unauthorised())
@@ -60,16 +102,83 @@ async fn metrics(
     Ok(Response::new(Body::from(s)))
 }
 
+/// Server-sent events equivalent of `AuditWatch` RPC.
+///
+/// Reconnecting clients resume after the ID in the `Last-Event-ID`
+/// header, which takes precedence over the `offset_id` query parameter.
+async fn audit_watch(
+    options: Arc<GrpcServerOptions>,
+    driver: Arc<Postgres>,
+    req: Request<Body>,
+    remote: SocketAddr,
+) -> Result<Response<Body>, hyper::Error> {
+    let remote = format!("{}", remote);
+    let (mut audit_meta, auth) = (
+        AuditMeta::from_header_map(req.headers(), remote, options.trusted_proxy_cidr()),
+        HeaderAuth::from_header_map(req.headers(), false),
+    );
+    audit_meta.set_scope(Some(KeyScope::AuditRead));
+
+    let query = req.uri().query().unwrap_or("");
+    let mut query: AuditWatchQuery = match serde_urlencoded::from_str(query) {
+        Ok(x) => x,
+        Err(e) => return Ok(response_from_error(GrpcMethodError::BadRequest(e.into()))),
+    };
+    if let Some(last_event_id) = req.headers().get("Last-Event-ID") {
+        query.offset_id = last_event_id.to_str().ok().map(|x| x.to_owned());
+    }
+    let watch: AuditWatch = match validate::validate(pb::AuditWatchRequest::from(query)) {
+        Ok(x) => x.into(),
+        Err(e) => return Ok(response_from_error(GrpcMethodError::BadRequest(e))),
+    };
+
+    let interval = options.audit_watch_interval();
+    let rx = match audit_watch_spawn(driver, interval, audit_meta, auth, watch).await {
+        Ok(rx) => rx,
+        Err(e) => return Ok(response_from_error(e)),
+    };
+    let events = rx.map(|x| {
+        let event = match x {
+            Ok(audit) => format!(
+                "id: {}\nevent: audit\ndata: {}\n\n",
+                audit.id,
+                audit.to_value()
+            ),
+            Err(e) => {
+                let status = e.get_status();
+                let data = json!({
+                    "code": status.code() as i32,
+                    "message": status.message(),
+                });
+                format!("event: error\ndata: {}\n\n", data)
+            }
+        };
+        Ok::<_, hyper::Error>(event)
+    });
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(CONTENT_TYPE, "text/event-stream")
+        .header(CACHE_CONTROL, "no-cache")
+        .body(Body::wrap_stream(events))
+        .unwrap())
+}
+
 async fn traefik_self(
+    options: Arc<GrpcServerOptions>,
     driver: Arc<Postgres>,
     req: Request<Body>,
     remote: SocketAddr,
 ) -> Result<Response<Body>, hyper::Error> {
     let remote = format!("{}", remote);
-    let (audit_meta, auth) = (
-        AuditMeta::from_header_map(req.headers(), remote),
+    let (mut audit_meta, auth) = (
+        AuditMeta::from_header_map(req.headers(), remote, options.trusted_proxy_cidr()),
         HeaderAuth::from_header_map(req.headers(), false),
     );
+    // Hook is called by Traefik, client address is resolved from forwarded headers.
+    audit_meta.set_remote_forwarded(options.trusted_proxy_cidr());
+    // Hook does not have a scope, forwarded requests are authenticated by key
+    // identifier headers and scope of method is checked when they are handled.
 
     let driver = driver.clone();
     let audit_builder = blocking_method(move || {
@@ -79,7 +188,7 @@ async fn traefik_self(
             AuditType::Traefik,
             |driver, audit| {
                 pattern::key_authenticate(driver, audit, &auth)
-                    .map_err(GrpcMethodError::Unauthorised)?;
+                    .map_err(GrpcMethodError::authenticate)?;
                 Ok(audit.clone())
             },
         )
@@ -87,21 +196,25 @@ async fn traefik_self(
     .await;
 
     Ok(match audit_builder {
-        Ok(audit) => response_from_audit_builder(audit),
+        Ok(audit) => response_from_audit_builder(audit, &[]),
         Err(_e) => response_unauthorised(),
     })
 }
 
 async fn traefik_service(
+    options: Arc<GrpcServerOptions>,
     driver: Arc<Postgres>,
     req: Request<Body>,
     remote: SocketAddr,
 ) -> Result<Response<Body>, hyper::Error> {
     let remote = format!("{}", remote);
-    let (audit_meta, auth) = (
-        AuditMeta::from_header_map(req.headers(), remote),
+    let (mut audit_meta, auth) = (
+        AuditMeta::from_header_map(req.headers(), remote, options.trusted_proxy_cidr()),
         HeaderAuth::from_header_map(req.headers(), false),
     );
+    // Hook is called by Traefik, client address is resolved from forwarded headers.
+    audit_meta.set_remote_forwarded(options.trusted_proxy_cidr());
+    audit_meta.set_scope(traefik_service_scope(&auth));
     let service_key = header::service_authorisation(req.headers());
 
     let driver = driver.clone();
@@ -111,20 +224,36 @@ async fn traefik_service(
             audit_meta,
             AuditType::Traefik,
             |driver, audit| {
-                pattern::user_key_token_authenticate(driver, audit, &auth, service_key.clone())
-                    .map_err(GrpcMethodError::Unauthorised)?;
-                Ok(audit.clone())
+                let user =
+                    pattern::user_key_token_authenticate(driver, audit, &auth, service_key.clone())
+                        .map_err(GrpcMethodError::authenticate)?;
+                let roles = match audit.get_service_id() {
+                    Some(service_id) => driver
+                        .user_role_names(&service_id, &user.id)
+                        .map_err(GrpcMethodError::BadRequest)?,
+                    None => Vec::new(),
+                };
+                Ok((audit.clone(), roles))
             },
         )
     })
     .await;
 
     Ok(match audit_builder {
-        Ok(audit) => response_from_audit_builder(audit),
+        Ok((audit, roles)) => response_from_audit_builder(audit, &roles),
         Err(_e) => response_unauthorised(),
     })
 }
 
+/// Returns scope of Traefik service hook, service keys must have the same scope
+/// as `AuthKeyVerify` or `AuthTokenVerify` to authenticate user keys or tokens.
+fn traefik_service_scope(auth: &HeaderAuth) -> Option<KeyScope> {
+    match auth {
+        HeaderAuth::Header(HeaderAuthType::Key(_)) => Some(KeyScope::AuthKey),
+        _ => Some(KeyScope::AuthToken),
+    }
+}
+
 fn response_unauthorised() -> Response<Body> {
     let grpc_status = format!("{}", tonic::Code::Unauthenticated as u8);
     Response::builder()
@@ -135,7 +264,26 @@ fn response_unauthorised() -> Response<Body> {
         .unwrap()
 }
 
-fn response_from_audit_builder(audit: AuditBuilder) -> Response<Body> {
+fn response_from_error(e: GrpcMethodError) -> Response<Body> {
+    let status = match e {
+        GrpcMethodError::BadRequest(_) => StatusCode::BAD_REQUEST,
+        GrpcMethodError::Unauthorised(_) => return response_unauthorised(),
+        GrpcMethodError::Forbidden(_) => StatusCode::FORBIDDEN,
+        GrpcMethodError::NotFound(_) => StatusCode::NOT_FOUND,
+        GrpcMethodError::InternalServerError(_) | GrpcMethodError::Status(_) => {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    };
+    let e = e.get_status();
+    let grpc_status = format!("{}", e.code() as u8);
+    Response::builder()
+        .status(status)
+        .header("Grpc-Status", grpc_status)
+        .body(Body::from(e.message().to_owned()))
+        .unwrap()
+}
+
+fn response_from_audit_builder(audit: AuditBuilder, roles: &[String]) -> Response<Body> {
     let mut builder = Response::builder().status(StatusCode::OK);
     if let Some(key_id) = audit.get_key_id() {
         builder = builder.header(header::GRPC_METADATA_SSO_KEY_ID, key_id.to_string());
@@ -152,5 +300,31 @@ fn response_from_audit_builder(audit: AuditBuilder) -> Response<Body> {
     if let Some(user_id) = audit.get_user_id() {
         builder = builder.header(header::GRPC_METADATA_SSO_USER_ID, user_id.to_string());
     }
+    if !roles.is_empty() {
+        builder = builder.header(header::GRPC_METADATA_SSO_USER_ROLES, roles.join(","));
+    }
+    if let Some(actor_key_id) = audit.get_actor_key_id() {
+        builder = builder.header(
+            header::GRPC_METADATA_SSO_ACTOR_KEY_ID,
+            actor_key_id.to_string(),
+        );
+    }
     builder.body(Body::empty()).unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn traefik_service_scope_by_user_auth_type() {
+        let auth = HeaderAuth::Header(HeaderAuthType::Key("key".to_owned()));
+        assert_eq!(traefik_service_scope(&auth), Some(KeyScope::AuthKey));
+        let auth = HeaderAuth::Header(HeaderAuthType::Token("token".to_owned()));
+        assert_eq!(traefik_service_scope(&auth), Some(KeyScope::AuthToken));
+        assert_eq!(
+            traefik_service_scope(&HeaderAuth::None),
+            Some(KeyScope::AuthToken)
+        );
+    }
+}
@@ -1,6 +1,9 @@
 use crate::prelude::*;
 use diesel::PgConnection;
-use jsonwebtoken::{dangerous_unsafe_decode, DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::{
+    dangerous_unsafe_decode, errors::ErrorKind, DecodingKey, EncodingKey, Header, Validation,
+};
+use serde_json::{Map, Value};
 
 /// JSON web token types.
 #[derive(Debug)]
@@ -15,6 +18,12 @@ pub enum JwtType {
     ResetPasswordToken,
     /// Revoke tokens used to revoke user tokens and keys.
     RevokeToken,
+    /// Verify email tokens used to verify user email addresses.
+    VerifyEmailToken,
+    /// Invite tokens used to accept user invitations.
+    InviteToken,
+    /// Update email tokens used to confirm pending user email address changes.
+    UpdateEmailToken,
 }
 
 impl JwtType {
@@ -26,6 +35,9 @@ impl JwtType {
             JwtType::RegisterToken => 2,
             JwtType::ResetPasswordToken => 3,
             JwtType::RevokeToken => 4,
+            JwtType::VerifyEmailToken => 5,
+            JwtType::InviteToken => 6,
+            JwtType::UpdateEmailToken => 7,
         }
     }
 
@@ -37,11 +49,23 @@ impl JwtType {
             2 => Ok(JwtType::RegisterToken),
             3 => Ok(JwtType::ResetPasswordToken),
             4 => Ok(JwtType::RevokeToken),
+            5 => Ok(JwtType::VerifyEmailToken),
+            6 => Ok(JwtType::InviteToken),
+            7 => Ok(JwtType::UpdateEmailToken),
             _ => Err(DriverError::JwtTypeInvalid),
         }
     }
 }
 
+/// JSON web token actor claims.
+///
+/// Present in impersonation tokens, subject is the root key which
+/// issued the token.
+#[derive(Debug, Serialize, Deserialize)]
+struct JwtActorClaims {
+    sub: String,
+}
+
 /// JSON web token claims.
 #[derive(Debug, Serialize, Deserialize)]
 struct JwtClaims {
@@ -53,6 +77,14 @@ struct JwtClaims {
     #[serde(rename = "x-csrf")]
     #[serde(skip_serializing_if = "Option::is_none")]
     x_csrf: Option<String>,
+    #[serde(rename = "x-roles")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    x_roles: Vec<String>,
+    #[serde(rename = "x-attributes")]
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    x_attributes: Map<String, Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    act: Option<JwtActorClaims>,
 }
 
 impl JwtClaims {
@@ -69,6 +101,9 @@ impl JwtClaims {
             exp: dt.timestamp(),
             x_type: x_type.to_i64(),
             x_csrf: None,
+            x_roles: Vec::new(),
+            x_attributes: Map::default(),
+            act: None,
         }
     }
 
@@ -122,11 +157,15 @@ impl Jwt {
     }
 
     /// Encode and return access and refresh tokens for a user with key.
+    /// Names of roles assigned to user for service and user attributes picked
+    /// by service are included in access token.
     pub fn encode_user(
         conn: &PgConnection,
         service: &Service,
         user: User,
         key: &KeyWithValue,
+        roles: Vec<String>,
+        attributes: Map<String, Value>,
         access_token_expires: Duration,
         refresh_token_expires: Duration,
     ) -> DriverResult<UserToken> {
@@ -135,6 +174,8 @@ impl Jwt {
             user.id,
             JwtType::AccessToken,
             &key.value,
+            roles,
+            attributes,
             access_token_expires,
         )?;
         let (refresh_token, refresh_token_expires) = Self::encode_csrf(
@@ -154,22 +195,62 @@ impl Jwt {
         })
     }
 
+    /// Encode and return impersonation access token for a user with key.
+    /// Token has an `act` claim naming the root key which issued it, no
+    /// refresh token is returned.
+    pub fn encode_impersonate(
+        service: &Service,
+        user: User,
+        key: &KeyWithValue,
+        actor_key_id: Uuid,
+        roles: Vec<String>,
+        attributes: Map<String, Value>,
+        access_token_expires: Duration,
+    ) -> DriverResult<UserTokenAccess> {
+        let mut claims = JwtClaims::new(
+            service.id.to_string(),
+            user.id.to_string(),
+            access_token_expires,
+            JwtType::AccessToken,
+        );
+        claims.x_roles = roles;
+        claims.x_attributes = attributes;
+        claims.act = Some(JwtActorClaims {
+            sub: actor_key_id.to_string(),
+        });
+        let access_token = jsonwebtoken::encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(key.value.as_bytes()),
+        )
+        .map_err(DriverError::Jsonwebtoken)?;
+        Ok(UserTokenAccess {
+            user,
+            access_token,
+            access_token_expires: claims.exp,
+        })
+    }
+
     /// Safely decode access token for user with key.
-    /// Returns expiry time.
+    /// Returns expiry time and actor key ID if token is an impersonation token.
     pub fn decode_access<T: AsRef<str>>(
         service: &Service,
         user: &User,
         key: &KeyWithValue,
         token: T,
-    ) -> DriverResult<i64> {
-        let (exp, _) = Self::decode(
+    ) -> DriverResult<(i64, Option<Uuid>)> {
+        let claims = Self::decode_claims(
             service.id,
             user.id,
             JwtType::AccessToken,
-            &key.value,
+            key,
             token.as_ref(),
         )?;
-        Ok(exp)
+        let actor_key_id = match claims.act {
+            Some(act) => Some(Uuid::parse_str(&act.sub).map_err(DriverError::UuidParse)?),
+            None => None,
+        };
+        Ok((claims.exp, actor_key_id))
     }
 
     /// Safely decode refresh token for user with key and verify CSRF key.
@@ -184,7 +265,7 @@ impl Jwt {
             service.id,
             user.id,
             JwtType::RefreshToken,
-            &key.value,
+            key,
             token.as_ref(),
         )?;
         CsrfVerify::verify(conn, service.id, csrf_key)?;
@@ -222,7 +303,121 @@ impl Jwt {
             service.id,
             user.id,
             JwtType::RegisterToken,
+            key,
+            token.as_ref(),
+        )?;
+        CsrfVerify::verify(conn, service.id, csrf_key)?;
+        Ok(())
+    }
+
+    /// Encode and return verify email token for user with key.
+    pub fn encode_verify_email(
+        conn: &PgConnection,
+        service: &Service,
+        user: &User,
+        key: &KeyWithValue,
+        token_expires: Duration,
+    ) -> DriverResult<String> {
+        let (token, _) = Self::encode_csrf(
+            conn,
+            service.id,
+            user.id,
+            JwtType::VerifyEmailToken,
             &key.value,
+            token_expires,
+        )?;
+        Ok(token)
+    }
+
+    /// Safely decode verify email token for user with key and verify CSRF key.
+    pub fn decode_verify_email<T: AsRef<str>>(
+        conn: &PgConnection,
+        service: &Service,
+        user: &User,
+        key: &KeyWithValue,
+        token: T,
+    ) -> DriverResult<()> {
+        let (_, csrf_key) = Self::decode(
+            service.id,
+            user.id,
+            JwtType::VerifyEmailToken,
+            key,
+            token.as_ref(),
+        )?;
+        CsrfVerify::verify(conn, service.id, csrf_key)?;
+        Ok(())
+    }
+
+    /// Encode and return invite token for user with key.
+    pub fn encode_invite(
+        conn: &PgConnection,
+        service: &Service,
+        user: &User,
+        key: &KeyWithValue,
+        token_expires: Duration,
+    ) -> DriverResult<String> {
+        let (token, _) = Self::encode_csrf(
+            conn,
+            service.id,
+            user.id,
+            JwtType::InviteToken,
+            &key.value,
+            token_expires,
+        )?;
+        Ok(token)
+    }
+
+    /// Safely decode invite token for user with key and verify CSRF key.
+    pub fn decode_invite<T: AsRef<str>>(
+        conn: &PgConnection,
+        service: &Service,
+        user: &User,
+        key: &KeyWithValue,
+        token: T,
+    ) -> DriverResult<()> {
+        let (_, csrf_key) = Self::decode(
+            service.id,
+            user.id,
+            JwtType::InviteToken,
+            key,
+            token.as_ref(),
+        )?;
+        CsrfVerify::verify(conn, service.id, csrf_key)?;
+        Ok(())
+    }
+
+    /// Encode and return update email token for user with key.
+    pub fn encode_update_email(
+        conn: &PgConnection,
+        service: &Service,
+        user: &User,
+        key: &KeyWithValue,
+        token_expires: Duration,
+    ) -> DriverResult<String> {
+        let (token, _) = Self::encode_csrf(
+            conn,
+            service.id,
+            user.id,
+            JwtType::UpdateEmailToken,
+            &key.value,
+            token_expires,
+        )?;
+        Ok(token)
+    }
+
+    /// Safely decode update email token for user with key and verify CSRF key.
+    pub fn decode_update_email<T: AsRef<str>>(
+        conn: &PgConnection,
+        service: &Service,
+        user: &User,
+        key: &KeyWithValue,
+        token: T,
+    ) -> DriverResult<()> {
+        let (_, csrf_key) = Self::decode(
+            service.id,
+            user.id,
+            JwtType::UpdateEmailToken,
+            key,
             token.as_ref(),
         )?;
         CsrfVerify::verify(conn, service.id, csrf_key)?;
@@ -260,7 +455,7 @@ impl Jwt {
             service.id,
             user.id,
             JwtType::ResetPasswordToken,
-            &key.value,
+            key,
             token.as_ref(),
         )?;
         CsrfVerify::verify(conn, service.id, csrf_key)?;
@@ -298,7 +493,7 @@ impl Jwt {
             service.id,
             user.id,
             JwtType::RevokeToken,
-            &key.value,
+            key,
             token.as_ref(),
         )?;
         CsrfVerify::verify(conn, service.id, csrf_key)?;
@@ -314,23 +509,27 @@ impl Jwt {
         token_type: JwtType,
         token: T,
     ) -> DriverResult<()> {
-        let (_, csrf_key) =
-            Self::decode(service.id, user.id, token_type, &key.value, token.as_ref())?;
+        let (_, csrf_key) = Self::decode(service.id, user.id, token_type, key, token.as_ref())?;
         if let Some(csrf_key) = csrf_key {
             CsrfRead::read(conn, &csrf_key)?;
         }
         Ok(())
     }
 
-    /// Encode a token with key of type without a CSRF code, returns token and expiry time.
+    /// Encode a token with key of type with roles and attributes and without a CSRF code,
+    /// returns token and expiry time.
     fn encode(
         service_id: Uuid,
         user_id: Uuid,
         x_type: JwtType,
         key_value: &str,
+        x_roles: Vec<String>,
+        x_attributes: Map<String, Value>,
         exp: Duration,
     ) -> DriverResult<(String, i64)> {
-        let claims = JwtClaims::new(service_id.to_string(), user_id.to_string(), exp, x_type);
+        let mut claims = JwtClaims::new(service_id.to_string(), user_id.to_string(), exp, x_type);
+        claims.x_roles = x_roles;
+        claims.x_attributes = x_attributes;
         let token = jsonwebtoken::encode(
             &Header::default(),
             &claims,
@@ -368,24 +567,50 @@ impl Jwt {
 
     /// Safely decodes a token with key, returns expiry time and optional CSRF key.
     /// This will return an error if the subject or issuer claims do not match the server
-    /// and user ID, if the token is expired, or if the type is unexpected.
+    /// and user ID, if the token is expired, if the type is unexpected, or if the
+    /// token is an impersonation token.
+    /// If key has been rotated, tokens signed by the predecessor are also accepted
+    /// during its grace period.
     fn decode(
         service_id: Uuid,
         user_id: Uuid,
         x_type: JwtType,
-        key_value: &str,
+        key: &KeyWithValue,
         token: &str,
     ) -> DriverResult<(i64, Option<String>)> {
+        let claims = Self::decode_claims(service_id, user_id, x_type, key, token)?;
+        if claims.act.is_some() {
+            return Err(DriverError::UserImpersonationForbidden);
+        }
+        Ok((claims.exp, claims.x_csrf))
+    }
+
+    /// Safely decodes a token with key, returns claims.
+    fn decode_claims(
+        service_id: Uuid,
+        user_id: Uuid,
+        x_type: JwtType,
+        key: &KeyWithValue,
+        token: &str,
+    ) -> DriverResult<JwtClaims> {
         let validation = JwtClaims::validation(service_id.to_string(), user_id.to_string());
-        let data = jsonwebtoken::decode::<JwtClaims>(
-            token,
-            &DecodingKey::from_secret(key_value.as_bytes()),
-            &validation,
-        )
-        .map_err(DriverError::Jsonwebtoken)?;
+        let decode = |key_value: &str| {
+            jsonwebtoken::decode::<JwtClaims>(
+                token,
+                &DecodingKey::from_secret(key_value.as_bytes()),
+                &validation,
+            )
+        };
+        let mut result = decode(&key.value);
+        if let (Err(e), Some(predecessor_value)) = (&result, &key.predecessor_value) {
+            if e.kind() == &ErrorKind::InvalidSignature {
+                result = decode(predecessor_value);
+            }
+        }
+        let data = result.map_err(DriverError::Jsonwebtoken)?;
         if data.claims.x_type != x_type.to_i64() {
             return Err(DriverError::JwtTypeMismatch);
         }
-        Ok((data.claims.exp, data.claims.x_csrf))
+        Ok(data.claims)
     }
 }
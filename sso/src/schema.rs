@@ -15,6 +15,54 @@ table! {
         service_id -> Nullable<Uuid>,
         user_id -> Nullable<Uuid>,
         user_key_id -> Nullable<Uuid>,
+        organisation_id -> Nullable<Uuid>,
+        actor_key_id -> Nullable<Uuid>,
+        chain_seq -> Nullable<Int8>,
+        chain_previous -> Nullable<Varchar>,
+        chain_hash -> Nullable<Varchar>,
+        chain_superseded -> Array<Varchar>,
+        proxy -> Nullable<Jsonb>,
+        remote_country -> Nullable<Varchar>,
+        remote_city -> Nullable<Varchar>,
+        remote_asn -> Nullable<Int8>,
+        remote_as_org -> Nullable<Varchar>,
+        user_agent_browser -> Nullable<Varchar>,
+        user_agent_os -> Nullable<Varchar>,
+        user_agent_device -> Nullable<Varchar>,
+    }
+}
+
+table! {
+    sso_audit_chain_point (id) {
+        created_at -> Timestamptz,
+        id -> Uuid,
+        #[sql_name = "type"]
+        type_ -> Varchar,
+        chain_seq -> Int8,
+        chain_hash -> Varchar,
+        count -> Int8,
+        signature -> Varchar,
+    }
+}
+
+table! {
+    sso_audit_export (name) {
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        name -> Varchar,
+        chain_seq -> Int8,
+    }
+}
+
+table! {
+    sso_audit_retention (id) {
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        id -> Uuid,
+        service_id -> Nullable<Uuid>,
+        #[sql_name = "type"]
+        type_ -> Nullable<Varchar>,
+        days -> Int4,
     }
 }
 
@@ -28,6 +76,26 @@ table! {
     }
 }
 
+table! {
+    sso_group (id) {
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        id -> Uuid,
+        is_enabled -> Bool,
+        name -> Varchar,
+        parent_id -> Nullable<Uuid>,
+        service_id -> Uuid,
+    }
+}
+
+table! {
+    sso_group_role (group_id, role_id) {
+        created_at -> Timestamptz,
+        group_id -> Uuid,
+        role_id -> Uuid,
+    }
+}
+
 table! {
     sso_key (id) {
         created_at -> Timestamptz,
@@ -38,9 +106,40 @@ table! {
         #[sql_name = "type"]
         type_ -> Varchar,
         name -> Varchar,
-        value -> Varchar,
+        value -> Nullable<Varchar>,
         service_id -> Nullable<Uuid>,
         user_id -> Nullable<Uuid>,
+        scope -> Array<Varchar>,
+        value_prefix -> Varchar,
+        value_hash -> Nullable<Varchar>,
+        secret -> Nullable<Varchar>,
+        predecessor_id -> Nullable<Uuid>,
+        successor_id -> Nullable<Uuid>,
+        revoke_at -> Nullable<Timestamptz>,
+        last_used_at -> Nullable<Timestamptz>,
+        last_used_remote -> Nullable<Varchar>,
+        allow_remote -> Array<Varchar>,
+        organisation_id -> Nullable<Uuid>,
+    }
+}
+
+table! {
+    sso_organisation (id) {
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        id -> Uuid,
+        is_enabled -> Bool,
+        name -> Varchar,
+    }
+}
+
+table! {
+    sso_role (id) {
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        id -> Uuid,
+        service_id -> Uuid,
+        name -> Varchar,
     }
 }
 
@@ -57,6 +156,10 @@ table! {
         provider_local_url -> Nullable<Varchar>,
         provider_github_oauth2_url -> Nullable<Varchar>,
         provider_microsoft_oauth2_url -> Nullable<Varchar>,
+        allow_remote -> Array<Varchar>,
+        user_claims -> Array<Varchar>,
+        organisation_id -> Nullable<Uuid>,
+        user_require_email_verified -> Bool,
     }
 }
 
@@ -73,13 +176,113 @@ table! {
         password_allow_reset -> Bool,
         password_require_update -> Bool,
         password_hash -> Nullable<Varchar>,
+        last_login_at -> Nullable<Timestamptz>,
+        last_login_remote -> Nullable<Varchar>,
+        attributes -> Jsonb,
+        organisation_id -> Nullable<Uuid>,
+        email_verified_at -> Nullable<Timestamptz>,
+        email_pending -> Nullable<Varchar>,
+    }
+}
+
+table! {
+    sso_user_group (user_id, group_id) {
+        created_at -> Timestamptz,
+        user_id -> Uuid,
+        group_id -> Uuid,
+    }
+}
+
+table! {
+    sso_user_role (user_id, role_id) {
+        created_at -> Timestamptz,
+        user_id -> Uuid,
+        role_id -> Uuid,
+    }
+}
+
+table! {
+    sso_user_service (user_id, service_id) {
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        user_id -> Uuid,
+        service_id -> Uuid,
+        attributes -> Jsonb,
+        status -> Nullable<Varchar>,
+        invite_expires_at -> Nullable<Timestamptz>,
+    }
+}
+
+table! {
+    sso_webhook (id) {
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        id -> Uuid,
+        service_id -> Uuid,
+        is_enabled -> Bool,
+        name -> Varchar,
+        url -> Varchar,
+        secret -> Varchar,
+        audit_type -> Array<Varchar>,
+    }
+}
+
+table! {
+    sso_webhook_delivery (id) {
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        id -> Uuid,
+        webhook_id -> Uuid,
+        audit_id -> Uuid,
+        audit_type -> Varchar,
+        status -> Varchar,
+        attempts -> Int4,
+        next_attempt_at -> Timestamptz,
+        delivered_at -> Nullable<Timestamptz>,
+        status_code -> Nullable<Int2>,
+        error -> Nullable<Varchar>,
     }
 }
 
+joinable!(sso_audit -> sso_organisation (organisation_id));
 joinable!(sso_audit -> sso_service (service_id));
 joinable!(sso_audit -> sso_user (user_id));
+joinable!(sso_audit_retention -> sso_service (service_id));
 joinable!(sso_csrf -> sso_service (service_id));
+joinable!(sso_group -> sso_service (service_id));
+joinable!(sso_group_role -> sso_group (group_id));
+joinable!(sso_group_role -> sso_role (role_id));
+joinable!(sso_key -> sso_organisation (organisation_id));
 joinable!(sso_key -> sso_service (service_id));
 joinable!(sso_key -> sso_user (user_id));
+joinable!(sso_role -> sso_service (service_id));
+joinable!(sso_service -> sso_organisation (organisation_id));
+joinable!(sso_user -> sso_organisation (organisation_id));
+joinable!(sso_user_group -> sso_group (group_id));
+joinable!(sso_user_group -> sso_user (user_id));
+joinable!(sso_user_role -> sso_role (role_id));
+joinable!(sso_user_role -> sso_user (user_id));
+joinable!(sso_user_service -> sso_service (service_id));
+joinable!(sso_user_service -> sso_user (user_id));
+joinable!(sso_webhook -> sso_service (service_id));
+joinable!(sso_webhook_delivery -> sso_webhook (webhook_id));
 
-allow_tables_to_appear_in_same_query!(sso_audit, sso_csrf, sso_key, sso_service, sso_user,);
+allow_tables_to_appear_in_same_query!(
+    sso_audit,
+    sso_audit_chain_point,
+    sso_audit_export,
+    sso_audit_retention,
+    sso_csrf,
+    sso_group,
+    sso_group_role,
+    sso_key,
+    sso_organisation,
+    sso_role,
+    sso_service,
+    sso_user,
+    sso_user_group,
+    sso_user_role,
+    sso_user_service,
+    sso_webhook,
+    sso_webhook_delivery,
+);
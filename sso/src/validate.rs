@@ -26,6 +26,21 @@ pub fn url_opt(errors: &mut ValidationErrors, field: &'static str, value: Option
     }
 }
 
+pub fn url_http(errors: &mut ValidationErrors, field: &'static str, value: &str) {
+    let scheme_ok = url::Url::parse(value)
+        .map(|x| x.scheme() == "http" || x.scheme() == "https")
+        .unwrap_or(false);
+    if !validator::validate_url(value) || !scheme_ok {
+        errors.add(field, ValidationError::new("url_invalid"));
+    }
+}
+
+pub fn url_http_opt(errors: &mut ValidationErrors, field: &'static str, value: Option<&str>) {
+    if let Some(value) = value {
+        url_http(errors, field, value);
+    }
+}
+
 pub fn password(errors: &mut ValidationErrors, field: &'static str, value: &str) {
     if value.len() < MIN_USER_PASSWORD || value.len() > MAX_USER_PASSWORD {
         errors.add(field, ValidationError::new("password_invalid"));
@@ -112,6 +127,24 @@ pub fn audit_type_vec(errors: &mut ValidationErrors, field: &'static str, value:
     }
 }
 
+pub fn audit_data_filter_vec(
+    errors: &mut ValidationErrors,
+    field: &'static str,
+    value: &[pb::AuditDataFilter],
+) {
+    for v in value {
+        if v.path.is_empty()
+            || v.path.len() > MAX_AUDIT_DATA_PATH
+            || v.path
+                .iter()
+                .any(|x| x.is_empty() || x.len() > MAX_AUDIT_TYPE)
+            || v.value.is_none()
+        {
+            errors.add(field, ValidationError::new("audit_data_filter_invalid"));
+        }
+    }
+}
+
 pub fn limit(errors: &mut ValidationErrors, field: &'static str, value: i64) {
     if value < 0 {
         errors.add(field, ValidationError::new("limit_invalid"));
@@ -124,6 +157,14 @@ pub fn limit_opt(errors: &mut ValidationErrors, field: &'static str, value: Opti
     }
 }
 
+pub fn status_code_opt(errors: &mut ValidationErrors, field: &'static str, value: Option<u32>) {
+    if let Some(value) = value {
+        if value > 999 {
+            errors.add(field, ValidationError::new("status_code_invalid"));
+        }
+    }
+}
+
 pub fn uuid(errors: &mut ValidationErrors, field: &'static str, value: &str) {
     if let Err(_e) = Uuid::parse_str(value) {
         errors.add(field, ValidationError::new("uuid_invalid"));
@@ -173,6 +214,148 @@ pub fn key_type_vec(errors: &mut ValidationErrors, field: &'static str, value: &
     }
 }
 
+pub fn service_user_status(errors: &mut ValidationErrors, field: &'static str, value: i32) {
+    let x: Result<ServiceUserStatus, ()> = value.try_into();
+    if let Err(_e) = x {
+        errors.add(field, ValidationError::new("service_user_status_invalid"));
+    }
+}
+
+pub fn service_user_status_vec(errors: &mut ValidationErrors, field: &'static str, value: &[i32]) {
+    for v in value {
+        service_user_status(errors, field, *v);
+    }
+}
+
+pub fn audit_report_bucket(errors: &mut ValidationErrors, field: &'static str, value: i32) {
+    let x: Result<AuditReportBucket, ()> = value.try_into();
+    if let Err(_e) = x {
+        errors.add(field, ValidationError::new("audit_report_bucket_invalid"));
+    }
+}
+
+pub fn audit_report_group(errors: &mut ValidationErrors, field: &'static str, value: i32) {
+    let x: Result<AuditReportGroup, ()> = value.try_into();
+    if let Err(_e) = x {
+        errors.add(field, ValidationError::new("audit_report_group_invalid"));
+    }
+}
+
+pub fn audit_report_group_vec(errors: &mut ValidationErrors, field: &'static str, value: &[i32]) {
+    for v in value {
+        audit_report_group(errors, field, *v);
+    }
+}
+
+pub fn audit_report_range(errors: &mut ValidationErrors, field: &'static str, value: &AuditReport) {
+    if value.le < value.ge || value.buckets() > MAX_AUDIT_REPORT_BUCKETS {
+        errors.add(field, ValidationError::new("audit_report_range_invalid"));
+    }
+}
+
+pub fn webhook_delivery_status(errors: &mut ValidationErrors, field: &'static str, value: i32) {
+    let x: Result<WebhookDeliveryStatus, ()> = value.try_into();
+    if let Err(_e) = x {
+        errors.add(
+            field,
+            ValidationError::new("webhook_delivery_status_invalid"),
+        );
+    }
+}
+
+pub fn webhook_delivery_status_vec(
+    errors: &mut ValidationErrors,
+    field: &'static str,
+    value: &[i32],
+) {
+    for v in value {
+        webhook_delivery_status(errors, field, *v);
+    }
+}
+
+pub fn key_scope(errors: &mut ValidationErrors, field: &'static str, value: &str) {
+    if KeyScope::from_str(value).is_err() {
+        errors.add(field, ValidationError::new("key_scope_invalid"));
+    }
+}
+
+pub fn key_scope_vec(errors: &mut ValidationErrors, field: &'static str, value: &[String]) {
+    for v in value {
+        key_scope(errors, field, v);
+    }
+}
+
+pub fn cidr(errors: &mut ValidationErrors, field: &'static str, value: &str) {
+    if Cidr::from_str(value).is_err() {
+        errors.add(field, ValidationError::new("cidr_invalid"));
+    }
+}
+
+pub fn cidr_vec(errors: &mut ValidationErrors, field: &'static str, value: &[String]) {
+    for v in value {
+        cidr(errors, field, v);
+    }
+}
+
+pub fn cidr_vec_opt(errors: &mut ValidationErrors, field: &'static str, value: Option<&[String]>) {
+    if let Some(value) = value {
+        cidr_vec(errors, field, value);
+    }
+}
+
+/// Role names are sent as a comma separated header value, names are restricted
+/// to alphanumeric characters and `-`, `_`, `.` and `:`.
+pub fn role_name(errors: &mut ValidationErrors, field: &'static str, value: &str) {
+    let valid_char = |c: char| c.is_ascii_alphanumeric() || "-_.:".contains(c);
+    if value.is_empty() || value.len() > MAX_ROLE_NAME || !value.chars().all(valid_char) {
+        errors.add(field, ValidationError::new("role_name_invalid"));
+    }
+}
+
+pub fn role_name_opt(errors: &mut ValidationErrors, field: &'static str, value: Option<&str>) {
+    if let Some(value) = value {
+        role_name(errors, field, value);
+    }
+}
+
+pub fn user_claim(errors: &mut ValidationErrors, field: &'static str, value: &str) {
+    if value.is_empty() || value.len() > MAX_NAME {
+        errors.add(field, ValidationError::new("user_claim_invalid"));
+    }
+}
+
+pub fn user_claim_vec(errors: &mut ValidationErrors, field: &'static str, value: &[String]) {
+    for v in value {
+        user_claim(errors, field, v);
+    }
+}
+
+pub fn user_claim_vec_opt(
+    errors: &mut ValidationErrors,
+    field: &'static str,
+    value: Option<&[String]>,
+) {
+    if let Some(value) = value {
+        user_claim_vec(errors, field, value);
+    }
+}
+
+pub fn key_grace_period_s(errors: &mut ValidationErrors, field: &'static str, value: i64) {
+    if value < 0 || value > MAX_KEY_GRACE_PERIOD_S {
+        errors.add(field, ValidationError::new("key_grace_period_s_invalid"));
+    }
+}
+
+pub fn key_grace_period_s_opt(
+    errors: &mut ValidationErrors,
+    field: &'static str,
+    value: Option<i64>,
+) {
+    if let Some(value) = value {
+        key_grace_period_s(errors, field, value);
+    }
+}
+
 pub fn text(errors: &mut ValidationErrors, field: &'static str, value: &str) {
     if value.len() > MAX_TEXT {
         errors.add(field, ValidationError::new("text_invalid"));
@@ -185,6 +368,12 @@ pub fn text_opt(errors: &mut ValidationErrors, field: &'static str, value: Optio
     }
 }
 
+pub fn text_vec(errors: &mut ValidationErrors, field: &'static str, value: &[String]) {
+    for v in value {
+        text(errors, field, v);
+    }
+}
+
 pub fn totp(errors: &mut ValidationErrors, field: &'static str, value: &str) {
     if value.is_empty() || value.len() > MAX_TOTP {
         errors.add(field, ValidationError::new("totp_invalid"));
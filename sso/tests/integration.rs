@@ -8,13 +8,17 @@ use support::*;
 audit_integration_test!();
 auth_csrf_integration_test!();
 auth_key_integration_test!();
+role_integration_test!();
 auth_local_integration_test!();
 auth_token_integration_test!();
 auth_totp_integration_test!();
+group_integration_test!();
 guide_integration_test!();
 key_integration_test!();
+organisation_integration_test!();
 service_integration_test!();
 user_integration_test!();
+webhook_integration_test!();
 
 #[test]
 #[ignore]
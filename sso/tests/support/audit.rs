@@ -204,6 +204,172 @@ macro_rules! audit_integration_test {
             assert_eq!(r2_3, r1_3);
         }
 
+        #[test]
+        #[ignore]
+        fn audit_list_bad_request_invalid_data_filter() {
+            let mut client = client_create(None);
+
+            let mut body = pb::AuditListRequest::type_subject(Vec::new(), Vec::new());
+            body.data = vec![pb::AuditDataFilter {
+                path: Vec::new(),
+                value: None,
+            }];
+            let res = client.audit_list(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::InvalidArgument);
+        }
+
+        #[test]
+        #[ignore]
+        fn audit_list_data_filter_ok() {
+            let mut client = client_create(None);
+            let (_service, service_key) = service_key_create(&mut client);
+            let mut client = client_create(Some(&service_key.value));
+            let type_ = Uuid::new_v4().to_simple().to_string();
+
+            let a1 = client
+                .audit_create(
+                    pb::AuditCreateRequest::new(type_.clone())
+                        .data(pb::value_to_struct_opt(json!({ "a": { "reason": "x" } }))),
+                )
+                .unwrap()
+                .into_inner()
+                .data
+                .unwrap();
+            client
+                .audit_create(
+                    pb::AuditCreateRequest::new(type_.clone())
+                        .data(pb::value_to_struct_opt(json!({ "a": { "reason": "y" } }))),
+                )
+                .unwrap();
+
+            let mut body = pb::AuditListRequest::type_subject(vec![type_], Vec::new());
+            body.data = vec![AuditDataFilter::new(
+                vec!["a".to_owned(), "reason".to_owned()],
+                json!("x"),
+            )
+            .into()];
+            let res = client.audit_list(body).unwrap().into_inner();
+            assert_eq!(res.data.len(), 1);
+            assert_eq!(res.data[0].id, a1.id);
+        }
+
+        #[test]
+        #[ignore]
+        fn audit_list_key_status_code_remote_filter_ok() {
+            let mut client = client_create(None);
+            let (_service, service_key) = service_key_create(&mut client);
+            let key_id = service_key.key.as_ref().unwrap().id.clone();
+            let mut client = client_create(Some(&service_key.value));
+            let type_ = Uuid::new_v4().to_simple().to_string();
+
+            let a1 = client
+                .audit_create(pb::AuditCreateRequest::new(type_.clone()))
+                .unwrap()
+                .into_inner()
+                .data
+                .unwrap();
+
+            let mut body = pb::AuditListRequest::type_subject(vec![type_.clone()], Vec::new());
+            body.key_id = vec![key_id];
+            body.remote_cidr = vec!["0.0.0.0/0".to_owned(), "::/0".to_owned()];
+            let res = client.audit_list(body).unwrap().into_inner();
+            assert_eq!(res.data.len(), 1);
+            assert_eq!(res.data[0].id, a1.id);
+
+            let mut body = pb::AuditListRequest::type_subject(vec![type_.clone()], Vec::new());
+            body.key_id = vec![UUID_NIL.to_owned()];
+            let res = client.audit_list(body).unwrap().into_inner();
+            assert_eq!(res.data.len(), 0);
+
+            let mut body = pb::AuditListRequest::type_subject(vec![type_.clone()], Vec::new());
+            body.status_code_ge = Some(400);
+            let res = client.audit_list(body).unwrap().into_inner();
+            assert_eq!(res.data.len(), 0);
+
+            let mut body = pb::AuditListRequest::type_subject(vec![type_], Vec::new());
+            body.remote = Some("%".to_owned());
+            let res = client.audit_list(body).unwrap().into_inner();
+            assert_eq!(res.data.len(), 0);
+        }
+
+        #[test]
+        #[ignore]
+        fn audit_list_enrichment_filter_ok() {
+            let mut client = client_create(None);
+            let (_service, service_key) = service_key_create(&mut client);
+            let mut client = client_create(Some(&service_key.value));
+            let type_ = Uuid::new_v4().to_simple().to_string();
+
+            let a1 = client
+                .audit_create(pb::AuditCreateRequest::new(type_.clone()))
+                .unwrap()
+                .into_inner()
+                .data
+                .unwrap();
+            assert!(a1.enrichment.is_some());
+
+            let mut body = pb::AuditListRequest::type_subject(vec![type_.clone()], Vec::new());
+            body.remote_country = vec!["ZZ".to_owned()];
+            let res = client.audit_list(body).unwrap().into_inner();
+            assert_eq!(res.data.len(), 0);
+
+            let mut body = pb::AuditListRequest::type_subject(vec![type_.clone()], Vec::new());
+            body.remote_asn = vec![0];
+            let res = client.audit_list(body).unwrap().into_inner();
+            assert_eq!(res.data.len(), 0);
+
+            let mut body = pb::AuditListRequest::type_subject(vec![type_], Vec::new());
+            body.user_agent_device = vec!["ZZ".to_owned()];
+            let res = client.audit_list(body).unwrap().into_inner();
+            assert_eq!(res.data.len(), 0);
+        }
+
+        #[test]
+        #[ignore]
+        fn audit_report_bad_request_invalid_range() {
+            let mut client = client_create(None);
+
+            let mut body = pb::AuditReportRequest::new(pb::AuditReportBucket::Minute);
+            body.ge = Some(prost_types::Timestamp {
+                seconds: 0,
+                nanos: 0,
+            });
+            let res = client.audit_report(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::InvalidArgument);
+        }
+
+        #[test]
+        #[ignore]
+        fn audit_report_ok() {
+            let mut client = client_create(None);
+            let (_service1, service_key1) = service_key_create(&mut client);
+            let (_service2, service_key2) = service_key_create(&mut client);
+            let mut client = client_create(Some(&service_key1.value));
+            let type_ = Uuid::new_v4().to_simple().to_string();
+
+            for _ in 0..3 {
+                client
+                    .audit_create(pb::AuditCreateRequest::new(type_.clone()))
+                    .unwrap();
+            }
+
+            let body = pb::AuditReportRequest::new(pb::AuditReportBucket::Hour)
+                .group(vec![pb::AuditReportGroup::Type])
+                .audit_type(vec![type_.clone()]);
+            let res = client.audit_report(body.clone()).unwrap().into_inner();
+            let count: i64 = res.data.iter().map(|x| x.count).sum();
+            assert_eq!(count, 3);
+            for row in res.data.iter() {
+                assert_eq!(row.r#type.as_ref(), Some(&type_));
+                assert_eq!(row.status_code, None);
+            }
+
+            // Report is masked by service.
+            let mut client = client_create(Some(&service_key2.value));
+            let res = client.audit_report(body).unwrap().into_inner();
+            assert_eq!(res.data.len(), 0);
+        }
+
         #[test]
         #[ignore]
         fn audit_read_not_found_does_not_exist() {
@@ -401,5 +567,63 @@ macro_rules! audit_integration_test {
             assert_eq!(key1, "foo");
             assert_eq!(key2, "foo");
         }
+
+        #[test]
+        #[ignore]
+        fn audit_watch_not_found_offset_does_not_exist() {
+            let mut client = client_create(None);
+            let (_service, service_key) = service_key_create(&mut client);
+            let mut client = client_create(Some(&service_key.value));
+
+            let res = client
+                .audit_watch(pb::AuditWatchRequest {
+                    offset_id: Some(UUID_NIL.to_owned()),
+                    ..Default::default()
+                })
+                .unwrap_err();
+            assert_eq!(res.code(), tonic::Code::NotFound);
+        }
+
+        #[test]
+        #[ignore]
+        fn audit_watch_offset_ok() {
+            let mut client = client_create(None);
+            let (_service, service_key) = service_key_create(&mut client);
+            let mut client = client_create(Some(&service_key.value));
+            let type_ = "test_watch".to_owned();
+
+            let a1 = client
+                .audit_create(pb::AuditCreateRequest::new(type_.clone()))
+                .unwrap()
+                .into_inner()
+                .data
+                .unwrap();
+            let a2 = client
+                .audit_create(pb::AuditCreateRequest::new(type_.clone()))
+                .unwrap()
+                .into_inner()
+                .data
+                .unwrap();
+
+            let mut stream = client
+                .audit_watch(pb::AuditWatchRequest {
+                    offset_id: Some(a1.id.clone()),
+                    r#type: vec![type_.clone()],
+                    ..Default::default()
+                })
+                .unwrap()
+                .into_inner();
+            let r2 = client.audit_watch_message(&mut stream).unwrap().unwrap();
+            assert_eq!(r2.id, a2.id);
+
+            let a3 = client
+                .audit_create(pb::AuditCreateRequest::new(type_.clone()))
+                .unwrap()
+                .into_inner()
+                .data
+                .unwrap();
+            let r3 = client.audit_watch_message(&mut stream).unwrap().unwrap();
+            assert_eq!(r3.id, a3.id);
+        }
     };
 }
@@ -264,6 +264,62 @@ macro_rules! auth_local_integration_test {
             assert_eq!(res.user.unwrap().id, user.id);
         }
 
+        #[test]
+        #[ignore]
+        fn auth_local_login_last_login_ok() {
+            let mut client = client_create(None);
+            let (service, service_key) = service_key_create(&mut client);
+            let user_email = email_create();
+
+            let mut client = client_create(Some(&service_key.value));
+            let user = user_create_with_password(
+                &mut client,
+                true,
+                USER_NAME,
+                &user_email,
+                false,
+                false,
+                USER_PASSWORD,
+            );
+            assert!(user.last_login_at.is_none());
+            let (user, _user_key) =
+                user_key_create(&mut client, KEY_NAME, KeyType::Token, service.id, user);
+
+            let body = pb::AuthLoginRequest::new(&user_email, USER_PASSWORD);
+            client.auth_local_login(body).unwrap();
+
+            let user = client
+                .user_read(pb::UserReadRequest { id: user.id })
+                .unwrap()
+                .into_inner()
+                .data
+                .unwrap();
+            assert!(user.last_login_at.is_some());
+        }
+
+        #[test]
+        #[ignore]
+        fn auth_local_login_revoke_unauthorised() {
+            let mut client = client_create(Some(INVALID_KEY));
+            let body = pb::AuthTokenRequest::new(INVALID_KEY, None);
+            let res = client.auth_local_login_revoke(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::Unauthenticated);
+            assert_eq!(res.message(), ERR_REDACTED);
+        }
+
+        #[test]
+        #[ignore]
+        fn auth_local_login_revoke_bad_request_invalid_token() {
+            let mut client = client_create(None);
+            let (_service, service_key) = service_key_create(&mut client);
+
+            let mut client = client_create(Some(&service_key.value));
+            let body = pb::AuthTokenRequest::new(INVALID_KEY, None);
+            let res = client.auth_local_login_revoke(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::InvalidArgument);
+            assert_eq!(res.message(), ERR_REDACTED);
+        }
+
         #[test]
         #[ignore]
         fn auth_local_register_unauthorised() {
@@ -578,6 +634,57 @@ macro_rules! auth_local_integration_test {
             assert_eq!(res.message(), ERR_REDACTED);
         }
 
+        #[test]
+        #[ignore]
+        fn auth_local_update_email_bad_request_new_email_exists() {
+            let mut client = client_create(None);
+            let (service, service_key) = service_key_create(&mut client);
+            let user_email = email_create();
+            let other_email = email_create();
+
+            let mut client = client_create(Some(&service_key.value));
+            let user = user_create_with_password(
+                &mut client,
+                true,
+                USER_NAME,
+                &user_email,
+                false,
+                false,
+                USER_PASSWORD,
+            );
+            let (user, _user_key) =
+                user_key_create(&mut client, KEY_NAME, KeyType::Token, service.id, user);
+            user_create(&mut client, true, USER_NAME, &other_email);
+
+            let body = pb::AuthUpdateEmailRequest {
+                email: user.email,
+                password: USER_PASSWORD.to_owned(),
+                new_email: other_email,
+            };
+            let res = client.auth_local_update_email(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::InvalidArgument);
+            assert_eq!(res.message(), ERR_REDACTED);
+
+            let res = client
+                .user_read(pb::UserReadRequest { id: user.id })
+                .unwrap()
+                .into_inner();
+            assert!(res.data.unwrap().email_pending.is_none());
+        }
+
+        #[test]
+        #[ignore]
+        fn auth_local_update_email_confirm_bad_request_invalid_token() {
+            let mut client = client_create(None);
+            let (_service, service_key) = service_key_create(&mut client);
+
+            let mut client = client_create(Some(&service_key.value));
+            let body = pb::AuthTokenRequest::new(INVALID_KEY, None);
+            let res = client.auth_local_update_email_confirm(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::InvalidArgument);
+            assert_eq!(res.message(), ERR_REDACTED);
+        }
+
         #[test]
         #[ignore]
         fn auth_local_update_password_unauthorised() {
@@ -608,5 +715,96 @@ macro_rules! auth_local_integration_test {
             assert_eq!(res.code(), tonic::Code::InvalidArgument);
             assert_eq!(res.message(), ERR_VALIDATION);
         }
+
+        #[test]
+        #[ignore]
+        fn auth_local_login_forbidden_user_email_not_verified() {
+            let mut client = client_create(None);
+            let (service, service_key) = service_key_create(&mut client);
+            let user_email = email_create();
+
+            let mut client = client_create(Some(&service_key.value));
+            let mut req = pb::ServiceUpdateRequest::default();
+            req.id = service.id.clone();
+            req.user_require_email_verified = Some(true);
+            client.service_update(req).unwrap();
+
+            let user = user_create_with_password(
+                &mut client,
+                true,
+                USER_NAME,
+                &user_email,
+                false,
+                false,
+                USER_PASSWORD,
+            );
+            assert!(user.email_verified_at.is_none());
+            let _user_key = user_key_create(
+                &mut client,
+                KEY_NAME,
+                KeyType::Token,
+                service.id.clone(),
+                user,
+            );
+
+            let body = pb::AuthLoginRequest::new(&user_email, USER_PASSWORD);
+            let res = client.auth_local_login(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::PermissionDenied);
+            assert_eq!(res.message(), ERR_REDACTED);
+        }
+
+        #[test]
+        #[ignore]
+        fn auth_local_verify_email_unauthorised() {
+            let mut client = client_create(Some(INVALID_KEY));
+            let user_email = email_create();
+
+            let body = pb::AuthVerifyEmailRequest::new(&user_email);
+            let res = client.auth_local_verify_email(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::Unauthenticated);
+            assert_eq!(res.message(), ERR_REDACTED);
+        }
+
+        #[test]
+        #[ignore]
+        fn auth_local_verify_email_unknown_email_ok() {
+            let mut client = client_create(None);
+            let (_service, service_key) = service_key_create(&mut client);
+            let user_email = email_create();
+
+            let mut client = client_create(Some(&service_key.value));
+            let body = pb::AuthVerifyEmailRequest::new(&user_email);
+            client.auth_local_verify_email(body).unwrap();
+        }
+
+        #[test]
+        #[ignore]
+        fn auth_local_verify_email_confirm_bad_request_invalid_token() {
+            let mut client = client_create(None);
+            let (_service, service_key) = service_key_create(&mut client);
+
+            let mut client = client_create(Some(&service_key.value));
+            let body = pb::AuthTokenRequest::new(INVALID_KEY, None);
+            let res = client.auth_local_verify_email_confirm(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::InvalidArgument);
+            assert_eq!(res.message(), ERR_REDACTED);
+        }
+
+        #[test]
+        #[ignore]
+        fn auth_local_invite_confirm_bad_request_invalid_token() {
+            let mut client = client_create(None);
+            let (_service, service_key) = service_key_create(&mut client);
+
+            let mut client = client_create(Some(&service_key.value));
+            let body = pb::AuthRegisterConfirmRequest {
+                token: INVALID_KEY.to_owned(),
+                password: None,
+                password_allow_reset: None,
+            };
+            let res = client.auth_local_invite_confirm(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::InvalidArgument);
+            assert_eq!(res.message(), ERR_REDACTED);
+        }
     };
 }
@@ -272,5 +272,134 @@ macro_rules! auth_token_integration_test {
             assert_eq!(res.code(), tonic::Code::InvalidArgument);
             assert_eq!(res.message(), ERR_REDACTED);
         }
+
+        #[test]
+        #[ignore]
+        fn auth_impersonate_unauthorised_service_key() {
+            let mut client = client_create(None);
+            let (service, service_key) = service_key_create(&mut client);
+            let user_email = email_create();
+
+            let mut client = client_create(Some(&service_key.value));
+            let user = user_create(&mut client, true, USER_NAME, &user_email);
+            let body = pb::AuthImpersonateRequest::new(user.id, service.id);
+            let res = client.auth_impersonate(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::Unauthenticated);
+        }
+
+        #[test]
+        #[ignore]
+        fn auth_impersonate_not_found_organisation_mask() {
+            let mut client = client_create(None);
+            let (service, service_key) = service_key_create(&mut client);
+            let user_email = email_create();
+
+            let mut service_client = client_create(Some(&service_key.value));
+            let user = user_create(&mut service_client, true, USER_NAME, &user_email);
+            let (user, _user_key) = user_key_create(
+                &mut service_client,
+                KEY_NAME,
+                KeyType::Token,
+                service.id.clone(),
+                user,
+            );
+
+            let mut client = client_organisation_create();
+            let body = pb::AuthImpersonateRequest::new(user.id, service.id);
+            let res = client.auth_impersonate(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::NotFound);
+        }
+
+        #[test]
+        #[ignore]
+        fn auth_impersonate_ok() {
+            let mut client = client_create(None);
+            let (service, service_key) = service_key_create(&mut client);
+            let user_email = email_create();
+
+            let mut service_client = client_create(Some(&service_key.value));
+            let user = user_create_with_password(
+                &mut service_client,
+                true,
+                USER_NAME,
+                &user_email,
+                false,
+                false,
+                USER_PASSWORD,
+            );
+            let (user, _user_key) = user_key_create(
+                &mut service_client,
+                KEY_NAME,
+                KeyType::Token,
+                service.id.clone(),
+                user,
+            );
+
+            let body = pb::AuthImpersonateRequest::new(user.id.clone(), service.id.clone());
+            let res = client.auth_impersonate(body).unwrap().into_inner();
+            assert_eq!(res.user.unwrap().id, user.id);
+            let access_token = res.access.unwrap().token;
+
+            let body = pb::AuthTokenRequest::new(&access_token, None);
+            let res = service_client.auth_token_verify(body).unwrap().into_inner();
+            assert_eq!(res.user.unwrap().id, user.id);
+            let actor_key_id = res.actor_key_id.unwrap();
+
+            let mut body = pb::AuditListRequest::type_subject(
+                vec![AuditType::AuthTokenVerify.to_string()],
+                Vec::new(),
+            );
+            body.user_id = vec![user.id.clone()];
+            let audit_list = client.audit_list(body).unwrap().into_inner().data;
+            assert_eq!(audit_list.len(), 1);
+            assert_eq!(audit_list[0].actor_key_id.as_ref(), Some(&actor_key_id));
+
+            let mut user_client = client_user_create(&service_key.value, &access_token);
+            let body = pb::AuthUpdatePasswordRequest {
+                email: user_email.clone(),
+                password: String::from(USER_PASSWORD),
+                new_password: String::from(USER_PASSWORD),
+            };
+            let res = user_client.auth_local_update_password(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::PermissionDenied);
+        }
+
+        #[test]
+        #[ignore]
+        fn auth_impersonate_forbidden_user_token() {
+            let mut client = client_create(None);
+            let (service, service_key) = service_key_create(&mut client);
+            let user_email = email_create();
+
+            let mut service_client = client_create(Some(&service_key.value));
+            let user = user_create(&mut service_client, true, USER_NAME, &user_email);
+            let (user, _user_key) = user_key_create(
+                &mut service_client,
+                KEY_NAME,
+                KeyType::Token,
+                service.id.clone(),
+                user,
+            );
+
+            let body = pb::AuthImpersonateRequest::new(user.id.clone(), service.id.clone());
+            let res = client.auth_impersonate(body).unwrap().into_inner();
+            let access_token = res.access.unwrap().token;
+
+            let mut user_client = client_user_create(&service_key.value, &access_token);
+            let body = pb::KeyCreateRequest::with_user_id(true, KeyType::Key, KEY_NAME, user.id);
+            let res = user_client.key_create(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::PermissionDenied);
+
+            let body = pb::AuthTokenRequest::new(&access_token, None);
+            let res = service_client.auth_token_revoke(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::InvalidArgument);
+
+            let body = pb::AuthTokenRequest::new(&access_token, None);
+            let res = service_client.auth_token_refresh(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::InvalidArgument);
+
+            let body = pb::AuthTokenRequest::new(&access_token, None);
+            service_client.auth_token_verify(body).unwrap();
+        }
     };
 }
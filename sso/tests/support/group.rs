@@ -0,0 +1,177 @@
+#[macro_export]
+macro_rules! group_integration_test {
+    () => {
+        #[test]
+        #[ignore]
+        fn group_create_bad_request_duplicate_name() {
+            let mut client = client_create(None);
+            let (service, _service_key) = service_key_create(&mut client);
+            let name = Uuid::new_v4().to_string();
+
+            let body = pb::GroupCreateRequest::new(service.id.clone(), true, &name);
+            client.group_create(body).unwrap();
+            let body = pb::GroupCreateRequest::new(service.id, true, &name);
+            let res = client.group_create(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::InvalidArgument);
+        }
+
+        #[test]
+        #[ignore]
+        fn group_create_duplicate_name_other_service_ok() {
+            let mut client = client_create(None);
+            let (service1, _service1_key) = service_key_create(&mut client);
+            let (service2, _service2_key) = service_key_create(&mut client);
+            let name = Uuid::new_v4().to_string();
+
+            let body = pb::GroupCreateRequest::new(service1.id, true, &name);
+            client.group_create(body).unwrap();
+            let body = pb::GroupCreateRequest::new(service2.id, true, &name);
+            client.group_create(body).unwrap();
+        }
+
+        #[test]
+        #[ignore]
+        fn group_read_not_found_other_service() {
+            let mut client = client_create(None);
+            let (service1, service1_key) = service_key_create(&mut client);
+            let (service2, service2_key) = service_key_create(&mut client);
+
+            let mut client1 = client_create(Some(&service1_key.value));
+            let body =
+                pb::GroupCreateRequest::new(service1.id.clone(), true, Uuid::new_v4().to_string());
+            let group = client1
+                .group_create(body)
+                .unwrap()
+                .into_inner()
+                .data
+                .unwrap();
+
+            let mut client2 = client_create(Some(&service2_key.value));
+            let body = pb::GroupCreateRequest::new(service1.id, true, Uuid::new_v4().to_string());
+            let res = client2.group_create(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::NotFound);
+
+            let body = pb::GroupReadRequest {
+                id: group.id.clone(),
+            };
+            let res = client2.group_read(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::NotFound);
+
+            let res = client2
+                .group_list(pb::GroupListRequest::default())
+                .unwrap()
+                .into_inner();
+            assert!(res.data.iter().all(|x| x.service_id == service2.id));
+
+            let body = pb::RoleCreateRequest::new(service2.id, "member");
+            let role = client2
+                .role_create(body)
+                .unwrap()
+                .into_inner()
+                .data
+                .unwrap();
+            let body = pb::GroupRoleRequest::new(group.id, role.id);
+            let res = client.group_role_create(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::NotFound);
+        }
+
+        #[test]
+        #[ignore]
+        fn group_update_bad_request_parent_cycle() {
+            let mut client = client_create(None);
+            let (service, _service_key) = service_key_create(&mut client);
+
+            let body =
+                pb::GroupCreateRequest::new(service.id.clone(), true, Uuid::new_v4().to_string());
+            let g1 = client
+                .group_create(body)
+                .unwrap()
+                .into_inner()
+                .data
+                .unwrap();
+            let body = pb::GroupCreateRequest::new(service.id, true, Uuid::new_v4().to_string())
+                .parent_id(g1.id.clone());
+            let g2 = client
+                .group_create(body)
+                .unwrap()
+                .into_inner()
+                .data
+                .unwrap();
+            assert_eq!(g2.parent_id.unwrap(), g1.id);
+
+            let body = pb::GroupUpdateRequest {
+                id: g1.id,
+                is_enabled: None,
+                name: None,
+                parent: Some(pb::GroupParent { id: Some(g2.id) }),
+            };
+            let res = client.group_update(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::InvalidArgument);
+        }
+
+        #[test]
+        #[ignore]
+        fn group_auth_key_verify_nested_ok() {
+            let mut client = client_create(None);
+            let (service, service_key) = service_key_create(&mut client);
+            let user_email = email_create();
+
+            let mut client = client_create(Some(&service_key.value));
+            let user = user_create(&mut client, true, USER_NAME, &user_email);
+            let user_id = user.id.clone();
+            let (_user, user_key) = user_key_create(
+                &mut client,
+                KEY_NAME,
+                KeyType::Key,
+                service.id.clone(),
+                user,
+            );
+
+            let body = pb::RoleCreateRequest::new(service.id.clone(), "member");
+            let role = client.role_create(body).unwrap().into_inner().data.unwrap();
+
+            let name1 = format!("a-{}", Uuid::new_v4());
+            let body = pb::GroupCreateRequest::new(service.id.clone(), true, &name1);
+            let g1 = client
+                .group_create(body)
+                .unwrap()
+                .into_inner()
+                .data
+                .unwrap();
+            let name2 = format!("b-{}", Uuid::new_v4());
+            let body =
+                pb::GroupCreateRequest::new(service.id, true, &name2).parent_id(g1.id.clone());
+            let g2 = client
+                .group_create(body)
+                .unwrap()
+                .into_inner()
+                .data
+                .unwrap();
+
+            client
+                .group_role_create(pb::GroupRoleRequest::new(g1.id.clone(), role.id))
+                .unwrap();
+            client
+                .group_user_create(pb::GroupUserRequest::new(g2.id, user_id))
+                .unwrap();
+
+            let body = pb::AuthKeyRequest::new(&user_key.value, None);
+            let res = client.auth_key_verify(body).unwrap().into_inner();
+            assert_eq!(res.groups, vec![name1, name2]);
+            assert_eq!(res.roles, vec!["member".to_owned()]);
+
+            let body = pb::GroupUpdateRequest {
+                id: g1.id,
+                is_enabled: Some(false),
+                name: None,
+                parent: None,
+            };
+            client.group_update(body).unwrap();
+
+            let body = pb::AuthKeyRequest::new(&user_key.value, None);
+            let res = client.auth_key_verify(body).unwrap().into_inner();
+            assert_eq!(res.groups.len(), 1);
+            assert!(res.roles.is_empty());
+        }
+    };
+}
@@ -288,5 +288,289 @@ macro_rules! key_integration_test {
                 .unwrap_err();
             assert_eq!(res.code(), tonic::Code::NotFound);
         }
+
+        #[test]
+        #[ignore]
+        fn key_create_bad_request_invalid_scope() {
+            let mut client = client_create(None);
+            let (service, _service_key) = service_key_create(&mut client);
+            let mut body =
+                pb::KeyCreateRequest::with_service_id(true, KeyType::Key, KEY_NAME, service.id);
+            body.scope = vec!["user:invalid".to_owned()];
+            let res = client.key_create(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::InvalidArgument);
+        }
+
+        #[test]
+        #[ignore]
+        fn key_scope_forbidden() {
+            let mut client = client_create(None);
+            let (service, _service_key) = service_key_create(&mut client);
+            let body =
+                pb::KeyCreateRequest::with_service_id(true, KeyType::Key, KEY_NAME, service.id)
+                    .scope(vec![KeyScope::UserRead]);
+            let service_key = client.key_create(body).unwrap().into_inner().data.unwrap();
+            let key = service_key.key.as_ref().unwrap();
+            assert_eq!(key.scope, vec!["user:read".to_owned()]);
+
+            let mut client = client_create(Some(&service_key.value));
+            client.user_list(pb::UserListRequest::limit(1)).unwrap();
+            let body = pb::UserCreateRequest::new(true, USER_NAME, email_create());
+            let res = client.user_create(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::PermissionDenied);
+        }
+
+        #[test]
+        #[ignore]
+        fn key_allow_remote_forbidden() {
+            let mut client = client_create(None);
+            let (service, _service_key) = service_key_create(&mut client);
+            let body =
+                pb::KeyCreateRequest::with_service_id(true, KeyType::Key, KEY_NAME, service.id)
+                    .allow_remote(vec!["192.0.2.0/24".parse().unwrap()]);
+            let service_key = client.key_create(body).unwrap().into_inner().data.unwrap();
+            let key = service_key.key.as_ref().unwrap();
+            assert_eq!(key.allow_remote, vec!["192.0.2.0/24".to_owned()]);
+
+            let mut client = client_create(Some(&service_key.value));
+            let res = client.user_list(pb::UserListRequest::limit(1)).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::PermissionDenied);
+        }
+
+        #[test]
+        #[ignore]
+        fn key_rotate_bad_request_invalid_grace_period() {
+            let mut client = client_create(None);
+            let (_service, service_key) = service_key_create(&mut client);
+            let body = pb::KeyRotateRequest::new(service_key.key.unwrap().id, Some(-1));
+            let res = client.key_rotate(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::InvalidArgument);
+        }
+
+        #[test]
+        #[ignore]
+        fn key_rotate_bad_request_service_key() {
+            let mut client = client_create(None);
+            let (_service, service_key) = service_key_create(&mut client);
+            let service_key_id = service_key.key.unwrap().id;
+
+            let mut client = client_create(Some(&service_key.value));
+            let body = pb::KeyRotateRequest::new(service_key_id, None);
+            let res = client.key_rotate(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::InvalidArgument);
+        }
+
+        #[test]
+        #[ignore]
+        fn key_rotate_bad_request_already_rotated() {
+            let mut client = client_create(None);
+            let (_service, service_key) = service_key_create(&mut client);
+            let service_key_id = service_key.key.unwrap().id;
+
+            let body = pb::KeyRotateRequest::new(service_key_id.clone(), None);
+            client.key_rotate(body).unwrap();
+            let body = pb::KeyRotateRequest::new(service_key_id, None);
+            let res = client.key_rotate(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::InvalidArgument);
+        }
+
+        #[test]
+        #[ignore]
+        fn key_rotate_forbidden_scoped_root_key() {
+            let mut client = client_create(None);
+            let (_service, service_key) = service_key_create(&mut client);
+
+            let mut client = client_scoped_create();
+            let body = pb::KeyRotateRequest::new(service_key.key.unwrap().id, None);
+            let res = client.key_rotate(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::PermissionDenied);
+        }
+
+        #[test]
+        #[ignore]
+        fn key_rotate_service_key_ok() {
+            let mut client = client_create(None);
+            let (_service, service_key) = service_key_create(&mut client);
+            let service_key_id = service_key.key.unwrap().id;
+
+            let body = pb::KeyRotateRequest::new(service_key_id.clone(), None);
+            let successor = client.key_rotate(body).unwrap().into_inner().data.unwrap();
+            let successor_key = successor.key.unwrap();
+            assert_eq!(successor_key.predecessor_id.unwrap(), service_key_id);
+
+            let predecessor_key = client
+                .key_read(pb::KeyReadRequest {
+                    id: service_key_id,
+                    user_id: None,
+                })
+                .unwrap()
+                .into_inner()
+                .data
+                .unwrap();
+            assert_eq!(predecessor_key.successor_id.unwrap(), successor_key.id);
+            assert!(predecessor_key.revoke_at.is_some());
+            assert!(!predecessor_key.is_revoked);
+
+            let mut client = client_create(Some(&service_key.value));
+            client.key_list(pb::KeyListRequest::limit(1)).unwrap();
+            let mut client = client_create(Some(&successor.value));
+            client.key_list(pb::KeyListRequest::limit(1)).unwrap();
+        }
+
+        #[test]
+        #[ignore]
+        fn key_rotate_service_key_grace_period_expired() {
+            let mut client = client_create(None);
+            let (_service, service_key) = service_key_create(&mut client);
+            let service_key_id = service_key.key.unwrap().id;
+
+            let body = pb::KeyRotateRequest::new(service_key_id, Some(0));
+            let successor = client.key_rotate(body).unwrap().into_inner().data.unwrap();
+
+            let mut client = client_create(Some(&service_key.value));
+            let res = client.key_list(pb::KeyListRequest::limit(1)).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::Unauthenticated);
+            let mut client = client_create(Some(&successor.value));
+            client.key_list(pb::KeyListRequest::limit(1)).unwrap();
+        }
+
+        #[test]
+        #[ignore]
+        fn key_rotate_service_key_predecessor_unauthorised_after_revoke_at() {
+            let mut client = client_create(None);
+            let (_service, service_key) = service_key_create(&mut client);
+            let service_key_id = service_key.key.unwrap().id;
+
+            let body = pb::KeyRotateRequest::new(service_key_id, Some(1));
+            client.key_rotate(body).unwrap();
+
+            let mut client = client_create(Some(&service_key.value));
+            client.key_list(pb::KeyListRequest::limit(1)).unwrap();
+            std::thread::sleep(std::time::Duration::from_secs(2));
+            let res = client.key_list(pb::KeyListRequest::limit(1)).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::Unauthenticated);
+        }
+
+        #[test]
+        #[ignore]
+        fn key_rotate_user_token_key_ok() {
+            let mut client = client_create(None);
+            let (service, service_key) = service_key_create(&mut client);
+            let user_email = email_create();
+
+            let mut client = client_create(Some(&service_key.value));
+            let user = user_create_with_password(
+                &mut client,
+                true,
+                USER_NAME,
+                &user_email,
+                false,
+                false,
+                USER_PASSWORD,
+            );
+            let (user, user_key) =
+                user_key_create(&mut client, KEY_NAME, KeyType::Token, service.id, user);
+            let user_token = auth_local_login(&mut client, &user.id, &user_email, USER_PASSWORD);
+
+            let body = pb::KeyRotateRequest::new(user_key.key.unwrap().id, None);
+            client.key_rotate(body).unwrap();
+
+            // Tokens signed by predecessor are verified during grace period.
+            user_token_verify(&mut client, &user_token);
+            let user_token = auth_local_login(&mut client, &user.id, &user_email, USER_PASSWORD);
+            user_token_verify(&mut client, &user_token);
+        }
+
+        #[test]
+        #[ignore]
+        fn key_last_used_ok() {
+            let mut client = client_create(None);
+            let (_service, service_key) = service_key_create(&mut client);
+            let service_key_id = service_key.key.unwrap().id;
+
+            let mut service_client = client_create(Some(&service_key.value));
+            service_client
+                .key_list(pb::KeyListRequest::limit(1))
+                .unwrap();
+
+            let key = client
+                .key_read(pb::KeyReadRequest {
+                    id: service_key_id.clone(),
+                    user_id: None,
+                })
+                .unwrap()
+                .into_inner()
+                .data
+                .unwrap();
+            assert!(key.last_used_at.is_some());
+
+            let mut body = pb::KeyListRequest::limit_id(1, vec![service_key_id.clone()]);
+            body.last_used_lt = pb::datetime_to_timestamp_opt(Utc::now());
+            let res = client.key_list(body).unwrap().into_inner();
+            assert_eq!(res.data.len(), 1);
+
+            let mut body = pb::KeyListRequest::limit_id(1, vec![service_key_id]);
+            body.last_used_lt =
+                pb::datetime_to_timestamp_opt(Utc::now() - chrono::Duration::days(90));
+            let res = client.key_list(body).unwrap().into_inner();
+            assert_eq!(res.data.len(), 0);
+        }
+
+        #[test]
+        #[ignore]
+        fn key_last_used_disabled_unchanged() {
+            let mut client = client_create(None);
+            let (service, _service_key) = service_key_create(&mut client);
+            let body =
+                pb::KeyCreateRequest::with_service_id(false, KeyType::Key, KEY_NAME, service.id);
+            let service_key = client.key_create(body).unwrap().into_inner().data.unwrap();
+            let service_key_id = service_key.key.unwrap().id;
+
+            let mut service_client = client_create(Some(&service_key.value));
+            let res = service_client
+                .key_list(pb::KeyListRequest::limit(1))
+                .unwrap_err();
+            assert_eq!(res.code(), tonic::Code::Unauthenticated);
+
+            let key = client
+                .key_read(pb::KeyReadRequest {
+                    id: service_key_id,
+                    user_id: None,
+                })
+                .unwrap()
+                .into_inner()
+                .data
+                .unwrap();
+            assert!(key.last_used_at.is_none());
+        }
+
+        #[test]
+        #[ignore]
+        fn key_last_used_audit_create_unchanged() {
+            let mut client = client_create(None);
+            let (service, service_key) = service_key_create(&mut client);
+            let user_email = email_create();
+
+            let mut client = client_create(Some(&service_key.value));
+            let user = user_create(&mut client, true, USER_NAME, &user_email);
+            let (_user, user_key) =
+                user_key_create(&mut client, KEY_NAME, KeyType::Key, service.id, user);
+            let user_key_id = user_key.key.unwrap().id;
+
+            let mut body = pb::AuditCreateRequest::new("test".to_owned());
+            body.user_key_id = Some(user_key_id.clone());
+            client.audit_create(body).unwrap();
+
+            let key = client
+                .key_read(pb::KeyReadRequest {
+                    id: user_key_id,
+                    user_id: None,
+                })
+                .unwrap()
+                .into_inner()
+                .data
+                .unwrap();
+            assert!(key.last_used_at.is_none());
+        }
     };
 }
@@ -4,10 +4,14 @@ mod auth_key;
 mod auth_local;
 mod auth_token;
 mod auth_totp;
+mod group;
 mod guide;
 mod key;
+mod organisation;
+mod role;
 mod service;
 mod user;
+mod webhook;
 
 pub use chrono::Utc;
 pub use serde_json::Value;
@@ -31,6 +35,16 @@ fn env_test_sso_key() -> String {
     std::env::var("SSO_TEST_KEY").expect("SSO_TEST_KEY is undefined, integration test disabled")
 }
 
+fn env_test_sso_organisation_key() -> String {
+    std::env::var("SSO_TEST_ORGANISATION_KEY")
+        .expect("SSO_TEST_ORGANISATION_KEY is undefined, integration test disabled")
+}
+
+fn env_test_sso_scoped_key() -> String {
+    std::env::var("SSO_TEST_SCOPED_KEY")
+        .expect("SSO_TEST_SCOPED_KEY is undefined, integration test disabled")
+}
+
 fn channel_tls() -> GrpcClientChannelTls {
     GrpcClientChannelTls::from_env(
         "SSO_TEST_TLS_DOMAIN",
@@ -57,6 +71,14 @@ pub fn client_create(key: Option<&str>) -> GrpcClientBlocking {
     }
 }
 
+pub fn client_organisation_create() -> GrpcClientBlocking {
+    client_create(Some(&env_test_sso_organisation_key()))
+}
+
+pub fn client_scoped_create() -> GrpcClientBlocking {
+    client_create(Some(&env_test_sso_scoped_key()))
+}
+
 pub fn client_user_create(key: &str, user_key: &str) -> GrpcClientBlocking {
     GrpcClientBlocking::new(
         env_test_sso_url(),
@@ -227,3 +249,58 @@ pub fn auth_local_login(
     assert_eq!(login.user.as_ref().unwrap().id, user_id);
     login
 }
+
+/// Webhook stub request, lowercase header names and body.
+pub struct WebhookStubRequest {
+    pub headers: std::collections::HashMap<String, String>,
+    pub body: String,
+}
+
+/// Bind local HTTP stub for webhook deliveries, returns listener and URL.
+pub fn webhook_stub_bind() -> (std::net::TcpListener, String) {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/webhook", listener.local_addr().unwrap());
+    (listener, url)
+}
+
+/// Accept one request on webhook stub and respond with status code.
+pub fn webhook_stub_accept(listener: &std::net::TcpListener, status: u16) -> WebhookStubRequest {
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    let (stream, _) = listener.accept().unwrap();
+    stream
+        .set_read_timeout(Some(std::time::Duration::from_secs(30)))
+        .unwrap();
+    let mut reader = BufReader::new(stream);
+    let mut headers = std::collections::HashMap::new();
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    loop {
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        let mut parts = line.splitn(2, ':');
+        let name = parts.next().unwrap().trim().to_lowercase();
+        let value = parts.next().unwrap_or("").trim().to_owned();
+        headers.insert(name, value);
+    }
+    let length: usize = headers
+        .get("content-length")
+        .map(|x| x.parse().unwrap())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).unwrap();
+
+    let response = format!(
+        "HTTP/1.1 {} Stub\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        status
+    );
+    reader.get_mut().write_all(response.as_bytes()).unwrap();
+    WebhookStubRequest {
+        headers,
+        body: String::from_utf8(body).unwrap(),
+    }
+}
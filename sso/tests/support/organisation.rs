@@ -0,0 +1,69 @@
+#[macro_export]
+macro_rules! organisation_integration_test {
+    () => {
+        #[test]
+        #[ignore]
+        fn organisation_user_email_unique_per_organisation_ok() {
+            let mut client = client_create(None);
+            let user_email = email_create();
+
+            let body = pb::OrganisationCreateRequest::new(true, Uuid::new_v4().to_string());
+            let o1 = client
+                .organisation_create(body)
+                .unwrap()
+                .into_inner()
+                .data
+                .unwrap();
+            let body = pb::OrganisationCreateRequest::new(true, Uuid::new_v4().to_string());
+            let o2 = client
+                .organisation_create(body)
+                .unwrap()
+                .into_inner()
+                .data
+                .unwrap();
+
+            let body = pb::UserCreateRequest::new(true, USER_NAME, &user_email)
+                .organisation_id(o1.id.clone());
+            let u1 = client.user_create(body).unwrap().into_inner().data.unwrap();
+            assert_eq!(u1.organisation_id.unwrap(), o1.id);
+            let body = pb::UserCreateRequest::new(true, USER_NAME, &user_email)
+                .organisation_id(o2.id.clone());
+            let u2 = client.user_create(body).unwrap().into_inner().data.unwrap();
+            assert_eq!(u2.organisation_id.unwrap(), o2.id);
+
+            let body = pb::UserCreateRequest::new(true, USER_NAME, &user_email)
+                .organisation_id(o1.id.clone());
+            let res = client.user_create(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::InvalidArgument);
+        }
+
+        #[test]
+        #[ignore]
+        fn organisation_delete_bad_request_owns_service() {
+            let mut client = client_create(None);
+
+            let body = pb::OrganisationCreateRequest::new(true, Uuid::new_v4().to_string());
+            let organisation = client
+                .organisation_create(body)
+                .unwrap()
+                .into_inner()
+                .data
+                .unwrap();
+            let body = pb::ServiceCreateRequest::new(true, "test", "http://localhost")
+                .organisation_id(organisation.id.clone());
+            let service = client
+                .service_create(body)
+                .unwrap()
+                .into_inner()
+                .data
+                .unwrap();
+            assert_eq!(service.organisation_id.unwrap(), organisation.id);
+
+            let body = pb::OrganisationReadRequest {
+                id: organisation.id,
+            };
+            let res = client.organisation_delete(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::InvalidArgument);
+        }
+    };
+}
@@ -0,0 +1,84 @@
+#[macro_export]
+macro_rules! role_integration_test {
+    () => {
+        #[test]
+        #[ignore]
+        fn role_create_bad_request_invalid_name() {
+            let mut client = client_create(None);
+            let (service, _) = service_key_create(&mut client);
+
+            let body = pb::RoleCreateRequest::new(service.id, "admin,user");
+            let res = client.role_create(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::InvalidArgument);
+        }
+
+        #[test]
+        #[ignore]
+        fn role_create_bad_request_duplicate_name() {
+            let mut client = client_create(None);
+            let (service, _) = service_key_create(&mut client);
+
+            let body = pb::RoleCreateRequest::new(service.id.clone(), "admin");
+            client.role_create(body).unwrap();
+            let body = pb::RoleCreateRequest::new(service.id, "admin");
+            let res = client.role_create(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::InvalidArgument);
+        }
+
+        #[test]
+        #[ignore]
+        fn role_create_not_found_other_service() {
+            let mut client = client_create(None);
+            let (_service1, service_key1) = service_key_create(&mut client);
+            let (service2, _) = service_key_create(&mut client);
+
+            let mut client = client_create(Some(&service_key1.value));
+            let body = pb::RoleCreateRequest::new(service2.id, "admin");
+            let res = client.role_create(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::NotFound);
+        }
+
+        #[test]
+        #[ignore]
+        fn role_user_auth_key_verify_ok() {
+            let mut client = client_create(None);
+            let (service, service_key) = service_key_create(&mut client);
+            let user_email = email_create();
+
+            let mut client = client_create(Some(&service_key.value));
+            let user = user_create(&mut client, true, USER_NAME, &user_email);
+            let user_id = user.id.clone();
+            let (_user, user_key) = user_key_create(
+                &mut client,
+                KEY_NAME,
+                KeyType::Key,
+                service.id.clone(),
+                user,
+            );
+
+            let body = pb::RoleCreateRequest::new(service.id.clone(), "editor");
+            let r1 = client.role_create(body).unwrap().into_inner().data.unwrap();
+            let body = pb::RoleCreateRequest::new(service.id, "admin");
+            let r2 = client.role_create(body).unwrap().into_inner().data.unwrap();
+
+            client
+                .user_role_create(pb::UserRoleRequest::new(user_id.clone(), r1.id.clone()))
+                .unwrap();
+            client
+                .user_role_create(pb::UserRoleRequest::new(user_id.clone(), r2.id.clone()))
+                .unwrap();
+
+            let body = pb::AuthKeyRequest::new(&user_key.value, None);
+            let res = client.auth_key_verify(body).unwrap().into_inner();
+            assert_eq!(res.roles, vec!["admin".to_owned(), "editor".to_owned()]);
+
+            client
+                .user_role_delete(pb::UserRoleRequest::new(user_id, r2.id))
+                .unwrap();
+
+            let body = pb::AuthKeyRequest::new(&user_key.value, None);
+            let res = client.auth_key_verify(body).unwrap().into_inner();
+            assert_eq!(res.roles, vec!["editor".to_owned()]);
+        }
+    };
+}
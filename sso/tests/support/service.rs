@@ -138,5 +138,101 @@ macro_rules! service_integration_test {
                 .unwrap_err();
             assert_eq!(res.code(), tonic::Code::NotFound);
         }
+
+        #[test]
+        #[ignore]
+        fn service_create_bad_request_invalid_allow_remote() {
+            let mut client = client_create(None);
+            let mut body = pb::ServiceCreateRequest::new(true, "test", "http://localhost");
+            body.allow_remote = vec!["10.0.0.0/33".to_owned()];
+            let res = client.service_create(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::InvalidArgument);
+        }
+
+        #[test]
+        #[ignore]
+        fn service_allow_remote_forbidden() {
+            let mut client = client_create(None);
+            let body = pb::ServiceCreateRequest::new(true, "test", "http://localhost")
+                .allow_remote(vec!["192.0.2.0/24".parse().unwrap()]);
+            let service = client
+                .service_create(body)
+                .unwrap()
+                .into_inner()
+                .data
+                .unwrap();
+            assert_eq!(service.allow_remote, vec!["192.0.2.0/24".to_owned()]);
+            let body = pb::KeyCreateRequest::with_service_id(
+                true,
+                KeyType::Key,
+                KEY_NAME,
+                service.id.clone(),
+            );
+            let service_key = client.key_create(body).unwrap().into_inner().data.unwrap();
+
+            let mut service_client = client_create(Some(&service_key.value));
+            let res = service_client
+                .user_list(pb::UserListRequest::limit(1))
+                .unwrap_err();
+            assert_eq!(res.code(), tonic::Code::PermissionDenied);
+
+            let mut req = pb::ServiceUpdateRequest::default();
+            req.id = service.id;
+            req.allow_remote = Some(pb::AllowRemote { cidr: Vec::new() });
+            client.service_update(req).unwrap();
+            service_client
+                .user_list(pb::UserListRequest::limit(1))
+                .unwrap();
+        }
+
+        #[test]
+        #[ignore]
+        fn service_user_suspended_bad_request() {
+            let mut client = client_create(None);
+            let (service, service_key) = service_key_create(&mut client);
+            let user_email = email_create();
+
+            let mut client = client_create(Some(&service_key.value));
+            let user = user_create(&mut client, true, USER_NAME, &user_email);
+            let (user, user_key) = user_key_create(
+                &mut client,
+                KEY_NAME,
+                KeyType::Key,
+                service.id.clone(),
+                user,
+            );
+
+            let res = client
+                .service_user_list(pb::ServiceUserListRequest {
+                    service_id: service.id.clone(),
+                    ..Default::default()
+                })
+                .unwrap()
+                .into_inner();
+            assert_eq!(res.data.len(), 1);
+            assert_eq!(res.data[0].user_id, user.id);
+            assert_eq!(res.data[0].status, pb::ServiceUserStatus::Active as i32);
+
+            let body = pb::AuthKeyRequest::new(&user_key.value, None);
+            client.auth_key_verify(body).unwrap();
+
+            let res = client
+                .service_user_update(pb::ServiceUserUpdateRequest {
+                    service_id: service.id.clone(),
+                    user_id: user.id.clone(),
+                    status: pb::ServiceUserStatus::Suspended as i32,
+                })
+                .unwrap()
+                .into_inner();
+            assert_eq!(
+                res.data.unwrap().status,
+                pb::ServiceUserStatus::Suspended as i32
+            );
+
+            let body = pb::AuthKeyRequest::new(&user_key.value, None);
+            let res = client.auth_key_verify(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::InvalidArgument);
+            assert_eq!(res.message(), ERR_REDACTED);
+        }
     };
 }
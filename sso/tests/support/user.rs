@@ -448,5 +448,225 @@ macro_rules! user_integration_test {
                 user1_key.key.unwrap().id
             );
         }
+
+        #[test]
+        #[ignore]
+        fn user_update_attributes_ok() {
+            let mut client = client_create(None);
+            let (_service, service_key) = service_key_create(&mut client);
+            let user_email = email_create();
+
+            let mut client = client_create(Some(&service_key.value));
+            let user1 = user_create(&mut client, true, USER_NAME, &user_email);
+
+            let reply = client
+                .user_update(
+                    pb::UserUpdateRequest::new(user1.id.clone())
+                        .attributes(json!({ "team": "red" }))
+                        .service_attributes(json!({ "level": 2.0 })),
+                )
+                .unwrap()
+                .into_inner();
+            let user2 = reply.data.unwrap();
+            assert_eq!(
+                pb::struct_opt_to_value_opt(user2.attributes),
+                Some(json!({ "team": "red" }))
+            );
+            assert_eq!(
+                pb::struct_opt_to_value_opt(reply.service_attributes),
+                Some(json!({ "level": 2.0 }))
+            );
+
+            let reply = client
+                .user_read(pb::UserReadRequest {
+                    id: user1.id.clone(),
+                })
+                .unwrap()
+                .into_inner();
+            assert_eq!(
+                pb::struct_opt_to_value_opt(reply.service_attributes),
+                Some(json!({ "level": 2.0 }))
+            );
+        }
+
+        #[test]
+        #[ignore]
+        fn user_update_service_attributes_bad_request_root_key() {
+            let mut client = client_create(None);
+            let user_email = email_create();
+            let user1 = user_create(&mut client, true, USER_NAME, &user_email);
+
+            let res = client
+                .user_update(
+                    pb::UserUpdateRequest::new(user1.id.clone())
+                        .service_attributes(json!({ "level": 2.0 })),
+                )
+                .unwrap_err();
+            assert_eq!(res.code(), tonic::Code::InvalidArgument);
+        }
+
+        #[test]
+        #[ignore]
+        fn user_export_erase_ok() {
+            let mut client = client_create(None);
+            let (service, service_key) = service_key_create(&mut client);
+            let user_email = email_create();
+
+            let mut service_client = client_create(Some(&service_key.value));
+            let user = user_create(&mut service_client, true, USER_NAME, &user_email);
+            let (user, user_key) = user_key_create(
+                &mut service_client,
+                KEY_NAME,
+                KeyType::Key,
+                service.id.clone(),
+                user,
+            );
+            user_key_verify(&mut service_client, &user_key);
+
+            let export = client
+                .user_export(pb::UserReadRequest {
+                    id: user.id.clone(),
+                })
+                .unwrap()
+                .into_inner();
+            let export = pb::struct_opt_to_value_opt(export.data).unwrap();
+            assert_eq!(export["user"]["id"], json!(user.id));
+            assert!(export["user"].get("password_hash").is_none());
+            assert_eq!(export["keys"].as_array().unwrap().len(), 1);
+            assert!(export["keys"][0].get("value").is_none());
+            assert!(!export["audit"].as_array().unwrap().is_empty());
+
+            client
+                .user_erase(pb::UserReadRequest {
+                    id: user.id.clone(),
+                })
+                .unwrap();
+            let res = client
+                .user_read(pb::UserReadRequest {
+                    id: user.id.clone(),
+                })
+                .unwrap_err();
+            assert_eq!(res.code(), tonic::Code::NotFound);
+            user_key_verify_bad_request(&mut service_client, &user_key.value);
+        }
+
+        #[test]
+        #[ignore]
+        fn user_erase_unauthorised_service_key() {
+            let mut client = client_create(None);
+            let (_service, service_key) = service_key_create(&mut client);
+            let user_email = email_create();
+
+            let mut client = client_create(Some(&service_key.value));
+            let user = user_create(&mut client, true, USER_NAME, &user_email);
+            let res = client
+                .user_erase(pb::UserReadRequest {
+                    id: user.id.clone(),
+                })
+                .unwrap_err();
+            assert_eq!(res.code(), tonic::Code::Unauthenticated);
+        }
+
+        #[test]
+        #[ignore]
+        fn user_invite_resend_revoke_ok() {
+            let mut client = client_create(None);
+            let (service, service_key) = service_key_create(&mut client);
+            let user_email = email_create();
+
+            let mut client = client_create(Some(&service_key.value));
+            let body = pb::UserInviteRequest::new(service.id.clone(), USER_NAME, &user_email);
+            let res = client.user_invite(body).unwrap().into_inner();
+            let user = res.user.unwrap();
+            let data = res.data.unwrap();
+            assert_eq!(user.email, user_email);
+            assert_eq!(data.user_id, user.id);
+            assert_eq!(data.status, pb::ServiceUserStatus::Invited as i32);
+            assert!(data.invite_expires_at.is_some());
+
+            let res = client
+                .service_user_list(pb::ServiceUserListRequest {
+                    service_id: service.id.clone(),
+                    status: vec![pb::ServiceUserStatus::Invited as i32],
+                    ..Default::default()
+                })
+                .unwrap()
+                .into_inner();
+            assert_eq!(res.data.len(), 1);
+            assert_eq!(res.data[0].user_id, user.id);
+
+            let body = pb::AuthLoginRequest::new(&user_email, USER_PASSWORD);
+            let res = client.auth_local_login(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::InvalidArgument);
+
+            let res = client
+                .user_invite_resend(pb::UserInviteResendRequest {
+                    service_id: service.id.clone(),
+                    user_id: user.id.clone(),
+                })
+                .unwrap()
+                .into_inner();
+            let resend = res.data.unwrap();
+            assert_eq!(resend.status, pb::ServiceUserStatus::Invited as i32);
+
+            client
+                .user_invite_revoke(pb::UserInviteRevokeRequest {
+                    service_id: service.id.clone(),
+                    user_id: user.id.clone(),
+                })
+                .unwrap();
+
+            let res = client
+                .service_user_list(pb::ServiceUserListRequest {
+                    service_id: service.id.clone(),
+                    status: vec![pb::ServiceUserStatus::Invited as i32],
+                    ..Default::default()
+                })
+                .unwrap()
+                .into_inner();
+            assert_eq!(res.data.len(), 0);
+
+            let res = client
+                .user_invite_resend(pb::UserInviteResendRequest {
+                    service_id: service.id.clone(),
+                    user_id: user.id.clone(),
+                })
+                .unwrap_err();
+            assert_eq!(res.code(), tonic::Code::InvalidArgument);
+        }
+
+        #[test]
+        #[ignore]
+        fn user_invite_bad_request_email_exists() {
+            let mut client = client_create(None);
+            let (service, service_key) = service_key_create(&mut client);
+            let user_email = email_create();
+
+            let mut client = client_create(Some(&service_key.value));
+            user_create(&mut client, true, USER_NAME, &user_email);
+            let body = pb::UserInviteRequest::new(service.id.clone(), USER_NAME, &user_email);
+            let res = client.user_invite(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::InvalidArgument);
+        }
+
+        #[test]
+        #[ignore]
+        fn user_invite_revoke_bad_request_active_user() {
+            let mut client = client_create(None);
+            let (service, service_key) = service_key_create(&mut client);
+            let user_email = email_create();
+
+            let mut client = client_create(Some(&service_key.value));
+            let user = user_create(&mut client, true, USER_NAME, &user_email);
+            let (user, _user_key) =
+                user_key_create(&mut client, KEY_NAME, KeyType::Key, service.id.clone(), user);
+            let res = client
+                .user_invite_revoke(pb::UserInviteRevokeRequest {
+                    service_id: service.id.clone(),
+                    user_id: user.id.clone(),
+                })
+                .unwrap_err();
+            assert_eq!(res.code(), tonic::Code::InvalidArgument);
+        }
     };
 }
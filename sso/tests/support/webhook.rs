@@ -0,0 +1,157 @@
+#[macro_export]
+macro_rules! webhook_integration_test {
+    () => {
+        #[test]
+        #[ignore]
+        fn webhook_create_bad_request_invalid_url() {
+            let mut client = client_create(None);
+            let (service, _) = service_key_create(&mut client);
+
+            let body = pb::WebhookCreateRequest::new(service.id, "webhook", "invalid-url");
+            let res = client.webhook_create(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::InvalidArgument);
+        }
+
+        #[test]
+        #[ignore]
+        fn webhook_create_not_found_other_service() {
+            let mut client = client_create(None);
+            let (_service1, service_key1) = service_key_create(&mut client);
+            let (service2, _) = service_key_create(&mut client);
+
+            let mut client = client_create(Some(&service_key1.value));
+            let body =
+                pb::WebhookCreateRequest::new(service2.id, "webhook", "http://localhost/webhook");
+            let res = client.webhook_create(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::NotFound);
+        }
+
+        #[test]
+        #[ignore]
+        fn webhook_read_not_found_other_service() {
+            let mut client = client_create(None);
+            let (_service1, service_key1) = service_key_create(&mut client);
+            let (service2, _) = service_key_create(&mut client);
+
+            let body =
+                pb::WebhookCreateRequest::new(service2.id, "webhook", "http://localhost/webhook");
+            let webhook = client
+                .webhook_create(body)
+                .unwrap()
+                .into_inner()
+                .data
+                .unwrap();
+
+            let mut client = client_create(Some(&service_key1.value));
+            let body = pb::WebhookReadRequest { id: webhook.id };
+            let res = client.webhook_read(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::NotFound);
+        }
+
+        #[test]
+        #[ignore]
+        fn webhook_delivery_retry_not_found_does_not_exist() {
+            let mut client = client_create(None);
+
+            let body = pb::WebhookDeliveryRetryRequest {
+                id: UUID_NIL.to_owned(),
+            };
+            let res = client.webhook_delivery_retry(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::NotFound);
+        }
+
+        #[test]
+        #[ignore]
+        fn webhook_delivery_signed_ok() {
+            let mut client = client_create(None);
+            let (service, service_key) = service_key_create(&mut client);
+            let (listener, url) = webhook_stub_bind();
+            let type_ = Uuid::new_v4().to_simple().to_string();
+
+            let mut client = client_create(Some(&service_key.value));
+            let body = pb::WebhookCreateRequest::new(service.id, "webhook", url)
+                .audit_type(vec![type_.clone()]);
+            let res = client.webhook_create(body).unwrap().into_inner();
+            let webhook = res.data.unwrap();
+            let secret = res.secret;
+
+            let audit = client
+                .audit_create(pb::AuditCreateRequest::new(type_.clone()))
+                .unwrap()
+                .into_inner()
+                .data
+                .unwrap();
+
+            let req = webhook_stub_accept(&listener, 200);
+            let timestamp: i64 = req.headers["sso-webhook-timestamp"].parse().unwrap();
+            let signature = Webhook::signature(&secret, timestamp, &req.body);
+            assert_eq!(req.headers["sso-webhook-signature"], signature);
+
+            let body: Value = serde_json::from_str(&req.body).unwrap();
+            assert_eq!(body["id"], req.headers["sso-webhook-id"].as_str());
+            assert_eq!(body["webhook_id"], webhook.id.as_str());
+            assert_eq!(body["audit"]["id"], audit.id.as_str());
+            assert_eq!(body["audit"]["type"], type_.as_str());
+
+            // Delivery result is recorded after response.
+            std::thread::sleep(std::time::Duration::from_secs(1));
+            let body = pb::WebhookDeliveryListRequest {
+                webhook_id: webhook.id,
+                gt: None,
+                lt: None,
+                limit: None,
+                status: vec![pb::WebhookDeliveryStatus::Delivered as i32],
+            };
+            let res = client.webhook_delivery_list(body).unwrap().into_inner();
+            assert_eq!(res.data.len(), 1);
+            assert_eq!(res.data[0].audit_id, audit.id);
+            assert_eq!(res.data[0].status_code, Some(200));
+        }
+
+        #[test]
+        #[ignore]
+        fn webhook_delivery_failed_retry_pending() {
+            let mut client = client_create(None);
+            let (service, service_key) = service_key_create(&mut client);
+            let (listener, url) = webhook_stub_bind();
+            let type_ = Uuid::new_v4().to_simple().to_string();
+
+            let mut client = client_create(Some(&service_key.value));
+            let body = pb::WebhookCreateRequest::new(service.id, "webhook", url)
+                .audit_type(vec![type_.clone()]);
+            let webhook = client
+                .webhook_create(body)
+                .unwrap()
+                .into_inner()
+                .data
+                .unwrap();
+
+            client
+                .audit_create(pb::AuditCreateRequest::new(type_))
+                .unwrap();
+            webhook_stub_accept(&listener, 500);
+
+            std::thread::sleep(std::time::Duration::from_secs(1));
+            let body = pb::WebhookDeliveryListRequest {
+                webhook_id: webhook.id,
+                gt: None,
+                lt: None,
+                limit: None,
+                status: Vec::new(),
+            };
+            let res = client.webhook_delivery_list(body).unwrap().into_inner();
+            assert_eq!(res.data.len(), 1);
+            let delivery = &res.data[0];
+            assert_eq!(delivery.status, pb::WebhookDeliveryStatus::Pending as i32);
+            assert_eq!(delivery.attempts, 1);
+            assert_eq!(delivery.status_code, Some(500));
+
+            // Only dead lettered deliveries can be retried.
+            let body = pb::WebhookDeliveryRetryRequest {
+                id: delivery.id.clone(),
+            };
+            let res = client.webhook_delivery_retry(body).unwrap_err();
+            assert_eq!(res.code(), tonic::Code::InvalidArgument);
+        }
+    };
+}